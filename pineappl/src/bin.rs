@@ -8,7 +8,7 @@ use std::f64;
 use std::ops::Range;
 use thiserror::Error;
 
-#[derive(Debug, Deserialize, PartialEq, Serialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 enum Limits {
     Equal { left: f64, right: f64, bins: usize },
     Unequal { limits: Vec<f64> },
@@ -28,10 +28,19 @@ pub enum MergeBinError {
         /// left-most limit of the `BinLimits` object that is being merged.
         rhs: f64,
     },
+    /// Returned when two `BinRemapper` objects `a` and `b` were tried to be merged using
+    /// `a.merge(b)`, but when their bins have different dimensions.
+    #[error("can not merge bins with {lhs} dimensions with bins that have {rhs} dimensions")]
+    DifferentDimensions {
+        /// dimensions of the `BinRemapper` object that is being merged into.
+        lhs: usize,
+        /// dimensions of the `BinRemapper` object that is being merged.
+        rhs: usize,
+    },
 }
 
 /// Structure representing bin limits.
-#[derive(Debug, Deserialize, PartialEq, Serialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct BinLimits(Limits);
 
 /// Error type that is returned by the constructor of `BinRemapper`.
@@ -49,7 +58,7 @@ pub enum BinRemapperNewError {
 }
 
 /// Structure for remapping bin limits.
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct BinRemapper {
     normalizations: Vec<f64>,
     limits: Vec<(f64, f64)>,
@@ -234,6 +243,36 @@ impl BinRemapper {
         }
     }
 
+    /// Deletes the bins in the given `ranges`, which must be sorted and non-overlapping.
+    pub fn delete_bins(&mut self, ranges: &[Range<usize>]) {
+        let dim = self.dimensions();
+
+        for range in ranges.iter().rev() {
+            self.normalizations.drain(range.clone());
+            self.limits.drain(dim * range.start..dim * range.end);
+        }
+    }
+
+    /// Appends the bins of `other` to the bins of this object, together with their
+    /// normalizations.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the bins of `self` and `other` have different dimensions.
+    pub fn merge(&mut self, other: &Self) -> Result<(), MergeBinError> {
+        if self.dimensions() != other.dimensions() {
+            return Err(MergeBinError::DifferentDimensions {
+                lhs: self.dimensions(),
+                rhs: other.dimensions(),
+            });
+        }
+
+        self.normalizations.extend_from_slice(&other.normalizations);
+        self.limits.extend_from_slice(&other.limits);
+
+        Ok(())
+    }
+
     /// Return the normalization factors for all bins.
     #[must_use]
     pub fn normalizations(&self) -> &[f64] {
@@ -374,6 +413,20 @@ impl BinLimits {
         }
     }
 
+    /// Deletes the `bins` left-most bins.
+    pub fn delete_bins_left(&mut self, bins: usize) {
+        let mut limits = self.limits();
+        limits.drain(..bins);
+        *self = Self::new(limits);
+    }
+
+    /// Deletes the `bins` right-most bins.
+    pub fn delete_bins_right(&mut self, bins: usize) {
+        let mut limits = self.limits();
+        limits.truncate(limits.len() - bins);
+        *self = Self::new(limits);
+    }
+
     /// Merges the bins for the corresponding range together in a single one.
     pub fn merge_bins(&mut self, bins: Range<usize>) {
         let mut new_limits = self.limits();
@@ -703,6 +756,75 @@ mod test {
         assert_eq!(limits.index(1.3), None);
     }
 
+    #[test]
+    fn bin_limits_delete_bins() {
+        let mut limits = BinLimits::new(vec![0.0, 0.125, 0.25, 0.5, 1.0]);
+
+        limits.delete_bins_left(1);
+        assert_eq!(limits.limits(), [0.125, 0.25, 0.5, 1.0]);
+
+        limits.delete_bins_right(2);
+        assert_eq!(limits.limits(), [0.125, 0.25]);
+        assert_eq!(limits.bins(), 1);
+    }
+
+    #[test]
+    fn bin_remapper_delete_bins() {
+        let mut remapper = BinRemapper::new(
+            vec![1.0, 2.0, 3.0, 4.0, 5.0],
+            vec![
+                (0.0, 1.0),
+                (0.0, 0.5),
+                (0.0, 1.0),
+                (0.5, 1.0),
+                (1.0, 2.0),
+                (0.0, 0.5),
+                (1.0, 2.0),
+                (0.5, 1.0),
+                (2.0, 3.0),
+                (0.0, 1.0),
+            ],
+        )
+        .unwrap();
+
+        remapper.delete_bins(&[0..1, 2..4]);
+
+        assert_eq!(remapper.bins(), 2);
+        assert_eq!(remapper.normalizations(), [2.0, 5.0]);
+        assert_eq!(
+            remapper.limits(),
+            [(0.0, 1.0), (0.5, 1.0), (2.0, 3.0), (0.0, 1.0)]
+        );
+    }
+
+    #[test]
+    fn bin_remapper_merge() {
+        let mut remapper = BinRemapper::new(
+            vec![1.0, 2.0],
+            vec![(0.0, 1.0), (0.0, 0.5), (0.0, 1.0), (0.5, 1.0)],
+        )
+        .unwrap();
+        let other = BinRemapper::new(
+            vec![3.0, 4.0],
+            vec![(1.0, 2.0), (0.0, 0.5), (1.0, 2.0), (0.5, 1.0)],
+        )
+        .unwrap();
+
+        remapper.merge(&other).unwrap();
+
+        assert_eq!(remapper.bins(), 4);
+        assert_eq!(remapper.dimensions(), 2);
+        assert_eq!(remapper.normalizations(), [1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(remapper.slices(), [(0, 2), (2, 4)]);
+
+        let other = BinRemapper::new(vec![1.0], vec![(2.0, 3.0)]).unwrap();
+
+        assert!(matches!(
+            remapper.merge(&other),
+            Err(MergeBinError::DifferentDimensions { lhs: 2, rhs: 1 })
+        ));
+    }
+
     #[test]
     fn bin_remapper() {
         let remapper = BinRemapper::new(
@@ -0,0 +1,475 @@
+//! Module containing `Composite`, which combines the predictions of several grids into a single
+//! observable, for instance a ratio of cross sections or a normalized distribution.
+
+use super::grid::{self, Grid};
+use super::pdf::PdfProvider;
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::ops::{Add, Div, Mul, Sub};
+use thiserror::Error;
+
+/// Error type returned by `Composite::new`, `Composite::convolute` and `Composite::read`.
+#[derive(Debug, Eq, Error, PartialEq)]
+pub enum CompositeError {
+    /// Returned if the expression refers to a grid that the composite does not have.
+    #[error("the expression refers to grid {index}, but there are only {grids} grids")]
+    UnknownGrid {
+        /// Index of the grid in the expression.
+        index: usize,
+        /// Number of grids of the composite.
+        grids: usize,
+    },
+    /// Returned if the number of grids given to `Composite::convolute` is not the number of grids
+    /// of the composite.
+    #[error("the composite needs {expected} grids, but {found} were given")]
+    WrongNumberOfGrids {
+        /// Number of grids of the composite.
+        expected: usize,
+        /// Number of grids given.
+        found: usize,
+    },
+    /// Returned if the bin limits or normalizations of a grid are not the same as the ones of the
+    /// first grid.
+    #[error("the bins of grid {0} are not the same as the bins of the first grid")]
+    DifferentBins(usize),
+    /// Returned if the input does not start with the magic bytes of a composite.
+    #[error("the input is not a composite")]
+    UnknownFormat,
+    /// Returned if the composite was written with a newer version of the file format.
+    #[error("file format version {0} is not supported")]
+    UnsupportedVersion(u64),
+}
+
+/// Magic bytes at the beginning of every file written by `Composite::write`.
+const FILE_MAGIC: [u8; 8] = *b"PineCOMP";
+
+/// Version of the file format written by `Composite::write`.
+const FILE_VERSION: u64 = 1;
+
+/// Expression that computes a composite prediction from the predictions of several grids. All
+/// operations act bin by bin, and the expressions can be combined with the operators `+`, `-`,
+/// `*` and `/`.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum CompositeExpr {
+    /// The prediction of the grid with the given index.
+    Grid(usize),
+    /// A constant, which is the same for every bin.
+    Constant(f64),
+    /// The sum of two expressions.
+    Add(Box<Self>, Box<Self>),
+    /// The difference of two expressions.
+    Sub(Box<Self>, Box<Self>),
+    /// The product of two expressions.
+    Mul(Box<Self>, Box<Self>),
+    /// The quotient of two expressions.
+    Div(Box<Self>, Box<Self>),
+    /// The expression divided by its integral, which is the sum over all bins of the expression
+    /// multiplied with the bin sizes of the first grid. Applied to a differential distribution
+    /// `dσ/dx` this gives the normalized distribution `1/σ dσ/dx`.
+    Normalize(Box<Self>),
+}
+
+impl CompositeExpr {
+    /// Returns the expression for the prediction of the grid with the given `index`.
+    #[must_use]
+    pub const fn grid(index: usize) -> Self {
+        Self::Grid(index)
+    }
+
+    /// Returns this expression normalized to its integral, see `CompositeExpr::Normalize`.
+    #[must_use]
+    pub fn normalize(self) -> Self {
+        Self::Normalize(Box::new(self))
+    }
+
+    fn check(&self, grids: usize) -> Result<(), CompositeError> {
+        match self {
+            Self::Grid(index) => {
+                if *index < grids {
+                    Ok(())
+                } else {
+                    Err(CompositeError::UnknownGrid {
+                        index: *index,
+                        grids,
+                    })
+                }
+            }
+            Self::Constant(_) => Ok(()),
+            Self::Add(lhs, rhs)
+            | Self::Sub(lhs, rhs)
+            | Self::Mul(lhs, rhs)
+            | Self::Div(lhs, rhs) => {
+                lhs.check(grids)?;
+                rhs.check(grids)
+            }
+            Self::Normalize(expr) => expr.check(grids),
+        }
+    }
+
+    /// Evaluates this expression for the `results` of each grid, which have the layout of the
+    /// results of `Grid::convolute` with `scales` elements of `xi`.
+    fn evaluate(&self, results: &[Vec<f64>], normalizations: &[f64], scales: usize) -> Vec<f64> {
+        let binary = |lhs: &Self, rhs: &Self, op: fn(f64, f64) -> f64| -> Vec<f64> {
+            lhs.evaluate(results, normalizations, scales)
+                .into_iter()
+                .zip(rhs.evaluate(results, normalizations, scales))
+                .map(|(lhs, rhs)| op(lhs, rhs))
+                .collect()
+        };
+
+        match self {
+            Self::Grid(index) => results[*index].clone(),
+            Self::Constant(value) => vec![*value; normalizations.len() * scales],
+            Self::Add(lhs, rhs) => binary(lhs, rhs, |lhs, rhs| lhs + rhs),
+            Self::Sub(lhs, rhs) => binary(lhs, rhs, |lhs, rhs| lhs - rhs),
+            Self::Mul(lhs, rhs) => binary(lhs, rhs, |lhs, rhs| lhs * rhs),
+            Self::Div(lhs, rhs) => binary(lhs, rhs, |lhs, rhs| lhs / rhs),
+            Self::Normalize(expr) => {
+                let mut values = expr.evaluate(results, normalizations, scales);
+                let integrals: Vec<f64> = (0..scales)
+                    .map(|scale| {
+                        values
+                            .iter()
+                            .skip(scale)
+                            .step_by(scales)
+                            .zip(normalizations)
+                            .map(|(value, normalization)| value * normalization)
+                            .sum()
+                    })
+                    .collect();
+
+                for (index, value) in values.iter_mut().enumerate() {
+                    *value /= integrals[index % scales];
+                }
+
+                values
+            }
+        }
+    }
+}
+
+impl Add for CompositeExpr {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self::Add(Box::new(self), Box::new(rhs))
+    }
+}
+
+impl Sub for CompositeExpr {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self::Sub(Box::new(self), Box::new(rhs))
+    }
+}
+
+impl Mul for CompositeExpr {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Self::Mul(Box::new(self), Box::new(rhs))
+    }
+}
+
+impl Div for CompositeExpr {
+    type Output = Self;
+
+    fn div(self, rhs: Self) -> Self {
+        Self::Div(Box::new(self), Box::new(rhs))
+    }
+}
+
+/// Prediction that is computed from the predictions of several grids, which are convoluted with
+/// the same PDF.
+///
+/// The composite refers to its grids by their names, for instance the paths of the files
+/// containing them, and can be written to and read from a file next to the grids. Its
+/// `CompositeExpr` determines how the predictions of the grids are combined, for instance
+/// `CompositeExpr::grid(0) / CompositeExpr::grid(1)` for the ratio of two cross sections.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Composite {
+    grids: Vec<String>,
+    expr: CompositeExpr,
+}
+
+impl Composite {
+    /// Constructor. The grid with the index `i` in `expr` is the one with the name `grids[i]`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `expr` refers to a grid that is not in `grids`.
+    pub fn new(grids: Vec<String>, expr: CompositeExpr) -> Result<Self, CompositeError> {
+        expr.check(grids.len())?;
+
+        Ok(Self { grids, expr })
+    }
+
+    /// Returns the names of the grids.
+    #[must_use]
+    pub fn grids(&self) -> &[String] {
+        &self.grids
+    }
+
+    /// Returns the expression that combines the predictions of the grids.
+    #[must_use]
+    pub const fn expr(&self) -> &CompositeExpr {
+        &self.expr
+    }
+
+    /// Convolutes each of the `grids`, which must be given in the same order as their names, see
+    /// `Composite::grids`, with `pdf` and combines the results. Every grid uses `pdf` for each of
+    /// its convolutions. The meaning of `xi` and the layout of the result are the same as for
+    /// `Grid::convolute`, and the expression is evaluated separately for each element of `xi`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the number of `grids` is not the number of names of this composite, or
+    /// if the grids do not have the same bin limits and normalizations.
+    ///
+    /// # Panics
+    ///
+    /// Panics for the same reasons as `Grid::convolute`.
+    pub fn convolute(
+        &self,
+        grids: &[Grid],
+        pdf: &dyn PdfProvider,
+        xi: &[(f64, f64, f64)],
+    ) -> Result<Vec<f64>, CompositeError> {
+        if grids.len() != self.grids.len() {
+            return Err(CompositeError::WrongNumberOfGrids {
+                expected: self.grids.len(),
+                found: grids.len(),
+            });
+        }
+
+        if let Some(first) = grids.first() {
+            if let Some(index) = grids
+                .iter()
+                .position(|grid| grid.bin_info() != first.bin_info())
+            {
+                return Err(CompositeError::DifferentBins(index));
+            }
+        }
+
+        let normalizations = grids
+            .first()
+            .map_or_else(Vec::new, |grid| grid.bin_info().normalizations());
+
+        let results: Vec<_> = grids
+            .iter()
+            .map(|grid| grid.convolute(&vec![pdf; grid.convolutions()], &[], &[], &[], xi))
+            .collect();
+
+        Ok(self.expr.evaluate(&results, &normalizations, xi.len()))
+    }
+
+    /// Constructs a `Composite` by deserializing it from `reader`, which must contain a composite
+    /// written by `Composite::write`. Reading is not buffered.
+    ///
+    /// # Errors
+    ///
+    /// If reading fails, if the input is not a composite, if the file format version is not
+    /// supported, if the checksum does not match, or if the expression refers to a grid that the
+    /// composite does not have, an error is returned.
+    pub fn read(mut reader: impl Read) -> anyhow::Result<Self> {
+        let mut magic = [0; 8];
+        reader
+            .read_exact(&mut magic)
+            .map_err(|err| grid::read_error(err, "magic bytes"))?;
+
+        if magic != FILE_MAGIC {
+            return Err(CompositeError::UnknownFormat.into());
+        }
+
+        match grid::read_u64(&mut reader, "file format version")? {
+            FILE_VERSION => {
+                let bytes = grid::read_section(reader, "composite")?;
+                let composite: Self = bincode::deserialize(&bytes)?;
+                composite.expr.check(composite.grids.len())?;
+
+                Ok(composite)
+            }
+            version => Err(CompositeError::UnsupportedVersion(version).into()),
+        }
+    }
+
+    /// Serializes `self` into `writer`. Like grids, composites start with magic bytes and the file
+    /// format version, which are followed by the composite and its checksum. Writing is not
+    /// buffered.
+    ///
+    /// # Errors
+    ///
+    /// If writing fails an error is returned.
+    pub fn write(&self, mut writer: impl Write) -> anyhow::Result<()> {
+        writer.write_all(&FILE_MAGIC)?;
+        writer.write_all(&FILE_VERSION.to_le_bytes())?;
+        grid::write_section(writer, &bincode::serialize(self)?)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::{Ntuple, Order};
+    use crate::lumi_entry;
+    use crate::pdf::ClosurePdf;
+    use crate::subgrid::SubgridParams;
+    use float_cmp::approx_eq;
+
+    fn grid(weights: &[f64]) -> Grid {
+        let mut grid = Grid::new(
+            vec![lumi_entry![2, 2, 1.0; 4, 4, 1.0]],
+            vec![Order::new(0, 2, 0, 0, 0)],
+            vec![0.0, 0.5, 2.0],
+            SubgridParams::default(),
+        );
+
+        for (&observable, &weight) in [0.25, 1.0].iter().zip(weights) {
+            grid.fill(
+                0,
+                observable,
+                0,
+                &Ntuple {
+                    x1: 0.1,
+                    x2: 0.2,
+                    q2: 90.0_f64.powi(2),
+                    q2r: 90.0_f64.powi(2),
+                    weight,
+                },
+            )
+            .unwrap();
+        }
+
+        grid
+    }
+
+    #[test]
+    fn composite_new() {
+        assert_eq!(
+            Composite::new(
+                vec!["a.pineappl".to_owned()],
+                CompositeExpr::grid(0) / CompositeExpr::grid(1)
+            ),
+            Err(CompositeError::UnknownGrid { index: 1, grids: 1 })
+        );
+    }
+
+    #[test]
+    fn composite_convolute() {
+        let grids = vec![grid(&[1.0, 2.0]), grid(&[4.0, 1.0])];
+        let names = vec!["a.pineappl".to_owned(), "b.pineappl".to_owned()];
+        let pdf = ClosurePdf::new(2212, |_, x: f64, _| x * (1.0 - x), |q2: f64| 1.0 / q2.ln());
+        let xi = [(1.0, 1.0, 1.0), (2.0, 2.0, 1.0)];
+
+        let a = grids[0].convolute(&[&pdf, &pdf], &[], &[], &[], &xi);
+        let b = grids[1].convolute(&[&pdf, &pdf], &[], &[], &[], &xi);
+
+        let ratio = Composite::new(
+            names.clone(),
+            CompositeExpr::grid(0) / CompositeExpr::grid(1),
+        )
+        .unwrap();
+        let result = ratio.convolute(&grids, &pdf, &xi).unwrap();
+
+        assert_eq!(result.len(), 4);
+
+        for ((result, a), b) in result.iter().zip(&a).zip(&b) {
+            assert!(approx_eq!(f64, *result, a / b, ulps = 4));
+        }
+
+        let normalized = Composite::new(
+            names,
+            (CompositeExpr::grid(0) - CompositeExpr::Constant(0.5) * CompositeExpr::grid(1))
+                .normalize(),
+        )
+        .unwrap();
+        let result = normalized.convolute(&grids, &pdf, &xi).unwrap();
+
+        // the bins have the sizes 0.5 and 1.5, and each scale is normalized separately
+        for scale in 0..xi.len() {
+            let integral: f64 = [0.5, 1.5]
+                .iter()
+                .enumerate()
+                .map(|(bin, size)| size * result[scale + xi.len() * bin])
+                .sum();
+
+            assert!(approx_eq!(f64, integral, 1.0, ulps = 4));
+        }
+
+        assert_eq!(
+            normalized.convolute(&grids[..1], &pdf, &xi),
+            Err(CompositeError::WrongNumberOfGrids {
+                expected: 2,
+                found: 1
+            })
+        );
+
+        // the grids have the same number of bins, but different limits
+        let other = Grid::new(
+            vec![lumi_entry![2, 2, 1.0]],
+            vec![Order::new(0, 2, 0, 0, 0)],
+            vec![0.0, 1.0, 2.0],
+            SubgridParams::default(),
+        );
+
+        assert_eq!(
+            normalized.convolute(&[grid(&[1.0, 2.0]), other], &pdf, &xi),
+            Err(CompositeError::DifferentBins(1))
+        );
+    }
+
+    #[test]
+    fn composite_write_read() {
+        let composite = Composite::new(
+            vec!["a.pineappl".to_owned(), "b.pineappl".to_owned()],
+            CompositeExpr::grid(0) / CompositeExpr::grid(1),
+        )
+        .unwrap();
+
+        let mut buffer = Vec::new();
+        composite.write(&mut buffer).unwrap();
+
+        assert_eq!(buffer[..8], FILE_MAGIC);
+        assert_eq!(Composite::read(buffer.as_slice()).unwrap(), composite);
+
+        // a single flipped bit is detected by the checksum
+        let mut corrupted = buffer.clone();
+        corrupted[24] ^= 1;
+
+        assert_eq!(
+            Composite::read(corrupted.as_slice())
+                .unwrap_err()
+                .to_string(),
+            "the file is corrupted: the checksum of the composite does not match"
+        );
+
+        assert_eq!(
+            Composite::read(&buffer[..buffer.len() - 1])
+                .unwrap_err()
+                .to_string(),
+            "the file is truncated: the composite is incomplete"
+        );
+
+        let mut unsupported = buffer.clone();
+        unsupported[8] = 2;
+
+        assert_eq!(
+            Composite::read(unsupported.as_slice())
+                .unwrap_err()
+                .downcast::<CompositeError>()
+                .unwrap(),
+            CompositeError::UnsupportedVersion(2)
+        );
+
+        assert_eq!(
+            Composite::read(&b"PineAPPL"[..])
+                .unwrap_err()
+                .downcast::<CompositeError>()
+                .unwrap(),
+            CompositeError::UnknownFormat
+        );
+    }
+}
@@ -1,7 +1,7 @@
 //! TODO
 
 use super::grid::Ntuple;
-use super::subgrid::{Subgrid, SubgridEnum};
+use super::subgrid::{Mu2, Subgrid, SubgridEnum};
 use either::Either;
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
@@ -15,10 +15,9 @@ pub struct EmptySubgridV1 {}
 impl Subgrid for EmptySubgridV1 {
     fn convolute(
         &self,
-        _: &[f64],
-        _: &[f64],
-        _: &[f64],
-        _: Either<&dyn Fn(usize, usize, usize) -> f64, &dyn Fn(f64, f64, f64) -> f64>,
+        _: &[&[f64]],
+        _: &[Mu2],
+        _: Either<&dyn Fn(&[usize], usize) -> f64, &dyn Fn(&[f64], Mu2) -> f64>,
     ) -> f64 {
         0.0
     }
@@ -63,7 +62,7 @@ impl Subgrid for EmptySubgridV1 {
         Self::default().into()
     }
 
-    fn iter(&self) -> Box<dyn Iterator<Item = ((usize, usize, usize), &f64)> + '_> {
+    fn iter(&self) -> Box<dyn Iterator<Item = (Vec<usize>, &f64)> + '_> {
         Box::new(iter::empty())
     }
 }
@@ -75,10 +74,7 @@ mod tests {
     #[test]
     fn create_empty() {
         let mut subgrid = EmptySubgridV1::default();
-        assert_eq!(
-            subgrid.convolute(&[], &[], &[], Either::Left(&|_, _, _| 0.0)),
-            0.0,
-        );
+        assert_eq!(subgrid.convolute(&[], &[], Either::Left(&|_, _| 0.0)), 0.0,);
         assert!(subgrid.is_empty());
         subgrid.merge(&mut EmptySubgridV1::default().into(), false);
         subgrid.scale(2.0);
@@ -94,6 +90,7 @@ mod tests {
             x1: 0.0,
             x2: 0.0,
             q2: 0.0,
+            q2r: 0.0,
             weight: 0.0,
         });
     }
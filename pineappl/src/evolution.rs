@@ -0,0 +1,201 @@
+//! Module containing the evolution operator used to turn interpolation grids into FK tables.
+
+use lz_fear::{framed::DecompressionError::WrongMagic, LZ4FrameReader};
+use ndarray::Array5;
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Seek, SeekFrom, Write};
+use thiserror::Error;
+
+/// Error type returned by the constructor of `EvolutionOperator`.
+#[derive(Debug, Error)]
+pub enum EvolutionOperatorNewError {
+    /// Returned if the shape of the operator does not agree with the lengths of the vectors
+    /// describing its axes.
+    #[error("the operator has shape {operator_shape:?}, but the axes require {axes_shape:?}")]
+    ShapeMismatch {
+        /// Shape of the operator.
+        operator_shape: Vec<usize>,
+        /// Shape determined from the lengths of `x`, `pids`, `x0`, `pids0` and `q2`.
+        axes_shape: Vec<usize>,
+    },
+}
+
+/// Evolution operator, which maps PDFs at the starting scale to PDFs at other scales.
+///
+/// The operator maps PDFs given at the starting scale `q2_0`, for the particle ids `pids0` and on
+/// the momentum fractions `x0`, to the PDFs for the particle ids `pids` on the momentum fractions
+/// `x` at each of the scales `q2`. It acts on $x f(x)$, i.e. on the values that are returned from
+/// the PDF callbacks given to `Grid::convolute`.
+#[derive(Deserialize, Serialize)]
+pub struct EvolutionOperator {
+    operator: Array5<f64>,
+    x: Vec<f64>,
+    pids: Vec<i32>,
+    x0: Vec<f64>,
+    pids0: Vec<i32>,
+    q2: Vec<f64>,
+    q2_0: f64,
+}
+
+impl EvolutionOperator {
+    /// Constructor. The dimensions of `operator` correspond to the target momentum fractions
+    /// `x`, the target particle ids `pids`, the source momentum fractions `x0`, the source
+    /// particle ids `pids0` and the target scales `q2`, in this order. The source scale is given
+    /// by `q2_0`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the shape of `operator` does not agree with the lengths of the given
+    /// vectors.
+    pub fn new(
+        operator: Array5<f64>,
+        x: Vec<f64>,
+        pids: Vec<i32>,
+        x0: Vec<f64>,
+        pids0: Vec<i32>,
+        q2: Vec<f64>,
+        q2_0: f64,
+    ) -> Result<Self, EvolutionOperatorNewError> {
+        let axes_shape = vec![x.len(), pids.len(), x0.len(), pids0.len(), q2.len()];
+
+        if operator.shape() != axes_shape.as_slice() {
+            return Err(EvolutionOperatorNewError::ShapeMismatch {
+                operator_shape: operator.shape().to_vec(),
+                axes_shape,
+            });
+        }
+
+        Ok(Self {
+            operator,
+            x,
+            pids,
+            x0,
+            pids0,
+            q2,
+            q2_0,
+        })
+    }
+
+    /// Constructs an `EvolutionOperator` by deserializing it from `reader`. The input may
+    /// optionally be LZ4-compressed. Reading is not buffered.
+    ///
+    /// # Errors
+    ///
+    /// If reading from the compressed or uncompressed stream fails an error is returned.
+    pub fn read(mut reader: impl Read + Seek) -> anyhow::Result<Self> {
+        match LZ4FrameReader::new(&mut reader) {
+            Ok(reader) => Ok(bincode::deserialize_from(reader.into_read())?),
+            Err(WrongMagic(_)) => {
+                reader.seek(SeekFrom::Start(0))?;
+                Ok(bincode::deserialize_from(reader)?)
+            }
+            Err(e) => Err(anyhow::Error::new(e)),
+        }
+    }
+
+    /// Serializes `self` into `writer`. Writing is not buffered.
+    ///
+    /// # Errors
+    ///
+    /// If writing fails an error is returned.
+    pub fn write(&self, writer: impl Write) -> anyhow::Result<()> {
+        Ok(bincode::serialize_into(writer, self)?)
+    }
+
+    /// Returns the five-dimensional operator.
+    #[must_use]
+    pub const fn operator(&self) -> &Array5<f64> {
+        &self.operator
+    }
+
+    /// Returns the target momentum fractions.
+    #[must_use]
+    pub fn x(&self) -> &[f64] {
+        &self.x
+    }
+
+    /// Returns the target particle ids.
+    #[must_use]
+    pub fn pids(&self) -> &[i32] {
+        &self.pids
+    }
+
+    /// Returns the source momentum fractions.
+    #[must_use]
+    pub fn x0(&self) -> &[f64] {
+        &self.x0
+    }
+
+    /// Returns the source particle ids.
+    #[must_use]
+    pub fn pids0(&self) -> &[i32] {
+        &self.pids0
+    }
+
+    /// Returns the target scales.
+    #[must_use]
+    pub fn q2(&self) -> &[f64] {
+        &self.q2
+    }
+
+    /// Returns the source scale.
+    #[must_use]
+    pub const fn q2_0(&self) -> f64 {
+        self.q2_0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn new_shape_mismatch() {
+        let result = EvolutionOperator::new(
+            Array5::zeros((2, 1, 2, 1, 1)),
+            vec![0.1, 0.2],
+            vec![21, 1],
+            vec![0.1, 0.2],
+            vec![21],
+            vec![10.0],
+            1.0,
+        );
+
+        assert!(matches!(
+            result,
+            Err(EvolutionOperatorNewError::ShapeMismatch { operator_shape, axes_shape })
+                if (operator_shape == [2, 1, 2, 1, 1]) && (axes_shape == [2, 2, 2, 1, 1])
+        ));
+    }
+
+    #[test]
+    fn read_write() {
+        let operator = EvolutionOperator::new(
+            Array5::from_elem((2, 1, 2, 1, 1), 0.5),
+            vec![0.1, 0.2],
+            vec![21],
+            vec![0.1, 0.2],
+            vec![21],
+            vec![10.0],
+            1.0,
+        )
+        .unwrap();
+
+        let mut buffer = Vec::new();
+        operator.write(&mut buffer).unwrap();
+
+        let operator = EvolutionOperator::read(Cursor::new(buffer)).unwrap();
+
+        assert_eq!(
+            operator.operator(),
+            &Array5::from_elem((2, 1, 2, 1, 1), 0.5)
+        );
+        assert_eq!(operator.x(), [0.1, 0.2]);
+        assert_eq!(operator.pids(), [21]);
+        assert_eq!(operator.x0(), [0.1, 0.2]);
+        assert_eq!(operator.pids0(), [21]);
+        assert_eq!(operator.q2(), [10.0]);
+        assert_eq!(operator.q2_0(), 1.0);
+    }
+}
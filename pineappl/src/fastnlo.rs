@@ -0,0 +1,1727 @@
+//! Module for reading and writing tables in the ASCII format of fastNLO version 2, see
+//! <https://fastnlo.hepforge.org>.
+
+use super::bin::BinRemapper;
+use super::convert::f64_from_usize;
+use super::grid::{Grid, Order};
+use super::import_only_subgrid::{ImportOnlySubgridV1, ImportOnlySubgridV2};
+use super::lumi::LumiEntry;
+use super::sparse_array3::SparseArray3;
+use super::subgrid::{Mu2, Subgrid, SubgridEnum, SubgridParams};
+use ndarray::Array3;
+use std::f64::consts::PI;
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
+use thiserror::Error;
+
+/// Number that fastNLO writes at the beginning of each section of a table.
+const MAGIC: &str = "1234567890";
+
+/// Error type returned by `import` and `export`.
+#[derive(Debug, Error)]
+pub enum FastNloError {
+    /// Returned if a value of the table could not be parsed.
+    #[error("line {line}: expected {expected}, but found `{found}`")]
+    Parse {
+        /// Line number of the value.
+        line: usize,
+        /// Description of what was expected.
+        expected: &'static str,
+        /// The value that was found instead.
+        found: String,
+    },
+    /// Returned if the table ended before it was completely read.
+    #[error("the table ended unexpectedly")]
+    UnexpectedEof,
+    /// Returned if the table uses a feature that is not supported.
+    #[error("the table is not supported: {0}")]
+    Unsupported(String),
+    /// Returned if the sizes of the vectors of a table do not match each other.
+    #[error("the table is inconsistent: {0}")]
+    Inconsistent(String),
+    /// Returned if reading the table failed.
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+type Vector5 = Vec<Vec<Vec<Vec<Vec<f64>>>>>;
+
+/// Function of the two scales of a flexible-scale contribution, which gives its renormalization
+/// or its factorization scale.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FlexibleScale {
+    /// The first scale.
+    Scale1,
+    /// The second scale.
+    Scale2,
+    /// The square root of the sum of the squares of both scales.
+    QuadraticSum,
+    /// The square root of the mean of the squares of both scales.
+    QuadraticMean,
+}
+
+impl FlexibleScale {
+    /// Returns the square of this scale for the scales `mu1` and `mu2`.
+    fn mu2(self, mu1: f64, mu2: f64) -> f64 {
+        match self {
+            Self::Scale1 => mu1 * mu1,
+            Self::Scale2 => mu2 * mu2,
+            Self::QuadraticSum => mu1.mul_add(mu1, mu2 * mu2),
+            Self::QuadraticMean => 0.5 * mu1.mul_add(mu1, mu2 * mu2),
+        }
+    }
+}
+
+struct Tokens<R> {
+    reader: R,
+    line: String,
+    position: usize,
+    line_number: usize,
+}
+
+impl<R: BufRead> Tokens<R> {
+    const fn new(reader: R) -> Self {
+        Self {
+            reader,
+            line: String::new(),
+            position: 0,
+            line_number: 0,
+        }
+    }
+
+    fn next_line(&mut self) -> Result<(), FastNloError> {
+        self.line.clear();
+        self.position = 0;
+
+        if self.reader.read_line(&mut self.line)? == 0 {
+            return Err(FastNloError::UnexpectedEof);
+        }
+
+        self.line_number += 1;
+
+        Ok(())
+    }
+
+    fn token(&mut self) -> Result<String, FastNloError> {
+        loop {
+            let rest = &self.line[self.position..];
+
+            if let Some(start) = rest.find(|c: char| !c.is_whitespace()) {
+                let rest = &rest[start..];
+                let len = rest.find(char::is_whitespace).unwrap_or(rest.len());
+                self.position += start + len;
+
+                return Ok(rest[..len].to_owned());
+            }
+
+            self.next_line()?;
+        }
+    }
+
+    fn parse<T: FromStr>(&mut self, expected: &'static str) -> Result<T, FastNloError> {
+        let token = self.token()?;
+
+        token.parse().map_err(|_| FastNloError::Parse {
+            line: self.line_number,
+            expected,
+            found: token,
+        })
+    }
+
+    fn f64(&mut self) -> Result<f64, FastNloError> {
+        self.parse("a floating-point number")
+    }
+
+    fn int(&mut self) -> Result<i32, FastNloError> {
+        self.parse("an integer")
+    }
+
+    fn size(&mut self) -> Result<usize, FastNloError> {
+        self.parse("a non-negative integer")
+    }
+
+    /// Skips all tokens up to the next separator, which is not consumed.
+    fn skip_to_magic(&mut self) -> Result<(), FastNloError> {
+        loop {
+            let token = self.token()?;
+
+            if token == MAGIC {
+                self.position -= token.len();
+                return Ok(());
+            }
+        }
+    }
+
+    fn magic(&mut self) -> Result<(), FastNloError> {
+        let token = self.token()?;
+
+        if token == MAGIC {
+            Ok(())
+        } else {
+            Err(FastNloError::Parse {
+                line: self.line_number,
+                expected: "the separator 1234567890",
+                found: token,
+            })
+        }
+    }
+
+    /// Reads the next line as a whole. Like fastNLO, the remainder of the current line is
+    /// ignored.
+    fn line(&mut self) -> Result<String, FastNloError> {
+        self.next_line()?;
+        self.position = self.line.len();
+
+        Ok(self.line.trim_end_matches(&['\r', '\n'][..]).to_owned())
+    }
+
+    /// Reads a number of lines followed by the lines themselves.
+    fn lines(&mut self) -> Result<Vec<String>, FastNloError> {
+        let len = self.size()?;
+
+        (0..len).map(|_| self.line()).collect()
+    }
+
+    /// Reads a vector in the format of fastNLO, which writes its size followed by its elements.
+    fn vector<T>(
+        &mut self,
+        mut element: impl FnMut(&mut Self) -> Result<T, FastNloError>,
+    ) -> Result<Vec<T>, FastNloError> {
+        let len = self.size()?;
+
+        (0..len).map(|_| element(self)).collect()
+    }
+
+    fn vector5(&mut self) -> Result<Vector5, FastNloError> {
+        self.vector(|t| t.vector(|t| t.vector(|t| t.vector(|t| t.vector(Self::f64)))))
+    }
+}
+
+struct Scenario {
+    description: Vec<String>,
+    labels: Vec<String>,
+    limits: Vec<(f64, f64)>,
+    normalizations: Vec<f64>,
+}
+
+fn read_scenario(tokens: &mut Tokens<impl BufRead>) -> Result<Scenario, FastNloError> {
+    tokens.magic()?;
+    // `Ipublunits`
+    tokens.int()?;
+    let description = tokens.lines()?;
+    // `Ecms` and `ILOord`
+    tokens.f64()?;
+    tokens.int()?;
+    let bins = tokens.size()?;
+    let dimensions = tokens.size()?;
+
+    if bins == 0 || dimensions == 0 {
+        return Err(FastNloError::Inconsistent(
+            "the table has no bins or no dimensions".to_owned(),
+        ));
+    }
+
+    // fastNLO writes the labels and the bin limits beginning with the last dimension
+    let mut labels = Vec::with_capacity(dimensions);
+
+    for _ in 0..dimensions {
+        labels.push(tokens.line()?);
+    }
+
+    labels.reverse();
+
+    let mut diff_bin = (0..dimensions)
+        .map(|_| tokens.int())
+        .collect::<Result<Vec<_>, _>>()?;
+    diff_bin.reverse();
+
+    let mut limits = vec![(0.0, 0.0); bins * dimensions];
+
+    for bin in 0..bins {
+        for dim in (0..dimensions).rev() {
+            let left = tokens.f64()?;
+            // only bin-wise differential dimensions have an upper limit
+            let right = if diff_bin[dim] == 2 {
+                tokens.f64()?
+            } else {
+                left
+            };
+
+            limits[bin * dimensions + dim] = (left, right);
+        }
+    }
+
+    let normalizations = (0..bins)
+        .map(|_| tokens.f64())
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if tokens.int()? != 0 {
+        return Err(FastNloError::Unsupported("normalized tables".to_owned()));
+    }
+
+    Ok(Scenario {
+        description,
+        labels,
+        limits,
+        normalizations,
+    })
+}
+
+/// Coefficients of a single term of a contribution, indexed by bin, scale node, x node and
+/// subprocess.
+type SigmaTilde = Vec<Vec<Vec<Vec<f64>>>>;
+
+/// Terms proportional to `ln^a(muR^2) ln^b(muF^2)`, where `a` and `b` are the first two members of
+/// each tuple.
+type Terms = Vec<(u32, u32, SigmaTilde)>;
+
+struct Contribution {
+    reference: bool,
+    events: f64,
+    alphas: u32,
+    pdg_ids: Vec<i32>,
+    x_dimensions: i32,
+    subprocesses: Vec<Vec<(i32, i32)>>,
+    x1_nodes: Vec<Vec<f64>>,
+    x2_nodes: Vec<Vec<f64>>,
+    /// The squared renormalization and factorization scales of each bin.
+    scale_nodes: Vec<Vec<Mu2>>,
+    sigma_tildes: Terms,
+}
+
+/// Reads an additive contribution, whose flexible scales are combined into the renormalization and
+/// factorization scale using `mur` and `muf`. If the contribution is a data or multiplicative
+/// contribution, `None` is returned without reading the rest of it.
+fn read_contribution(
+    tokens: &mut Tokens<impl BufRead>,
+    mur: FlexibleScale,
+    muf: FlexibleScale,
+) -> Result<Option<Contribution>, FastNloError> {
+    tokens.magic()?;
+    // `IXsectUnits`
+    tokens.int()?;
+    let data_flag = tokens.int()?;
+    let add_mult_flag = tokens.int()?;
+
+    if data_flag != 0 || add_mult_flag != 0 {
+        return Ok(None);
+    }
+
+    // `IContrFlag1` and `IContrFlag2`
+    tokens.int()?;
+    tokens.int()?;
+    let scale_dep = tokens.int()?;
+    // `CtrbDescript` and `CodeDescript`
+    tokens.lines()?;
+    tokens.lines()?;
+
+    let reference = tokens.int()? > 0;
+    // `IScaleDep`
+    tokens.int()?;
+    let events = tokens.f64()?;
+    let alphas = tokens.parse("a non-negative integer")?;
+    let pdg_ids = tokens.vector(Tokens::int)?;
+    let x_dimensions = tokens.int()?;
+
+    if pdg_ids.len() != 2 {
+        return Err(FastNloError::Unsupported(
+            "contributions without two PDFs".to_owned(),
+        ));
+    }
+
+    if !tokens.vector(Tokens::int)?.is_empty() {
+        return Err(FastNloError::Unsupported(
+            "fragmentation functions".to_owned(),
+        ));
+    }
+
+    // `NFFDim`
+    tokens.int()?;
+    let subprocesses = tokens.size()?;
+    // `IPDFdef1`
+    tokens.int()?;
+    let pdf_def2 = tokens.int()?;
+    // `IPDFdef3`
+    tokens.int()?;
+
+    if pdf_def2 != 0 {
+        return Err(FastNloError::Unsupported(
+            "predefined PDF linear combinations".to_owned(),
+        ));
+    }
+
+    let subprocesses = (0..subprocesses)
+        .map(|_| tokens.vector(|t| Ok((t.int()?, t.int()?))))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let x1_nodes = tokens.vector(|t| t.vector(Tokens::f64))?;
+    let x2_nodes = match x_dimensions {
+        1 => x1_nodes.clone(),
+        2 => tokens.vector(|t| t.vector(Tokens::f64))?,
+        _ => {
+            return Err(FastNloError::Unsupported(format!(
+                "x-node dimension {}",
+                x_dimensions
+            )))
+        }
+    };
+
+    let scale_dimensions = tokens.size()?;
+
+    for _ in 0..scale_dimensions {
+        // `ScaleDescript`
+        tokens.lines()?;
+    }
+
+    let (scale_nodes, sigma_tildes) = if scale_dep == 0 {
+        read_fixed_scales(tokens, scale_dimensions)?
+    } else {
+        read_flexible_scales(tokens, scale_dep, reference, mur, muf)?
+    };
+
+    Ok(Some(Contribution {
+        reference,
+        events,
+        alphas,
+        pdg_ids,
+        x_dimensions,
+        subprocesses,
+        x1_nodes,
+        x2_nodes,
+        scale_nodes,
+        sigma_tildes,
+    }))
+}
+
+fn read_fixed_scales(
+    tokens: &mut Tokens<impl BufRead>,
+    scale_dimensions: usize,
+) -> Result<(Vec<Vec<Mu2>>, Terms), FastNloError> {
+    if scale_dimensions != 1 {
+        return Err(FastNloError::Unsupported(format!(
+            "{} scale dimensions",
+            scale_dimensions
+        )));
+    }
+
+    let scale_vars = tokens.size()?;
+    // `Nscalenode`
+    tokens.size()?;
+    let scale_factors = (0..scale_vars)
+        .map(|_| tokens.f64())
+        .collect::<Result<Vec<_>, _>>()?;
+    let scale_nodes = tokens.vector(|t| t.vector(|t| t.vector(|t| t.vector(Tokens::f64))))?;
+    let sigma_tilde = tokens.vector5()?;
+
+    // only the central scale is needed, the others are recovered from the scale logarithms
+    let central = scale_factors
+        .iter()
+        .position(|&factor| (factor - 1.0).abs() < 1e-8)
+        .ok_or_else(|| FastNloError::Unsupported("tables without central scale".to_owned()))?;
+
+    let inconsistent = || FastNloError::Inconsistent("size of the scale variations".to_owned());
+    let scale_nodes = scale_nodes
+        .into_iter()
+        .map(|mut nodes| {
+            nodes
+                .get_mut(0)
+                .filter(|nodes| nodes.len() > central)
+                .map(|nodes| {
+                    nodes
+                        .swap_remove(central)
+                        .into_iter()
+                        .map(|mu| Mu2 {
+                            ren: mu * mu,
+                            fac: mu * mu,
+                        })
+                        .collect()
+                })
+                .ok_or_else(inconsistent)
+        })
+        .collect::<Result<_, _>>()?;
+    let sigma_tilde = sigma_tilde
+        .into_iter()
+        .map(|mut sigma| {
+            if sigma.len() > central {
+                Ok(sigma.swap_remove(central))
+            } else {
+                Err(inconsistent())
+            }
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok((scale_nodes, vec![(0, 0, sigma_tilde)]))
+}
+
+fn read_flexible_scales(
+    tokens: &mut Tokens<impl BufRead>,
+    scale_dep: i32,
+    reference: bool,
+    mur: FlexibleScale,
+    muf: FlexibleScale,
+) -> Result<(Vec<Vec<Mu2>>, Terms), FastNloError> {
+    let scale_nodes1 = tokens.vector(|t| t.vector(Tokens::f64))?;
+    let scale_nodes2 = tokens.vector(|t| t.vector(Tokens::f64))?;
+
+    if scale_nodes1.len() != scale_nodes2.len() {
+        return Err(FastNloError::Inconsistent("size of ScaleNode2".to_owned()));
+    }
+
+    let (scale_nodes, indices): (Vec<_>, Vec<_>) = scale_nodes1
+        .iter()
+        .zip(&scale_nodes2)
+        .map(|(nodes1, nodes2)| combine_scales(nodes1, nodes2, mur, muf))
+        .unzip();
+
+    let mut sigma_tildes = vec![(0, 0, map_scales(tokens.vector5()?, &indices)?)];
+
+    if scale_dep == 3 || scale_dep >= 5 {
+        sigma_tildes.push((0, 1, map_scales(tokens.vector5()?, &indices)?));
+        sigma_tildes.push((1, 0, map_scales(tokens.vector5()?, &indices)?));
+    }
+
+    if scale_dep >= 6 {
+        sigma_tildes.push((2, 0, map_scales(tokens.vector5()?, &indices)?));
+    }
+
+    if scale_dep == 7 {
+        sigma_tildes.push((0, 2, map_scales(tokens.vector5()?, &indices)?));
+        sigma_tildes.push((1, 1, map_scales(tokens.vector5()?, &indices)?));
+    }
+
+    if reference {
+        for _ in 0..3 {
+            tokens.vector5()?;
+        }
+    }
+
+    Ok((scale_nodes, sigma_tildes))
+}
+
+/// Combines each pair of the flexible scales `nodes1` and `nodes2` of a bin into the squared
+/// renormalization and factorization scale using `mur` and `muf`. Returns the different pairs of
+/// squared scales, sorted, and for each pair of nodes the index of its scales.
+fn combine_scales(
+    nodes1: &[f64],
+    nodes2: &[f64],
+    mur: FlexibleScale,
+    muf: FlexibleScale,
+) -> (Vec<Mu2>, Vec<Vec<usize>>) {
+    let scale = |mu1, mu2| Mu2 {
+        ren: mur.mu2(mu1, mu2),
+        fac: muf.mu2(mu1, mu2),
+    };
+
+    let mut scales: Vec<_> = nodes1
+        .iter()
+        .flat_map(|&mu1| nodes2.iter().map(move |&mu2| scale(mu1, mu2)))
+        .collect();
+    // the same order as the one used by `ImportOnlySubgridV2` when merging
+    scales.sort_by(|a, b| (a.fac, a.ren).partial_cmp(&(b.fac, b.ren)).unwrap());
+    scales.dedup();
+
+    let indices = nodes1
+        .iter()
+        .map(|&mu1| {
+            nodes2
+                .iter()
+                .map(|&mu2| {
+                    let mu2 = scale(mu1, mu2);
+                    scales.iter().position(|other| *other == mu2).unwrap()
+                })
+                .collect()
+        })
+        .collect();
+
+    (scales, indices)
+}
+
+/// Converts a coefficient vector of a flexible-scale table, which is indexed by bin, x node, the
+/// nodes of both scales, and subprocess, into a `SigmaTilde`. The coefficients of the nodes of
+/// both scales are added to the pair of squared scales given by `indices`, see
+/// `combine_scales`.
+fn map_scales(sigma: Vector5, indices: &[Vec<Vec<usize>>]) -> Result<SigmaTilde, FastNloError> {
+    let inconsistent = || FastNloError::Inconsistent("size of SigmaTilde".to_owned());
+
+    if sigma.len() != indices.len() {
+        return Err(inconsistent());
+    }
+
+    sigma
+        .into_iter()
+        .zip(indices)
+        .map(|(bin, indices)| {
+            let nodes = indices.iter().flatten().max().map_or(0, |&index| index + 1);
+            let mut result = vec![Vec::with_capacity(bin.len()); nodes];
+
+            for x in bin {
+                let mut values = vec![Vec::new(); nodes];
+
+                if x.len() != indices.len() {
+                    return Err(inconsistent());
+                }
+
+                for (scale1, indices) in x.into_iter().zip(indices) {
+                    if scale1.len() != indices.len() {
+                        return Err(inconsistent());
+                    }
+
+                    for (scale2, &index) in scale1.into_iter().zip(indices) {
+                        let values = &mut values[index];
+
+                        if values.is_empty() {
+                            *values = scale2;
+                        } else if values.len() == scale2.len() {
+                            for (value, other) in values.iter_mut().zip(scale2) {
+                                *value += other;
+                            }
+                        } else {
+                            return Err(inconsistent());
+                        }
+                    }
+                }
+
+                for (node, values) in values.into_iter().enumerate() {
+                    result[node].push(values);
+                }
+            }
+
+            Ok(result)
+        })
+        .collect()
+}
+
+fn pdg_id(id: i32) -> Result<i32, FastNloError> {
+    match id {
+        0 => Ok(21),
+        -6..=6 => Ok(id),
+        _ => Err(FastNloError::Unsupported(format!("parton id {}", id))),
+    }
+}
+
+/// Returns the indices of the first and second x node for each x index of a `SigmaTilde`. If the
+/// table has a single x-node dimension, only the lower half of the matrix is stored.
+fn x_indices(x_dimensions: i32, nx1: usize, nx2: usize) -> Vec<(usize, usize)> {
+    if x_dimensions == 1 {
+        (0..nx1)
+            .flat_map(|a| (0..=a).map(move |b| (a, b)))
+            .collect()
+    } else {
+        (0..nx1)
+            .flat_map(|a| (0..nx2).map(move |b| (a, b)))
+            .collect()
+    }
+}
+
+fn binomial(n: u32, k: u32) -> f64 {
+    (0..k).fold(1.0, |result, i| {
+        result * f64::from(n - i) / f64::from(i + 1)
+    })
+}
+
+fn lumi(subprocesses: &[Vec<(i32, i32)>]) -> Result<Vec<LumiEntry>, FastNloError> {
+    subprocesses
+        .iter()
+        .map(|pairs| {
+            if pairs.is_empty() {
+                return Err(FastNloError::Inconsistent(
+                    "subprocess without parton pairs".to_owned(),
+                ));
+            }
+
+            Ok(LumiEntry::new(
+                pairs
+                    .iter()
+                    .map(|&(a, b)| Ok((vec![pdg_id(a)?, pdg_id(b)?], 1.0)))
+                    .collect::<Result<_, FastNloError>>()?,
+            ))
+        })
+        .collect()
+}
+
+#[allow(clippy::float_cmp)]
+fn convert_contribution(contribution: &Contribution, bins: usize) -> Result<Grid, FastNloError> {
+    // a term proportional to `ln^a(muR^2) ln^b(muF^2)` contributes to all orders with
+    // `ln^i(xir^2) ln^j(xif^2)`, where `i <= a` and `j <= b`
+    let mut logs: Vec<_> = contribution
+        .sigma_tildes
+        .iter()
+        .flat_map(|&(a, b, _)| (0..=a).flat_map(move |i| (0..=b).map(move |j| (i, j))))
+        .collect();
+    logs.sort_unstable();
+    logs.dedup();
+
+    let orders = logs
+        .iter()
+        .map(|&(i, j)| Order::new(contribution.alphas, 0, i, j, 0))
+        .collect();
+    let mut grid = Grid::new(
+        lumi(&contribution.subprocesses)?,
+        orders,
+        (0..=bins).map(f64_from_usize).collect(),
+        SubgridParams::default(),
+    );
+
+    let inconsistent = |what| FastNloError::Inconsistent(format!("size of {}", what));
+    let factor = (2.0 * PI).powf(-f64::from(contribution.alphas)) / contribution.events;
+    let subprocesses = contribution.subprocesses.len();
+
+    for bin in 0..bins {
+        let x1 = contribution
+            .x1_nodes
+            .get(bin)
+            .ok_or_else(|| inconsistent("XNode1"))?;
+        let x2 = contribution
+            .x2_nodes
+            .get(bin)
+            .ok_or_else(|| inconsistent("XNode2"))?;
+        let mu2 = contribution
+            .scale_nodes
+            .get(bin)
+            .ok_or_else(|| inconsistent("ScaleNode"))?;
+        let indices = x_indices(contribution.x_dimensions, x1.len(), x2.len());
+        let mut arrays =
+            vec![vec![Array3::zeros((mu2.len(), x1.len(), x2.len())); subprocesses]; logs.len()];
+
+        for (a, b, sigma_tilde) in &contribution.sigma_tildes {
+            let sigma = sigma_tilde
+                .get(bin)
+                .filter(|sigma| {
+                    sigma.len() == mu2.len()
+                        && sigma.iter().all(|node| {
+                            node.len() == indices.len()
+                                && node.iter().all(|values| values.len() == subprocesses)
+                        })
+                })
+                .ok_or_else(|| inconsistent("SigmaTilde"))?;
+
+            for (order, &(i, j)) in logs.iter().enumerate() {
+                if i > *a || j > *b {
+                    continue;
+                }
+
+                let coefficient = binomial(*a, i) * binomial(*b, j);
+
+                for (node, (mu2, sigma)) in mu2.iter().zip(sigma).enumerate() {
+                    let log =
+                        mu2.ren.ln().powf(f64::from(*a - i)) * mu2.fac.ln().powf(f64::from(*b - j));
+
+                    for (&(ix1, ix2), values) in indices.iter().zip(sigma) {
+                        for (array, value) in arrays[order].iter_mut().zip(values) {
+                            array[[node, ix1, ix2]] += coefficient * log * value;
+                        }
+                    }
+                }
+            }
+        }
+
+        // if both scales are exactly the same, a single scale is stored, which can be exported
+        let single_scale = mu2.iter().all(|mu2| mu2.ren == mu2.fac);
+        let q2: Vec<_> = mu2.iter().map(|mu2| mu2.fac).collect();
+
+        for (order, arrays) in arrays.into_iter().enumerate() {
+            for (lumi, mut array) in arrays.into_iter().enumerate() {
+                if array.iter().all(|&value| value == 0.0) {
+                    continue;
+                }
+
+                for ((_, ix1, ix2), value) in array.indexed_iter_mut() {
+                    *value *= factor * x1[ix1] * x2[ix2];
+                }
+
+                let array = SparseArray3::from_ndarray(&array, 0, q2.len());
+                let subgrid = if single_scale {
+                    ImportOnlySubgridV1::new(array, q2.clone(), x1.clone(), x2.clone()).into()
+                } else {
+                    ImportOnlySubgridV2::new(array, mu2.clone(), x1.clone(), x2.clone()).into()
+                };
+
+                grid.set_subgrid(order, bin, lumi, subgrid);
+            }
+        }
+    }
+
+    Ok(grid)
+}
+
+/// Reads a table in the ASCII format of fastNLO version 2 from `reader` and converts it into a
+/// `Grid`.
+///
+/// Fixed-scale and flexible-scale contributions are supported. The two scales of a flexible-scale
+/// contribution are combined into its renormalization scale using `mur` and into its
+/// factorization scale using `muf`; if both scales are different, the subgrids are of type
+/// `ImportOnlySubgridV2` instead of `ImportOnlySubgridV1`. The observables of the table are stored
+/// in a `BinRemapper`. Data and multiplicative contributions, for instance non-perturbative
+/// corrections, are skipped.
+///
+/// # Errors
+///
+/// Returns an error if the table can not be read or parsed, or if it uses features that are not
+/// supported, like fragmentation functions or normalized tables.
+pub fn import(
+    reader: impl BufRead,
+    mur: FlexibleScale,
+    muf: FlexibleScale,
+) -> Result<Grid, FastNloError> {
+    let mut tokens = Tokens::new(reader);
+
+    tokens.magic()?;
+    let version = tokens.int()?;
+
+    if !(20000..30000).contains(&version) {
+        return Err(FastNloError::Unsupported(format!(
+            "table version {}",
+            version
+        )));
+    }
+
+    // `ScenName`
+    tokens.token()?;
+    let contributions = tokens.size()?;
+
+    // `Nmult`, `Ndata`, `NuserString`, `NuserInt`, `NuserFloat` and `Imachine`
+    for _ in 0..6 {
+        tokens.int()?;
+    }
+
+    let scenario = read_scenario(&mut tokens)?;
+    let bins = scenario.normalizations.len();
+    let mut result: Option<Grid> = None;
+    let mut pdg_ids = Vec::new();
+
+    for index in 0..contributions {
+        let contribution = match read_contribution(&mut tokens, mur, muf)? {
+            // reference contributions are only used to check the interpolation
+            Some(contribution) if contribution.reference => continue,
+            Some(contribution) => contribution,
+            None => {
+                // skip the rest of the data or multiplicative contribution, which ends with the
+                // separator of the next contribution
+                if index + 1 < contributions {
+                    tokens.skip_to_magic()?;
+                }
+
+                continue;
+            }
+        };
+
+        pdg_ids.clone_from(&contribution.pdg_ids);
+        let grid = convert_contribution(&contribution, bins)?;
+
+        if let Some(result) = &mut result {
+            // the grids have the same bins and convolutions
+            result.merge(grid).unwrap_or_else(|_| unreachable!());
+        } else {
+            result = Some(grid);
+        }
+    }
+
+    let mut grid = result
+        .ok_or_else(|| FastNloError::Unsupported("tables without contributions".to_owned()))?;
+
+    grid.set_remapper(
+        BinRemapper::new(scenario.normalizations, scenario.limits)
+            .unwrap_or_else(|_| unreachable!()),
+    )
+    .unwrap_or_else(|_| unreachable!());
+
+    for (index, pdg_id) in pdg_ids.iter().enumerate() {
+        grid.set_key_value(&format!("initial_state_{}", index + 1), &pdg_id.to_string());
+    }
+
+    for (index, label) in scenario.labels.iter().enumerate() {
+        grid.set_key_value(&format!("x{}_label", index + 1), label);
+    }
+
+    grid.set_key_value("description", &scenario.description.join("\n"));
+
+    Ok(grid)
+}
+
+/// Subprocess of an exported table: the index of the channel it belongs to, the factor of the
+/// luminosity function, and the pairs of fastNLO parton ids.
+type Subprocess = (usize, f64, Vec<(i32, i32)>);
+
+/// Nodes and coefficients of a single bin of an exported contribution. The coefficients are
+/// indexed by scale node, x index and subprocess.
+struct ExportBin {
+    q2: Vec<f64>,
+    x1: Vec<f64>,
+    x2: Vec<f64>,
+    sigma: Vec<f64>,
+}
+
+fn fastnlo_id(pdg_id: i32) -> Result<i32, FastNloError> {
+    match pdg_id {
+        0 | 21 => Ok(0),
+        -6..=6 => Ok(pdg_id),
+        _ => Err(FastNloError::Unsupported(format!("PDG id {}", pdg_id))),
+    }
+}
+
+/// Splits each channel into subprocesses, one for each different factor of its luminosity
+/// function. Only identical factors share a subprocess, which is why the factors are compared
+/// exactly.
+#[allow(clippy::float_cmp)]
+fn subprocesses(lumi: &[LumiEntry]) -> Result<Vec<Subprocess>, FastNloError> {
+    let mut subprocesses: Vec<Subprocess> = Vec::new();
+
+    for (index, entry) in lumi.iter().enumerate() {
+        let start = subprocesses.len();
+
+        for (pids, factor) in entry.entry() {
+            let pair = (fastnlo_id(pids[0])?, fastnlo_id(pids[1])?);
+
+            if let Some((_, _, pairs)) = subprocesses[start..]
+                .iter_mut()
+                .find(|(_, other, _)| other == factor)
+            {
+                pairs.push(pair);
+            } else {
+                subprocesses.push((index, *factor, vec![pair]));
+            }
+        }
+    }
+
+    Ok(subprocesses)
+}
+
+fn union(grids: impl Iterator<Item = Vec<f64>>) -> Vec<f64> {
+    let mut union: Vec<_> = grids.flatten().collect();
+    union.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    union.dedup();
+    union
+}
+
+fn position(grid: &[f64], value: f64) -> usize {
+    grid.binary_search_by(|probe| probe.partial_cmp(&value).unwrap())
+        .unwrap_or_else(|_| unreachable!())
+}
+
+fn export_bin(
+    grid: &Grid,
+    order: usize,
+    bin: usize,
+    subprocesses: &[Subprocess],
+) -> Result<ExportBin, FastNloError> {
+    let mut subgrids = Vec::new();
+
+    for lumi in 0..grid.lumi().len() {
+        match grid.subgrid(order, bin, lumi) {
+            SubgridEnum::EmptySubgridV1(_) => {}
+            SubgridEnum::ImportOnlySubgridV1(subgrid) => subgrids.push((lumi, subgrid)),
+            _ => {
+                return Err(FastNloError::Unsupported(
+                    "subgrids that are not of type `ImportOnlySubgridV1`".to_owned(),
+                ))
+            }
+        }
+    }
+
+    let q2 = union(
+        subgrids
+            .iter()
+            .map(|(_, subgrid)| subgrid.q2_grid().into_owned()),
+    );
+    let x1 = union(
+        subgrids
+            .iter()
+            .map(|(_, subgrid)| subgrid.x1_grid().into_owned()),
+    );
+    let x2 = union(
+        subgrids
+            .iter()
+            .map(|(_, subgrid)| subgrid.x2_grid().into_owned()),
+    );
+    let alphas = grid.orders()[order].alphas;
+    let factor = (2.0 * PI).powf(f64::from(alphas));
+    let mut sigma = vec![0.0; q2.len() * x1.len() * x2.len() * subprocesses.len()];
+
+    for (lumi, subgrid) in subgrids {
+        let q2_grid = subgrid.q2_grid();
+        let x1_grid = subgrid.x1_grid();
+        let x2_grid = subgrid.x2_grid();
+
+        for (index, value) in subgrid.iter() {
+            let (iq2, ix1, ix2) = (index[0], index[1], index[2]);
+            let node = position(&q2, q2_grid[iq2]);
+            let x = position(&x1, x1_grid[ix1]) * x2.len() + position(&x2, x2_grid[ix2]);
+
+            for (index, (_, lumi_factor, _)) in subprocesses
+                .iter()
+                .enumerate()
+                .filter(|(_, (index, _, _))| *index == lumi)
+            {
+                sigma[(node * x1.len() * x2.len() + x) * subprocesses.len() + index] +=
+                    value * lumi_factor * factor / (x1_grid[ix1] * x2_grid[ix2]);
+            }
+        }
+    }
+
+    Ok(ExportBin { q2, x1, x2, sigma })
+}
+
+fn write_lines(writer: &mut impl Write, lines: &[&str]) -> io::Result<()> {
+    writeln!(writer, "{}", lines.len())?;
+
+    for line in lines {
+        writeln!(writer, "{}", line)?;
+    }
+
+    Ok(())
+}
+
+fn write_vector(writer: &mut impl Write, values: &[f64]) -> io::Result<()> {
+    writeln!(writer, "{}", values.len())?;
+
+    for value in values {
+        writeln!(writer, "{:e}", value)?;
+    }
+
+    Ok(())
+}
+
+fn write_scenario(writer: &mut impl Write, grid: &Grid, alphas: u32) -> io::Result<()> {
+    let bin_info = grid.bin_info();
+    let dimensions = bin_info.dimensions();
+    let key_values = grid.key_values();
+    let key_value = |key: &str| key_values.and_then(|key_values| key_values.get(key));
+    let description: Vec<_> =
+        key_value("description").map_or_else(Vec::new, |description| description.lines().collect());
+    let labels: Vec<_> = (1..=dimensions)
+        .map(|dimension| {
+            key_value(&format!("x{}_label", dimension))
+                .map_or_else(|| format!("x{}", dimension), Clone::clone)
+        })
+        .collect();
+    let left: Vec<_> = (0..dimensions).map(|dim| bin_info.left(dim)).collect();
+    let right: Vec<_> = (0..dimensions).map(|dim| bin_info.right(dim)).collect();
+
+    writeln!(writer, "{}", MAGIC)?;
+    // `Ipublunits`; the unit of the cross sections is not known
+    writeln!(writer, "0")?;
+    write_lines(writer, &description)?;
+    // `Ecms` is not known
+    writeln!(writer, "0")?;
+    writeln!(writer, "{}", alphas)?;
+    writeln!(writer, "{}", bin_info.bins())?;
+    writeln!(writer, "{}", dimensions)?;
+
+    // fastNLO writes the labels and the bin limits beginning with the last dimension
+    for label in labels.iter().rev() {
+        writeln!(writer, "{}", label)?;
+    }
+
+    // all dimensions are bin-wise differential
+    for _ in 0..dimensions {
+        writeln!(writer, "2")?;
+    }
+
+    for bin in 0..bin_info.bins() {
+        for dim in (0..dimensions).rev() {
+            writeln!(writer, "{:e}", left[dim][bin])?;
+            writeln!(writer, "{:e}", right[dim][bin])?;
+        }
+    }
+
+    for normalization in bin_info.normalizations() {
+        writeln!(writer, "{:e}", normalization)?;
+    }
+
+    // `INormFlag`
+    writeln!(writer, "0")
+}
+
+fn write_contribution(
+    writer: &mut impl Write,
+    grid: &Grid,
+    order: usize,
+    subprocesses: &[Subprocess],
+) -> Result<(), FastNloError> {
+    let bins = (0..grid.bin_info().bins())
+        .map(|bin| export_bin(grid, order, bin, subprocesses))
+        .collect::<Result<Vec<_>, _>>()?;
+    let key_values = grid.key_values();
+    let pdg_ids: Vec<_> = (1..=2)
+        .map(|index| {
+            key_values
+                .and_then(|key_values| key_values.get(&format!("initial_state_{}", index)))
+                .map_or("2212", String::as_str)
+        })
+        .collect();
+    let alphas = grid.orders()[order].alphas;
+
+    writeln!(writer, "{}", MAGIC)?;
+    // `IXsectUnits`, `IDataFlag`, `IAddMultFlag`, `IContrFlag1`, `IContrFlag2` and `NScaleDep`
+    writeln!(writer, "0\n0\n0\n1\n{}\n0", order + 1)?;
+    write_lines(writer, &[&format!("alphas^{}", alphas)])?;
+    write_lines(writer, &["PineAPPL"])?;
+    // `IRef`, `IScaleDep` and `Nevt`
+    writeln!(writer, "0\n0\n1")?;
+    writeln!(writer, "{}", alphas)?;
+    writeln!(writer, "2\n{}\n{}", pdg_ids[0], pdg_ids[1])?;
+    // `NPDFDim`, `NFragFunc`, `NFFDim`
+    writeln!(writer, "2\n0\n0")?;
+    writeln!(writer, "{}", subprocesses.len())?;
+    // `IPDFdef1`, `IPDFdef2` and `IPDFdef3`; the linear combinations are given explicitly
+    writeln!(writer, "3\n0\n0")?;
+
+    for (_, _, pairs) in subprocesses {
+        writeln!(writer, "{}", pairs.len())?;
+
+        for (a, b) in pairs {
+            writeln!(writer, "{} {}", a, b)?;
+        }
+    }
+
+    writeln!(writer, "{}", bins.len())?;
+
+    for bin in &bins {
+        write_vector(writer, &bin.x1)?;
+    }
+
+    writeln!(writer, "{}", bins.len())?;
+
+    for bin in &bins {
+        write_vector(writer, &bin.x2)?;
+    }
+
+    // `NScaleDim` and `ScaleDescript`
+    writeln!(writer, "1")?;
+    write_lines(writer, &["Q"])?;
+    // only the central scale is exported
+    writeln!(writer, "1")?;
+    writeln!(
+        writer,
+        "{}",
+        bins.iter().map(|bin| bin.q2.len()).max().unwrap_or(0)
+    )?;
+    writeln!(writer, "1")?;
+
+    writeln!(writer, "{}", bins.len())?;
+
+    for bin in &bins {
+        let mu: Vec<_> = bin.q2.iter().map(|q2| q2.sqrt()).collect();
+        writeln!(writer, "1\n1")?;
+        write_vector(writer, &mu)?;
+    }
+
+    writeln!(writer, "{}", bins.len())?;
+
+    for bin in &bins {
+        writeln!(writer, "1\n{}", bin.q2.len())?;
+
+        let x_len = bin.x1.len() * bin.x2.len();
+
+        for node in 0..bin.q2.len() {
+            writeln!(writer, "{}", x_len)?;
+
+            for x in 0..x_len {
+                let start = (node * x_len + x) * subprocesses.len();
+                write_vector(writer, &bin.sigma[start..start + subprocesses.len()])?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes `grid` as a fixed-scale table in the ASCII format of fastNLO version 2 to `writer`.
+///
+/// Each order becomes a contribution, and each channel becomes one subprocess for each different
+/// factor of its luminosity function. The bins, including their remapper, become the observable
+/// bins of the table. The x and q2 nodes of each bin are the union of the nodes of its subgrids,
+/// which must be of type `ImportOnlySubgridV1`, as produced by `Grid::optimize`; the table is
+/// therefore compact only if the subgrids share their nodes. Only the central scale is exported,
+/// which means that orders with logarithms of the scale factors are skipped.
+///
+/// # Errors
+///
+/// Returns an error if writing fails or if `grid` can not be represented as a fastNLO table, for
+/// instance because it has subgrids of other types, two orders with the same power of the strong
+/// coupling, orders with powers of the electromagnetic coupling, which fastNLO tables can not
+/// store, or channels with partons other than quarks and gluons.
+pub fn export(grid: &Grid, mut writer: impl Write) -> Result<(), FastNloError> {
+    if grid.convolutions() != 2 {
+        return Err(FastNloError::Unsupported(
+            "grids without two convolutions".to_owned(),
+        ));
+    }
+
+    let orders: Vec<_> = grid
+        .orders()
+        .iter()
+        .enumerate()
+        .filter(|(_, order)| order.logxir == 0 && order.logxif == 0 && order.logxia == 0)
+        .collect();
+
+    if orders.iter().any(|(_, order)| order.alpha != 0) {
+        return Err(FastNloError::Unsupported(
+            "orders with powers of the electromagnetic coupling".to_owned(),
+        ));
+    }
+
+    for (index, (_, order)) in orders.iter().enumerate() {
+        if orders[..index]
+            .iter()
+            .any(|(_, other)| other.alphas == order.alphas)
+        {
+            return Err(FastNloError::Unsupported(
+                "orders with the same power of the strong coupling".to_owned(),
+            ));
+        }
+    }
+
+    let subprocesses = subprocesses(grid.lumi())?;
+    let lowest_alphas = orders.iter().map(|(_, order)| order.alphas).min();
+
+    writeln!(writer, "{}", MAGIC)?;
+    writeln!(writer, "23000")?;
+    writeln!(writer, "pineappl")?;
+    writeln!(writer, "{}", orders.len())?;
+    // `Nmult`, `Ndata`, `NuserString`, `NuserInt`, `NuserFloat` and `Imachine`
+    writeln!(writer, "0\n0\n0\n0\n0\n0")?;
+
+    write_scenario(&mut writer, grid, lowest_alphas.unwrap_or(0))?;
+
+    for (order, _) in orders {
+        write_contribution(&mut writer, grid, order, &subprocesses)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::Ntuple;
+    use crate::lumi_entry;
+    use crate::pdf::ClosurePdf;
+    use float_cmp::approx_eq;
+
+    fn vector<T>(elements: &[T], element: impl Fn(&T) -> String) -> String {
+        let elements: Vec<_> = elements.iter().map(element).collect();
+        format!("{} {}", elements.len(), elements.join(" "))
+    }
+
+    const HEADER: &str = "1234567890
+23000
+test
+1
+0 0 0 0 0 0
+1234567890
+-12
+1
+dsigma/dx_[pb]
+13000
+2
+";
+
+    fn contribution(scale_dep: i32, alphas: u32, pdf_dim: i32, pairs: &str) -> String {
+        format!(
+            "1234567890
+12
+0 0
+1 1
+{}
+1
+description
+1
+code
+0
+0
+2.0
+{}
+2 2212 2212
+{}
+0
+0
+1
+3 0 0
+{}
+",
+            scale_dep, alphas, pdf_dim, pairs
+        )
+    }
+
+    #[test]
+    fn import_fixed_scale_table() {
+        let x_nodes = [0.1, 0.5];
+        let mu = [10.0, 20.0];
+        // indexed by bin, scale node and x index of the lower half of the matrix
+        let sigma = [
+            [[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]],
+            [[-1.0, 0.0, 0.5], [0.0, 0.0, 0.0]],
+        ];
+        let scale_nodes = vector(&[0, 1], |_| {
+            vector(&[0], |_| vector(&[0], |_| vector(&mu, ToString::to_string)))
+        });
+        let sigma_tilde = vector(&sigma, |bin| {
+            vector(&[0], |_| {
+                vector(bin, |node| {
+                    vector(node, |value| vector(&[*value], ToString::to_string))
+                })
+            })
+        });
+        let table = format!(
+            "{}2\n1\nx\n2\n0.0 1.0\n1.0 3.0\n1.0 2.0\n0\n{}{}\n1\n1\nmu\n1 2\n1.0\n{}\n{}\n",
+            HEADER,
+            contribution(0, 2, 1, "1 0 0"),
+            vector(&[0, 1], |_| vector(&x_nodes, ToString::to_string)),
+            scale_nodes,
+            sigma_tilde,
+        );
+
+        let grid = import(
+            table.as_bytes(),
+            FlexibleScale::Scale1,
+            FlexibleScale::Scale1,
+        )
+        .unwrap();
+
+        assert_eq!(grid.lumi().len(), 1);
+        assert_eq!(grid.lumi()[0].entry(), [(vec![21, 21], 1.0)]);
+        assert_eq!(grid.orders(), [Order::new(2, 0, 0, 0, 0)]);
+        assert_eq!(grid.key_values().unwrap()["x1_label"], "x");
+        assert_eq!(grid.key_values().unwrap()["description"], "dsigma/dx_[pb]");
+        assert_eq!(grid.bin_info().left(0), [0.0, 1.0]);
+        assert_eq!(grid.bin_info().right(0), [1.0, 3.0]);
+
+        let xfx = |_, x: f64, q2: f64| x * (1.0 - x) / q2.ln();
+        let alphas = |q2: f64| 1.0 / q2.ln();
+        let pdf = ClosurePdf::new(2212, xfx, alphas);
+        let indices = [(0, 0), (1, 0), (1, 1)];
+
+        let expected: Vec<_> = sigma
+            .iter()
+            .zip(&[1.0, 2.0])
+            .map(|(bin, size)| {
+                bin.iter()
+                    .zip(&mu)
+                    .map(|(node, mu)| {
+                        let q2 = mu * mu;
+                        node.iter()
+                            .zip(&indices)
+                            .map(|(value, &(a, b))| {
+                                (alphas(q2) / (2.0 * PI)).powi(2)
+                                    * xfx(21, x_nodes[a], q2)
+                                    * xfx(21, x_nodes[b], q2)
+                                    * value
+                                    / 2.0
+                            })
+                            .sum::<f64>()
+                    })
+                    .sum::<f64>()
+                    / size
+            })
+            .collect();
+
+        let result = grid.convolute(&[&pdf, &pdf], &[], &[], &[], &[(1.0, 1.0, 1.0)]);
+
+        for (&result, &expected) in result.iter().zip(&expected) {
+            assert!(approx_eq!(f64, result, expected, ulps = 16));
+        }
+    }
+
+    #[test]
+    fn import_flexible_scale_table() {
+        let x1_nodes = [0.1, 0.3];
+        let x2_nodes = [0.2];
+        let mu1 = [10.0, 20.0];
+        let mu2 = [5.0, 7.0];
+        // indexed by term, x index, first scale node and second scale node
+        let sigma = [
+            [[[1.0, 2.0], [3.0, 0.0]], [[0.5, 0.0], [0.0, 1.5]]],
+            [[[0.1, 0.2], [0.0, 0.0]], [[0.0, 0.3], [0.4, 0.0]]],
+            [[[0.0, 0.0], [-0.2, 0.1]], [[0.3, 0.0], [0.0, -0.1]]],
+        ];
+        let terms: Vec<_> = sigma
+            .iter()
+            .map(|term| {
+                vector(&[0], |_| {
+                    vector(term, |x| {
+                        vector(x, |s1| {
+                            vector(s1, |value| vector(&[*value], ToString::to_string))
+                        })
+                    })
+                })
+            })
+            .collect();
+        let table = format!(
+            "{}1\n2\ny\nx\n1 2\n2.0 0.0 1.0\n4.0\n0\n{}{}\n{}\n1\n1\nmu\n{}\n{}\n{}\n",
+            HEADER,
+            contribution(3, 1, 2, "2 1 -1 2 -2"),
+            vector(&[0], |_| vector(&x1_nodes, ToString::to_string)),
+            vector(&[0], |_| vector(&x2_nodes, ToString::to_string)),
+            vector(&[0], |_| vector(&mu1, ToString::to_string)),
+            vector(&[0], |_| vector(&mu2, ToString::to_string)),
+            terms.join("\n"),
+        );
+
+        let grid = import(
+            table.as_bytes(),
+            FlexibleScale::Scale1,
+            FlexibleScale::Scale1,
+        )
+        .unwrap();
+
+        assert_eq!(
+            grid.lumi()[0].entry(),
+            [(vec![1, -1], 1.0), (vec![2, -2], 1.0)]
+        );
+        assert_eq!(
+            grid.orders(),
+            [
+                Order::new(1, 0, 0, 0, 0),
+                Order::new(1, 0, 0, 1, 0),
+                Order::new(1, 0, 1, 0, 0)
+            ]
+        );
+        assert_eq!(grid.key_values().unwrap()["x1_label"], "x");
+        assert_eq!(grid.key_values().unwrap()["x2_label"], "y");
+        assert_eq!(grid.bin_info().left(0), [0.0]);
+        assert_eq!(grid.bin_info().right(0), [1.0]);
+        assert_eq!(grid.bin_info().left(1), [2.0]);
+        assert_eq!(grid.bin_info().right(1), [2.0]);
+        assert!(matches!(
+            grid.subgrid(0, 0, 0),
+            SubgridEnum::ImportOnlySubgridV1(_)
+        ));
+
+        let xfx = |_, x: f64, q2: f64| x * (1.0 - x) / q2.ln();
+        let alphas = |q2: f64| 1.0 / q2.ln();
+        let pdf = ClosurePdf::new(2212, xfx, alphas);
+        let xi = [(1.0, 1.0, 1.0), (2.0, 0.5, 1.0), (0.5, 2.0, 1.0)];
+
+        for &(mur, muf) in &[
+            (FlexibleScale::Scale1, FlexibleScale::Scale1),
+            (FlexibleScale::Scale1, FlexibleScale::Scale2),
+            (FlexibleScale::QuadraticSum, FlexibleScale::QuadraticMean),
+        ] {
+            let grid = import(table.as_bytes(), mur, muf).unwrap();
+            let result = grid.convolute(&[&pdf, &pdf], &[], &[], &[], &xi);
+
+            for (&(xir, xif, _), &result) in xi.iter().zip(&result) {
+                let mut expected = 0.0;
+
+                for (ix, &x1) in x1_nodes.iter().enumerate() {
+                    for (s1, &mu1) in mu1.iter().enumerate() {
+                        for (s2, &mu2) in mu2.iter().enumerate() {
+                            let q2r = xir * xir * mur.mu2(mu1, mu2);
+                            let q2f = xif * xif * muf.mu2(mu1, mu2);
+                            let value = q2r.ln().mul_add(
+                                sigma[2][ix][s1][s2],
+                                q2f.ln().mul_add(sigma[1][ix][s1][s2], sigma[0][ix][s1][s2]),
+                            );
+
+                            expected += alphas(q2r) / (2.0 * PI)
+                                * 2.0
+                                * xfx(1, x1, q2f)
+                                * xfx(1, x2_nodes[0], q2f)
+                                * value
+                                / 2.0
+                                / 4.0;
+                        }
+                    }
+                }
+
+                assert!(approx_eq!(f64, result, expected, ulps = 16));
+            }
+        }
+
+        // with different scales the subgrids store both of them
+        let grid = import(
+            table.as_bytes(),
+            FlexibleScale::Scale1,
+            FlexibleScale::Scale2,
+        )
+        .unwrap();
+
+        assert!(matches!(
+            grid.subgrid(0, 0, 0),
+            SubgridEnum::ImportOnlySubgridV2(_)
+        ));
+        assert_eq!(grid.subgrid(0, 0, 0).mu2_grid().len(), 4);
+    }
+
+    #[test]
+    fn merge_flexible_and_fixed_scale_tables() {
+        let x1_nodes = [0.1, 0.3];
+        let x2_nodes = [0.2];
+        let mu1 = [10.0, 20.0];
+        let mu2 = [5.0, 7.0];
+        // indexed by term, x index, first scale node and second scale node
+        let sigma = [
+            [[[1.0, 2.0], [3.0, 0.0]], [[0.5, 0.0], [0.0, 1.5]]],
+            [[[0.1, 0.2], [0.0, 0.0]], [[0.0, 0.3], [0.4, 0.0]]],
+        ];
+        let terms: Vec<_> = sigma
+            .iter()
+            .map(|term| {
+                vector(&[0], |_| {
+                    vector(term, |x| {
+                        vector(x, |s1| {
+                            vector(s1, |value| vector(&[*value], ToString::to_string))
+                        })
+                    })
+                })
+            })
+            .collect();
+        let table = format!(
+            "{}1\n1\nx\n1\n0.0 1.0\n0\n{}{}\n{}\n1\n1\nmu\n{}\n{}\n{}\n",
+            HEADER,
+            contribution(2, 1, 2, "2 1 -1 2 -2"),
+            vector(&[0], |_| vector(&x1_nodes, ToString::to_string)),
+            vector(&[0], |_| vector(&x2_nodes, ToString::to_string)),
+            vector(&[0], |_| vector(&mu1, ToString::to_string)),
+            vector(&[0], |_| vector(&mu2, ToString::to_string)),
+            terms.join("\n"),
+        );
+
+        // the same table imported with equal scales gives `ImportOnlySubgridV1`s, with different
+        // scales `ImportOnlySubgridV2`s
+        let fixed = || {
+            import(
+                table.as_bytes(),
+                FlexibleScale::Scale1,
+                FlexibleScale::Scale1,
+            )
+            .unwrap()
+        };
+        let flexible = || {
+            import(
+                table.as_bytes(),
+                FlexibleScale::Scale1,
+                FlexibleScale::Scale2,
+            )
+            .unwrap()
+        };
+
+        assert!(matches!(
+            fixed().subgrid(0, 0, 0),
+            SubgridEnum::ImportOnlySubgridV1(_)
+        ));
+        assert!(matches!(
+            flexible().subgrid(0, 0, 0),
+            SubgridEnum::ImportOnlySubgridV2(_)
+        ));
+
+        let xfx = |_, x: f64, q2: f64| x * (1.0 - x) / q2.ln();
+        let alphas = |q2: f64| 1.0 / q2.ln();
+        let pdf = ClosurePdf::new(2212, xfx, alphas);
+        let xi = [(1.0, 1.0, 1.0), (2.0, 0.5, 1.0), (0.5, 2.0, 1.0)];
+
+        let expected: Vec<_> = fixed()
+            .convolute(&[&pdf, &pdf], &[], &[], &[], &xi)
+            .iter()
+            .zip(flexible().convolute(&[&pdf, &pdf], &[], &[], &[], &xi))
+            .map(|(fixed, flexible)| fixed + flexible)
+            .collect();
+
+        // merge in both directions, the result always stores both scales
+        for (mut grid, other) in vec![(flexible(), fixed()), (fixed(), flexible())] {
+            grid.merge(other).unwrap();
+
+            assert_eq!(grid.bin_info().bins(), 1);
+
+            for order in 0..grid.orders().len() {
+                assert!(matches!(
+                    grid.subgrid(order, 0, 0),
+                    SubgridEnum::ImportOnlySubgridV2(_)
+                ));
+            }
+
+            let result = grid.convolute(&[&pdf, &pdf], &[], &[], &[], &xi);
+
+            for (&result, &expected) in result.iter().zip(&expected) {
+                assert!(approx_eq!(f64, result, expected, ulps = 16));
+            }
+        }
+    }
+
+    #[test]
+    fn import_skips_data_contributions() {
+        let x_nodes = [0.1];
+        let mu = [10.0];
+        let table = |contributions: &[String]| {
+            format!(
+                "{}2\n1\nx\n2\n0.0 1.0\n1.0 3.0\n1.0 2.0\n0\n{}",
+                HEADER.replacen(
+                    "\n1\n0 0 0 0 0 0",
+                    &format!("\n{}\n0 0 0 0 0 0", contributions.len()),
+                    1
+                ),
+                contributions.concat()
+            )
+        };
+        let additive = format!(
+            "{}{}\n1\n1\nmu\n1 2\n1.0\n{}\n{}\n",
+            contribution(0, 2, 1, "1 0 0"),
+            vector(&[0, 1], |_| vector(&x_nodes, ToString::to_string)),
+            vector(&[0, 1], |_| {
+                vector(&[0], |_| vector(&[0], |_| vector(&mu, ToString::to_string)))
+            }),
+            vector(&[0, 1], |_| {
+                vector(&[0], |_| {
+                    vector(&[0], |_| {
+                        vector(&[0], |_| vector(&[1.0], ToString::to_string))
+                    })
+                })
+            }),
+        );
+        // a data contribution, which has a different layout than additive contributions
+        let data = "1234567890\n12\n1 0\n0 0\n0\n1\ndata\n0\n2\n0.5 1.5\n".to_owned();
+
+        let reference = import(
+            table(&[additive.clone()]).as_bytes(),
+            FlexibleScale::Scale1,
+            FlexibleScale::Scale1,
+        )
+        .unwrap();
+        let grid = import(
+            table(&[data.clone(), additive.clone(), data]).as_bytes(),
+            FlexibleScale::Scale1,
+            FlexibleScale::Scale1,
+        )
+        .unwrap();
+
+        let pdf = ClosurePdf::new(2212, |_, x, _| x, |_| 1.0);
+        let reference = reference.convolute(&[&pdf, &pdf], &[], &[], &[], &[(1.0, 1.0, 1.0)]);
+        let result = grid.convolute(&[&pdf, &pdf], &[], &[], &[], &[(1.0, 1.0, 1.0)]);
+
+        assert!(reference.iter().all(|&value| value != 0.0));
+        assert_eq!(result, reference);
+    }
+
+    #[test]
+    fn export_and_import() {
+        let mut grid = Grid::new(
+            vec![
+                lumi_entry![21, 21, 1.0],
+                lumi_entry![2, -2, 1.0; 4, -4, 0.5; -2, 2, 1.0],
+                lumi_entry![1, 21, 2.0],
+            ],
+            vec![
+                Order::new(0, 0, 0, 0, 0),
+                Order::new(1, 0, 0, 0, 0),
+                Order::new(1, 0, 1, 0, 0),
+            ],
+            vec![0.0, 1.0, 2.0, 3.0],
+            SubgridParams::default(),
+        );
+
+        for i in 0..30 {
+            let i_f64 = f64_from_usize(i);
+
+            grid.fill(
+                i % 3,
+                (0.1 * i_f64) % 3.0,
+                i % 3,
+                &Ntuple {
+                    x1: 0.03_f64.mul_add(i_f64, 0.01),
+                    x2: 0.03_f64.mul_add(-i_f64, 0.95),
+                    q2: 100.0 * (1.0 + i_f64).powi(2),
+                    q2r: 100.0 * (1.0 + i_f64).powi(2),
+                    weight: 1.0 + i_f64,
+                },
+            )
+            .unwrap();
+        }
+
+        grid.set_remapper(
+            BinRemapper::new(
+                vec![0.5, 1.0, 2.0],
+                vec![
+                    (0.0, 1.0),
+                    (10.0, 20.0),
+                    (1.0, 2.0),
+                    (10.0, 20.0),
+                    (2.0, 4.0),
+                    (20.0, 30.0),
+                ],
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        grid.set_key_value("x1_label", "pT");
+        grid.optimize();
+
+        let mut buffer = Vec::new();
+        export(&grid, &mut buffer).unwrap();
+        let imported = import(
+            buffer.as_slice(),
+            FlexibleScale::Scale1,
+            FlexibleScale::Scale1,
+        )
+        .unwrap();
+
+        assert_eq!(
+            imported.orders(),
+            [Order::new(0, 0, 0, 0, 0), Order::new(1, 0, 0, 0, 0)]
+        );
+        assert_eq!(imported.key_values().unwrap()["x1_label"], "pT");
+        assert_eq!(imported.key_values().unwrap()["x2_label"], "x2");
+
+        for dimension in 0..2 {
+            assert_eq!(
+                imported.bin_info().left(dimension),
+                grid.bin_info().left(dimension)
+            );
+            assert_eq!(
+                imported.bin_info().right(dimension),
+                grid.bin_info().right(dimension)
+            );
+        }
+
+        assert_eq!(
+            imported.bin_info().normalizations(),
+            grid.bin_info().normalizations()
+        );
+
+        let xfx =
+            |id: i32, x: f64, q2: f64| x.sqrt() * (1.0 - x).powi(3) * f64::from(id + 10) / q2.ln();
+        let pdf = ClosurePdf::new(2212, xfx, |q2: f64| 1.0 / q2.ln());
+        let expected = grid.convolute(&[&pdf, &pdf], &[], &[], &[], &[(1.0, 1.0, 1.0)]);
+        let result = imported.convolute(&[&pdf, &pdf], &[], &[], &[], &[(1.0, 1.0, 1.0)]);
+
+        for (&result, &expected) in result.iter().zip(&expected) {
+            assert!(expected != 0.0);
+            assert!(approx_eq!(
+                f64,
+                result,
+                expected,
+                epsilon = 1e-13 * expected.abs()
+            ));
+        }
+    }
+
+    #[test]
+    fn export_electromagnetic_coupling() {
+        let mut grid = Grid::new(
+            vec![lumi_entry![2, -2, 1.0]],
+            vec![Order::new(0, 2, 0, 0, 0)],
+            vec![0.0, 1.0],
+            SubgridParams::default(),
+        );
+
+        grid.fill(
+            0,
+            0.5,
+            0,
+            &Ntuple {
+                x1: 0.1,
+                x2: 0.2,
+                q2: 1e4,
+                q2r: 1e4,
+                weight: 1.0,
+            },
+        )
+        .unwrap();
+        grid.optimize();
+
+        assert_eq!(
+            export(&grid, Vec::new()).unwrap_err().to_string(),
+            "the table is not supported: orders with powers of the electromagnetic coupling"
+        );
+    }
+}
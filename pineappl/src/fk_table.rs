@@ -0,0 +1,410 @@
+//! Provides the [`FkTable`] type.
+
+use super::grid::{Grid, Order};
+use super::lumi::LumiEntry;
+use super::pdf::PdfProvider;
+use super::subgrid::{Subgrid, SubgridEnum};
+use ndarray::Array4;
+use std::convert::TryFrom;
+use thiserror::Error;
+
+/// Structure implementing FK tables. These are special [`Grid`]s, for which the following
+/// additional guarantees are given:
+///
+/// - the grid has exactly two convolutions,
+/// - all subgrids of the grid evaluate the PDFs at a single scale `q2`, which is the same for the
+///   renormalization and the factorization scale,
+/// - all subgrids share the same `x` grid, which is used for both initial states,
+/// - the grid has a single order with vanishing exponents for the strong coupling and the
+///   logarithms of the scale factors, i.e. the strong coupling has already been absorbed into the
+///   subgrids and scale variations are not possible anymore.
+///
+/// These guarantees are checked when an `FkTable` is constructed with [`FkTable::try_from`].
+pub struct FkTable {
+    grid: Grid,
+}
+
+/// Error type returned when converting a [`Grid`] into an [`FkTable`] fails.
+#[derive(Debug, Error)]
+pub enum TryFromGridError {
+    /// Returned if the subgrids of the grid use more than a single scale.
+    #[error("multiple scales detected")]
+    MultipleScales,
+    /// Returned if the grid does not have exactly one order.
+    #[error("the grid has {0} orders, but exactly one is required")]
+    MultipleOrders(usize),
+    /// Returned if the single order of the grid has a non-vanishing exponent for the strong
+    /// coupling or for one of the logarithms of the scale factors.
+    #[error(
+        "the order {0:?} is not allowed, it must not have powers of the strong coupling or scale \
+         logarithms"
+    )]
+    NonTrivialOrder(Order),
+    /// Returned if the subgrids of the grid use different `x` grids.
+    #[error("multiple x grids detected")]
+    MultipleXGrids,
+    /// Returned if the grid does not have exactly two convolutions.
+    #[error("the grid has {0} convolutions, but exactly two are required")]
+    UnsupportedConvolutions(usize),
+    /// Returned if the renormalization scale of a subgrid is not the same as its factorization
+    /// scale.
+    #[error("the renormalization scale differs from the factorization scale")]
+    DifferentScales,
+}
+
+impl FkTable {
+    /// Returns the [`Grid`] object for this `FkTable`.
+    #[must_use]
+    pub const fn grid(&self) -> &Grid {
+        &self.grid
+    }
+
+    /// Converts the `FkTable` back to a [`Grid`].
+    #[must_use]
+    pub fn into_grid(self) -> Grid {
+        self.grid
+    }
+
+    /// Returns the luminosity function of the FK table.
+    #[must_use]
+    pub fn lumi(&self) -> &[LumiEntry] {
+        self.grid.lumi()
+    }
+
+    /// Returns the number of bins of the FK table.
+    #[must_use]
+    pub fn bins(&self) -> usize {
+        self.grid.bin_info().bins()
+    }
+
+    /// Returns the scale at which the PDFs must be evaluated, or `None` if all subgrids are empty.
+    #[must_use]
+    pub fn q2(&self) -> Option<f64> {
+        self.non_empty_subgrids()
+            .next()
+            .map(|subgrid| subgrid.q2_grid()[0])
+    }
+
+    /// Returns the `x` grid shared by all subgrids for both initial states. If all subgrids are
+    /// empty, the returned vector is empty as well.
+    #[must_use]
+    pub fn x_grid(&self) -> Vec<f64> {
+        self.non_empty_subgrids()
+            .next()
+            .map_or_else(Vec::new, |subgrid| subgrid.x1_grid().into_owned())
+    }
+
+    /// Returns the FK table as a dense four-dimensional array, whose dimensions correspond to the
+    /// bins, the luminosity function and the `x` grids of the first and second initial state, in
+    /// this order.
+    #[must_use]
+    pub fn table(&self) -> Array4<f64> {
+        let x_grid = self.x_grid();
+        let mut table = Array4::zeros((self.bins(), self.lumi().len(), x_grid.len(), x_grid.len()));
+
+        for bin in 0..self.bins() {
+            for lumi in 0..self.lumi().len() {
+                for (index, value) in self.grid.subgrid(0, bin, lumi).iter() {
+                    let (ix1, ix2) = (index[1], index[2]);
+                    table[[bin, lumi, ix1, ix2]] = *value;
+                }
+            }
+        }
+
+        table
+    }
+
+    /// Performs a convolution of the FK table with the PDFs `pdfs`, one for each convolution,
+    /// which are evaluated at the scale returned by [`FkTable::q2`]. The strong coupling of the
+    /// PDFs is never evaluated. The parameters `bin_indices` and `lumi_mask` have the same meaning
+    /// as for [`Grid::convolute`].
+    ///
+    /// # Panics
+    ///
+    /// Panics for the same reasons as [`Grid::convolute`].
+    pub fn convolute(
+        &self,
+        pdfs: &[&dyn PdfProvider],
+        bin_indices: &[usize],
+        lumi_mask: &[bool],
+    ) -> Vec<f64> {
+        let pdfs: Vec<_> = pdfs.iter().map(|&pdf| WithoutAlphas(pdf)).collect();
+        let pdfs: Vec<_> = pdfs.iter().map(|pdf| pdf as &dyn PdfProvider).collect();
+
+        self.grid
+            .convolute(&pdfs, &[], bin_indices, lumi_mask, &[(1.0, 1.0, 1.0)])
+    }
+
+    fn non_empty_subgrids(&self) -> impl Iterator<Item = &SubgridEnum> {
+        (0..self.bins())
+            .flat_map(move |bin| (0..self.lumi().len()).map(move |lumi| (bin, lumi)))
+            .map(move |(bin, lumi)| self.grid.subgrid(0, bin, lumi))
+            .filter(|subgrid| !subgrid.is_empty())
+    }
+}
+
+/// Wrapper that forwards everything but the strong coupling, which is not needed for FK tables.
+struct WithoutAlphas<'a>(&'a dyn PdfProvider);
+
+impl PdfProvider for WithoutAlphas<'_> {
+    fn particle_id(&self) -> i32 {
+        self.0.particle_id()
+    }
+
+    fn xfx(&self, id: i32, x: f64, q2: f64) -> f64 {
+        self.0.xfx(id, x, q2)
+    }
+
+    fn alphas(&self, _: f64) -> f64 {
+        1.0
+    }
+
+    fn xfx_batch(&self, id: i32, x: &[f64], q2: f64, xfx: &mut [f64]) {
+        self.0.xfx_batch(id, x, q2, xfx);
+    }
+}
+
+impl TryFrom<Grid> for FkTable {
+    type Error = TryFromGridError;
+
+    #[allow(clippy::float_cmp)]
+    fn try_from(grid: Grid) -> Result<Self, Self::Error> {
+        if grid.convolutions() != 2 {
+            return Err(TryFromGridError::UnsupportedConvolutions(
+                grid.convolutions(),
+            ));
+        }
+
+        match grid.orders() {
+            [order] => {
+                if (order.alphas != 0)
+                    || (order.logxir != 0)
+                    || (order.logxif != 0)
+                    || (order.logxia != 0)
+                {
+                    return Err(TryFromGridError::NonTrivialOrder(order.clone()));
+                }
+            }
+            orders => return Err(TryFromGridError::MultipleOrders(orders.len())),
+        }
+
+        let mut q2: Option<f64> = None;
+        let mut shared_x_grid: Option<Vec<f64>> = None;
+
+        for bin in 0..grid.bin_info().bins() {
+            for lumi in 0..grid.lumi().len() {
+                let subgrid = grid.subgrid(0, bin, lumi);
+
+                if subgrid.is_empty() {
+                    continue;
+                }
+
+                let mu2_grid = subgrid.mu2_grid();
+
+                match (mu2_grid.as_ref(), q2) {
+                    ([mu2], _) if mu2.ren != mu2.fac => {
+                        return Err(TryFromGridError::DifferentScales)
+                    }
+                    ([mu2], None) => q2 = Some(mu2.fac),
+                    ([mu2], Some(q2)) if mu2.fac == q2 => {}
+                    _ => return Err(TryFromGridError::MultipleScales),
+                }
+
+                let x1_grid = subgrid.x1_grid();
+
+                if x1_grid != subgrid.x2_grid() {
+                    return Err(TryFromGridError::MultipleXGrids);
+                }
+
+                if let Some(x_grid) = &shared_x_grid {
+                    if x_grid.as_slice() != x1_grid.as_ref() {
+                        return Err(TryFromGridError::MultipleXGrids);
+                    }
+                } else {
+                    shared_x_grid = Some(x1_grid.into_owned());
+                }
+            }
+        }
+
+        Ok(Self { grid })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import_only_subgrid::{ImportOnlySubgridV1, ImportOnlySubgridV2};
+    use crate::lumi_entry;
+    use crate::pdf::ClosurePdf;
+    use crate::sparse_array3::SparseArray3;
+    use crate::subgrid::{Mu2, SubgridParams};
+
+    fn grid_with_subgrids(order: Order, subgrids: Vec<(Vec<f64>, Vec<f64>, Vec<f64>)>) -> Grid {
+        let mut grid = Grid::new(
+            vec![lumi_entry![21, 21, 1.0], lumi_entry![1, -1, 1.0]],
+            vec![order],
+            vec![0.0, 1.0, 2.0],
+            SubgridParams::default(),
+        );
+
+        for (index, (q2_grid, x1_grid, x2_grid)) in subgrids.into_iter().enumerate() {
+            let mut array = SparseArray3::new(q2_grid.len(), x1_grid.len(), x2_grid.len());
+            array[[0, 0, 0]] = 1.0;
+            array[[0, 1, 0]] = 2.0;
+
+            grid.set_subgrid(
+                0,
+                index / 2,
+                index % 2,
+                ImportOnlySubgridV1::new(array, q2_grid, x1_grid, x2_grid).into(),
+            );
+        }
+
+        grid
+    }
+
+    #[test]
+    fn try_from_grid() {
+        let x = vec![0.1, 0.5];
+        let grid = grid_with_subgrids(
+            Order::new(0, 0, 0, 0, 0),
+            vec![
+                (vec![1.65], x.clone(), x.clone()),
+                (vec![1.65], x.clone(), x.clone()),
+                (vec![1.65], x.clone(), x.clone()),
+            ],
+        );
+
+        let fk_table = FkTable::try_from(grid).unwrap();
+
+        assert_eq!(fk_table.bins(), 2);
+        assert_eq!(fk_table.lumi().len(), 2);
+        assert_eq!(fk_table.q2(), Some(1.65));
+        assert_eq!(fk_table.x_grid(), x);
+
+        let table = fk_table.table();
+
+        assert_eq!(table.dim(), (2, 2, 2, 2));
+        assert_eq!(table[[0, 1, 1, 0]], 2.0);
+        assert_eq!(table[[1, 1, 0, 0]], 0.0);
+
+        let pdf = ClosurePdf::new(2212, |_, x, _| x, |_| panic!("alphas must not be called"));
+        let reference = ClosurePdf::new(2212, |_, x, _| x, |_| 0.5);
+
+        assert_eq!(
+            fk_table.convolute(&[&pdf, &pdf], &[], &[]),
+            fk_table
+                .grid()
+                .convolute(&[&reference, &reference], &[], &[], &[], &[(1.0, 1.0, 1.0)])
+        );
+    }
+
+    #[test]
+    fn try_from_grid_errors() {
+        let x = vec![0.1, 0.5];
+
+        assert!(matches!(
+            FkTable::try_from(grid_with_subgrids(
+                Order::new(0, 0, 0, 0, 0),
+                vec![(vec![1.65, 2.0], x.clone(), x.clone())]
+            )),
+            Err(TryFromGridError::MultipleScales)
+        ));
+        assert!(matches!(
+            FkTable::try_from(grid_with_subgrids(
+                Order::new(0, 0, 0, 0, 0),
+                vec![
+                    (vec![1.65], x.clone(), x.clone()),
+                    (vec![2.0], x.clone(), x.clone())
+                ]
+            )),
+            Err(TryFromGridError::MultipleScales)
+        ));
+        assert!(matches!(
+            FkTable::try_from(grid_with_subgrids(
+                Order::new(1, 0, 0, 0, 0),
+                vec![(vec![1.65], x.clone(), x.clone())]
+            )),
+            Err(TryFromGridError::NonTrivialOrder(order)) if order == Order::new(1, 0, 0, 0, 0)
+        ));
+        assert!(matches!(
+            FkTable::try_from(grid_with_subgrids(
+                Order::new(0, 0, 0, 1, 0),
+                vec![(vec![1.65], x.clone(), x.clone())]
+            )),
+            Err(TryFromGridError::NonTrivialOrder(_))
+        ));
+        assert!(matches!(
+            FkTable::try_from(grid_with_subgrids(
+                Order::new(0, 0, 0, 0, 1),
+                vec![(vec![1.65], x.clone(), x.clone())]
+            )),
+            Err(TryFromGridError::NonTrivialOrder(_))
+        ));
+        assert!(matches!(
+            FkTable::try_from(grid_with_subgrids(
+                Order::new(0, 0, 0, 0, 0),
+                vec![(vec![1.65], x.clone(), vec![0.1, 0.6])]
+            )),
+            Err(TryFromGridError::MultipleXGrids)
+        ));
+        assert!(matches!(
+            FkTable::try_from(grid_with_subgrids(
+                Order::new(0, 0, 0, 0, 0),
+                vec![
+                    (vec![1.65], x.clone(), x.clone()),
+                    (vec![1.65], vec![0.1, 0.6], vec![0.1, 0.6])
+                ]
+            )),
+            Err(TryFromGridError::MultipleXGrids)
+        ));
+
+        let mut grid = grid_with_subgrids(Order::new(0, 0, 0, 0, 0), vec![]);
+        grid.merge(grid_with_subgrids(
+            Order::new(0, 0, 1, 0, 0),
+            vec![(vec![1.65], x.clone(), x.clone())],
+        ))
+        .unwrap();
+
+        assert!(matches!(
+            FkTable::try_from(grid),
+            Err(TryFromGridError::MultipleOrders(2))
+        ));
+
+        let grid = Grid::new(
+            vec![LumiEntry::new(vec![(vec![21], 1.0)])],
+            vec![Order::new(0, 0, 0, 0, 0)],
+            vec![0.0, 1.0],
+            SubgridParams::default(),
+        );
+
+        assert!(matches!(
+            FkTable::try_from(grid),
+            Err(TryFromGridError::UnsupportedConvolutions(1))
+        ));
+
+        let mut grid = grid_with_subgrids(Order::new(0, 0, 0, 0, 0), vec![]);
+        let mut array = SparseArray3::new(1, 2, 2);
+        array[[0, 0, 0]] = 1.0;
+        grid.set_subgrid(
+            0,
+            0,
+            0,
+            ImportOnlySubgridV2::new(
+                array,
+                vec![Mu2 {
+                    ren: 2.0,
+                    fac: 1.65,
+                }],
+                x.clone(),
+                x,
+            )
+            .into(),
+        );
+
+        assert!(matches!(
+            FkTable::try_from(grid),
+            Err(TryFromGridError::DifferentScales)
+        ));
+    }
+}
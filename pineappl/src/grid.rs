@@ -1,30 +1,46 @@
 //! Module containing all traits and supporting structures for grids.
 
 use super::bin::{BinInfo, BinLimits, BinRemapper};
+use super::convert::f64_from_usize;
 use super::empty_subgrid::EmptySubgridV1;
-use super::import_only_subgrid::ImportOnlySubgridV1;
-use super::lagrange_subgrid::{LagrangeSparseSubgridV1, LagrangeSubgridV1, LagrangeSubgridV2};
+use super::evolution::EvolutionOperator;
+use super::import_only_subgrid::{ImportOnlySubgridV1, ImportOnlySubgridV2};
+use super::lagrange_subgrid::{
+    LagrangeSparseSubgridV1, LagrangeSubgridV1, LagrangeSubgridV2, LagrangeSubgridV3,
+};
 use super::lumi::LumiEntry;
-use super::ntuple_subgrid::NtupleSubgridV1;
-use super::subgrid::{ExtraSubgridParams, Subgrid, SubgridEnum, SubgridParams};
+use super::ntuple_subgrid::NtupleSubgridV2;
+use super::pdf::{InitialStatePdf, PdfProvider};
+use super::pids::{self, PidBasis};
+use super::sparse_array3::SparseArray3;
+use super::splitting::{self, Splitting};
+use super::statistics::McStatistics;
+use super::subgrid::{ExtraSubgridParams, Mu2, Subgrid, SubgridEnum, SubgridParams};
 use either::Either::{Left, Right};
 use float_cmp::approx_eq;
 use git_version::git_version;
-use itertools::Itertools;
-use lz_fear::{framed::DecompressionError::WrongMagic, LZ4FrameReader};
-use ndarray::{Array3, Dimension};
+use itertools::{iproduct, Itertools};
+use lz_fear::{framed::DecompressionError::WrongMagic, CompressionSettings, LZ4FrameReader};
+use ndarray::{s, Array2, Array3, Axis, Dimension, Ix3};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 use rustc_hash::FxHashMap;
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
 use std::cell::RefCell;
 use std::cmp::Ordering;
 use std::collections::HashMap;
-use std::convert::TryInto;
-use std::io::{Read, Seek, SeekFrom, Write};
+use std::convert::{TryFrom, TryInto};
+use std::error;
+use std::f64::consts::PI;
+use std::hash::Hasher;
+use std::io::{self, Cursor, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::iter;
 use std::mem;
 use std::ops::Range;
-use std::ptr;
+use std::sync::{Mutex, MutexGuard};
 use thiserror::Error;
+use twox_hash::XxHash64;
 
 // TODO: when possible change the types from `u32` to `u8` to change `try_into` to `into`
 
@@ -39,6 +55,8 @@ pub struct Order {
     pub logxir: u32,
     /// Exponent of the logarithm of the scale factor of the factorization scale.
     pub logxif: u32,
+    /// Exponent of the logarithm of the scale factor of the fragmentation scale.
+    pub logxia: u32,
 }
 
 impl Ord for Order {
@@ -47,10 +65,11 @@ impl Ord for Order {
         // rest lexicographically
         (self.alphas + self.alpha)
             .cmp(&(other.alphas + other.alpha))
-            .then((self.alpha, self.logxir, self.logxif).cmp(&(
+            .then((self.alpha, self.logxir, self.logxif, self.logxia).cmp(&(
                 other.alpha,
                 other.logxir,
                 other.logxif,
+                other.logxia,
             )))
     }
 }
@@ -65,12 +84,13 @@ impl Order {
     /// Constructor. This function mainly exists to have a way of constructing `Order` that is less
     /// verbose.
     #[must_use]
-    pub const fn new(alphas: u32, alpha: u32, logxir: u32, logxif: u32) -> Self {
+    pub const fn new(alphas: u32, alpha: u32, logxir: u32, logxif: u32, logxia: u32) -> Self {
         Self {
             alphas,
             alpha,
             logxir,
             logxif,
+            logxia,
         }
     }
 
@@ -87,7 +107,18 @@ impl Order {
     }
 }
 
-/// This structure represents a position (`x1`, `x2`, `q2`) in a `Subgrid` together with a
+/// Type of the function that is convoluted with one of the momentum fractions of a grid.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ConvolutionType {
+    /// Parton distribution function of a hadron in the initial state, which is evaluated at the
+    /// factorization scale.
+    Pdf,
+    /// Fragmentation function of an identified hadron in the final state, which is evaluated at
+    /// the fragmentation scale.
+    FragmentationFunction,
+}
+
+/// This structure represents a position (`x1`, `x2`, `q2`, `q2r`) in a `Subgrid` together with a
 /// corresponding `weight`. The type `W` can either be a `f64` or `()`, which is used when multiple
 /// weights should be signaled.
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
@@ -96,12 +127,158 @@ pub struct Ntuple<W> {
     pub x1: f64,
     /// Momentum fraction of the second parton.
     pub x2: f64,
-    /// Squared scale.
+    /// Squared factorization scale.
     pub q2: f64,
+    /// Squared renormalization scale. For processes with a single scale this is the same value
+    /// as `q2`, which is the only case supported by interpolating subgrids.
+    pub q2r: f64,
     /// Weight of this entry.
     pub weight: W,
 }
 
+/// A single event of a batch of events, see `Grid::fill_array`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Event {
+    /// Momentum fractions of the partons, one for each convolution of the grid.
+    pub x: Vec<f64>,
+    /// Squared scale, which is used as both the renormalization and the factorization scale.
+    pub q2: f64,
+    /// Index of the order this event is filled into.
+    pub order: usize,
+    /// Value of the observable, which determines the bin this event is filled into.
+    pub observable: f64,
+    /// Index of the luminosity this event is filled into.
+    pub lumi: usize,
+    /// Weight of this event.
+    pub weight: f64,
+}
+
+/// Returns the sorted and unique elements of `indices` that are smaller than `len`.
+fn sorted_indices(indices: &[usize], len: usize) -> Vec<usize> {
+    let mut indices: Vec<_> = indices
+        .iter()
+        .copied()
+        .filter(|&index| index < len)
+        .collect();
+    indices.sort_unstable();
+    indices.dedup();
+    indices
+}
+
+fn xfx_batch(pdf: &dyn PdfProvider, id: i32, x: &[f64], q2: f64) -> Vec<f64> {
+    let mut xfx = vec![0.0; x.len()];
+    pdf.xfx_batch(id, x, q2, &mut xfx);
+    xfx
+}
+
+/// PDF and coupling values of `Grid::convolute_subgrids`, which are valid for the scale and
+/// momentum fraction grids stored alongside them.
+#[derive(Default)]
+struct ConvolutionCache {
+    pdfs: RefCell<FxHashMap<(usize, i32, usize), Vec<f64>>>,
+    alphas: RefCell<FxHashMap<usize, (f64, f64)>>,
+    mu2_grid: Vec<Mu2>,
+    x_grids: Vec<Vec<f64>>,
+    xif_xia: (f64, f64),
+}
+
+const fn is_import_only(subgrid: &SubgridEnum) -> bool {
+    matches!(
+        subgrid,
+        SubgridEnum::ImportOnlySubgridV1(_) | SubgridEnum::ImportOnlySubgridV2(_)
+    )
+}
+
+/// Converts `subgrid` into an import-only subgrid if it is a `LagrangeSubgridV2`. If
+/// `separate_scales` is `true` the result is an `ImportOnlySubgridV2`.
+fn convert_to_import_only(subgrid: &mut SubgridEnum, separate_scales: bool) {
+    if let SubgridEnum::LagrangeSubgridV2(grid) = subgrid {
+        let converted = ImportOnlySubgridV1::from(&*grid);
+        *subgrid = converted.into();
+    }
+
+    if separate_scales {
+        if let SubgridEnum::ImportOnlySubgridV1(_) = subgrid {
+            if let SubgridEnum::ImportOnlySubgridV1(grid) =
+                mem::replace(subgrid, EmptySubgridV1::default().into())
+            {
+                *subgrid = ImportOnlySubgridV2::from(grid).into();
+            }
+        }
+    }
+}
+
+/// Merges `other` into `subgrid`, which must be possible according to `mergeable`.
+fn merge_subgrids(subgrid: &mut SubgridEnum, other: &mut SubgridEnum) {
+    if subgrid.is_empty() {
+        mem::swap(subgrid, other);
+    } else {
+        if is_import_only(subgrid) || is_import_only(other) {
+            // an interpolating subgrid is merged with an import-only subgrid after converting it,
+            // and the scales of both must be stored separately if one of them does that
+            let separate_scales = matches!(other, SubgridEnum::ImportOnlySubgridV2(_));
+
+            convert_to_import_only(subgrid, separate_scales);
+            convert_to_import_only(other, false);
+        }
+
+        subgrid.merge(other, false);
+    }
+}
+
+/// Returns whether `other` can be merged into `subgrid` by `Grid::merge`.
+fn mergeable(subgrid: &SubgridEnum, other: &SubgridEnum) -> bool {
+    if subgrid.is_empty() || other.is_empty() {
+        return true;
+    }
+
+    // import-only subgrids, and interpolating subgrids that are converted into them, can only be
+    // merged if they share their momentum fractions
+    if is_import_only(subgrid) || is_import_only(other) {
+        return [subgrid, other].iter().all(|subgrid| {
+            matches!(
+                subgrid,
+                SubgridEnum::LagrangeSubgridV2(_)
+                    | SubgridEnum::ImportOnlySubgridV1(_)
+                    | SubgridEnum::ImportOnlySubgridV2(_)
+            )
+        }) && (subgrid.x1_grid() == other.x1_grid())
+            && (subgrid.x2_grid() == other.x2_grid());
+    }
+
+    (mem::discriminant(subgrid) == mem::discriminant(other))
+        || matches!(
+            (subgrid, other),
+            (
+                SubgridEnum::NtupleSubgridV2(_),
+                SubgridEnum::NtupleSubgridV1(_)
+            )
+        )
+}
+
+/// Returns the non-zero values of `subgrid` together with their indices.
+fn nonzero_values(subgrid: &SubgridEnum) -> Vec<(Vec<usize>, f64)> {
+    subgrid
+        .iter()
+        .filter(|&(_, &value)| value != 0.0)
+        .map(|(index, &value)| (index, value))
+        .collect()
+}
+
+/// Returns the number of convolutions of the luminosity function `lumi`. If `lumi` is empty, two
+/// convolutions are assumed.
+fn convolutions(lumi: &[LumiEntry]) -> usize {
+    let convolutions = lumi.first().map_or(2, LumiEntry::convolutions);
+
+    assert!(
+        lumi.iter()
+            .all(|entry| entry.convolutions() == convolutions),
+        "the luminosity function has entries with different numbers of convolutions"
+    );
+
+    convolutions
+}
+
 /// Error returned when merging two grids fails.
 #[derive(Debug, Error)]
 pub enum GridMergeError {
@@ -116,8 +293,43 @@ pub enum GridMergeError {
     /// Returned when trying to merge two `Grid` objects with incompatible bin limits.
     #[error(transparent)]
     DifferentBins(super::bin::MergeBinError),
+    /// Returned when trying to merge two `Grid` objects with different numbers of convolutions.
+    #[error("the merged grid has a different number of convolutions")]
+    DifferentConvolutions,
+    /// Returned when trying to merge two `Grid` objects whose luminosity functions use different
+    /// particle id bases.
+    #[error("the merged grid uses a different basis for its particle ids")]
+    DifferentPidBasis,
+    /// Returned when one of the grids has an unknown basis of particle ids.
+    #[error(transparent)]
+    UnknownPidBasis(#[from] UnknownPidBasis),
+    /// Returned when a non-empty subgrid of the merged grid would have to be merged into a
+    /// non-empty subgrid of a type it can not be merged with.
+    #[error("the merged grid has a subgrid of an incompatible type")]
+    IncompatibleSubgrids,
+}
+
+/// Error returned by `Grid::add_scaled`.
+#[derive(Debug, Error)]
+pub enum GridCombineError {
+    /// Returned when the bins of the two grids, including their normalizations and the limits of
+    /// every dimension, are not the same.
+    #[error("the added grid has different bins")]
+    DifferentBins,
+    /// Returned when the two grids have different initial states, given by the metadata
+    /// `initial_state_1`, `initial_state_2`, and so on, or a different number of them.
+    #[error("the added grid has different initial states")]
+    DifferentInitialStates,
+    /// Returned when the two grids can not be merged, see `Grid::merge`.
+    #[error(transparent)]
+    Merge(#[from] GridMergeError),
 }
 
+/// Error returned by `Grid::pid_basis` if the metadata `pid_basis` has an unknown value.
+#[derive(Debug, Error)]
+#[error("unknown basis of particle ids `{0}`")]
+pub struct UnknownPidBasis(String);
+
 /// Error returned when trying to construct a `Grid` using an unknown subgrid type.
 #[derive(Debug, Error)]
 #[error("tried constructing a Grid with unknown Subgrid type `{0}`")]
@@ -165,25 +377,23 @@ impl Default for Mmv2 {
 }
 
 impl Mmv3 {
-    fn new(subgrid_template: SubgridEnum) -> Self {
+    fn new(subgrid_template: SubgridEnum, convolutions: usize) -> Self {
         Self {
             remapper: None,
-            key_value_db: [
-                (
-                    "pineappl_gitversion".to_owned(),
-                    git_version!(
-                        args = ["--always", "--dirty", "--long", "--tags"],
-                        cargo_prefix = "cargo:",
-                        fallback = "unknown"
-                    )
-                    .to_owned(),
-                ),
-                // by default we assume there are protons in the initial state
-                ("initial_state_1".to_owned(), "2212".to_owned()),
-                ("initial_state_2".to_owned(), "2212".to_owned()),
-            ]
-            .iter()
-            .cloned()
+            key_value_db: iter::once((
+                "pineappl_gitversion".to_owned(),
+                git_version!(
+                    args = ["--always", "--dirty", "--long", "--tags"],
+                    cargo_prefix = "cargo:",
+                    fallback = "unknown"
+                )
+                .to_owned(),
+            ))
+            // by default we assume there are protons in the initial state
+            .chain(
+                (1..=convolutions)
+                    .map(|index| (format!("initial_state_{}", index), "2212".to_owned())),
+            )
             .collect(),
             subgrid_template,
         }
@@ -208,6 +418,52 @@ impl MoreMembers {
     }
 }
 
+/// Error type returned by `Grid::fill`.
+#[derive(Debug, Error)]
+pub enum GridFillError {
+    /// Returned if the renormalization and factorization scales of an n-tuple are different, but
+    /// the subgrid it is filled into supports only a single scale.
+    #[error("the subgrid with indices ({order}, {bin}, {lumi}) does not support different scales")]
+    DifferentScales {
+        /// Order index of the subgrid.
+        order: usize,
+        /// Bin index of the subgrid.
+        bin: usize,
+        /// Luminosity index of the subgrid.
+        lumi: usize,
+    },
+}
+
+/// Replaces `subgrid` with an empty subgrid of the type that the grid was created with, if it is an
+/// `EmptySubgridV1`, which can not be filled.
+fn make_fillable(subgrid: &mut SubgridEnum, more_members: &MoreMembers) {
+    if let SubgridEnum::EmptySubgridV1(_) = subgrid {
+        if let MoreMembers::V3(mmv3) = more_members {
+            *subgrid = mmv3.subgrid_template.clone_empty();
+        } else {
+            unreachable!();
+        }
+    }
+}
+
+/// Fills `subgrid`, which has the indices `order`, `bin` and `lumi`, with `ntuple`. Only subgrids
+/// of type `NtupleSubgridV2` support n-tuples with different renormalization and factorization
+/// scales, for all other subgrids an error is returned.
+#[allow(clippy::float_cmp)]
+fn fill_subgrid(
+    subgrid: &mut SubgridEnum,
+    (order, bin, lumi): (usize, usize, usize),
+    ntuple: &Ntuple<f64>,
+) -> Result<(), GridFillError> {
+    if (ntuple.q2r != ntuple.q2) && !matches!(subgrid, SubgridEnum::NtupleSubgridV2(_)) {
+        return Err(GridFillError::DifferentScales { order, bin, lumi });
+    }
+
+    subgrid.fill(ntuple);
+
+    Ok(())
+}
+
 /// Error type returned by `Grid::set_remapper`.
 #[derive(Debug, Error)]
 pub enum GridSetBinRemapperError {
@@ -221,955 +477,5003 @@ pub enum GridSetBinRemapperError {
     },
 }
 
-/// Main data structure of `PineAPPL`. This structure contains a `Subgrid` for each `LumiEntry`,
-/// bin, and coupling order it was created with.
-#[derive(Deserialize, Serialize)]
-pub struct Grid {
-    subgrids: Array3<SubgridEnum>,
-    lumi: Vec<LumiEntry>,
-    bin_limits: BinLimits,
-    orders: Vec<Order>,
-    subgrid_params: SubgridParams,
-    more_members: MoreMembers,
+/// Error type returned by `Grid::set_subgrid_params`.
+#[derive(Debug, Error)]
+pub enum GridSetSubgridParamsError {
+    /// Returned if the momentum fractions or the scales have no bins.
+    #[error("the subgrid parameters must have at least one bin")]
+    NoBins,
+    /// Returned if the limits of the momentum fractions or of the scales are not positive, or if
+    /// the minimum is not smaller than the maximum.
+    #[error("the limits {min} and {max} of the subgrid parameters are invalid")]
+    InvalidLimits {
+        /// The minimum.
+        min: f64,
+        /// The maximum.
+        max: f64,
+    },
 }
 
-impl Grid {
-    /// Constructor.
+/// Error type returned by `Grid::evolve`.
+#[derive(Debug, Error)]
+pub enum GridEvolveError {
+    /// Returned if a subgrid has a type that can not be evolved.
+    #[error("the subgrid with indices ({order}, {bin}, {lumi}) can not be evolved")]
+    UnsupportedSubgrid {
+        /// Order index of the subgrid.
+        order: usize,
+        /// Bin index of the subgrid.
+        bin: usize,
+        /// Luminosity index of the subgrid.
+        lumi: usize,
+    },
+    /// Returned if the evolution operator does not contain a factorization scale needed by a
+    /// subgrid.
+    #[error("the evolution operator does not contain the scale q2 = {0}")]
+    MissingScale(f64),
+    /// Returned if the evolution operator does not contain a momentum fraction needed by a
+    /// subgrid.
+    #[error("the evolution operator does not contain the momentum fraction x = {0}")]
+    MissingMomentumFraction(f64),
+    /// Returned if the evolution operator does not contain a particle id used in the luminosity
+    /// function.
+    #[error("the evolution operator does not contain the particle id {0}")]
+    MissingParticleId(i32),
+    /// Returned if the grid has a convolution with a fragmentation function, which can not be
+    /// evolved with an operator for PDFs.
+    #[error("the convolution {0} is not with a PDF and can not be evolved")]
+    UnsupportedConvolutionType(usize),
+    /// Returned if the grid does not have exactly two convolutions.
+    #[error("grids with {0} convolutions can not be evolved")]
+    UnsupportedConvolutions(usize),
+}
+
+/// Error type returned by `Grid::generate_scale_logs`.
+#[derive(Debug, Error)]
+pub enum GridScaleLogsError {
+    /// Returned if a subgrid has a type for which the scale logarithms can not be generated.
+    #[error(
+        "the scale logarithms of the subgrid with indices ({order}, {bin}, {lumi}) can not be \
+         generated"
+    )]
+    UnsupportedSubgrid {
+        /// Order index of the subgrid.
+        order: usize,
+        /// Bin index of the subgrid.
+        bin: usize,
+        /// Luminosity index of the subgrid.
+        lumi: usize,
+    },
+    /// Returned if the grid does not have exactly two convolutions.
+    #[error("the scale logarithms of grids with {0} convolutions can not be generated")]
+    UnsupportedConvolutions(usize),
+    /// Returned if the grid has a convolution with a fragmentation function, whose splitting
+    /// functions are not supported.
+    #[error("the convolution {0} is not with a PDF")]
+    UnsupportedConvolutionType(usize),
+    /// Returned if the grid has an order beyond the next-to-leading order without any scale
+    /// logarithms, which can not be generated.
+    #[error("the scale logarithms of the order as^{alphas} a^{alpha} can not be generated")]
+    UnsupportedOrder {
+        /// Exponent of the strong coupling of the order.
+        alphas: u32,
+        /// Exponent of the electromagnetic coupling of the order.
+        alpha: u32,
+    },
+    /// Returned if the grid has an unknown basis of particle ids.
+    #[error(transparent)]
+    UnknownPidBasis(#[from] UnknownPidBasis),
+}
+
+/// Error type returned by `Grid::read`, `Grid::read_metadata` and `GridReader`, and by
+/// `Composite::read` for truncated or corrupted files.
+#[derive(Debug, Error)]
+pub enum GridReadError {
+    /// Returned if the file was written with a newer version of the file format.
+    #[error("file format version {0} is not supported")]
+    UnsupportedVersion(u64),
+    /// Returned if the input ends before the given section of the file is complete.
+    #[error("the file is truncated: the {0} is incomplete")]
+    Truncated(String),
+    /// Returned if the checksum of the given section does not match its contents.
+    #[error("the file is corrupted: the checksum of the {0} does not match")]
+    ChecksumMismatch(String),
+    /// Returned if the given section can not be decoded.
+    #[error("the file is corrupted: the {section} can not be decoded")]
+    Corrupted {
+        /// Name of the section.
+        section: String,
+        /// Error that occurred while decoding the section.
+        source: Box<dyn error::Error + Send + Sync>,
+    },
+    /// Returned if the index of the subgrids does not agree with the metadata.
+    #[error("the file is corrupted: the index of the subgrids does not agree with the metadata")]
+    InvalidIndex,
+    /// Returned if reading from the input fails.
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+/// Severity of a `GridFinding`.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Severity {
+    /// The grid can be used, but it possibly gives unexpected results.
+    Warning,
+    /// The grid is broken and gives wrong results or can not be used at all.
+    Error,
+}
+
+/// Problem found by `Grid::validate`.
+#[derive(Clone, Debug, Error, PartialEq)]
+pub enum GridFinding {
+    /// The shape of the subgrids does not agree with the number of orders, bins and channels.
+    #[error(
+        "the subgrids have the shape {found:?}, but the orders, bins and channels require \
+         {expected:?}"
+    )]
+    InconsistentShape {
+        /// Number of orders, bins and channels.
+        expected: (usize, usize, usize),
+        /// Shape of the subgrids.
+        found: (usize, usize, usize),
+    },
+    /// The number of bins of the remapper does not agree with the number of bins of the grid.
+    #[error(
+        "the number of bins in the remapper, {remapper_bins}, does not agree with the number of \
+         bins in the grid: {grid_bins}"
+    )]
+    RemapperBinMismatch {
+        /// Number of bins in the grid.
+        grid_bins: usize,
+        /// Number of bins in the remapper.
+        remapper_bins: usize,
+    },
+    /// The metadata `initial_state_{0}` is missing, in which case a proton is assumed.
+    #[error("the metadata `initial_state_{0}` is missing, a proton is assumed")]
+    MissingInitialState(usize),
+    /// The metadata `initial_state_{index}` is not a PDG MC id.
+    #[error("the metadata `initial_state_{index}` is not a particle id: `{value}`")]
+    InvalidInitialState {
+        /// Index of the initial state, which starts at `1`.
+        index: usize,
+        /// Value of the metadata.
+        value: String,
+    },
+    /// The metadata `pid_basis` is neither `PDG` nor `EVOL`, see `Grid::pid_basis`, which makes
+    /// every convolution of the grid panic.
+    #[error("the metadata `pid_basis` has the unknown value `{0}`")]
+    UnknownPidBasis(String),
+    /// A subgrid contains a value that is either NaN or infinite.
+    #[error(
+        "the subgrid with indices ({order}, {bin}, {lumi}) contains values that are not finite"
+    )]
+    NonFiniteValue {
+        /// Order index of the subgrid.
+        order: usize,
+        /// Bin index of the subgrid.
+        bin: usize,
+        /// Luminosity index of the subgrid.
+        lumi: usize,
+    },
+    /// An interpolating subgrid has a node outside the limits given by the `SubgridParams` of the
+    /// grid.
+    #[error(
+        "the subgrid with indices ({order}, {bin}, {lumi}) has the node {variable} = {value}, \
+         which is outside the limits of the subgrid parameters"
+    )]
+    NodeOutsideLimits {
+        /// Order index of the subgrid.
+        order: usize,
+        /// Bin index of the subgrid.
+        bin: usize,
+        /// Luminosity index of the subgrid.
+        lumi: usize,
+        /// Name of the variable, either `x` or `q2`.
+        variable: &'static str,
+        /// Value of the node.
+        value: f64,
+    },
+}
+
+impl GridFinding {
+    /// Returns the severity of this finding.
     #[must_use]
-    pub fn new(
-        lumi: Vec<LumiEntry>,
-        orders: Vec<Order>,
-        bin_limits: Vec<f64>,
-        subgrid_params: SubgridParams,
-    ) -> Self {
-        Self {
-            subgrids: Array3::from_shape_simple_fn(
-                (orders.len(), bin_limits.len() - 1, lumi.len()),
-                || EmptySubgridV1::default().into(),
-            ),
-            orders,
-            lumi,
-            bin_limits: BinLimits::new(bin_limits),
-            more_members: MoreMembers::V3(Mmv3::new(
-                LagrangeSubgridV2::new(&subgrid_params, &ExtraSubgridParams::from(&subgrid_params))
-                    .into(),
-            )),
-            subgrid_params,
+    pub const fn severity(&self) -> Severity {
+        match self {
+            Self::MissingInitialState(_) | Self::NodeOutsideLimits { .. } => Severity::Warning,
+            Self::InconsistentShape { .. }
+            | Self::RemapperBinMismatch { .. }
+            | Self::InvalidInitialState { .. }
+            | Self::UnknownPidBasis(_)
+            | Self::NonFiniteValue { .. } => Severity::Error,
         }
     }
+}
 
-    /// Constructor. This function can be used like `new`, but the additional parameter
-    /// `subgrid_type` selects the underlying `Subgrid` type. Supported values are:
-    /// - `LagrangeSubgrid`
-    /// - `LagrangeSparseSubgrid`
-    /// - `NtupleSubgrid`
+/// Reader for grid files that loads the metadata of a grid, which is everything but its subgrids,
+/// immediately and its subgrids only when they are requested.
+pub struct GridReader<R> {
+    reader: R,
+    grid: Grid,
+    loaded: Vec<bool>,
+    offsets: Vec<u64>,
+    start: u64,
+}
+
+impl<R: Read + Seek> GridReader<R> {
+    /// Constructor. Reads the metadata of the grid stored in `reader`. Files without a header, or
+    /// files that are compressed as a whole, do not support loading single subgrids and are read
+    /// completely.
     ///
     /// # Errors
     ///
-    /// If `subgrid_type` is none of the values listed above, an error is returned.
-    pub fn with_subgrid_type(
-        lumi: Vec<LumiEntry>,
-        orders: Vec<Order>,
-        bin_limits: Vec<f64>,
-        subgrid_params: SubgridParams,
-        extra: ExtraSubgridParams,
-        subgrid_type: &str,
-    ) -> Result<Self, UnknownSubgrid> {
-        let subgrid_template: SubgridEnum = match subgrid_type {
-            "LagrangeSubgrid" | "LagrangeSubgridV2" => {
-                LagrangeSubgridV2::new(&subgrid_params, &extra).into()
+    /// If reading fails, or if the file format version is not supported, an error is returned.
+    pub fn new(mut reader: R) -> Result<Self, GridReadError> {
+        let mut magic = [0; 8];
+
+        if reader.read_exact(&mut magic).is_ok() && (magic == FILE_MAGIC) {
+            let version = read_u64(&mut reader, "file format version")?;
+
+            if version == FILE_VERSION {
+                let grid = read_header(&mut reader)?;
+                let start = reader.stream_position()?;
+                let offsets = read_trailing_index(&mut reader, &grid, start)?;
+
+                return Ok(Self {
+                    reader,
+                    loaded: vec![false; grid.subgrids.len()],
+                    grid,
+                    offsets,
+                    start,
+                });
             }
-            "LagrangeSubgridV1" => LagrangeSubgridV1::new(&subgrid_params).into(),
-            "NtupleSubgrid" => NtupleSubgridV1::new().into(),
-            "LagrangeSparseSubgrid" => LagrangeSparseSubgridV1::new(&subgrid_params).into(),
-            _ => return Err(UnknownSubgrid(subgrid_type.to_string())),
-        };
+        }
+
+        reader.seek(SeekFrom::Start(0))?;
+        let grid = Grid::read(&mut reader)?;
 
         Ok(Self {
-            subgrids: Array3::from_shape_simple_fn(
-                (orders.len(), bin_limits.len() - 1, lumi.len()),
-                || EmptySubgridV1::default().into(),
-            ),
-            orders,
-            lumi,
-            bin_limits: BinLimits::new(bin_limits),
-            subgrid_params,
-            more_members: MoreMembers::V3(Mmv3::new(subgrid_template)),
+            reader,
+            loaded: vec![true; grid.subgrids.len()],
+            grid,
+            offsets: Vec::new(),
+            start: 0,
         })
     }
 
-    /// Performs a convolution of the contained subgrids with the given PDFs, `xfx1` for the first
-    /// parton and `xfx2` for the second parton, `alphas` for the evaluation of the strong
-    /// coupling. The parameters `order_mask` and `lumi_mask` can be used to selectively enable
-    /// perturbative orders and luminosities; they must either be empty (everything enabled) or as
-    /// large as the orders and luminosity function, respectively. If the corresponding entry is
-    /// `true` the order/luminosity is enable, `false` disables the entry. The tuple `xi` can be
-    /// used to independently vary the renormalization (first element) and factorization scale
-    /// (second element) from their central value `(1.0, 1.0)`.
+    /// Returns the grid read so far. Its metadata is complete, but subgrids that have not been
+    /// loaded with `GridReader::subgrid` are empty.
+    #[must_use]
+    pub const fn grid(&self) -> &Grid {
+        &self.grid
+    }
+
+    /// Loads the subgrid with the indices `order`, `bin` and `lumi`, unless it has been loaded
+    /// before, and returns it.
+    ///
+    /// # Errors
+    ///
+    /// If reading the subgrid fails an error is returned.
     ///
     /// # Panics
     ///
-    /// TODO
-    pub fn convolute(
-        &self,
-        xfx1: &dyn Fn(i32, f64, f64) -> f64,
-        xfx2: &dyn Fn(i32, f64, f64) -> f64,
-        alphas: &dyn Fn(f64) -> f64,
-        order_mask: &[bool],
-        bin_indices: &[usize],
-        lumi_mask: &[bool],
-        xi: &[(f64, f64)],
-    ) -> Vec<f64> {
-        let bin_indices = if bin_indices.is_empty() {
-            (0..self.bin_limits.bins()).collect()
-        } else {
-            bin_indices.to_vec()
-        };
-        let mut bins: Vec<f64> = vec![0.0; bin_indices.len() * xi.len()];
-        let bin_sizes = self.bin_info().normalizations();
-
-        let pdf_cache1 = RefCell::new(FxHashMap::default());
-        let pdf_cache2 = RefCell::new(FxHashMap::default());
-        let alphas_cache = RefCell::new(FxHashMap::default());
-        let mut last_xif = 0.0;
-
-        let (mut q2_grid, mut x1_grid, mut x2_grid) = self
-            .subgrids
-            .iter()
-            .find(|subgrid| !subgrid.is_empty())
-            .map_or_else(
-                || (Cow::default(), Cow::default(), Cow::default()),
-                |grid| (grid.q2_grid(), grid.x1_grid(), grid.x2_grid()),
-            );
-        let use_cache = !q2_grid.is_empty() && !x1_grid.is_empty() && !x2_grid.is_empty();
-        let two_caches = !ptr::eq(&xfx1, &xfx2);
-
-        let mut xir_values: Vec<_> = xi.iter().map(|xi| xi.0).collect();
-        xir_values.sort_by(|lhs, rhs| lhs.partial_cmp(rhs).unwrap());
-        xir_values.dedup();
-        let xir_indices: Vec<_> = xi
-            .iter()
-            .map(|xi| xir_values.iter().position(|xir| xi.0 == *xir).unwrap())
-            .collect();
+    /// Panics if one of the indices is out of range.
+    pub fn subgrid(
+        &mut self,
+        order: usize,
+        bin: usize,
+        lumi: usize,
+    ) -> Result<&SubgridEnum, GridReadError> {
+        let (_, bins, lumis) = self.grid.subgrids.dim();
+        assert!((bin < bins) && (lumi < lumis));
+        let index = (order * bins + bin) * lumis + lumi;
+
+        if !self.loaded[index] {
+            let offset = self.offsets[index];
+            self.reader.seek(SeekFrom::Start(self.start + offset))?;
+            self.grid.subgrids[[order, bin, lumi]] =
+                read_subgrid(&mut self.reader, (order, bin, lumi))?;
+            self.loaded[index] = true;
+        }
 
-        // iterate over the elements of `xi` and a corresponding index, but sorted using the
-        // factorisation value of `xi`
-        for (l, (&(xir, xif), &xir_index)) in xi
-            .iter()
-            .zip(xir_indices.iter())
-            .enumerate()
-            .sorted_by(|lhs, rhs| (lhs.1).1.partial_cmp((rhs.1).1).unwrap())
-        {
-            // whenever the value `xif` changes we can clear the PDF cache
-            if xif != last_xif {
-                pdf_cache1.borrow_mut().clear();
-                pdf_cache2.borrow_mut().clear();
-                last_xif = xif;
-            }
+        Ok(&self.grid.subgrids[[order, bin, lumi]])
+    }
 
-            for ((i, j, k), subgrid) in self.subgrids.indexed_iter() {
-                let order = &self.orders[i];
+    /// Loads all subgrids that have not been loaded yet and returns the complete grid.
+    ///
+    /// # Errors
+    ///
+    /// If reading one of the subgrids fails an error is returned.
+    pub fn into_grid(mut self) -> Result<Grid, GridReadError> {
+        let (orders, bins, lumis) = self.grid.subgrids.dim();
 
-                if ((order.logxir > 0) && (xir == 1.0)) || ((order.logxif > 0) && (xif == 1.0)) {
-                    continue;
-                }
+        for (order, bin, lumi) in iproduct!(0..orders, 0..bins, 0..lumis) {
+            self.subgrid(order, bin, lumi)?;
+        }
 
-                if (!order_mask.is_empty() && !order_mask[i])
-                    || (!lumi_mask.is_empty() && !lumi_mask[k])
-                {
-                    continue;
-                }
+        Ok(self.grid)
+    }
+}
 
-                let bin_index = match bin_indices.iter().position(|&index| index == j) {
-                    Some(i) => i,
-                    None => continue,
-                };
+/// Front-end of a `Grid` that can be filled from many threads at the same time.
+///
+/// Each subgrid is protected by its own lock, so that threads filling different subgrids do not
+/// wait for each other, and all threads share the memory of a single grid. Use
+/// `ConcurrentGrid::into_grid` to get the filled grid back.
+pub struct ConcurrentGrid {
+    grid: Grid,
+    subgrids: Array3<Mutex<(SubgridEnum, McStatistics)>>,
+}
 
-                let lumi_entry = &self.lumi[k];
+impl From<Grid> for ConcurrentGrid {
+    fn from(mut grid: Grid) -> Self {
+        let dim = grid.subgrids.dim();
+        let mc_statistics = grid
+            .mc_statistics
+            .clone()
+            .unwrap_or_else(|| Array3::default(dim));
+        let subgrids = Array3::from_shape_fn(dim, |index| {
+            Mutex::new((
+                mem::replace(&mut grid.subgrids[index], EmptySubgridV1::default().into()),
+                mc_statistics[index],
+            ))
+        });
+
+        Self { grid, subgrids }
+    }
+}
 
-                let mut value = if subgrid.is_empty() {
-                    0.0
-                } else if use_cache {
-                    let new_q2_grid = subgrid.q2_grid();
-                    let new_x1_grid = subgrid.x1_grid();
-                    let new_x2_grid = subgrid.x2_grid();
-                    let q2_grid_changed = new_q2_grid != q2_grid;
-
-                    if q2_grid_changed {
-                        q2_grid = new_q2_grid;
-                        alphas_cache.borrow_mut().clear();
-                    }
+impl ConcurrentGrid {
+    /// Returns the grid without its subgrids, which can be used to query its metadata.
+    #[must_use]
+    pub const fn grid(&self) -> &Grid {
+        &self.grid
+    }
 
-                    if q2_grid_changed || (new_x1_grid != x1_grid) || (new_x2_grid != x2_grid) {
-                        x1_grid = new_x1_grid;
-                        x2_grid = new_x2_grid;
-                        pdf_cache1.borrow_mut().clear();
-                        pdf_cache2.borrow_mut().clear();
-                    }
-
-                    subgrid.convolute(
-                        &x1_grid,
-                        &x2_grid,
-                        &q2_grid,
-                        Left(&|ix1, ix2, iq2| {
-                            let mut pdf_cache1 = pdf_cache1.borrow_mut();
-                            let mut pdf_cache2 = pdf_cache2.borrow_mut();
-                            let x1 = x1_grid[ix1];
-                            let x2 = x2_grid[ix2];
-                            let q2 = q2_grid[iq2];
-                            let q2f = xif * xif * q2;
-
-                            let mut lumi = 0.0;
+    /// Fills the grid with an ntuple for the given `order`, `observable`, and `lumi`, see
+    /// `Grid::fill`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error for the same reasons as `Grid::fill`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the order or luminosity index is out of range, or if another thread panicked
+    /// while filling the same subgrid.
+    pub fn fill(
+        &self,
+        order: usize,
+        observable: f64,
+        lumi: usize,
+        ntuple: &Ntuple<f64>,
+    ) -> Result<(), GridFillError> {
+        if let Some(bin) = self.grid.bin_limits.index(observable) {
+            let mut subgrid = self.lock_subgrid(order, bin, lumi);
+            fill_subgrid(&mut subgrid.0, (order, bin, lumi), ntuple)?;
+            subgrid.1.fill(ntuple.weight);
+        }
 
-                            for entry in lumi_entry.entry() {
-                                let xfx1 = *pdf_cache1
-                                    .entry((entry.0, ix1, iq2))
-                                    .or_insert_with(|| xfx1(entry.0, x1, q2f));
-                                let xfx2 = if two_caches {
-                                    *pdf_cache2
-                                        .entry((entry.1, ix2, iq2))
-                                        .or_insert_with(|| xfx2(entry.1, x2, q2f))
-                                } else {
-                                    *pdf_cache1
-                                        .entry((entry.1, ix2, iq2))
-                                        .or_insert_with(|| xfx2(entry.1, x2, q2f))
-                                };
-                                lumi += xfx1 * xfx2 * entry.2 / (x1 * x2);
-                            }
+        Ok(())
+    }
 
-                            let mut alphas_cache = alphas_cache.borrow_mut();
-                            let alphas = alphas_cache
-                                .entry(xir_values.len() * iq2 + xir_index)
-                                .or_insert_with(|| alphas(xir * xir * q2));
+    /// Fills the grid with a batch of events, see `Grid::fill_array`. Each subgrid is locked
+    /// only once for all of its events.
+    ///
+    /// # Panics
+    ///
+    /// Panics for the same reasons as `Grid::fill_array`, or if another thread panicked while
+    /// filling one of the same subgrids.
+    pub fn fill_array(&self, events: &[Event]) {
+        check_events(events, self.grid.convolutions());
 
-                            lumi *= alphas.powi(order.alphas.try_into().unwrap());
-                            lumi
-                        }),
-                    )
-                } else {
-                    subgrid.convolute(
-                        &x1_grid,
-                        &x2_grid,
-                        &q2_grid,
-                        Right(&|x1, x2, q2| {
-                            let mut lumi = 0.0;
-                            let q2f = xif * xif * q2;
+        let groups = group_events(&self.grid.bin_limits, events);
+        self.fill_groups(groups, events, false);
+    }
 
-                            for entry in lumi_entry.entry() {
-                                let xfx1 = xfx1(entry.0, x1, q2f);
-                                let xfx2 = xfx2(entry.1, x2, q2f);
-                                lumi += xfx1 * xfx2 * entry.2 / (x1 * x2);
-                            }
+    /// Fills the grid with a group of correlated events, see `Grid::fill_group`.
+    ///
+    /// # Panics
+    ///
+    /// Panics for the same reasons as `Grid::fill_array`, or if another thread panicked while
+    /// filling one of the same subgrids.
+    pub fn fill_group(&self, events: &[Event]) {
+        check_events(events, self.grid.convolutions());
 
-                            lumi *= alphas(xir * xir * q2).powi(order.alphas.try_into().unwrap());
-                            lumi
-                        }),
-                    )
-                };
+        let groups = group_events(&self.grid.bin_limits, events);
+        self.fill_groups(groups, events, true);
+    }
 
-                if order.logxir > 0 {
-                    value *= (xir * xir).ln().powi(order.logxir.try_into().unwrap());
-                }
+    /// Returns the filled grid.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a thread panicked while filling a subgrid.
+    #[must_use]
+    pub fn into_grid(self) -> Grid {
+        let mut grid = self.grid;
+        let mut subgrids = self.subgrids;
 
-                if order.logxif > 0 {
-                    value *= (xif * xif).ln().powi(order.logxif.try_into().unwrap());
-                }
+        for ((indices, subgrid), mutex) in grid.subgrids.indexed_iter_mut().zip(subgrids.iter_mut())
+        {
+            let (filled, statistics) = mutex.get_mut().unwrap();
+            mem::swap(subgrid, filled);
 
-                bins[l + xi.len() * bin_index] += value / bin_sizes[j];
+            if let Some(mc_statistics) = &mut grid.mc_statistics {
+                mc_statistics[indices] = *statistics;
             }
         }
 
-        bins
+        grid
     }
 
-    /// Convolutes a single subgrid `(order, bin, lumi)` with the PDFs strong coupling given by
-    /// `xfx1`, `xfx2` and `alphas`. The convolution result is fully differentially, such that the
-    /// axes of the result correspond to the values given by the subgrid `q2`, `x1` and `x2` grid
-    /// values.
-    pub fn convolute_subgrid(
+    fn fill_groups(
+        &self,
+        groups: Vec<((usize, usize, usize), Vec<usize>)>,
+        events: &[Event],
+        correlated: bool,
+    ) {
+        for ((order, bin, lumi), indices) in groups {
+            let mut subgrid = self.lock_subgrid(order, bin, lumi);
+            fill_events(&mut subgrid.0, &indices, events);
+            record_events(&mut subgrid.1, &indices, events, correlated);
+        }
+    }
+
+    fn lock_subgrid(
         &self,
-        xfx1: &dyn Fn(i32, f64, f64) -> f64,
-        xfx2: &dyn Fn(i32, f64, f64) -> f64,
-        alphas: &dyn Fn(f64) -> f64,
         order: usize,
         bin: usize,
         lumi: usize,
-        xir: f64,
-        xif: f64,
-    ) -> Array3<f64> {
-        let normalization = self.bin_info().normalizations()[bin];
+    ) -> MutexGuard<(SubgridEnum, McStatistics)> {
+        let mut subgrid = self.subgrids[[order, bin, lumi]].lock().unwrap();
+        make_fillable(&mut subgrid.0, &self.grid.more_members);
+        subgrid
+    }
+}
 
-        let pdf_cache1 = RefCell::new(FxHashMap::default());
-        let pdf_cache2 = RefCell::new(FxHashMap::default());
-        let alphas_cache = RefCell::new(FxHashMap::default());
+/// Main data structure of `PineAPPL`. This structure contains a `Subgrid` for each `LumiEntry`,
+/// bin, and coupling order it was created with.
+pub struct Grid {
+    subgrids: Array3<SubgridEnum>,
+    lumi: Vec<LumiEntry>,
+    bin_limits: BinLimits,
+    orders: Vec<Order>,
+    subgrid_params: SubgridParams,
+    more_members: MoreMembers,
+    mc_statistics: Option<Array3<McStatistics>>,
+}
 
-        let subgrid = &self.subgrids[[order, bin, lumi]];
-        let order = &self.orders[order];
+/// Magic bytes at the beginning of every file written by `Grid::write`.
+const FILE_MAGIC: [u8; 8] = *b"PineAPPL";
 
-        let mut array = if subgrid.is_empty() {
-            Array3::zeros((0, 0, 0))
-        } else {
-            let q2_grid = subgrid.q2_grid();
-            let x1_grid = subgrid.x1_grid();
-            let x2_grid = subgrid.x2_grid();
+/// Version of the file format written by `Grid::write`. Files without a header have version zero
+/// and can still be read, files of later versions are rejected. Every change of the layout of the
+/// file, which includes adding a member to one of the serialized types, requires a new version.
+const FILE_VERSION: u64 = 1;
 
-            let use_cache = !q2_grid.is_empty() && !x1_grid.is_empty() && !x2_grid.is_empty();
-            let two_caches = !ptr::eq(&xfx1, &xfx2);
+/// Metadata of a grid, which is everything but its subgrids, as it is stored at the beginning of
+/// a file. The subgrids follow the metadata, each compressed separately.
+#[derive(Deserialize)]
+struct Header {
+    shape: (usize, usize, usize),
+    lumi: Vec<LumiEntry>,
+    bin_limits: BinLimits,
+    orders: Vec<Order>,
+    subgrid_params: SubgridParams,
+    more_members: MoreMembers,
+    mc_statistics: Option<Array3<McStatistics>>,
+}
 
-            let lumi_entry = &self.lumi[lumi];
+impl From<Header> for Grid {
+    fn from(header: Header) -> Self {
+        Self {
+            subgrids: Array3::from_shape_simple_fn(header.shape, || {
+                EmptySubgridV1::default().into()
+            }),
+            lumi: header.lumi,
+            bin_limits: header.bin_limits,
+            orders: header.orders,
+            subgrid_params: header.subgrid_params,
+            more_members: header.more_members,
+            mc_statistics: header.mc_statistics,
+        }
+    }
+}
 
-            if use_cache {
-                let mut array = Array3::zeros((q2_grid.len(), x1_grid.len(), x2_grid.len()));
+/// Returns the checksum of a section of a file.
+fn checksum(bytes: &[u8]) -> u64 {
+    let mut hasher = XxHash64::with_seed(0);
+    hasher.write(bytes);
+    hasher.finish()
+}
 
-                for ((iq2, ix1, ix2), value) in subgrid.iter() {
-                    let mut pdf_cache1 = pdf_cache1.borrow_mut();
-                    let mut pdf_cache2 = pdf_cache2.borrow_mut();
-                    let x1 = x1_grid[ix1];
-                    let x2 = x2_grid[ix2];
-                    let q2 = q2_grid[iq2];
-                    let q2f = xif * xif * q2;
+/// Converts an error that occurred while reading `section` from the input.
+pub(crate) fn read_error(err: io::Error, section: &str) -> GridReadError {
+    if err.kind() == ErrorKind::UnexpectedEof {
+        GridReadError::Truncated(section.to_owned())
+    } else {
+        GridReadError::Io(err)
+    }
+}
 
-                    let mut lumi = 0.0;
+/// Converts an error that occurred while deserializing `section` directly from the input.
+fn deserialize_error(err: bincode::Error, section: &str) -> GridReadError {
+    match *err {
+        bincode::ErrorKind::Io(err) => read_error(err, section),
+        _ => GridReadError::Corrupted {
+            section: section.to_owned(),
+            source: err,
+        },
+    }
+}
 
-                    for entry in lumi_entry.entry() {
-                        let xfx1 = *pdf_cache1
-                            .entry((entry.0, ix1, iq2))
-                            .or_insert_with(|| xfx1(entry.0, x1, q2f));
-                        let xfx2 = if two_caches {
-                            *pdf_cache2
-                                .entry((entry.1, ix2, iq2))
-                                .or_insert_with(|| xfx2(entry.1, x2, q2f))
-                        } else {
-                            *pdf_cache1
-                                .entry((entry.1, ix2, iq2))
-                                .or_insert_with(|| xfx2(entry.1, x2, q2f))
-                        };
-                        lumi += xfx1 * xfx2 * entry.2 / (x1 * x2);
-                    }
+/// Deserializes `section` from `bytes`, which have been read from the input before.
+fn deserialize<'a, T: Deserialize<'a>>(bytes: &'a [u8], section: &str) -> Result<T, GridReadError> {
+    bincode::deserialize(bytes).map_err(|err| GridReadError::Corrupted {
+        section: section.to_owned(),
+        source: err,
+    })
+}
 
-                    let mut alphas_cache = alphas_cache.borrow_mut();
-                    let alphas = alphas_cache
-                        .entry(iq2)
-                        .or_insert_with(|| alphas(xir * xir * q2));
+/// Reads an unsigned integer, which is part of `section`, from `reader`.
+pub(crate) fn read_u64(mut reader: impl Read, section: &str) -> Result<u64, GridReadError> {
+    let mut bytes = [0; 8];
+    reader
+        .read_exact(&mut bytes)
+        .map_err(|err| read_error(err, section))?;
+    Ok(u64::from_le_bytes(bytes))
+}
 
-                    lumi *= alphas.powi(order.alphas.try_into().unwrap());
-                    array[[iq2, ix1, ix2]] = lumi * value;
-                }
+/// Writes `bytes` as a section of a file: the number of bytes, the bytes, and their checksum.
+/// Returns the size of the section.
+pub(crate) fn write_section(mut writer: impl Write, bytes: &[u8]) -> anyhow::Result<u64> {
+    let size = u64::try_from(bytes.len())?;
+    writer.write_all(&size.to_le_bytes())?;
+    writer.write_all(bytes)?;
+    writer.write_all(&checksum(bytes).to_le_bytes())?;
+    Ok(size + 16)
+}
 
-                array
-            } else {
-                todo!();
-            }
-        };
+/// Reads a section of a file that was written by `write_section` and verifies its checksum.
+pub(crate) fn read_section(mut reader: impl Read, section: &str) -> Result<Vec<u8>, GridReadError> {
+    let size = read_u64(&mut reader, section)?;
 
-        if order.logxir > 0 {
-            array *= (xir * xir).ln().powi(order.logxir.try_into().unwrap());
-        }
+    let mut bytes = Vec::new();
+    (&mut reader)
+        .take(size)
+        .read_to_end(&mut bytes)
+        .map_err(|err| read_error(err, section))?;
 
-        if order.logxif > 0 {
-            array *= (xif * xif).ln().powi(order.logxif.try_into().unwrap());
-        }
+    if u64::try_from(bytes.len()) != Ok(size) {
+        return Err(GridReadError::Truncated(section.to_owned()));
+    }
 
-        array /= normalization;
-        array
+    if read_u64(reader, section)? != checksum(&bytes) {
+        return Err(GridReadError::ChecksumMismatch(section.to_owned()));
     }
 
-    /// Fills the grid with an ntuple for the given `order`, `observable`, and `lumi`.
-    ///
-    /// # Panics
-    ///
-    /// TODO
-    pub fn fill(&mut self, order: usize, observable: f64, lumi: usize, ntuple: &Ntuple<f64>) {
-        if let Some(bin) = self.bin_limits.index(observable) {
-            let subgrid = &mut self.subgrids[[order, bin, lumi]];
-            if let SubgridEnum::EmptySubgridV1(_) = subgrid {
-                if let MoreMembers::V3(mmv3) = &self.more_members {
-                    *subgrid = mmv3.subgrid_template.clone_empty();
-                } else {
-                    unreachable!();
-                }
-            }
+    Ok(bytes)
+}
 
-            subgrid.fill(ntuple);
-        }
-    }
+/// Reads the metadata of a file from `reader`, which must be positioned after the file version.
+/// Returns the grid without its subgrids.
+fn read_header(reader: impl Read) -> Result<Grid, GridReadError> {
+    let header = read_section(reader, "metadata")?;
+    Ok(deserialize::<Header>(&header, "metadata")?.into())
+}
 
-    /// Constructs a `Grid` by deserializing it from `reader`. Reading is not buffered.
-    ///
-    /// # Errors
-    ///
-    /// If reading from the compressed or uncompressed stream fails an error is returned.
-    pub fn read(mut reader: impl Read + Seek) -> anyhow::Result<Self> {
-        match LZ4FrameReader::new(&mut reader) {
-            Ok(reader) => Ok(bincode::deserialize_from(reader.into_read())?),
-            Err(WrongMagic(_)) => {
-                reader.seek(SeekFrom::Start(0))?;
-                Ok(bincode::deserialize_from(reader)?)
-            }
-            Err(e) => Err(anyhow::Error::new(e)),
-        }
-    }
+/// Reads the index of the subgrids of `grid`, which follows the subgrids. Returns the offsets of
+/// the subgrids relative to the first subgrid, followed by the offset of the end of the last
+/// subgrid.
+fn read_index(reader: impl Read, grid: &Grid) -> Result<Vec<u64>, GridReadError> {
+    let section = "index of the subgrids";
+    let offsets: Vec<u64> = deserialize(&read_section(reader, section)?, section)?;
 
-    /// Serializes `self` into `writer`. Writing is not buffered.
-    ///
-    /// # Errors
-    ///
-    /// If writing fails an error is returned.
-    pub fn write(&self, writer: impl Write) -> anyhow::Result<()> {
-        Ok(bincode::serialize_into(writer, self)?)
+    if (offsets.len() != grid.subgrids.len() + 1) || offsets.windows(2).any(|w| w[0] > w[1]) {
+        return Err(GridReadError::InvalidIndex);
     }
 
-    /// Fills the grid with events for the parton momentum fractions `x1` and `x2`, the scale `q2`,
-    /// and the `order` and `observable`. The events are stored in `weights` and must be ordered as
-    /// the corresponding luminosity function was created.
-    pub fn fill_all(
-        &mut self,
-        order: usize,
-        observable: f64,
-        ntuple: &Ntuple<()>,
-        weights: &[f64],
-    ) {
-        for (lumi, weight) in weights.iter().enumerate() {
-            self.fill(
-                order,
-                observable,
-                lumi,
-                &Ntuple {
-                    x1: ntuple.x1,
-                    x2: ntuple.x2,
-                    q2: ntuple.q2,
-                    weight: *weight,
-                },
-            );
-        }
+    Ok(offsets)
+}
+
+/// Reads the index of the subgrids of `grid` using its position, relative to the first subgrid
+/// at `start`, which is stored at the end of the file.
+fn read_trailing_index(
+    mut reader: impl Read + Seek,
+    grid: &Grid,
+    start: u64,
+) -> Result<Vec<u64>, GridReadError> {
+    let section = "position of the index of the subgrids";
+    reader
+        .seek(SeekFrom::End(-8))
+        .map_err(|_| GridReadError::Truncated(section.to_owned()))?;
+    let position = read_u64(&mut reader, section)?;
+    let offset = start
+        .checked_add(position)
+        .ok_or(GridReadError::InvalidIndex)?;
+    reader.seek(SeekFrom::Start(offset))?;
+    let offsets = read_index(reader, grid)?;
+
+    if offsets.last() != Some(&position) {
+        return Err(GridReadError::InvalidIndex);
     }
 
-    /// Returns the luminosity function.
-    #[must_use]
-    pub fn lumi(&self) -> &[LumiEntry] {
-        &self.lumi
+    Ok(offsets)
+}
+
+/// Reads the subgrid with the given indices from `reader`.
+fn read_subgrid(
+    reader: impl Read,
+    (order, bin, lumi): (usize, usize, usize),
+) -> Result<SubgridEnum, GridReadError> {
+    let section = format!("subgrid ({}, {}, {})", order, bin, lumi);
+    let bytes = read_section(reader, &section)?;
+    let corrupted = |source| GridReadError::Corrupted {
+        section: section.clone(),
+        source,
+    };
+
+    bincode::deserialize_from(
+        LZ4FrameReader::new(bytes.as_slice())
+            .map_err(|err| corrupted(err.into()))?
+            .into_read(),
+    )
+    .map_err(|err| corrupted(err))
+}
+
+/// Checks that every event of a batch, see `Grid::fill_array`, has one momentum fraction for each
+/// of the `convolutions` of the grid, which must be two, since subgrids do not support any other
+/// number.
+fn check_events(events: &[Event], convolutions: usize) {
+    assert_eq!(
+        convolutions, 2,
+        "only grids with two convolutions can be filled, but this grid has {}",
+        convolutions
+    );
+
+    if let Some(event) = events.iter().find(|event| event.x.len() != convolutions) {
+        panic!(
+            "the grid has {} convolutions, but an event has {} momentum fractions",
+            convolutions,
+            event.x.len()
+        );
     }
+}
 
-    /// Merges the bins for the corresponding range together in a single one.
-    pub fn merge_bins(&mut self, bins: Range<usize>) -> Result<(), ()> {
-        if (bins.start >= self.bin_limits.bins()) || (bins.end > self.bin_limits.bins()) {
-            return Err(());
-        }
+/// Groups the `events` by the subgrid they are filled into, skipping events outside of
+/// `bin_limits`. The groups are sorted by the indices of their subgrids, and the events of each
+/// group, given by their indices into `events`, are in the order they were given.
+fn group_events(
+    bin_limits: &BinLimits,
+    events: &[Event],
+) -> Vec<((usize, usize, usize), Vec<usize>)> {
+    let mut events: Vec<_> = events
+        .iter()
+        .enumerate()
+        .filter_map(|(index, event)| {
+            bin_limits
+                .index(event.observable)
+                .map(|bin| ((event.order, bin, event.lumi), index))
+        })
+        .collect();
+    // the sort is stable, which keeps the order of the events of each subgrid
+    events.sort_by_key(|&(indices, _)| indices);
+
+    events
+        .into_iter()
+        .group_by(|&(indices, _)| indices)
+        .into_iter()
+        .map(|(indices, group)| (indices, group.map(|(_, index)| index).collect()))
+        .collect()
+}
 
-        self.bin_limits.merge_bins(bins.clone());
+/// Fills `subgrid` with the elements of `events` given by `indices`, see `Grid::fill_array`.
+fn fill_events(subgrid: &mut SubgridEnum, indices: &[usize], events: &[Event]) {
+    for &index in indices {
+        let event = &events[index];
+
+        subgrid.fill(&Ntuple {
+            x1: event.x[0],
+            x2: event.x[1],
+            q2: event.q2,
+            q2r: event.q2,
+            weight: event.weight,
+        });
+    }
+}
 
-        match &mut self.more_members {
-            MoreMembers::V1(_) => {}
-            MoreMembers::V2(mmv2) => {
-                if let Some(remapper) = &mut mmv2.remapper {
-                    remapper.merge_bins(bins.clone())?;
-                }
-            }
-            MoreMembers::V3(mmv3) => {
-                if let Some(remapper) = &mut mmv3.remapper {
-                    remapper.merge_bins(bins.clone())?;
-                }
-            }
+/// Records the elements of `events` given by `indices` in `statistics`. If `correlated` is `true`
+/// the events are recorded as a single event, whose weight is the sum of their weights.
+fn record_events(
+    statistics: &mut McStatistics,
+    indices: &[usize],
+    events: &[Event],
+    correlated: bool,
+) {
+    if correlated {
+        statistics.fill(indices.iter().map(|&index| events[index].weight).sum());
+    } else {
+        for &index in indices {
+            statistics.fill(events[index].weight);
         }
+    }
+}
 
-        let mut old_subgrids = mem::replace(
-            &mut self.subgrids,
-            Array3::from_shape_simple_fn(
-                (self.orders.len(), self.bin_limits.bins(), self.lumi.len()),
-                || EmptySubgridV1::default().into(),
-            ),
-        );
+/// Luminosity function entry as it was stored in files without a header, which only supported
+/// two convolutions.
+#[derive(Deserialize)]
+struct LumiEntryV0 {
+    entry: Vec<(i32, i32, f64)>,
+}
 
-        for ((order, bin, lumi), subgrid) in old_subgrids.indexed_iter_mut() {
-            if bins.contains(&bin) {
-                let new_subgrid = &mut self.subgrids[[order, bins.start, lumi]];
+/// Coupling powers as they were stored in files without a header, which did not support
+/// fragmentation-scale logarithms.
+#[derive(Deserialize)]
+struct OrderV0 {
+    alphas: u32,
+    alpha: u32,
+    logxir: u32,
+    logxif: u32,
+}
 
-                if new_subgrid.is_empty() {
-                    mem::swap(new_subgrid, subgrid);
-                } else {
-                    new_subgrid.merge(subgrid, false);
-                }
-            } else {
-                let new_bin = if bin > bins.start {
-                    bin - (bins.end - bins.start) + 1
-                } else {
-                    bin
-                };
+/// Layout of `Grid` in files without a header.
+#[derive(Deserialize)]
+struct GridV0 {
+    subgrids: Array3<SubgridEnum>,
+    lumi: Vec<LumiEntryV0>,
+    bin_limits: BinLimits,
+    orders: Vec<OrderV0>,
+    subgrid_params: SubgridParams,
+    more_members: MoreMembers,
+}
 
-                mem::swap(&mut self.subgrids[[order, new_bin, lumi]], subgrid);
-            }
+impl From<GridV0> for Grid {
+    fn from(grid: GridV0) -> Self {
+        Self {
+            subgrids: grid.subgrids,
+            lumi: grid
+                .lumi
+                .into_iter()
+                .map(|entry| {
+                    LumiEntry::new(
+                        entry
+                            .entry
+                            .into_iter()
+                            .map(|(a, b, factor)| (vec![a, b], factor))
+                            .collect(),
+                    )
+                })
+                .collect(),
+            bin_limits: grid.bin_limits,
+            orders: grid
+                .orders
+                .into_iter()
+                .map(|order| Order::new(order.alphas, order.alpha, order.logxir, order.logxif, 0))
+                .collect(),
+            subgrid_params: grid.subgrid_params,
+            more_members: grid.more_members,
+            mc_statistics: None,
         }
-
-        Ok(())
     }
+}
 
-    /// Merges the non-empty `Subgrid`s contained in `other` into `self`. This performs one of two
-    /// possible operations:
-    /// 1. If the bin limits of `self` and `other` are different and can be concatenated with each
-    ///    other the bins are merged. In this case both grids are assumed to have the same orders
-    ///    and the same luminosity functions. If this is not the case, an error is returned.
-    /// 2. If the bin limits of `self` and `other` are the same, the luminosity functions and
-    ///    perturbative orders of `self` and `other` may be different.
-    ///
-    /// # Errors
-    ///
-    /// If in the first case describe above the perturbative orders or the luminosity function is
-    /// different an error is returned.
+impl Grid {
+    /// Constructor. The number of convolutions of the grid is given by the number of PDG ids in
+    /// the entries of `lumi`. Grids with two convolutions use subgrids of type
+    /// `LagrangeSubgridV2`, all other grids subgrids of type `LagrangeSubgridV3`.
     ///
     /// # Panics
     ///
-    /// TODO
-    pub fn merge(&mut self, mut other: Self) -> Result<(), GridMergeError> {
-        if self.bin_limits == other.bin_limits {
-            let mut new_orders: Vec<Order> = Vec::new();
-            let mut new_entries: Vec<LumiEntry> = Vec::new();
-
-            for ((i, _, k), _) in other
-                .subgrids
-                .indexed_iter_mut()
-                .filter(|((_, _, _), subgrid)| !subgrid.is_empty())
+    /// Panics if the entries of `lumi` have different numbers of convolutions.
+    #[must_use]
+    pub fn new(
+        lumi: Vec<LumiEntry>,
+        orders: Vec<Order>,
+        bin_limits: Vec<f64>,
+        subgrid_params: SubgridParams,
+    ) -> Self {
+        let convolutions = convolutions(&lumi);
+        let subgrid_template = if convolutions == 2 {
+            LagrangeSubgridV2::new(&subgrid_params, &ExtraSubgridParams::from(&subgrid_params))
+                .into()
+        } else {
+            LagrangeSubgridV3::new(&subgrid_params, convolutions).into()
+        };
+
+        Self {
+            subgrids: Array3::from_shape_simple_fn(
+                (orders.len(), bin_limits.len() - 1, lumi.len()),
+                || EmptySubgridV1::default().into(),
+            ),
+            orders,
+            lumi,
+            bin_limits: BinLimits::new(bin_limits),
+            more_members: MoreMembers::V3(Mmv3::new(subgrid_template, convolutions)),
+            subgrid_params,
+            mc_statistics: None,
+        }
+    }
+
+    /// Constructor. This function can be used like `new`, but the additional parameter
+    /// `subgrid_type` selects the underlying `Subgrid` type. Supported values are:
+    /// - `LagrangeSubgrid`
+    /// - `LagrangeSparseSubgrid`
+    /// - `LagrangeSubgridV3`
+    /// - `NtupleSubgrid`
+    ///
+    /// For grids that do not have two convolutions, `LagrangeSubgrid` selects `LagrangeSubgridV3`,
+    /// which is the only type supporting them. Only `NtupleSubgrid` can be filled with n-tuples
+    /// whose renormalization scale is different from the factorization scale.
+    ///
+    /// # Errors
+    ///
+    /// If `subgrid_type` is none of the values listed above, an error is returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the entries of `lumi` have different numbers of convolutions.
+    pub fn with_subgrid_type(
+        lumi: Vec<LumiEntry>,
+        orders: Vec<Order>,
+        bin_limits: Vec<f64>,
+        subgrid_params: SubgridParams,
+        extra: ExtraSubgridParams,
+        subgrid_type: &str,
+    ) -> Result<Self, UnknownSubgrid> {
+        let convolutions = convolutions(&lumi);
+        let subgrid_template: SubgridEnum = match subgrid_type {
+            "LagrangeSubgrid" if convolutions != 2 => {
+                LagrangeSubgridV3::new(&subgrid_params, convolutions).into()
+            }
+            "LagrangeSubgrid" | "LagrangeSubgridV2" => {
+                LagrangeSubgridV2::new(&subgrid_params, &extra).into()
+            }
+            "LagrangeSubgridV3" => LagrangeSubgridV3::new(&subgrid_params, convolutions).into(),
+            "LagrangeSubgridV1" => LagrangeSubgridV1::new(&subgrid_params).into(),
+            "NtupleSubgrid" => NtupleSubgridV2::new().into(),
+            "LagrangeSparseSubgrid" => LagrangeSparseSubgridV1::new(&subgrid_params).into(),
+            _ => return Err(UnknownSubgrid(subgrid_type.to_string())),
+        };
+
+        Ok(Self {
+            subgrids: Array3::from_shape_simple_fn(
+                (orders.len(), bin_limits.len() - 1, lumi.len()),
+                || EmptySubgridV1::default().into(),
+            ),
+            orders,
+            lumi,
+            bin_limits: BinLimits::new(bin_limits),
+            subgrid_params,
+            more_members: MoreMembers::V3(Mmv3::new(subgrid_template, convolutions)),
+            mc_statistics: None,
+        })
+    }
+
+    /// Performs a convolution of the contained subgrids with the given PDFs, one for each
+    /// convolution of this grid. For hadron-hadron collisions `pdfs[0]` must be the PDF of the
+    /// first initial state and `pdfs[1]` the one of the second initial state. The strong and the
+    /// electroweak coupling, see `PdfProvider::alpha`, are taken from `pdfs[0]`. Whether the PDFs
+    /// are used as they are, charge conjugated, or not at all is determined by comparing the
+    /// particle ids of the PDFs with the metadata `initial_state_1`, `initial_state_2`, and so on;
+    /// if the latter are missing, protons are assumed. The parameters `order_mask` and `lumi_mask`
+    /// can be used to selectively enable perturbative orders and luminosities; they must either be
+    /// empty (everything enabled) or as large as the orders and luminosity function, respectively.
+    /// If the corresponding entry is `true` the order/luminosity is enable, `false` disables the
+    /// entry. The tuple `xi` can be used to independently vary the renormalization (first element),
+    /// factorization (second element) and fragmentation scale (third element) from their central
+    /// value `(1.0, 1.0, 1.0)`. Convolutions whose type, see `Grid::convolution_types`, is a
+    /// fragmentation function are evaluated at the fragmentation scale; the corresponding element
+    /// of `pdfs` must then provide the fragmentation function.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of PDFs is not the same as the number of convolutions of this grid,
+    /// or if one of the PDFs can not be used for the corresponding initial state of this grid,
+    /// which is the case if the initial state is neither the particle of the PDF, its
+    /// anti-particle nor a lepton. It also panics if the basis of the particle ids of this grid
+    /// is unknown, see `Grid::pid_basis`, which `Grid::validate` reports.
+    pub fn convolute(
+        &self,
+        pdfs: &[&dyn PdfProvider],
+        order_mask: &[bool],
+        bin_indices: &[usize],
+        lumi_mask: &[bool],
+        xi: &[(f64, f64, f64)],
+    ) -> Vec<f64> {
+        let bin_indices = if bin_indices.is_empty() {
+            (0..self.bin_limits.bins()).collect()
+        } else {
+            bin_indices.to_vec()
+        };
+        let mut bins: Vec<f64> = vec![0.0; bin_indices.len() * xi.len()];
+        let bin_sizes = self.bin_info().normalizations();
+
+        self.convolute_subgrids(
+            pdfs,
+            order_mask,
+            &bin_indices,
+            lumi_mask,
+            xi,
+            &mut ConvolutionCache::default(),
+            |l, bin_index, (_, j, _), value| {
+                bins[l + xi.len() * bin_index] += value / bin_sizes[j];
+            },
+        );
+
+        bins
+    }
+
+    /// Convolutes every subgrid selected by `order_mask`, `bin_indices`, and `lumi_mask` for each
+    /// element of `xi`, see `Grid::convolute`, and calls `add` with the index of the element of
+    /// `xi`, the position of the bin in `bin_indices`, the indices of the subgrid and the result,
+    /// which is not divided by the bin size. The PDF and coupling values are stored in `cache`,
+    /// which can be reused for subsequent calls with the same `pdfs` and `xi`.
+    #[allow(clippy::float_cmp, clippy::too_many_arguments)]
+    fn convolute_subgrids(
+        &self,
+        pdfs: &[&dyn PdfProvider],
+        order_mask: &[bool],
+        bin_indices: &[usize],
+        lumi_mask: &[bool],
+        xi: &[(f64, f64, f64)],
+        cache: &mut ConvolutionCache,
+        mut add: impl FnMut(usize, usize, (usize, usize, usize), f64),
+    ) {
+        let pdfs = self.initial_state_pdfs(pdfs);
+        // for each initial state the index of the first initial state with the same PDFs
+        let same_pdfs: Vec<_> = (0..pdfs.len())
+            .map(|index| {
+                (0..index)
+                    .find(|&other| pdfs[other].same_pdfs(&pdfs[index]))
+                    .unwrap_or(index)
+            })
+            .collect();
+
+        let use_cache = self
+            .subgrids
+            .iter()
+            .find(|subgrid| !subgrid.is_empty())
+            .into_iter()
+            .any(|subgrid| {
+                !subgrid.mu2_grid().is_empty()
+                    && subgrid.x_grids().iter().all(|x_grid| !x_grid.is_empty())
+            });
+
+        let mut xir_values: Vec<_> = xi.iter().map(|xi| xi.0).collect();
+        xir_values.sort_by(|lhs, rhs| lhs.partial_cmp(rhs).unwrap());
+        xir_values.dedup();
+        let xir_indices: Vec<_> = xi
+            .iter()
+            .map(|xi| xir_values.iter().position(|xir| xi.0 == *xir).unwrap())
+            .collect();
+
+        // iterate over the elements of `xi` and a corresponding index, but sorted using the
+        // factorisation and fragmentation values of `xi`
+        for (l, (&(xir, xif, xia), &xir_index)) in xi
+            .iter()
+            .zip(xir_indices.iter())
+            .enumerate()
+            .sorted_by(|lhs, rhs| {
+                ((lhs.1).0 .1, (lhs.1).0 .2)
+                    .partial_cmp(&((rhs.1).0 .1, (rhs.1).0 .2))
+                    .unwrap()
+            })
+        {
+            // whenever the values `xif` or `xia` change we can clear the PDF cache
+            if (xif, xia) != cache.xif_xia {
+                cache.pdfs.get_mut().clear();
+                cache.xif_xia = (xif, xia);
+            }
+
+            let scale_factors = self.scale_factors(xif, xia);
+
+            // iterating over the selected bins, instead of over all subgrids, keeps the
+            // convolution of a few bins cheap; bins that do not exist are skipped
+            for (bin_index, (i, j, k)) in bin_indices
+                .iter()
+                .copied()
+                .enumerate()
+                .filter(|&(_, j)| j < self.subgrids.dim().1)
+                .flat_map(|(bin_index, j)| {
+                    iproduct!(0..self.orders.len(), 0..self.lumi.len())
+                        .map(move |(i, k)| (bin_index, (i, j, k)))
+                })
             {
-                let other_order = &other.orders[i];
-                let other_entry = &other.lumi[k];
+                let subgrid = &self.subgrids[[i, j, k]];
+                let order = &self.orders[i];
 
-                if !self
-                    .orders
-                    .iter()
-                    .chain(new_orders.iter())
-                    .any(|x| x == other_order)
+                if ((order.logxir > 0) && (xir == 1.0))
+                    || ((order.logxif > 0) && (xif == 1.0))
+                    || ((order.logxia > 0) && (xia == 1.0))
                 {
-                    new_orders.push(other_order.clone());
+                    continue;
                 }
 
-                if !self
-                    .lumi
-                    .iter()
-                    .chain(new_entries.iter())
-                    .any(|y| y == other_entry)
+                if (!order_mask.is_empty() && !order_mask[i])
+                    || (!lumi_mask.is_empty() && !lumi_mask[k])
                 {
-                    new_entries.push(other_entry.clone());
+                    continue;
                 }
-            }
 
-            if !new_orders.is_empty() || !new_entries.is_empty() {
-                self.increase_shape(&(new_orders.len(), 0, new_entries.len()));
+                let lumi_entry = &self.lumi[k];
+
+                let mut value = if subgrid.is_empty() {
+                    0.0
+                } else if use_cache {
+                    let new_mu2_grid = subgrid.mu2_grid();
+                    let new_x_grids = subgrid.x_grids();
+                    let mu2_grid_changed = *new_mu2_grid != *cache.mu2_grid;
+
+                    if mu2_grid_changed {
+                        cache.mu2_grid = new_mu2_grid.into_owned();
+                        cache.alphas.get_mut().clear();
+                    }
+
+                    if mu2_grid_changed
+                        || !new_x_grids
+                            .iter()
+                            .map(AsRef::as_ref)
+                            .eq(cache.x_grids.iter().map(Vec::as_slice))
+                    {
+                        cache.x_grids = new_x_grids.into_iter().map(Cow::into_owned).collect();
+                        cache.pdfs.get_mut().clear();
+                    }
+
+                    let mu2_grid = &cache.mu2_grid;
+                    let x_grids = &cache.x_grids;
+                    let pdf_cache = &cache.pdfs;
+                    let alphas_cache = &cache.alphas;
+
+                    // initial states with the same PDFs, scales and momentum fractions share the
+                    // entries of the PDF cache
+                    let cache_indices: Vec<_> = same_pdfs
+                        .iter()
+                        .enumerate()
+                        .map(|(index, &other)| {
+                            if (scale_factors[other] == scale_factors[index])
+                                && (x_grids[other] == x_grids[index])
+                            {
+                                other
+                            } else {
+                                index
+                            }
+                        })
+                        .collect();
+
+                    let x: Vec<&[f64]> = x_grids.iter().map(Vec::as_slice).collect();
+
+                    subgrid.convolute(
+                        &x,
+                        mu2_grid,
+                        Left(&|ix, imu2| {
+                            let mut pdf_cache = pdf_cache.borrow_mut();
+                            let mu2 = mu2_grid[imu2];
+
+                            let mut lumi = 0.0;
+
+                            // the PDFs are evaluated for all `x` values of a subgrid at once
+                            for (pids, factor) in lumi_entry.entry() {
+                                let mut xfx = 1.0;
+                                let mut x = 1.0;
+
+                                for (index, (&pid, &ix)) in pids.iter().zip(ix).enumerate() {
+                                    let cache_index = cache_indices[index];
+
+                                    xfx *= pdf_cache
+                                        .entry((cache_index, pid, imu2))
+                                        .or_insert_with(|| {
+                                            let fac = scale_factors[index] * mu2.fac;
+                                            xfx_batch(&pdfs[index], pid, &x_grids[index], fac)
+                                        })[ix];
+                                    x *= x_grids[index][ix];
+                                }
+
+                                lumi += xfx * factor / x;
+                            }
+
+                            let mut alphas_cache = alphas_cache.borrow_mut();
+                            let (alphas, alpha) = alphas_cache
+                                .entry(xir_values.len() * imu2 + xir_index)
+                                .or_insert_with(|| {
+                                    let ren = xir * xir * mu2.ren;
+                                    (pdfs[0].alphas(ren), pdfs[0].alpha(ren))
+                                });
+
+                            lumi *= alphas.powi(order.alphas.try_into().unwrap());
+                            lumi *= alpha.powi(order.alpha.try_into().unwrap());
+                            lumi
+                        }),
+                    )
+                } else {
+                    subgrid.convolute(
+                        &[],
+                        &[],
+                        Right(&|x, mu2| {
+                            let mut lumi = 0.0;
+
+                            for (pids, factor) in lumi_entry.entry() {
+                                let mut xfx = 1.0;
+                                let mut x_product = 1.0;
+
+                                for (((pdf, &pid), &x), &scale_factor) in
+                                    pdfs.iter().zip(pids).zip(x).zip(&scale_factors)
+                                {
+                                    xfx *= pdf.xfx(pid, x, scale_factor * mu2.fac);
+                                    x_product *= x;
+                                }
+
+                                lumi += xfx * factor / x_product;
+                            }
+
+                            let ren = xir * xir * mu2.ren;
+
+                            lumi *= pdfs[0].alphas(ren).powi(order.alphas.try_into().unwrap());
+                            lumi *= pdfs[0].alpha(ren).powi(order.alpha.try_into().unwrap());
+                            lumi
+                        }),
+                    )
+                };
+
+                if order.logxir > 0 {
+                    value *= (xir * xir).ln().powi(order.logxir.try_into().unwrap());
+                }
+
+                if order.logxif > 0 {
+                    value *= (xif * xif).ln().powi(order.logxif.try_into().unwrap());
+                }
+
+                if order.logxia > 0 {
+                    value *= (xia * xia).ln().powi(order.logxia.try_into().unwrap());
+                }
+
+                add(l, bin_index, (i, j, k), value);
             }
+        }
+    }
 
-            self.orders.append(&mut new_orders);
-            self.lumi.append(&mut new_entries);
+    /// Performs the same convolution as `Grid::convolute`, but distributes the subgrids of each
+    /// pair of bin and luminosity over the threads of the global `rayon` thread pool. The results
+    /// are the same as for the serial version, because the contributions of each bin are summed
+    /// in the same order. Every thread keeps its own PDF and coupling caches for all the pairs it
+    /// convolutes, which is why the PDFs must be `Sync`.
+    ///
+    /// This method is only available if the `rayon` feature is enabled.
+    ///
+    /// # Panics
+    ///
+    /// Panics for the same reasons as `Grid::convolute`, which includes an unknown basis of the
+    /// particle ids of this grid, see `Grid::pid_basis`.
+    #[cfg(feature = "rayon")]
+    pub fn par_convolute(
+        &self,
+        pdfs: &[&(dyn PdfProvider + Sync)],
+        order_mask: &[bool],
+        bin_indices: &[usize],
+        lumi_mask: &[bool],
+        xi: &[(f64, f64, f64)],
+    ) -> Vec<f64> {
+        let bin_indices: Vec<_> = if bin_indices.is_empty() {
+            (0..self.bin_limits.bins()).collect()
         } else {
-            if !Order::equal_after_sort(&self.orders, &other.orders) {
-                return Err(GridMergeError::DifferentOrders);
-            }
+            bin_indices.to_vec()
+        };
 
-            if !LumiEntry::equal_after_sort(&self.lumi, &other.lumi) {
-                return Err(GridMergeError::DifferentLumi);
+        let bin_sizes = self.bin_info().normalizations();
+        let orders = self.orders.len();
+        let lumis = self.lumi.len();
+
+        // every chunk convolutes the subgrids of a single bin and luminosity, and stores each
+        // contribution separately, so that they can be summed in the order of `Grid::convolute`
+        let chunks: Vec<_> = iproduct!(0..bin_indices.len(), 0..lumis)
+            .filter(|&(_, k)| lumi_mask.is_empty() || lumi_mask[k])
+            .collect();
+        let contributions: Vec<_> = chunks
+            .par_iter()
+            .map_init(ConvolutionCache::default, |cache, &(bin_index, k)| {
+                let pdfs: Vec<&dyn PdfProvider> = pdfs.iter().map(|&pdf| pdf as _).collect();
+                let mut chunk_lumi_mask = vec![false; lumis];
+                chunk_lumi_mask[k] = true;
+                let mut values = vec![0.0; xi.len() * orders];
+
+                self.convolute_subgrids(
+                    &pdfs,
+                    order_mask,
+                    &[bin_indices[bin_index]],
+                    &chunk_lumi_mask,
+                    xi,
+                    cache,
+                    |l, _, (i, j, _), value| values[l * orders + i] = value / bin_sizes[j],
+                );
+
+                values
+            })
+            .collect();
+
+        let mut bins = vec![0.0; bin_indices.len() * xi.len()];
+
+        for (bin_index, group) in &chunks
+            .iter()
+            .map(|&(bin_index, _)| bin_index)
+            .zip(&contributions)
+            .group_by(|&(bin_index, _)| bin_index)
+        {
+            let group: Vec<_> = group.map(|(_, values)| values).collect();
+
+            // contributions of skipped subgrids are zero and don't change the sums
+            for (l, i) in iproduct!(0..xi.len(), 0..orders) {
+                for values in &group {
+                    bins[l + xi.len() * bin_index] += values[l * orders + i];
+                }
             }
+        }
+
+        bins
+    }
+
+    /// Estimates the Monte Carlo uncertainty of the result of `Grid::convolute` for the central
+    /// scale choice, which has the same parameters except `xi`, from the statistics tracked
+    /// while filling this grid. Returns `None` if the statistics are not tracked, see
+    /// `Grid::enable_mc_statistics`.
+    ///
+    /// The convolution of each subgrid is multiplied with the relative uncertainty of the sum of
+    /// its weights, see `McStatistics::relative_uncertainty`, and the results of different
+    /// orders and luminosities are added in quadrature. Correlations between different subgrids,
+    /// which arise for instance when the same phase-space point is filled into several
+    /// luminosities, are neglected.
+    ///
+    /// # Panics
+    ///
+    /// Panics for the same reasons as `Grid::convolute`, which includes an unknown basis of the
+    /// particle ids of this grid, see `Grid::pid_basis`.
+    #[must_use]
+    pub fn convolute_mc_uncertainty(
+        &self,
+        pdfs: &[&dyn PdfProvider],
+        order_mask: &[bool],
+        bin_indices: &[usize],
+        lumi_mask: &[bool],
+    ) -> Option<Vec<f64>> {
+        let mc_statistics = self.mc_statistics.as_ref()?;
+        let bin_indices = if bin_indices.is_empty() {
+            (0..self.bin_limits.bins()).collect()
+        } else {
+            bin_indices.to_vec()
+        };
+        let mut variances = vec![0.0; bin_indices.len()];
+        let bin_sizes = self.bin_info().normalizations();
+
+        self.convolute_subgrids(
+            pdfs,
+            order_mask,
+            &bin_indices,
+            lumi_mask,
+            &[(1.0, 1.0, 1.0)],
+            &mut ConvolutionCache::default(),
+            |_, bin_index, (i, j, k), value| {
+                let uncertainty =
+                    mc_statistics[[i, j, k]].relative_uncertainty() * value / bin_sizes[j];
+                variances[bin_index] += uncertainty * uncertainty;
+            },
+        );
+
+        Some(variances.into_iter().map(f64::sqrt).collect())
+    }
+
+    /// Convolutes a single subgrid `(order, bin, lumi)` with the PDFs `pdfs[0]` and `pdfs[1]`, and
+    /// the strong and electroweak couplings of `pdfs[0]`. The convolution result is fully
+    /// differentially, such that the axes of the result correspond to the values given by the
+    /// subgrid `mu2`, `x1` and `x2` grid values. The scale factors `xir`, `xif` and `xia` have the
+    /// same meaning as the elements of `xi` in `Grid::convolute`. Subgrids of type `NtupleSubgrid`
+    /// do not have these grids; their n-tuples are histogrammed with the bins given by
+    /// `Grid::subgrid_params`, see `NtupleSubgridV1::histogram`, and the axes of the result
+    /// correspond to the bin centers.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this grid does not have exactly two convolutions, and for the same reasons as
+    /// `Grid::convolute`, which includes an unknown basis of the particle ids of this grid, see
+    /// `Grid::pid_basis`.
+    #[allow(clippy::float_cmp)]
+    pub fn convolute_subgrid(
+        &self,
+        pdfs: &[&dyn PdfProvider],
+        order: usize,
+        bin: usize,
+        lumi: usize,
+        xir: f64,
+        xif: f64,
+        xia: f64,
+    ) -> Array3<f64> {
+        assert_eq!(
+            self.convolutions(),
+            2,
+            "only grids with two convolutions are supported"
+        );
+
+        let pdfs = self.initial_state_pdfs(pdfs);
+        let (pdf1, pdf2) = (&pdfs[0], &pdfs[1]);
+        let scale_factors = self.scale_factors(xif, xia);
+        let normalization = self.bin_info().normalizations()[bin];
+
+        let mut pdf_cache1 = FxHashMap::default();
+        let mut pdf_cache2 = FxHashMap::default();
+        let mut alphas_cache = FxHashMap::default();
+
+        let subgrid = &self.subgrids[[order, bin, lumi]];
+        let order = &self.orders[order];
+
+        let histogram: SubgridEnum;
+        let subgrid = match subgrid {
+            SubgridEnum::NtupleSubgridV1(grid) if !subgrid.is_empty() => {
+                histogram = grid.histogram(&self.subgrid_params).into();
+                &histogram
+            }
+            SubgridEnum::NtupleSubgridV2(grid) if !subgrid.is_empty() => {
+                histogram = grid.histogram(&self.subgrid_params).into();
+                &histogram
+            }
+            _ => subgrid,
+        };
+
+        let mut array = if subgrid.is_empty() {
+            Array3::zeros((0, 0, 0))
+        } else {
+            let mu2_grid = subgrid.mu2_grid();
+            let x1_grid = subgrid.x1_grid();
+            let x2_grid = subgrid.x2_grid();
+
+            let use_cache = !mu2_grid.is_empty() && !x1_grid.is_empty() && !x2_grid.is_empty();
+
+            let lumi_entry = &self.lumi[lumi];
+            // both initial states share a cache if they have the same PDFs, scales and momentum
+            // fractions
+            let shared_cache = pdf1.same_pdfs(pdf2)
+                && (scale_factors[0] == scale_factors[1])
+                && (x1_grid == x2_grid);
+
+            if use_cache {
+                let mut array = Array3::zeros((mu2_grid.len(), x1_grid.len(), x2_grid.len()));
+
+                for (index, value) in subgrid.iter() {
+                    let (imu2, ix1, ix2) = (index[0], index[1], index[2]);
+                    let x1 = x1_grid[ix1];
+                    let x2 = x2_grid[ix2];
+                    let mu2 = mu2_grid[imu2];
+                    let fac = [scale_factors[0] * mu2.fac, scale_factors[1] * mu2.fac];
+
+                    let mut lumi = 0.0;
+
+                    for (pids, factor) in lumi_entry.entry() {
+                        let xfx1 = pdf_cache1
+                            .entry((pids[0], imu2))
+                            .or_insert_with(|| xfx_batch(pdf1, pids[0], &x1_grid, fac[0]))[ix1];
+                        let pdf_cache2 = if shared_cache {
+                            &mut pdf_cache1
+                        } else {
+                            &mut pdf_cache2
+                        };
+                        let xfx2 = pdf_cache2
+                            .entry((pids[1], imu2))
+                            .or_insert_with(|| xfx_batch(pdf2, pids[1], &x2_grid, fac[1]))[ix2];
+                        lumi += xfx1 * xfx2 * factor / (x1 * x2);
+                    }
+
+                    let (alphas, alpha) = alphas_cache.entry(imu2).or_insert_with(|| {
+                        let ren = xir * xir * mu2.ren;
+                        (pdf1.alphas(ren), pdf1.alpha(ren))
+                    });
+
+                    lumi *= alphas.powi(order.alphas.try_into().unwrap());
+                    lumi *= alpha.powi(order.alpha.try_into().unwrap());
+                    array[[imu2, ix1, ix2]] = lumi * value;
+                }
+
+                array
+            } else {
+                // all subgrids without grids, the n-tuple subgrids, were histogrammed above
+                unreachable!();
+            }
+        };
+
+        if order.logxir > 0 {
+            array *= (xir * xir).ln().powi(order.logxir.try_into().unwrap());
+        }
+
+        if order.logxif > 0 {
+            array *= (xif * xif).ln().powi(order.logxif.try_into().unwrap());
+        }
+
+        if order.logxia > 0 {
+            array *= (xia * xia).ln().powi(order.logxia.try_into().unwrap());
+        }
+
+        array /= normalization;
+        array
+    }
+
+    /// Returns the PDG MC id of the initial state with the given `index`, which starts at `1`. If
+    /// the metadata does not specify the initial state, a proton is assumed.
+    fn initial_state(&self, index: usize) -> i32 {
+        self.key_values()
+            .and_then(|map| map.get(&format!("initial_state_{}", index)))
+            .map_or(2212, |value| {
+                value.parse().unwrap_or_else(|_| {
+                    panic!("could not parse initial_state_{}: `{}`", index, value)
+                })
+            })
+    }
+
+    /// Returns the squared scale factor for each convolution, which is `xif * xif` for PDFs and
+    /// `xia * xia` for fragmentation functions.
+    fn scale_factors(&self, xif: f64, xia: f64) -> Vec<f64> {
+        self.convolution_types()
+            .iter()
+            .map(|convolution_type| match convolution_type {
+                ConvolutionType::Pdf => xif * xif,
+                ConvolutionType::FragmentationFunction => xia * xia,
+            })
+            .collect()
+    }
+
+    /// Wraps each PDF in `pdfs` such that it provides the PDFs for the corresponding initial state
+    /// of this grid.
+    fn initial_state_pdfs<'a>(
+        &self,
+        pdfs: &[&'a dyn PdfProvider],
+    ) -> Vec<InitialStatePdf<'a, dyn PdfProvider + 'a>> {
+        assert_eq!(
+            pdfs.len(),
+            self.convolutions(),
+            "the grid has {} convolutions, but {} PDFs were given",
+            self.convolutions(),
+            pdfs.len()
+        );
+
+        let pid_basis = self.pid_basis().unwrap();
+
+        pdfs.iter()
+            .enumerate()
+            .map(|(index, &pdf)| {
+                InitialStatePdf::new(self.initial_state(index + 1), pid_basis, pdf)
+            })
+            .collect()
+    }
+
+    /// Evolves this grid with the evolution `operator` into a new grid, commonly called a
+    /// fast-kernel (FK) table, which only needs to be convoluted with PDFs at the starting scale
+    /// of the operator. The strong coupling is evaluated with `alphas`, the electroweak coupling
+    /// with `alpha`, and both are absorbed into the result, which therefore has a single order
+    /// with all exponents being zero. The parameter `order_mask` selects the orders that are
+    /// evolved, like for `Grid::convolute`, and `xi` sets the renormalization (first element) and
+    /// factorization (second element) scale factor. The scales of the operator are interpreted as
+    /// factorization scales, so the factorization scale `fac` of every node of each subgrid must
+    /// be found as `xi.1 * xi.1 * fac` in the operator, while both couplings are evaluated at
+    /// `xi.0 * xi.0 * ren`. Grids that already include the electroweak couplings should pass a
+    /// closure returning one for `alpha`, like the default of `PdfProvider::alpha`. The luminosity
+    /// function of the resulting grid has one entry for each non-vanishing pair of particle ids of
+    /// the operator's source basis, and each subgrid uses the momentum fractions `x0` of the
+    /// operator for both initial states.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a non-empty subgrid is neither of type `ImportOnlySubgridV1`,
+    /// `ImportOnlySubgridV2` nor of type `LagrangeSubgridV2`, if the operator is missing a scale,
+    /// a momentum fraction or a particle id needed by one of the subgrids, or if one of the
+    /// convolutions of this grid is with a fragmentation function. Only grids with two
+    /// convolutions can be evolved.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `order_mask` is non-empty, but has fewer entries than this grid has orders.
+    pub fn evolve(
+        &self,
+        operator: &EvolutionOperator,
+        alphas: &dyn Fn(f64) -> f64,
+        alpha: &dyn Fn(f64) -> f64,
+        order_mask: &[bool],
+        xi: (f64, f64),
+    ) -> Result<Self, GridEvolveError> {
+        let (xir, xif) = xi;
+
+        if self.convolutions() != 2 {
+            return Err(GridEvolveError::UnsupportedConvolutions(
+                self.convolutions(),
+            ));
+        }
+
+        if let Some(index) = self
+            .convolution_types()
+            .iter()
+            .position(|&convolution_type| convolution_type != ConvolutionType::Pdf)
+        {
+            return Err(GridEvolveError::UnsupportedConvolutionType(index));
+        }
+
+        let (nx, npid, nx0, npid0, nq2) = operator.operator().dim();
+        let x0 = operator.x0();
+        let pids0 = operator.pids0();
+
+        let find_x = |x: f64| {
+            operator
+                .x()
+                .iter()
+                .position(|&op_x| approx_eq!(f64, op_x, x, ulps = 64))
+                .ok_or(GridEvolveError::MissingMomentumFraction(x))
+        };
+        let find_pid = |pid: i32| {
+            operator
+                .pids()
+                .iter()
+                .position(|&op_pid| op_pid == pid)
+                .ok_or(GridEvolveError::MissingParticleId(pid))
+        };
+        let find_q2 = |q2: f64| {
+            operator
+                .q2()
+                .iter()
+                .position(|&op_q2| approx_eq!(f64, op_q2, q2, ulps = 64))
+                .ok_or(GridEvolveError::MissingScale(q2))
+        };
+
+        let mut fk_tables = Vec::with_capacity(self.bin_limits.bins());
+
+        for bin in 0..self.bin_limits.bins() {
+            // for each scale of the operator accumulate the contributions of this bin in the
+            // target basis, indexed by `x * npid + pid` in both dimensions
+            let mut tables: Vec<Option<Array2<f64>>> = vec![None; nq2];
+
+            for ((order, lumi), subgrid) in self.subgrids.slice(s![.., bin, ..]).indexed_iter() {
+                let coupling_order = &self.orders[order];
+
+                // the fragmentation scale is not varied, so these logarithms vanish
+                if ((coupling_order.logxir > 0) && (xir == 1.0))
+                    || ((coupling_order.logxif > 0) && (xif == 1.0))
+                    || (coupling_order.logxia > 0)
+                    || (!order_mask.is_empty() && !order_mask[order])
+                    || subgrid.is_empty()
+                {
+                    continue;
+                }
+
+                let converted: SubgridEnum;
+                let subgrid = match subgrid {
+                    SubgridEnum::LagrangeSubgridV2(grid) => {
+                        converted = ImportOnlySubgridV1::from(grid).into();
+                        &converted
+                    }
+                    SubgridEnum::ImportOnlySubgridV1(_) | SubgridEnum::ImportOnlySubgridV2(_) => {
+                        subgrid
+                    }
+                    _ => return Err(GridEvolveError::UnsupportedSubgrid { order, bin, lumi }),
+                };
+
+                let mu2_grid = subgrid.mu2_grid();
+                let x1_grid = subgrid.x1_grid();
+                let x2_grid = subgrid.x2_grid();
+
+                let q2_indices = mu2_grid
+                    .iter()
+                    .map(|mu2| find_q2(xif * xif * mu2.fac))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let x1_indices = x1_grid
+                    .iter()
+                    .map(|&x| find_x(x))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let x2_indices = x2_grid
+                    .iter()
+                    .map(|&x| find_x(x))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let entries = self.lumi[lumi]
+                    .entry()
+                    .iter()
+                    .map(|(pids, factor)| Ok((find_pid(pids[0])?, find_pid(pids[1])?, *factor)))
+                    .collect::<Result<Vec<_>, GridEvolveError>>()?;
+
+                let mut logs = 1.0;
+
+                if coupling_order.logxir > 0 {
+                    logs *= (xir * xir)
+                        .ln()
+                        .powi(coupling_order.logxir.try_into().unwrap());
+                }
+
+                if coupling_order.logxif > 0 {
+                    logs *= (xif * xif)
+                        .ln()
+                        .powi(coupling_order.logxif.try_into().unwrap());
+                }
+
+                let couplings: Vec<_> = mu2_grid
+                    .iter()
+                    .map(|mu2| {
+                        let ren = xir * xir * mu2.ren;
+
+                        logs * alphas(ren).powi(coupling_order.alphas.try_into().unwrap())
+                            * alpha(ren).powi(coupling_order.alpha.try_into().unwrap())
+                    })
+                    .collect();
+
+                for (index, value) in subgrid.iter() {
+                    let (iq2, ix1, ix2) = (index[0], index[1], index[2]);
+                    let table = tables[q2_indices[iq2]]
+                        .get_or_insert_with(|| Array2::zeros((nx * npid, nx * npid)));
+                    let value = value * couplings[iq2] / (x1_grid[ix1] * x2_grid[ix2]);
+
+                    for &(pid1, pid2, factor) in &entries {
+                        table[[x1_indices[ix1] * npid + pid1, x2_indices[ix2] * npid + pid2]] +=
+                            factor * value;
+                    }
+                }
+            }
+
+            let mut fk_table = Array2::zeros((nx0 * npid0, nx0 * npid0));
+
+            for (iq2, table) in tables
+                .iter()
+                .enumerate()
+                .filter_map(|(iq2, table)| table.as_ref().map(|table| (iq2, table)))
+            {
+                let operator = Array2::from_shape_vec(
+                    (nx * npid, nx0 * npid0),
+                    operator
+                        .operator()
+                        .slice(s![.., .., .., .., iq2])
+                        .iter()
+                        .copied()
+                        .collect(),
+                )
+                .unwrap();
+
+                fk_table += &operator.t().dot(&table.dot(&operator));
+            }
+
+            fk_tables.push(fk_table);
+        }
+
+        let lumi_indices: Vec<_> = (0..npid0)
+            .cartesian_product(0..npid0)
+            .filter(|&(a, b)| {
+                fk_tables.iter().any(|fk_table| {
+                    (0..nx0)
+                        .cartesian_product(0..nx0)
+                        .any(|(i0, j0)| fk_table[[i0 * npid0 + a, j0 * npid0 + b]] != 0.0)
+                })
+            })
+            .collect();
+
+        let mut subgrids =
+            Array3::from_shape_simple_fn((1, self.bin_limits.bins(), lumi_indices.len()), || {
+                EmptySubgridV1::default().into()
+            });
+
+        for (bin, fk_table) in fk_tables.iter().enumerate() {
+            for (lumi, &(a, b)) in lumi_indices.iter().enumerate() {
+                let mut array = SparseArray3::new(1, nx0, nx0);
+
+                for (i0, j0) in (0..nx0).cartesian_product(0..nx0) {
+                    let value = fk_table[[i0 * npid0 + a, j0 * npid0 + b]];
+
+                    if value != 0.0 {
+                        array[[0, i0, j0]] = value * x0[i0] * x0[j0];
+                    }
+                }
+
+                if !array.is_empty() {
+                    subgrids[[0, bin, lumi]] = ImportOnlySubgridV1::new(
+                        array,
+                        vec![operator.q2_0()],
+                        x0.to_vec(),
+                        x0.to_vec(),
+                    )
+                    .into();
+                }
+            }
+        }
+
+        Ok(Self {
+            subgrids,
+            lumi: lumi_indices
+                .iter()
+                .map(|&(a, b)| LumiEntry::new(vec![(vec![pids0[a], pids0[b]], 1.0)]))
+                .collect(),
+            bin_limits: self.bin_limits.clone(),
+            orders: vec![Order::new(0, 0, 0, 0, 0)],
+            subgrid_params: self.subgrid_params.clone(),
+            more_members: MoreMembers::V3(Mmv3 {
+                remapper: match &self.more_members {
+                    MoreMembers::V1(_) => None,
+                    MoreMembers::V2(mmv2) => mmv2.remapper.clone(),
+                    MoreMembers::V3(mmv3) => mmv3.remapper.clone(),
+                },
+                key_value_db: self
+                    .key_values()
+                    .cloned()
+                    .unwrap_or_else(|| Mmv2::default().key_value_db),
+                // FK tables can not be filled
+                subgrid_template: EmptySubgridV1::default().into(),
+            }),
+            mc_statistics: None,
+        })
+    }
+
+    /// Fills the grid with an ntuple for the given `order`, `observable`, and `lumi`. If the
+    /// renormalization scale `ntuple.q2r` differs from the factorization scale `ntuple.q2`, the
+    /// grid must have been created with the subgrid type `NtupleSubgrid`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the scales of `ntuple` are different, but the subgrid for the given
+    /// indices supports only a single scale. In this case the grid is not changed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the order or luminosity index is out of range.
+    pub fn fill(
+        &mut self,
+        order: usize,
+        observable: f64,
+        lumi: usize,
+        ntuple: &Ntuple<f64>,
+    ) -> Result<(), GridFillError> {
+        if let Some(bin) = self.bin_limits.index(observable) {
+            fill_subgrid(
+                self.fillable_subgrid(order, bin, lumi),
+                (order, bin, lumi),
+                ntuple,
+            )?;
+
+            if let Some(mc_statistics) = &mut self.mc_statistics {
+                mc_statistics[[order, bin, lumi]].fill(ntuple.weight);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Fills the grid for the given `order`, `observable`, and `lumi` with `weight` for the
+    /// momentum fractions `x`, one for each convolution, and the scale `q2`. In contrast to
+    /// `Grid::fill`, this method supports grids with an arbitrary number of convolutions.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of momentum fractions is not the same as the number of convolutions.
+    pub fn fill_x(
+        &mut self,
+        order: usize,
+        observable: f64,
+        lumi: usize,
+        x: &[f64],
+        q2: f64,
+        weight: f64,
+    ) {
+        if let Some(bin) = self.bin_limits.index(observable) {
+            self.fillable_subgrid(order, bin, lumi)
+                .fill_x(x, q2, weight);
+
+            if let Some(mc_statistics) = &mut self.mc_statistics {
+                mc_statistics[[order, bin, lumi]].fill(weight);
+            }
+        }
+    }
+
+    /// Fills the grid with a batch of `events`. This gives the same result as calling
+    /// `Grid::fill` for each event, but the events are grouped by subgrid first, so that every
+    /// subgrid is looked up only once.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the grid does not have two convolutions, if the number of momentum fractions of
+    /// an event is not the number of convolutions, or if an order or luminosity index is out of
+    /// range.
+    pub fn fill_array(&mut self, events: &[Event]) {
+        check_events(events, self.convolutions());
+
+        let groups = group_events(&self.bin_limits, events);
+        self.fill_groups(groups, events, false);
+    }
+
+    /// Fills the grid with a group of correlated events, for example an event of the
+    /// real-emission matrix element and its counter-events, see `Grid::fill_array`. The subgrids
+    /// are filled in the same way, but if the Monte Carlo statistics are tracked, see
+    /// `Grid::enable_mc_statistics`, all events of the group that are filled into the same
+    /// subgrid are recorded as a single event, whose weight is the sum of their weights.
+    ///
+    /// # Panics
+    ///
+    /// Panics for the same reasons as `Grid::fill_array`.
+    pub fn fill_group(&mut self, events: &[Event]) {
+        check_events(events, self.convolutions());
+
+        let groups = group_events(&self.bin_limits, events);
+        self.fill_groups(groups, events, true);
+    }
+
+    fn fill_groups(
+        &mut self,
+        groups: Vec<((usize, usize, usize), Vec<usize>)>,
+        events: &[Event],
+        correlated: bool,
+    ) {
+        for ((order, bin, lumi), indices) in groups {
+            fill_events(self.fillable_subgrid(order, bin, lumi), &indices, events);
+
+            if let Some(mc_statistics) = &mut self.mc_statistics {
+                record_events(
+                    &mut mc_statistics[[order, bin, lumi]],
+                    &indices,
+                    events,
+                    correlated,
+                );
+            }
+        }
+    }
+
+    /// Performs the same filling as `Grid::fill_array`, but fills different subgrids in parallel
+    /// using the global `rayon` thread pool. The result is the same as for the serial version,
+    /// because the events of each subgrid are filled in the same order.
+    ///
+    /// This method is only available if the `rayon` feature is enabled.
+    ///
+    /// # Panics
+    ///
+    /// Panics for the same reasons as `Grid::fill_array`.
+    #[cfg(feature = "rayon")]
+    pub fn par_fill_array(&mut self, events: &[Event]) {
+        check_events(events, self.convolutions());
+
+        let groups = group_events(&self.bin_limits, events);
+
+        for ((order, bin, lumi), indices) in &groups {
+            self.fillable_subgrid(*order, *bin, *lumi);
+
+            if let Some(mc_statistics) = &mut self.mc_statistics {
+                record_events(
+                    &mut mc_statistics[[*order, *bin, *lumi]],
+                    indices,
+                    events,
+                    false,
+                );
+            }
+        }
+
+        let mut groups = groups.into_iter().peekable();
+        let mut subgrids = Vec::new();
+
+        // the groups are sorted in the same order as the subgrids are iterated over
+        for (indices, subgrid) in self.subgrids.indexed_iter_mut() {
+            if groups.peek().map(|&(group, _)| group) == Some(indices) {
+                subgrids.push((subgrid, groups.next().unwrap().1));
+            }
+        }
+
+        subgrids.into_par_iter().for_each(|(subgrid, indices)| {
+            fill_events(subgrid, &indices, events);
+        });
+    }
+
+    fn fillable_subgrid(&mut self, order: usize, bin: usize, lumi: usize) -> &mut SubgridEnum {
+        let subgrid = &mut self.subgrids[[order, bin, lumi]];
+        make_fillable(subgrid, &self.more_members);
+        subgrid
+    }
+
+    /// Constructs a `Grid` by deserializing it from `reader`. Reading is not buffered. Besides
+    /// the current file format, grids written by previous versions, which do not start with a
+    /// file header, can be read as well.
+    ///
+    /// # Errors
+    ///
+    /// If reading from the compressed or uncompressed stream fails, or if the file format version
+    /// is not supported, an error is returned.
+    pub fn read(mut reader: impl Read + Seek) -> Result<Self, GridReadError> {
+        match LZ4FrameReader::new(&mut reader) {
+            Ok(reader) => Self::read_uncompressed(reader.into_read()),
+            Err(WrongMagic(_)) => {
+                reader.seek(SeekFrom::Start(0))?;
+                Self::read_uncompressed(reader)
+            }
+            Err(err) => Err(GridReadError::Corrupted {
+                section: "LZ4 frame".to_owned(),
+                source: err.into(),
+            }),
+        }
+    }
+
+    fn read_uncompressed(mut reader: impl Read) -> Result<Self, GridReadError> {
+        let mut magic = [0; 8];
+        reader
+            .read_exact(&mut magic)
+            .map_err(|err| read_error(err, "magic bytes"))?;
+
+        if magic == FILE_MAGIC {
+            match read_u64(&mut reader, "file format version")? {
+                FILE_VERSION => {
+                    let mut grid = read_header(&mut reader)?;
+
+                    for (index, subgrid) in grid.subgrids.indexed_iter_mut() {
+                        *subgrid = read_subgrid(&mut reader, index)?;
+                    }
+
+                    // the index is not needed to read the subgrids one after another, but reading
+                    // it detects files whose end is missing
+                    let offsets = read_index(&mut reader, &grid)?;
+                    let section = "position of the index of the subgrids";
+
+                    if read_u64(reader, section)? != offsets[offsets.len() - 1] {
+                        return Err(GridReadError::InvalidIndex);
+                    }
+
+                    Ok(grid)
+                }
+                version => Err(GridReadError::UnsupportedVersion(version)),
+            }
+        } else {
+            // files without a header were written before the file format was versioned
+            bincode::deserialize_from::<_, GridV0>(Cursor::new(magic).chain(reader))
+                .map(Into::into)
+                .map_err(|err| deserialize_error(err, "grid"))
+        }
+    }
+
+    /// Reads only the metadata of a grid from `reader`, which is everything but its subgrids; all
+    /// subgrids of the returned grid are empty. For files in the current format this does not
+    /// read or decompress any subgrid. Files without a header, or files that are compressed as a
+    /// whole, must be read completely and the returned grid then contains all subgrids. Use
+    /// `GridReader` to load single subgrids.
+    ///
+    /// # Errors
+    ///
+    /// If reading fails, or if the file format version is not supported, an error is returned.
+    pub fn read_metadata(reader: impl Read + Seek) -> Result<Self, GridReadError> {
+        Ok(GridReader::new(reader)?.grid)
+    }
+
+    /// Serializes `self` into `writer`, starting with a header that contains the file format
+    /// version. The header is followed by the metadata of this grid, the subgrids, each of which
+    /// is compressed and written separately, and an index of the subgrids. This allows reading
+    /// the metadata and single subgrids without reading the entire file, see
+    /// `Grid::read_metadata` and `GridReader`. Each of these sections is followed by a checksum,
+    /// which is verified when it is read. Writing is not buffered.
+    ///
+    /// # Errors
+    ///
+    /// If writing fails an error is returned.
+    pub fn write(&self, mut writer: impl Write) -> anyhow::Result<()> {
+        writer.write_all(&FILE_MAGIC)?;
+        bincode::serialize_into(&mut writer, &FILE_VERSION)?;
+
+        // the same layout as `Header`
+        let header = bincode::serialize(&(
+            self.subgrids.dim(),
+            &self.lumi,
+            &self.bin_limits,
+            &self.orders,
+            &self.subgrid_params,
+            &self.more_members,
+            &self.mc_statistics,
+        ))?;
+        write_section(&mut writer, &header)?;
+
+        let mut settings = CompressionSettings::default();
+        // most subgrids are small and the default block size would be wasteful
+        settings.block_size(64 * 1024);
+
+        let mut offsets = vec![0];
+
+        // write each subgrid as soon as it is compressed, followed by the index of all subgrids
+        for subgrid in &self.subgrids {
+            let mut compressed = Vec::new();
+            settings.compress(bincode::serialize(subgrid)?.as_slice(), &mut compressed)?;
+            let size = write_section(&mut writer, &compressed)?;
+            offsets.push(offsets[offsets.len() - 1] + size);
+        }
+
+        write_section(&mut writer, &bincode::serialize(&offsets)?)?;
+        writer.write_all(&offsets[offsets.len() - 1].to_le_bytes())?;
+
+        Ok(())
+    }
+
+    /// Fills the grid with events for the parton momentum fractions `x1` and `x2`, the scale `q2`,
+    /// and the `order` and `observable`. The events are stored in `weights` and must be ordered as
+    /// the corresponding luminosity function was created.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error for the same reasons as `Grid::fill`.
+    pub fn fill_all(
+        &mut self,
+        order: usize,
+        observable: f64,
+        ntuple: &Ntuple<()>,
+        weights: &[f64],
+    ) -> Result<(), GridFillError> {
+        for (lumi, weight) in weights.iter().enumerate() {
+            self.fill(
+                order,
+                observable,
+                lumi,
+                &Ntuple {
+                    x1: ntuple.x1,
+                    x2: ntuple.x2,
+                    q2: ntuple.q2,
+                    q2r: ntuple.q2r,
+                    weight: *weight,
+                },
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Enables tracking the Monte Carlo statistics of the filled events, which is disabled by
+    /// default. Only events filled after calling this method are recorded, and the statistics
+    /// are stored together with the grid by `Grid::write`. If the statistics are tracked
+    /// already, this method does nothing.
+    pub fn enable_mc_statistics(&mut self) {
+        if self.mc_statistics.is_none() {
+            self.mc_statistics = Some(Array3::default(self.subgrids.dim()));
+        }
+    }
+
+    /// Returns the Monte Carlo statistics of the events filled into each subgrid, indexed in the
+    /// same way as the subgrids, or `None` if they are not tracked, see
+    /// `Grid::enable_mc_statistics`.
+    #[must_use]
+    pub const fn mc_statistics(&self) -> Option<&Array3<McStatistics>> {
+        self.mc_statistics.as_ref()
+    }
+
+    /// Returns the luminosity function.
+    #[must_use]
+    pub fn lumi(&self) -> &[LumiEntry] {
+        &self.lumi
+    }
+
+    /// Returns the number of convolutions of this grid, which is the number of PDFs needed by
+    /// `Grid::convolute`. If the luminosity function is empty, two convolutions are assumed.
+    #[must_use]
+    pub fn convolutions(&self) -> usize {
+        self.lumi.first().map_or(2, LumiEntry::convolutions)
+    }
+
+    /// Returns the type of each convolution of this grid, which is stored in the metadata
+    /// `convolution_type_1`, `convolution_type_2`, and so on, as either `PDF` or `FF`. If the
+    /// metadata is missing, the convolution is with a PDF.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the metadata has a value that is neither `PDF` nor `FF`.
+    #[must_use]
+    pub fn convolution_types(&self) -> Vec<ConvolutionType> {
+        (1..=self.convolutions())
+            .map(|index| {
+                let key = format!("convolution_type_{}", index);
+
+                self.key_values().and_then(|map| map.get(&key)).map_or(
+                    ConvolutionType::Pdf,
+                    |value| match value.as_str() {
+                        "PDF" => ConvolutionType::Pdf,
+                        "FF" => ConvolutionType::FragmentationFunction,
+                        _ => panic!("could not parse {}: `{}`", key, value),
+                    },
+                )
+            })
+            .collect()
+    }
+
+    /// Sets the type of the convolution with index `index`, which starts at zero and corresponds
+    /// to the index of the PDFs given to `Grid::convolute`. For fragmentation functions, the
+    /// metadata `initial_state_1`, `initial_state_2`, and so on, denote the identified hadrons in
+    /// the final state.
+    pub fn set_convolution_type(&mut self, index: usize, convolution_type: ConvolutionType) {
+        self.set_key_value(
+            &format!("convolution_type_{}", index + 1),
+            match convolution_type {
+                ConvolutionType::Pdf => "PDF",
+                ConvolutionType::FragmentationFunction => "FF",
+            },
+        );
+    }
+
+    /// Returns the basis of the particle ids used in the luminosity function, which is stored in
+    /// the metadata `pid_basis` as either `PDG` or `EVOL`. If the metadata is missing, PDG MC ids
+    /// are assumed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the metadata has a value that is neither `PDG` nor `EVOL`.
+    pub fn pid_basis(&self) -> Result<PidBasis, UnknownPidBasis> {
+        self.key_values()
+            .and_then(|map| map.get("pid_basis"))
+            .map_or(Ok(PidBasis::Pdg), |value| match value.as_str() {
+                "PDG" => Ok(PidBasis::Pdg),
+                "EVOL" => Ok(PidBasis::Evol),
+                _ => Err(UnknownPidBasis(value.clone())),
+            })
+    }
+
+    /// Rewrites the luminosity function such that its particle ids are given in the basis
+    /// `pid_basis`. Since each channel is a linear combination of products of PDFs, the subgrids
+    /// do not change and the transformation is exact up to rounding errors; rotating back into
+    /// the original basis therefore reproduces the results of `Grid::convolute`. The PDFs given to
+    /// `Grid::convolute` always use PDG MC ids, but an `EvolutionOperator` given to
+    /// `Grid::evolve` must use the basis of this grid.
+    ///
+    /// Only the luminosity function is rewritten, and the channels are not recombined. Since
+    /// every quark is a combination of twelve elements of the evolution basis, a channel with two
+    /// quarks, for instance, has up to 144 combinations after rotating into the evolution basis,
+    /// and `Grid::convolute` evaluates each PDF of the evolution basis from up to twelve PDFs
+    /// with PDG MC ids. Grids in the evolution basis are therefore slower to convolute and are
+    /// meant to be evolved with `Grid::evolve`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the current basis of this grid is unknown, see `Grid::pid_basis`.
+    pub fn rotate_pid_basis(&mut self, pid_basis: PidBasis) -> Result<(), UnknownPidBasis> {
+        let translator: fn(i32) -> Vec<(i32, f64)> = match (self.pid_basis()?, pid_basis) {
+            (PidBasis::Pdg, PidBasis::Evol) => pids::pdg_mc_ids_to_evol,
+            (PidBasis::Evol, PidBasis::Pdg) => pids::evol_to_pdg_mc_ids,
+            (PidBasis::Pdg, PidBasis::Pdg) | (PidBasis::Evol, PidBasis::Evol) => return Ok(()),
+        };
+
+        for entry in &mut self.lumi {
+            *entry = entry.translate(&translator);
+        }
+
+        self.set_key_value(
+            "pid_basis",
+            match pid_basis {
+                PidBasis::Pdg => "PDG",
+                PidBasis::Evol => "EVOL",
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Generates the orders with logarithms of the renormalization and of the factorization scale
+    /// factors, which `Grid::convolute` needs for scale variations, at next-to-leading order in
+    /// the strong coupling. For each power of the electroweak coupling the order with the lowest
+    /// power `p` of the strong coupling is the leading order, if the grid also contains the
+    /// next-to-leading order with power `p + 1` and without logarithms.
+    ///
+    /// The renormalization-scale logarithms are the leading order multiplied with `p` and with
+    /// the first coefficient of the QCD beta function for `nf` active flavours. The
+    /// factorization-scale logarithms are the convolutions of the leading order with the
+    /// leading-order Altarelli-Parisi splitting functions, which are evaluated on the momentum
+    /// fractions of each subgrid; the channels they need are appended to the luminosity function.
+    /// Orders that the grid already contains are not changed, and the generated subgrids are of
+    /// type `ImportOnlySubgridV2`. If the grid does not contain the next-to-leading order, no
+    /// logarithms are generated, because they are part of this order.
+    ///
+    /// The logarithms of higher orders, which need the second coefficient of the beta function,
+    /// the next-to-leading-order splitting functions, and products of the leading-order terms,
+    /// are not generated.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the grid does not have two convolutions, if one of the convolutions is
+    /// with a fragmentation function, or if a non-empty subgrid of a leading order is neither of
+    /// type `ImportOnlySubgridV1`, `ImportOnlySubgridV2` nor `LagrangeSubgridV2`. An error is
+    /// also returned if the grid has an order beyond the next-to-leading order, but none of its
+    /// logarithms, since scale variations of the resulting grid would be incomplete.
+    pub fn generate_scale_logs(&mut self, nf: u8) -> Result<(), GridScaleLogsError> {
+        if self.convolutions() != 2 {
+            return Err(GridScaleLogsError::UnsupportedConvolutions(
+                self.convolutions(),
+            ));
+        }
+
+        if let Some(index) = self
+            .convolution_types()
+            .iter()
+            .position(|&convolution_type| convolution_type != ConvolutionType::Pdf)
+        {
+            return Err(GridScaleLogsError::UnsupportedConvolutionType(index));
+        }
+
+        let central =
+            |order: &Order| (order.logxir == 0) && (order.logxif == 0) && (order.logxia == 0);
+        let pid_basis = self.pid_basis()?;
+        // the splitting functions act on PDG MC ids, the results are translated back afterwards
+        let translator: fn(i32) -> Vec<(i32, f64)> = match pid_basis {
+            PidBasis::Pdg => |pid| vec![(pid, 1.0)],
+            PidBasis::Evol => pids::pdg_mc_ids_to_evol,
+        };
+        let mut matrices: Vec<(Vec<f64>, Splitting, Array2<f64>)> = Vec::new();
+        let mut new_subgrids: Vec<(Order, usize, LumiEntry, SubgridEnum)> = Vec::new();
+
+        for (index, order) in self.orders.iter().enumerate() {
+            if !central(order)
+                || self.orders.iter().any(|other| {
+                    central(other) && (other.alpha == order.alpha) && (other.alphas < order.alphas)
+                })
+                || !self
+                    .orders
+                    .contains(&Order::new(order.alphas + 1, order.alpha, 0, 0, 0))
+            {
+                continue;
+            }
+
+            if let Some(higher) = self.orders.iter().find(|other| {
+                central(other)
+                    && (other.alpha == order.alpha)
+                    && (other.alphas > order.alphas + 1)
+                    && !self.orders.iter().any(|log| {
+                        !central(log) && (log.alpha == other.alpha) && (log.alphas == other.alphas)
+                    })
+            }) {
+                return Err(GridScaleLogsError::UnsupportedOrder {
+                    alphas: higher.alphas,
+                    alpha: higher.alpha,
+                });
+            }
+
+            let ren_order = Order::new(order.alphas + 1, order.alpha, 1, 0, 0);
+            let fac_order = Order::new(order.alphas + 1, order.alpha, 0, 1, 0);
+            let generate_ren = (order.alphas > 0) && !self.orders.contains(&ren_order);
+            let generate_fac = !self.orders.contains(&fac_order);
+            let ren_factor = f64::from(order.alphas) * splitting::beta0(nf) / (4.0 * PI);
+
+            for ((bin, lumi), subgrid) in self
+                .subgrids
+                .slice(s![index, .., ..])
+                .indexed_iter()
+                .filter(|(_, subgrid)| !subgrid.is_empty())
+            {
+                let converted: SubgridEnum;
+                let subgrid = match subgrid {
+                    SubgridEnum::LagrangeSubgridV2(grid) => {
+                        converted = ImportOnlySubgridV1::from(grid).into();
+                        &converted
+                    }
+                    SubgridEnum::ImportOnlySubgridV1(_) | SubgridEnum::ImportOnlySubgridV2(_) => {
+                        subgrid
+                    }
+                    _ => {
+                        return Err(GridScaleLogsError::UnsupportedSubgrid {
+                            order: index,
+                            bin,
+                            lumi,
+                        })
+                    }
+                };
+
+                let mu2_grid = subgrid.mu2_grid().into_owned();
+                let x_grids = subgrid.x_grids();
+                let mut array = Array3::zeros((mu2_grid.len(), x_grids[0].len(), x_grids[1].len()));
+
+                for (index, &value) in subgrid.iter() {
+                    let (imu2, ix1, ix2) = (index[0], index[1], index[2]);
+                    array[[imu2, ix1, ix2]] = value;
+                }
+
+                let import_only = |array: &Array3<f64>| -> SubgridEnum {
+                    ImportOnlySubgridV2::new(
+                        SparseArray3::from_ndarray(array, 0, mu2_grid.len()),
+                        mu2_grid.clone(),
+                        x_grids[0].to_vec(),
+                        x_grids[1].to_vec(),
+                    )
+                    .into()
+                };
+
+                if generate_ren {
+                    new_subgrids.push((
+                        ren_order.clone(),
+                        bin,
+                        self.lumi[lumi].clone(),
+                        import_only(&(&array * ren_factor)),
+                    ));
+                }
+
+                if !generate_fac {
+                    continue;
+                }
+
+                let entry = match pid_basis {
+                    PidBasis::Pdg => self.lumi[lumi].clone(),
+                    PidBasis::Evol => self.lumi[lumi].translate(&pids::evol_to_pdg_mc_ids),
+                };
+
+                for (leg, &splitting) in (0..2).cartesian_product(&[
+                    Splitting::QuarkQuark,
+                    Splitting::QuarkGluon,
+                    Splitting::GluonQuark,
+                    Splitting::GluonGluon,
+                ]) {
+                    let new_entry: Vec<_> = entry
+                        .entry()
+                        .iter()
+                        .flat_map(|(pids, factor)| {
+                            splitting
+                                .sources(pids[leg], nf)
+                                .into_iter()
+                                .map(move |pid| {
+                                    let mut pids = pids.clone();
+                                    pids[leg] = pid;
+                                    (pids, *factor)
+                                })
+                        })
+                        .collect();
+
+                    if new_entry.is_empty() {
+                        continue;
+                    }
+
+                    let x_grid = &x_grids[leg];
+                    let position = matrices
+                        .iter()
+                        .position(|(x, other, _)| (*other == splitting) && (x[..] == x_grid[..]))
+                        .unwrap_or_else(|| {
+                            matrices.push((
+                                x_grid.to_vec(),
+                                splitting,
+                                splitting.matrix(x_grid, nf),
+                            ));
+                            matrices.len() - 1
+                        });
+                    let matrix = &matrices[position].2;
+                    let mut result = Array3::zeros(array.dim());
+
+                    for imu2 in 0..mu2_grid.len() {
+                        let slice = array.slice(s![imu2, .., ..]);
+                        let convoluted = if leg == 0 {
+                            matrix.t().dot(&slice)
+                        } else {
+                            slice.dot(matrix)
+                        };
+
+                        result
+                            .slice_mut(s![imu2, .., ..])
+                            .assign(&(convoluted * (-1.0 / (2.0 * PI))));
+                    }
+
+                    new_subgrids.push((
+                        fac_order.clone(),
+                        bin,
+                        // translating sums identical combinations of particle ids
+                        LumiEntry::new(new_entry).translate(&translator),
+                        import_only(&result),
+                    ));
+                }
+            }
+        }
+
+        for (order, bin, entry, subgrid) in new_subgrids
+            .into_iter()
+            .filter(|(_, _, _, subgrid)| !subgrid.is_empty())
+        {
+            self.add_subgrid(order, bin, entry, subgrid);
+        }
+
+        Ok(())
+    }
+
+    /// Adds `subgrid` to the subgrid with the given `order`, `bin` and luminosity `entry`, which
+    /// are added to this grid if they do not exist yet.
+    fn add_subgrid(
+        &mut self,
+        order: Order,
+        bin: usize,
+        entry: LumiEntry,
+        mut subgrid: SubgridEnum,
+    ) {
+        let order = self
+            .orders
+            .iter()
+            .position(|other| *other == order)
+            .unwrap_or_else(|| {
+                self.increase_shape(&(1, 0, 0));
+                self.orders.push(order);
+                self.orders.len() - 1
+            });
+        // subgrids with different momentum fractions can not be merged
+        let lumi = self
+            .lumi
+            .iter()
+            .enumerate()
+            .position(|(lumi, other)| {
+                let other_subgrid = &self.subgrids[[order, bin, lumi]];
+
+                (*other == entry)
+                    && (other_subgrid.is_empty() || (other_subgrid.x_grids() == subgrid.x_grids()))
+            })
+            .unwrap_or_else(|| {
+                self.increase_shape(&(0, 0, 1));
+                self.lumi.push(entry);
+                self.lumi.len() - 1
+            });
+
+        if self.subgrids[[order, bin, lumi]].is_empty() {
+            mem::swap(&mut self.subgrids[[order, bin, lumi]], &mut subgrid);
+        } else {
+            self.subgrids[[order, bin, lumi]].merge(&mut subgrid, false);
+        }
+    }
+
+    /// Merges the bins for the corresponding range together in a single one.
+    pub fn merge_bins(&mut self, bins: Range<usize>) -> Result<(), ()> {
+        if (bins.start >= self.bin_limits.bins()) || (bins.end > self.bin_limits.bins()) {
+            return Err(());
+        }
+
+        let old_bins = self.bin_limits.bins();
+        self.bin_limits.merge_bins(bins.clone());
+
+        match &mut self.more_members {
+            MoreMembers::V1(_) => {}
+            MoreMembers::V2(mmv2) => {
+                if let Some(remapper) = &mut mmv2.remapper {
+                    remapper.merge_bins(bins.clone())?;
+                }
+            }
+            MoreMembers::V3(mmv3) => {
+                if let Some(remapper) = &mut mmv3.remapper {
+                    remapper.merge_bins(bins.clone())?;
+                }
+            }
+        }
+
+        let mut old_subgrids = mem::replace(
+            &mut self.subgrids,
+            Array3::from_shape_simple_fn(
+                (self.orders.len(), self.bin_limits.bins(), self.lumi.len()),
+                || EmptySubgridV1::default().into(),
+            ),
+        );
+
+        for ((order, bin, lumi), subgrid) in old_subgrids.indexed_iter_mut() {
+            if bins.contains(&bin) {
+                let new_subgrid = &mut self.subgrids[[order, bins.start, lumi]];
+
+                if new_subgrid.is_empty() {
+                    mem::swap(new_subgrid, subgrid);
+                } else {
+                    new_subgrid.merge(subgrid, false);
+                }
+            } else {
+                let new_bin = if bin > bins.start {
+                    bin - (bins.end - bins.start) + 1
+                } else {
+                    bin
+                };
+
+                mem::swap(&mut self.subgrids[[order, new_bin, lumi]], subgrid);
+            }
+        }
+
+        if let Some(mc_statistics) = &mut self.mc_statistics {
+            let (orders, _, lumis) = mc_statistics.dim();
+
+            for (order, bin, lumi) in iproduct!(0..orders, bins.clone().skip(1), 0..lumis) {
+                let statistics = mc_statistics[[order, bin, lumi]];
+                mc_statistics[[order, bins.start, lumi]].merge(&statistics);
+            }
+
+            let remaining: Vec<_> = (0..old_bins)
+                .filter(|&bin| (bin == bins.start) || !bins.contains(&bin))
+                .collect();
+            *mc_statistics = mc_statistics.select(Axis(1), &remaining);
+        }
+
+        Ok(())
+    }
+
+    /// Deletes the bins with the corresponding `bin_indices`. Indices that do not correspond to a
+    /// bin are ignored. If bins other than the left- or right-most ones are deleted, the remaining
+    /// bins are no longer consecutive; in this case a `BinRemapper` is created, if the grid
+    /// doesn't have one already, which stores the original limits and normalizations, and the bin
+    /// limits of the grid are replaced by the indices of the remaining bins. Filling such a grid
+    /// must then use these indices as observable values. If all bins are deleted, the remapper is
+    /// removed.
+    pub fn delete_bins(&mut self, bin_indices: &[usize]) {
+        let bin_indices = sorted_indices(bin_indices, self.bin_limits.bins());
+        let bins = self.bin_limits.bins();
+
+        // convert the indices into consecutive ranges
+        let mut bin_ranges: Vec<Range<usize>> = Vec::new();
+
+        for &index in &bin_indices {
+            match bin_ranges.last_mut() {
+                Some(range) if range.end == index => range.end += 1,
+                _ => bin_ranges.push(index..index + 1),
+            }
+        }
+
+        // the left- and right-most bins can be deleted without making the limits non-consecutive
+        let consecutive = bin_ranges
+            .iter()
+            .all(|range| (range.start == 0) || (range.end == bins));
+
+        if consecutive {
+            for range in &bin_ranges {
+                if range.end == bins {
+                    self.bin_limits.delete_bins_right(range.end - range.start);
+                } else {
+                    self.bin_limits.delete_bins_left(range.end);
+                }
+            }
+        } else {
+            if self.remapper().is_none() {
+                let remapper = self.bin_remapper();
+
+                self.set_remapper(remapper)
+                    .unwrap_or_else(|_| unreachable!());
+            }
+
+            self.bin_limits = BinLimits::new(
+                (0..=(bins - bin_indices.len()))
+                    .map(f64_from_usize)
+                    .collect(),
+            );
+        }
+
+        if bin_indices.len() == bins {
+            // a remapper without any bins has no dimensions
+            match &mut self.more_members {
+                MoreMembers::V1(_) => {}
+                MoreMembers::V2(mmv2) => mmv2.remapper = None,
+                MoreMembers::V3(mmv3) => mmv3.remapper = None,
+            }
+        } else if let Some(remapper) = self.remapper_mut() {
+            remapper.delete_bins(&bin_ranges);
+        }
+
+        self.delete_subgrids(1, &bin_indices);
+    }
+
+    /// Deletes the orders with the corresponding `order_indices`. Indices that do not correspond
+    /// to an order are ignored.
+    pub fn delete_orders(&mut self, order_indices: &[usize]) {
+        let order_indices = sorted_indices(order_indices, self.orders.len());
+
+        for &index in order_indices.iter().rev() {
+            self.orders.remove(index);
+        }
+
+        self.delete_subgrids(0, &order_indices);
+    }
+
+    /// Deletes the channels, i.e. the entries of the luminosity function, with the corresponding
+    /// `channel_indices`. Indices that do not correspond to a channel are ignored.
+    pub fn delete_channels(&mut self, channel_indices: &[usize]) {
+        let channel_indices = sorted_indices(channel_indices, self.lumi.len());
+
+        for &index in channel_indices.iter().rev() {
+            self.lumi.remove(index);
+        }
+
+        self.delete_subgrids(2, &channel_indices);
+    }
+
+    /// Removes the subgrids whose index along `axis` is one of `indices`, which must be sorted and
+    /// unique.
+    fn delete_subgrids(&mut self, axis: usize, indices: &[usize]) {
+        let mut shape = self.subgrids.raw_dim();
+        shape[axis] -= indices.len();
+
+        let mut old_subgrids = mem::replace(
+            &mut self.subgrids,
+            Array3::from_shape_simple_fn(shape, || EmptySubgridV1::default().into()),
+        );
+
+        for ((order, bin, lumi), subgrid) in old_subgrids.indexed_iter_mut() {
+            let mut index = Ix3(order, bin, lumi);
+
+            if indices.binary_search(&index[axis]).is_ok() {
+                continue;
+            }
+
+            index[axis] -= indices.iter().take_while(|&&i| i < index[axis]).count();
+            mem::swap(&mut self.subgrids[index], subgrid);
+        }
+
+        if let Some(mc_statistics) = &mut self.mc_statistics {
+            let remaining: Vec<_> = (0..mc_statistics.len_of(Axis(axis)))
+                .filter(|index| indices.binary_search(index).is_err())
+                .collect();
+            *mc_statistics = mc_statistics.select(Axis(axis), &remaining);
+        }
+    }
+
+    const fn remapper(&self) -> Option<&BinRemapper> {
+        match &self.more_members {
+            MoreMembers::V1(_) => None,
+            MoreMembers::V2(mmv2) => mmv2.remapper.as_ref(),
+            MoreMembers::V3(mmv3) => mmv3.remapper.as_ref(),
+        }
+    }
+
+    fn remapper_mut(&mut self) -> Option<&mut BinRemapper> {
+        match &mut self.more_members {
+            MoreMembers::V1(_) => None,
+            MoreMembers::V2(mmv2) => mmv2.remapper.as_mut(),
+            MoreMembers::V3(mmv3) => mmv3.remapper.as_mut(),
+        }
+    }
+
+    /// Merges the non-empty `Subgrid`s contained in `other` into `self`. This performs one of two
+    /// possible operations:
+    /// 1. If the bins of `self` and `other` are different and can be concatenated with each
+    ///    other the bins are merged. In this case both grids are assumed to have the same orders
+    ///    and the same luminosity functions. If this is not the case, an error is returned. If
+    ///    one of the grids has a `BinRemapper`, the bins of `other` are appended to the bins of
+    ///    `self`, including their normalizations, and the one-dimensional bin limits only
+    ///    enumerate the bins if they can not be concatenated.
+    /// 2. If the bins of `self` and `other` are the same, the luminosity functions and
+    ///    perturbative orders of `self` and `other` may be different. Subgrids with the same
+    ///    indices are merged if they have the same type, or if one of them is an import-only
+    ///    subgrid and the other one an import-only subgrid or a `LagrangeSubgridV2` with the same
+    ///    momentum fractions; in this case both are converted into import-only subgrids.
+    ///
+    /// # Errors
+    ///
+    /// If in the first case describe above the perturbative orders, the luminosity function or the
+    /// dimensions of the bins are different an error is returned. Grids with different numbers of
+    /// convolutions or different particle id bases can never be merged, and in the second case an
+    /// error is also returned if non-empty subgrids that can not be merged have the same indices.
+    ///
+    /// # Panics
+    ///
+    /// TODO
+    pub fn merge(&mut self, mut other: Self) -> Result<(), GridMergeError> {
+        if self.convolutions() != other.convolutions() {
+            return Err(GridMergeError::DifferentConvolutions);
+        }
+
+        if self.pid_basis()? != other.pid_basis()? {
+            return Err(GridMergeError::DifferentPidBasis);
+        }
+
+        if !self.subgrids_mergeable(&other) {
+            return Err(GridMergeError::IncompatibleSubgrids);
+        }
+
+        // the statistics of the merged grid are only known if both grids have them
+        if other.mc_statistics.is_none() {
+            self.mc_statistics = None;
+        }
+
+        let bin_offset;
+
+        if self.bin_info() == other.bin_info() {
+            bin_offset = 0;
+
+            let mut new_orders: Vec<Order> = Vec::new();
+            let mut new_entries: Vec<LumiEntry> = Vec::new();
+
+            for ((i, _, k), _) in other
+                .subgrids
+                .indexed_iter_mut()
+                .filter(|((_, _, _), subgrid)| !subgrid.is_empty())
+            {
+                let other_order = &other.orders[i];
+                let other_entry = &other.lumi[k];
+
+                if !self
+                    .orders
+                    .iter()
+                    .chain(new_orders.iter())
+                    .any(|x| x == other_order)
+                {
+                    new_orders.push(other_order.clone());
+                }
+
+                if !self
+                    .lumi
+                    .iter()
+                    .chain(new_entries.iter())
+                    .any(|y| y == other_entry)
+                {
+                    new_entries.push(other_entry.clone());
+                }
+            }
+
+            if !new_orders.is_empty() || !new_entries.is_empty() {
+                self.increase_shape(&(new_orders.len(), 0, new_entries.len()));
+            }
+
+            self.orders.append(&mut new_orders);
+            self.lumi.append(&mut new_entries);
+        } else {
+            if !Order::equal_after_sort(&self.orders, &other.orders) {
+                return Err(GridMergeError::DifferentOrders);
+            }
+
+            if !LumiEntry::equal_after_sort(&self.lumi, &other.lumi) {
+                return Err(GridMergeError::DifferentLumi);
+            }
+
+            let remapper = if self.remapper().is_some() || other.remapper().is_some() {
+                let mut remapper = self.bin_remapper();
+                remapper
+                    .merge(&other.bin_remapper())
+                    .map_err(GridMergeError::DifferentBins)?;
+                Some(remapper)
+            } else {
+                None
+            };
+
+            bin_offset = self.bin_limits.bins();
+            let new_bins = other.bin_limits.bins();
+
+            if let Err(e) = self.bin_limits.merge(&other.bin_limits) {
+                if remapper.is_none() {
+                    return Err(GridMergeError::DifferentBins(e));
+                }
+
+                // the remapper describes the bins, the limits only need to enumerate them
+                self.bin_limits =
+                    BinLimits::new((0..=(bin_offset + new_bins)).map(f64_from_usize).collect());
+            }
+
+            self.increase_shape(&(0, new_bins, 0));
+
+            if let Some(remapper) = remapper {
+                self.set_remapper(remapper)
+                    .unwrap_or_else(|_| unreachable!());
+            }
+        }
+
+        for ((i, j, k), subgrid) in other
+            .subgrids
+            .indexed_iter_mut()
+            .filter(|((_, _, _), subgrid)| !subgrid.is_empty())
+        {
+            let other_order = &other.orders[i];
+            let other_entry = &other.lumi[k];
+
+            let self_i = self.orders.iter().position(|x| x == other_order).unwrap();
+            let self_j = bin_offset + j;
+            let self_k = self.lumi.iter().position(|y| y == other_entry).unwrap();
+
+            merge_subgrids(&mut self.subgrids[[self_i, self_j, self_k]], subgrid);
+
+            if let (Some(mc_statistics), Some(other_statistics)) =
+                (&mut self.mc_statistics, &other.mc_statistics)
+            {
+                mc_statistics[[self_i, self_j, self_k]].merge(&other_statistics[[i, j, k]]);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns whether every subgrid of `other` can be merged into the subgrid of this grid with
+    /// the same order, bin and channel.
+    fn subgrids_mergeable(&self, other: &Self) -> bool {
+        // only grids with the same bins merge subgrids into each other, otherwise the subgrids of
+        // `other` are moved into new bins
+        if self.bin_info() != other.bin_info() {
+            return true;
+        }
+
+        other.subgrids.indexed_iter().all(|((i, j, k), subgrid)| {
+            let self_i = self.orders.iter().position(|x| *x == other.orders[i]);
+            let self_k = self.lumi.iter().position(|y| *y == other.lumi[k]);
+
+            if let (Some(self_i), Some(self_k)) = (self_i, self_k) {
+                mergeable(&self.subgrids[[self_i, j, self_k]], subgrid)
+            } else {
+                true
+            }
+        })
+    }
+
+    /// Adds `other`, multiplied with `factor`, to this grid. Together with `Grid::scale` this
+    /// forms linear combinations of grids, for instance the difference of two processes. The
+    /// grids must have the same bins, but their orders and luminosity functions may be
+    /// different; orders and channels of `other` that this grid does not have are added to it.
+    ///
+    /// # Errors
+    ///
+    /// If the bins or the initial states of the grids are different an error is returned. Grids
+    /// that can not be merged, see `Grid::merge`, return the error of the merge. In every case
+    /// this grid is not changed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the metadata `initial_state_1`, `initial_state_2`, and so on, of one of the
+    /// grids is not an integer.
+    pub fn add_scaled(&mut self, factor: f64, mut other: Self) -> Result<(), GridCombineError> {
+        if self.bin_info() != other.bin_info() {
+            return Err(GridCombineError::DifferentBins);
+        }
+
+        let initial_states = |grid: &Self| -> Vec<_> {
+            (1..=grid.convolutions())
+                .map(|index| grid.initial_state(index))
+                .collect()
+        };
+
+        if initial_states(self) != initial_states(&other) {
+            return Err(GridCombineError::DifferentInitialStates);
+        }
+
+        other.scale(factor);
+        self.merge(other)?;
+
+        Ok(())
+    }
+
+    fn increase_shape(&mut self, new_dim: &(usize, usize, usize)) {
+        let old_dim = self.subgrids.raw_dim().into_pattern();
+        let mut new_subgrids = Array3::from_shape_simple_fn(
+            (
+                old_dim.0 + new_dim.0,
+                old_dim.1 + new_dim.1,
+                old_dim.2 + new_dim.2,
+            ),
+            || self.subgrids[[0, 0, 0]].clone_empty(),
+        );
+
+        for ((i, j, k), subgrid) in self.subgrids.indexed_iter_mut() {
+            mem::swap(&mut new_subgrids[[i, j, k]], subgrid);
+        }
+
+        mem::swap(&mut self.subgrids, &mut new_subgrids);
+
+        if let Some(mc_statistics) = &mut self.mc_statistics {
+            let mut new_statistics = Array3::default(self.subgrids.dim());
+            new_statistics
+                .slice_mut(s![..old_dim.0, ..old_dim.1, ..old_dim.2])
+                .assign(mc_statistics);
+            *mc_statistics = new_statistics;
+        }
+    }
+
+    /// Scale all subgrids by `factor`.
+    pub fn scale(&mut self, factor: f64) {
+        self.subgrids
+            .iter_mut()
+            .for_each(|subgrid| subgrid.scale(factor));
+
+        if let Some(mc_statistics) = &mut self.mc_statistics {
+            mc_statistics
+                .iter_mut()
+                .for_each(|statistics| statistics.scale(factor));
+        }
+    }
+
+    /// Scales each subgrid by a factor which is the product of the given values `alphas`, `alpha`,
+    /// `logxir`, `logxif`, and `logxia`, each raised to the corresponding powers for each subgrid.
+    /// In addition, every subgrid is scaled by a factor `global` independently of its order.
+    ///
+    /// # Panics
+    ///
+    /// TODO
+    pub fn scale_by_order(
+        &mut self,
+        alphas: f64,
+        alpha: f64,
+        logxir: f64,
+        logxif: f64,
+        logxia: f64,
+        global: f64,
+    ) {
+        for ((i, j, k), subgrid) in self.subgrids.indexed_iter_mut() {
+            let order = &self.orders[i];
+            let factor = global
+                * alphas.powi(order.alphas.try_into().unwrap())
+                * alpha.powi(order.alpha.try_into().unwrap())
+                * logxir.powi(order.logxir.try_into().unwrap())
+                * logxif.powi(order.logxif.try_into().unwrap())
+                * logxia.powi(order.logxia.try_into().unwrap());
+
+            subgrid.scale(factor);
+
+            if let Some(mc_statistics) = &mut self.mc_statistics {
+                mc_statistics[[i, j, k]].scale(factor);
+            }
+        }
+    }
+
+    /// Returns the subgrid parameters.
+    #[must_use]
+    pub fn orders(&self) -> &[Order] {
+        &self.orders
+    }
+
+    /// Returns the subgrid with the specified indices `order`, `bin`, and `lumi`.
+    #[must_use]
+    pub fn subgrid(&self, order: usize, bin: usize, lumi: usize) -> &SubgridEnum {
+        &self.subgrids[[order, bin, lumi]]
+    }
+
+    /// Replaces the subgrid for the specified indices `order`, `bin`, and `lumi` with `subgrid`.
+    /// If the Monte Carlo statistics are tracked, the statistics of the replaced subgrid are
+    /// reset.
+    pub fn set_subgrid(&mut self, order: usize, bin: usize, lumi: usize, subgrid: SubgridEnum) {
+        self.subgrids[[order, bin, lumi]] = subgrid;
+
+        if let Some(mc_statistics) = &mut self.mc_statistics {
+            mc_statistics[[order, bin, lumi]] = McStatistics::default();
+        }
+    }
+
+    /// Sets a remapper. A remapper can change the dimensions and limits of each bin in this grid.
+    /// This is useful because many Monte Carlo integrators and also `PineAPPL` do not support
+    /// multi-dimensional bins. To work around the problem the multi-dimensional bins can be
+    /// projected to one-dimensional bins, and the remapper can be used to restore the multi
+    /// dimensionality. Furthermore, it allows to normalize each bin separately, and independently
+    /// of the bin widths.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the number of bins in the grid and in the remapper do not agree.
+    ///
+    /// # Panics
+    ///
+    /// TODO
+    pub fn set_remapper(&mut self, remapper: BinRemapper) -> Result<(), GridSetBinRemapperError> {
+        if remapper.bins() != self.bin_limits.bins() {
+            return Err(GridSetBinRemapperError::BinMismatch {
+                grid_bins: self.bin_limits.bins(),
+                remapper_bins: remapper.bins(),
+            });
+        }
+
+        self.more_members.upgrade();
+
+        match &mut self.more_members {
+            MoreMembers::V1(_) => unreachable!(),
+            MoreMembers::V2(mmv2) => mmv2.remapper = Some(remapper),
+            MoreMembers::V3(mmv3) => mmv3.remapper = Some(remapper),
+        }
+
+        Ok(())
+    }
+
+    /// Returns the subgrid parameters of this grid. They are used to interpolate subgrids of type
+    /// `NtupleSubgrid` in `Grid::optimize` and to histogram them in `Grid::convolute_subgrid`.
+    #[must_use]
+    pub const fn subgrid_params(&self) -> &SubgridParams {
+        &self.subgrid_params
+    }
+
+    /// Sets the subgrid parameters of this grid, see `Grid::subgrid_params`. Subgrids that
+    /// already exist are not changed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the momentum fractions or the scales have no bins, or if their limits
+    /// are not positive and increasing. In this case the subgrid parameters are not changed.
+    pub fn set_subgrid_params(
+        &mut self,
+        subgrid_params: SubgridParams,
+    ) -> Result<(), GridSetSubgridParamsError> {
+        if subgrid_params.x_bins() == 0 || subgrid_params.q2_bins() == 0 {
+            return Err(GridSetSubgridParamsError::NoBins);
+        }
+
+        for &(min, max) in &[
+            (subgrid_params.x_min(), subgrid_params.x_max()),
+            (subgrid_params.q2_min(), subgrid_params.q2_max()),
+        ] {
+            // the negation also rejects NaNs
+            if !((min > 0.0) && (min < max)) {
+                return Err(GridSetSubgridParamsError::InvalidLimits { min, max });
+            }
+        }
+
+        self.subgrid_params = subgrid_params;
+
+        Ok(())
+    }
+
+    /// Returns all information about the bins in this grid.
+    #[must_use]
+    pub const fn bin_info(&self) -> BinInfo {
+        BinInfo::new(&self.bin_limits, self.remapper())
+    }
+
+    /// Returns a `BinRemapper` describing the bins of this grid. If this grid does not have a
+    /// remapper, the returned one is one-dimensional and uses the bin limits and bin sizes.
+    fn bin_remapper(&self) -> BinRemapper {
+        if let Some(remapper) = self.remapper() {
+            return remapper.clone();
+        }
+
+        BinRemapper::new(
+            self.bin_limits.bin_sizes(),
+            self.bin_limits
+                .limits()
+                .windows(2)
+                .map(|limits| (limits[0], limits[1]))
+                .collect(),
+        )
+        .unwrap_or_else(|_| unreachable!())
+    }
+
+    /// Optimize the internal datastructures for space efficiency. This changes all subgrids of
+    /// type `LagrangeSubgrid` to `LagrangeSparseSubgrid`. Subgrids of type `NtupleSubgrid` are
+    /// interpolated using the `SubgridParams` of this grid; use `Grid::optimize_using` to choose
+    /// different interpolation parameters. Returns the number of n-tuples that were discarded
+    /// during the interpolation, see `Grid::optimize_using`.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as `Grid::optimize_using`.
+    pub fn optimize(&mut self) -> usize {
+        let subgrid_params = self.subgrid_params.clone();
+        let extra_params = ExtraSubgridParams::from(&subgrid_params);
+
+        self.optimize_using(&subgrid_params, &extra_params)
+    }
+
+    /// Optimize the internal datastructures for space efficiency, like `Grid::optimize`, but
+    /// interpolate subgrids of type `NtupleSubgrid` using `subgrid_params` and `extra_params`.
+    /// The interpolated subgrids are stored as `ImportOnlySubgridV1`, and n-tuples outside the
+    /// ranges of the interpolation grids are discarded; the number of discarded n-tuples is
+    /// returned, and if it is non-zero the interpolation ranges should be enlarged. N-tuple
+    /// subgrids with different renormalization and factorization scales are not interpolated.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this grid has metadata, but the key `initial_state_1` or `initial_state_2` is
+    /// missing.
+    pub fn optimize_using(
+        &mut self,
+        subgrid_params: &SubgridParams,
+        extra_params: &ExtraSubgridParams,
+    ) -> usize {
+        let mut discarded = 0;
+
+        // interpolate the n-tuples first, because n-tuple subgrids can not be symmetrized
+        for subgrid in &mut self.subgrids {
+            let interpolated = match subgrid {
+                SubgridEnum::NtupleSubgridV1(grid) => {
+                    Some(grid.interpolate(subgrid_params, extra_params))
+                }
+                SubgridEnum::NtupleSubgridV2(grid) => {
+                    grid.interpolate(subgrid_params, extra_params)
+                }
+                _ => None,
+            };
+
+            if let Some((interpolated, count)) = interpolated {
+                *subgrid = interpolated.into();
+                discarded += count;
+            }
+        }
+
+        if (self.convolutions() == 2)
+            && self
+                .key_values()
+                .map_or(true, |map| map["initial_state_1"] == map["initial_state_2"])
+            && (self.convolution_types()[0] == self.convolution_types()[1])
+        {
+            self.symmetrize();
+        }
+
+        for subgrid in self.subgrids.iter_mut() {
+            if subgrid.is_empty() {
+                *subgrid = EmptySubgridV1::default().into();
+            } else {
+                match subgrid {
+                    SubgridEnum::LagrangeSubgridV1(grid) => {
+                        let mut new_subgrid = LagrangeSparseSubgridV1::from(&*grid).into();
+                        mem::swap(subgrid, &mut new_subgrid);
+                    }
+                    SubgridEnum::LagrangeSubgridV2(grid) => {
+                        let mut new_subgrid = ImportOnlySubgridV1::from(&*grid).into();
+                        mem::swap(subgrid, &mut new_subgrid);
+                    }
+                    SubgridEnum::EmptySubgridV1(_)
+                    | SubgridEnum::LagrangeSparseSubgridV1(_)
+                    | SubgridEnum::ImportOnlySubgridV1(_)
+                    | SubgridEnum::LagrangeSubgridV3(_)
+                    | SubgridEnum::ImportOnlySubgridV2(_)
+                    | SubgridEnum::NtupleSubgridV1(_)
+                    | SubgridEnum::NtupleSubgridV2(_) => {
+                        // nothing to optimize here
+                    }
+                }
+            }
+        }
+
+        discarded
+    }
+
+    /// Removes channels whose subgrids are empty in every order and bin, and merges channels
+    /// whose subgrids are proportional to each other in every order and bin. The luminosity
+    /// function of a merged channel is the sum of the luminosity functions of the original
+    /// channels, each multiplied with the corresponding proportionality factor. This changes the
+    /// luminosity function and the indices of the channels, and grids whose channels were merged
+    /// separately can in general no longer be merged with `Grid::merge`.
+    pub fn merge_channels(&mut self) {
+        let empty_channels: Vec<_> = (0..self.lumi.len())
+            .filter(|&lumi| {
+                self.subgrids
+                    .slice(s![.., .., lumi])
+                    .iter()
+                    .all(Subgrid::is_empty)
+            })
+            .collect();
+
+        self.delete_channels(&empty_channels);
+
+        let mut lumi = 0;
+
+        while lumi < self.lumi.len() {
+            let proportional: Vec<_> = (lumi + 1..self.lumi.len())
+                .filter_map(|other| {
+                    self.channel_factor(lumi, other)
+                        .map(|factor| (other, factor))
+                })
+                .collect();
+
+            if !proportional.is_empty() {
+                let mut entry = self.lumi[lumi].entry().to_vec();
+
+                for &(other, factor) in &proportional {
+                    for (pids, other_factor) in self.lumi[other].entry() {
+                        if let Some((_, entry_factor)) =
+                            entry.iter_mut().find(|(entry_pids, _)| entry_pids == pids)
+                        {
+                            *entry_factor += factor * other_factor;
+                        } else {
+                            entry.push((pids.clone(), factor * other_factor));
+                        }
+                    }
+                }
+
+                self.lumi[lumi] = LumiEntry::new(entry);
+
+                let other_channels: Vec<_> = proportional.iter().map(|&(other, _)| other).collect();
+                self.delete_channels(&other_channels);
+            }
+
+            lumi += 1;
+        }
+    }
+
+    /// Returns the factor with which the subgrids of the channel `lhs` must be multiplied to give
+    /// the subgrids of the channel `rhs` in every order and bin, or `None` if there is no such
+    /// factor.
+    fn channel_factor(&self, lhs: usize, rhs: usize) -> Option<f64> {
+        let mut factor = None;
+
+        for (lhs, rhs) in self
+            .subgrids
+            .slice(s![.., .., lhs])
+            .iter()
+            .zip(self.subgrids.slice(s![.., .., rhs]).iter())
+        {
+            match (lhs.is_empty(), rhs.is_empty()) {
+                (true, true) => continue,
+                (false, false) => {}
+                _ => return None,
+            }
+
+            // n-tuple subgrids can not be iterated over
+            if (mem::discriminant(lhs) != mem::discriminant(rhs))
+                || matches!(
+                    lhs,
+                    SubgridEnum::NtupleSubgridV1(_) | SubgridEnum::NtupleSubgridV2(_)
+                )
+                || (lhs.mu2_grid() != rhs.mu2_grid())
+                || (lhs.x_grids() != rhs.x_grids())
+            {
+                return None;
+            }
+
+            let lhs_values = nonzero_values(lhs);
+            let rhs_values = nonzero_values(rhs);
+
+            if lhs_values.len() != rhs_values.len() {
+                return None;
+            }
+
+            for ((lhs_index, lhs_value), (rhs_index, rhs_value)) in
+                lhs_values.into_iter().zip(rhs_values)
+            {
+                let factor = *factor.get_or_insert(rhs_value / lhs_value);
+
+                if (lhs_index != rhs_index)
+                    || !approx_eq!(f64, rhs_value, factor * lhs_value, ulps = 64)
+                {
+                    return None;
+                }
+            }
+        }
+
+        factor
+    }
+
+    fn symmetrize(&mut self) {
+        let mut indices: Vec<usize> = (0..self.lumi.len()).rev().collect();
+        let mut pairs: Vec<(usize, usize)> = Vec::new();
+        let mut not_symmetrized: Vec<usize> = Vec::new();
+
+        'looop: while let Some(index) = indices.pop() {
+            let lumi_entry = &self.lumi[index];
+
+            if *lumi_entry == lumi_entry.transpose() {
+                for order in 0..self.orders.len() {
+                    for bin in 0..self.bin_limits.bins() {
+                        let subgrid = &self.subgrids[[order, bin, index]];
+
+                        // check if in all cases the limits are compatible with merging
+                        if !subgrid.is_empty() && (subgrid.x1_grid() != subgrid.x2_grid()) {
+                            not_symmetrized.push(index);
+
+                            continue 'looop;
+                        }
+                    }
+                }
+
+                pairs.push((index, index));
+            } else if let Some((j, &other_index)) = indices
+                .iter()
+                .enumerate()
+                .find(|(_, i)| self.lumi[**i] == lumi_entry.transpose())
+            {
+                indices.remove(j);
+
+                for order in 0..self.orders.len() {
+                    for bin in 0..self.bin_limits.bins() {
+                        let lhs = &self.subgrids[[order, bin, index]];
+                        let rhs = &self.subgrids[[order, bin, other_index]];
+
+                        // check if in all cases the limits are compatible with merging; n-tuple
+                        // subgrids can not be merged with transposed momentum fractions
+                        if !lhs.is_empty()
+                            && !rhs.is_empty()
+                            && ((lhs.x1_grid() != rhs.x2_grid())
+                                || (lhs.x2_grid() != rhs.x1_grid())
+                                || matches!(lhs, SubgridEnum::NtupleSubgridV2(_))
+                                || matches!(rhs, SubgridEnum::NtupleSubgridV2(_)))
+                        {
+                            not_symmetrized.push(index);
+                            not_symmetrized.push(other_index);
+
+                            continue 'looop;
+                        }
+                    }
+                }
+
+                pairs.push((index, other_index));
+            } else {
+                not_symmetrized.push(index);
+            }
+        }
+
+        let i_size = self.orders.len();
+        let j_size = self.bin_limits.bins();
+        let k_size = self.lumi.len();
+
+        let subgrids = mem::replace(
+            &mut self.subgrids,
+            Array3::from_shape_vec((0, 0, 0), vec![]).unwrap(),
+        );
+        let mut subgrids = Array3::from_shape_vec(
+            (i_size, j_size, k_size),
+            subgrids.into_raw_vec().into_iter().map(Some).collect(),
+        )
+        .unwrap();
+
+        for i in 0..i_size {
+            for j in 0..j_size {
+                for &(k1, k2) in &pairs {
+                    if k1 == k2 {
+                        subgrids[[i, j, k1]].as_mut().unwrap().symmetrize();
+                    } else {
+                        let mut lhs = mem::replace(&mut subgrids[[i, j, k1]], None).unwrap();
+                        let mut rhs = mem::replace(&mut subgrids[[i, j, k2]], None).unwrap();
+
+                        subgrids[[i, j, k1]] = Some(if rhs.is_empty() {
+                            lhs
+                        } else if lhs.is_empty() {
+                            let mut new_lhs = rhs.clone_empty();
+                            new_lhs.merge(&mut rhs, true);
+                            new_lhs
+                        } else {
+                            lhs.merge(&mut rhs, true);
+                            lhs
+                        });
+                    }
+                }
+            }
+        }
+
+        self.subgrids = Array3::from_shape_vec(
+            (i_size, j_size, pairs.len() + not_symmetrized.len()),
+            subgrids.into_raw_vec().into_iter().flatten().collect(),
+        )
+        .unwrap();
+
+        let mut new_lumi_indices: Vec<_> = pairs
+            .iter()
+            .map(|(index, _)| index)
+            .chain(not_symmetrized.iter())
+            .copied()
+            .collect();
+        new_lumi_indices.sort_unstable();
+
+        self.symmetrize_mc_statistics(&pairs, &new_lumi_indices);
+
+        self.lumi = new_lumi_indices
+            .iter()
+            .map(|i| self.lumi[*i].clone())
+            .collect();
+    }
+
+    /// Merges the Monte Carlo statistics of the channels given by `pairs` in the same way as
+    /// `Grid::symmetrize` merges their subgrids, and keeps only the channels in `lumi_indices`.
+    fn symmetrize_mc_statistics(&mut self, pairs: &[(usize, usize)], lumi_indices: &[usize]) {
+        if let Some(mc_statistics) = &mut self.mc_statistics {
+            let (orders, bins, _) = mc_statistics.dim();
+
+            for (i, j, &(k1, k2)) in iproduct!(0..orders, 0..bins, pairs) {
+                if k1 != k2 {
+                    let statistics = mc_statistics[[i, j, k2]];
+                    mc_statistics[[i, j, k1]].merge(&statistics);
+                }
+            }
+
+            *mc_statistics = mc_statistics.select(Axis(2), lumi_indices);
+        }
+    }
+
+    /// Upgrades the internal data structures to their latest versions.
+    pub fn upgrade(&mut self) {
+        self.more_members.upgrade();
+    }
+
+    /// Checks this grid for inconsistencies and returns a list of findings, which is empty if no
+    /// problems were found. The checks cover the shape of the subgrids, the number of bins of the
+    /// remapper, the metadata `initial_state_1`, `initial_state_2`, and so on, subgrids with
+    /// values that are not finite, and interpolating subgrids with nodes of the momentum fractions
+    /// or the factorization scale outside the limits given by `Grid::subgrid_params`. The nodes of
+    /// other subgrids, for instance imported ones, are not bound to these limits.
+    #[must_use]
+    pub fn validate(&self) -> Vec<GridFinding> {
+        let mut findings = Vec::new();
+
+        let expected = (self.orders.len(), self.bin_limits.bins(), self.lumi.len());
+        let found = self.subgrids.dim();
+
+        if expected != found {
+            findings.push(GridFinding::InconsistentShape { expected, found });
+        }
+
+        if let Some(remapper) = self.remapper() {
+            if remapper.bins() != self.bin_limits.bins() {
+                findings.push(GridFinding::RemapperBinMismatch {
+                    grid_bins: self.bin_limits.bins(),
+                    remapper_bins: remapper.bins(),
+                });
+            }
+        }
+
+        for index in 1..=self.convolutions() {
+            match self
+                .key_values()
+                .and_then(|map| map.get(&format!("initial_state_{}", index)))
+            {
+                Some(value) if value.parse::<i32>().is_err() => {
+                    findings.push(GridFinding::InvalidInitialState {
+                        index,
+                        value: value.clone(),
+                    });
+                }
+                Some(_) => {}
+                None => findings.push(GridFinding::MissingInitialState(index)),
+            }
+        }
+
+        if let Err(UnknownPidBasis(value)) = self.pid_basis() {
+            findings.push(GridFinding::UnknownPidBasis(value));
+        }
+
+        let params = &self.subgrid_params;
+        // allow for rounding errors of the interpolation nodes
+        let outside = |value: f64, min: f64, max: f64| {
+            (value < min * (1.0 - 1e-9)) || (value > max * (1.0 + 1e-9))
+        };
+
+        for ((order, bin, lumi), subgrid) in self.subgrids.indexed_iter() {
+            if subgrid.is_empty() {
+                continue;
+            }
+
+            let non_finite = match subgrid {
+                // n-tuples can not be iterated over, but any weight that is not finite makes the
+                // sum of all weights not finite
+                SubgridEnum::NtupleSubgridV1(_) | SubgridEnum::NtupleSubgridV2(_) => {
+                    !subgrid.convolute(&[], &[], Right(&|_, _| 1.0)).is_finite()
+                }
+                _ => subgrid.iter().any(|(_, value)| !value.is_finite()),
+            };
+
+            if non_finite {
+                findings.push(GridFinding::NonFiniteValue { order, bin, lumi });
+            }
+
+            if !matches!(
+                subgrid,
+                SubgridEnum::LagrangeSubgridV1(_)
+                    | SubgridEnum::LagrangeSubgridV2(_)
+                    | SubgridEnum::LagrangeSubgridV3(_)
+                    | SubgridEnum::LagrangeSparseSubgridV1(_)
+            ) {
+                continue;
+            }
+
+            if let Some(&value) = subgrid
+                .x_grids()
+                .iter()
+                .flat_map(|x| x.iter())
+                .find(|&&x| outside(x, params.x_min(), params.x_max()))
+            {
+                findings.push(GridFinding::NodeOutsideLimits {
+                    order,
+                    bin,
+                    lumi,
+                    variable: "x",
+                    value,
+                });
+            }
+
+            if let Some(value) = subgrid
+                .mu2_grid()
+                .iter()
+                .map(|mu2| mu2.fac)
+                .find(|&fac| outside(fac, params.q2_min(), params.q2_max()))
+            {
+                findings.push(GridFinding::NodeOutsideLimits {
+                    order,
+                    bin,
+                    lumi,
+                    variable: "q2",
+                    value,
+                });
+            }
+        }
+
+        findings
+    }
+
+    /// Returns a map with key-value pairs, if there are any stored in this grid.
+    #[must_use]
+    pub const fn key_values(&self) -> Option<&HashMap<String, String>> {
+        match &self.more_members {
+            MoreMembers::V3(mmv3) => Some(&mmv3.key_value_db),
+            MoreMembers::V2(mmv2) => Some(&mmv2.key_value_db),
+            MoreMembers::V1(_) => None,
+        }
+    }
+
+    /// Returns a map with key-value pairs, if there are any stored in this grid.
+    ///
+    /// # Panics
+    ///
+    /// TODO
+    #[must_use]
+    pub fn key_values_mut(&mut self) -> &mut HashMap<String, String> {
+        self.more_members.upgrade();
+
+        match &mut self.more_members {
+            MoreMembers::V1(_) => unreachable!(),
+            MoreMembers::V2(mmv2) => &mut mmv2.key_value_db,
+            MoreMembers::V3(mmv3) => &mut mmv3.key_value_db,
+        }
+    }
+
+    /// Sets a specific key-value pair in this grid.
+    ///
+    /// # Panics
+    ///
+    /// TODO
+    pub fn set_key_value(&mut self, key: &str, value: &str) {
+        self.more_members.upgrade();
+
+        let key_value_db = match &mut self.more_members {
+            MoreMembers::V1(_) => unreachable!(),
+            MoreMembers::V2(mmv2) => &mut mmv2.key_value_db,
+            MoreMembers::V3(mmv3) => &mut mmv3.key_value_db,
+        };
+
+        key_value_db.insert(key.to_owned(), value.to_owned());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bin::MergeBinError;
+    use crate::lumi_entry;
+    use crate::pdf::{AlphaPdf, ClosurePdf};
+    use crate::subgrid::Mu2;
+    use itertools::izip;
+    use ndarray::Array5;
+    use std::cell::Cell;
+    use std::convert::TryFrom;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn order_cmp() {
+        let mut orders = vec![
+            Order::new(1, 2, 1, 0, 0),
+            Order::new(1, 2, 0, 1, 0),
+            Order::new(1, 2, 0, 0, 0),
+            Order::new(0, 3, 1, 0, 0),
+            Order::new(0, 3, 0, 1, 0),
+            Order::new(0, 3, 0, 0, 0),
+            Order::new(0, 2, 0, 0, 0),
+        ];
+
+        orders.sort();
+
+        assert_eq!(orders[0], Order::new(0, 2, 0, 0, 0));
+        assert_eq!(orders[1], Order::new(1, 2, 0, 0, 0));
+        assert_eq!(orders[2], Order::new(1, 2, 0, 1, 0));
+        assert_eq!(orders[3], Order::new(1, 2, 1, 0, 0));
+        assert_eq!(orders[4], Order::new(0, 3, 0, 0, 0));
+        assert_eq!(orders[5], Order::new(0, 3, 0, 1, 0));
+        assert_eq!(orders[6], Order::new(0, 3, 1, 0, 0));
+    }
+
+    #[test]
+    fn grid_with_subgrid_type() {
+        let subgrid_type = String::from("Idontexist");
+        let result = Grid::with_subgrid_type(
+            vec![],
+            vec![],
+            vec![],
+            SubgridParams::default(),
+            ExtraSubgridParams::default(),
+            &subgrid_type,
+        );
+
+        matches!(result, Err(UnknownSubgrid(x)) if x == subgrid_type);
+    }
+
+    #[test]
+    fn grid_fill_different_scales() {
+        let mut grid = Grid::new(
+            vec![lumi_entry![2, 2, 1.0]],
+            vec![Order::new(0, 2, 0, 0, 0)],
+            vec![0.0, 1.0],
+            SubgridParams::default(),
+        );
+
+        let ntuple = Ntuple {
+            x1: 0.1,
+            x2: 0.2,
+            q2: 1e4,
+            q2r: 4e4,
+            weight: 1.0,
+        };
+
+        // interpolating subgrids support only a single scale
+        assert!(matches!(
+            grid.fill(0, 0.5, 0, &ntuple),
+            Err(GridFillError::DifferentScales {
+                order: 0,
+                bin: 0,
+                lumi: 0
+            })
+        ));
+        assert!(grid.subgrids[[0, 0, 0]].is_empty());
+
+        // n-tuple subgrids support different scales
+        let mut grid = Grid::with_subgrid_type(
+            vec![lumi_entry![2, 2, 1.0]],
+            vec![Order::new(0, 2, 0, 0, 0)],
+            vec![0.0, 1.0],
+            SubgridParams::default(),
+            ExtraSubgridParams::default(),
+            "NtupleSubgrid",
+        )
+        .unwrap();
+
+        grid.fill(0, 0.5, 0, &ntuple).unwrap();
+
+        assert!(!grid.subgrids[[0, 0, 0]].is_empty());
+    }
+
+    #[test]
+    fn grid_convolutions() {
+        let mut subgrid_params = SubgridParams::default();
+        subgrid_params.set_reweight(false);
+
+        // a grid with a single convolution, for instance for DIS
+        let mut grid = Grid::new(
+            vec![LumiEntry::new(vec![(vec![2], 1.0), (vec![-2], 0.5)])],
+            vec![Order::new(0, 0, 0, 0, 0)],
+            vec![0.0, 0.5, 1.0],
+            subgrid_params.clone(),
+        );
+
+        assert_eq!(grid.convolutions(), 1);
+        assert_eq!(grid.key_values().unwrap()["initial_state_1"], "2212");
+        assert!(!grid.key_values().unwrap().contains_key("initial_state_2"));
+
+        grid.fill_x(0, 0.25, 0, &[0.1], 90.0_f64.powi(2), 2.0);
+        grid.fill_x(0, 0.75, 0, &[0.2], 90.0_f64.powi(2), 4.0);
+
+        // with unit PDFs the interpolation weights add up to one
+        let pdf = ClosurePdf::new(2212, |_, x, _| x, |_| 1.0);
+        let result = grid.convolute(&[&pdf], &[], &[], &[], &[(1.0, 1.0, 1.0)]);
+
+        assert!(approx_eq!(f64, result[0], 1.5 * 2.0 / 0.5, ulps = 64));
+        assert!(approx_eq!(f64, result[1], 1.5 * 4.0 / 0.5, ulps = 64));
+
+        // a grid with three convolutions
+        let mut grid = Grid::new(
+            vec![
+                LumiEntry::new(vec![(vec![2, -2, 21], 1.0)]),
+                LumiEntry::new(vec![(vec![21, 21, 21], 1.0)]),
+            ],
+            vec![Order::new(0, 0, 0, 0, 0)],
+            vec![0.0, 1.0],
+            subgrid_params,
+        );
+
+        assert_eq!(grid.convolutions(), 3);
+        assert_eq!(grid.key_values().unwrap()["initial_state_3"], "2212");
+
+        grid.fill_x(0, 0.5, 0, &[0.1, 0.2, 0.3], 90.0_f64.powi(2), 2.0);
+        grid.fill_x(0, 0.5, 1, &[0.01, 0.5, 0.05], 90.0_f64.powi(2), 3.0);
+
+        let pdf = ClosurePdf::new(2212, |id, x, _| if id == 21 { 2.0 * x } else { x }, |_| 1.0);
+        let result = grid.convolute(&[&pdf, &pdf, &pdf], &[], &[], &[], &[(1.0, 1.0, 1.0)]);
+
+        // the first channel has a single gluon, the second one three
+        assert!(approx_eq!(f64, result[0], 28.0, ulps = 64));
+
+        let result = grid.convolute(
+            &[&pdf, &pdf, &pdf],
+            &[],
+            &[],
+            &[true, false],
+            &[(1.0, 1.0, 1.0)],
+        );
+
+        assert!(approx_eq!(f64, result[0], 2.0 * 2.0, ulps = 64));
+    }
+
+    #[test]
+    fn grid_convolute_shared_pdf_cache() {
+        let mut grid = Grid::new(
+            vec![lumi_entry![2, 2, 1.0]],
+            vec![Order::new(0, 2, 0, 0, 0)],
+            vec![0.0, 1.0],
+            SubgridParams::default(),
+        );
+
+        grid.fill(
+            0,
+            0.5,
+            0,
+            &Ntuple {
+                x1: 0.1,
+                x2: 0.2,
+                q2: 90.0_f64.powi(2),
+                q2r: 90.0_f64.powi(2),
+                weight: 1.0,
+            },
+        )
+        .unwrap();
+        grid.optimize();
+
+        let calls = Cell::new(0);
+        let xfx = |_, x, _| {
+            calls.set(calls.get() + 1);
+            x
+        };
+        let pdf = ClosurePdf::new(2212, xfx, |_| 1.0);
+        let other_pdf = ClosurePdf::new(2212, xfx, |_| 1.0);
+        let xi = [(1.0, 1.0, 1.0)];
+
+        let result = grid.convolute(&[&pdf, &pdf], &[], &[], &[], &xi);
+        let shared_calls = calls.replace(0);
+
+        assert!(shared_calls > 0);
+
+        // different PDF objects do not share the cache, but give the same result
+        assert_eq!(
+            grid.convolute(&[&pdf, &other_pdf], &[], &[], &[], &xi),
+            result
+        );
+        assert_eq!(calls.get(), 2 * shared_calls);
+
+        calls.set(0);
+        let _ = grid.convolute_subgrid(&[&pdf, &pdf], 0, 0, 0, 1.0, 1.0, 1.0);
+        let shared_calls = calls.replace(0);
+        let _ = grid.convolute_subgrid(&[&pdf, &other_pdf], 0, 0, 0, 1.0, 1.0, 1.0);
+
+        assert_eq!(calls.get(), 2 * shared_calls);
+    }
+
+    #[test]
+    #[should_panic(expected = "the grid has 3 convolutions, but 2 PDFs were given")]
+    fn grid_convolute_wrong_number_of_pdfs() {
+        let grid = Grid::new(
+            vec![LumiEntry::new(vec![(vec![2, -2, 21], 1.0)])],
+            vec![Order::new(0, 0, 0, 0, 0)],
+            vec![0.0, 1.0],
+            SubgridParams::default(),
+        );
+        let pdf = ClosurePdf::new(2212, |_, x, _| x, |_| 1.0);
+
+        let _ = grid.convolute(&[&pdf, &pdf], &[], &[], &[], &[(1.0, 1.0, 1.0)]);
+    }
+
+    #[test]
+    fn grid_fragmentation_function() {
+        let x = vec![0.1, 0.5];
+        let mut grid = Grid::new(
+            vec![lumi_entry![2, 21, 1.0]],
+            vec![Order::new(0, 0, 0, 0, 0), Order::new(0, 0, 0, 0, 1)],
+            vec![0.0, 1.0],
+            SubgridParams::default(),
+        );
+
+        assert_eq!(
+            grid.convolution_types(),
+            [ConvolutionType::Pdf, ConvolutionType::Pdf]
+        );
+
+        // the second convolution is with the fragmentation function of a pion
+        grid.set_convolution_type(1, ConvolutionType::FragmentationFunction);
+        grid.set_key_value("initial_state_2", "211");
+
+        assert_eq!(
+            grid.convolution_types(),
+            [ConvolutionType::Pdf, ConvolutionType::FragmentationFunction]
+        );
+
+        for order in 0..2 {
+            let mut array = SparseArray3::new(1, 2, 2);
+            array[[0, 0, 1]] = 1.0;
+
+            grid.set_subgrid(
+                order,
+                0,
+                0,
+                ImportOnlySubgridV1::new(array, vec![100.0], x.clone(), x.clone()).into(),
+            );
+        }
+
+        let pdf = ClosurePdf::new(2212, |_, x, q2| x * q2, |_| 1.0);
+        let ff = ClosurePdf::new(211, |_, x, q2| x * q2 * q2, |_| 1.0);
+
+        let result = grid.convolute(
+            &[&pdf, &ff],
+            &[],
+            &[],
+            &[],
+            &[(1.0, 1.0, 1.0), (2.0, 2.0, 1.0), (1.0, 1.0, 2.0)],
+        );
+
+        // the PDF is evaluated at the factorization and the fragmentation function at the
+        // fragmentation scale, which only the last order depends on
+        assert!(approx_eq!(f64, result[0], 100.0 * 1e4, ulps = 4));
+        assert!(approx_eq!(f64, result[1], 400.0 * 1e4, ulps = 4));
+        assert!(approx_eq!(
+            f64,
+            result[2],
+            100.0 * 1.6e5 * (1.0 + 4.0_f64.ln()),
+            ulps = 4
+        ));
+
+        let operator = EvolutionOperator::new(
+            Array5::zeros((1, 1, 1, 1, 1)),
+            vec![0.1],
+            vec![21],
+            vec![0.1],
+            vec![21],
+            vec![100.0],
+            1.0,
+        )
+        .unwrap();
+
+        assert!(matches!(
+            grid.evolve(&operator, &|_| 1.0, &|_| 1.0, &[], (1.0, 1.0)),
+            Err(GridEvolveError::UnsupportedConvolutionType(1))
+        ));
+    }
+
+    #[test]
+    fn grid_separate_scales() {
+        let x = vec![0.1, 0.5];
+        let mut array = SparseArray3::new(1, 2, 2);
+        array[[0, 0, 1]] = 1.0;
+
+        let mut import_only = Grid::new(
+            vec![lumi_entry![2, 2, 1.0]],
+            vec![Order::new(1, 0, 0, 0, 0)],
+            vec![0.0, 1.0],
+            SubgridParams::default(),
+        );
+        import_only.set_subgrid(
+            0,
+            0,
+            0,
+            ImportOnlySubgridV2::new(
+                array,
+                vec![Mu2 {
+                    ren: 400.0,
+                    fac: 100.0,
+                }],
+                x.clone(),
+                x.clone(),
+            )
+            .into(),
+        );
+
+        let mut ntuple = Grid::with_subgrid_type(
+            vec![lumi_entry![2, 2, 1.0]],
+            vec![Order::new(1, 0, 0, 0, 0)],
+            vec![0.0, 1.0],
+            SubgridParams::default(),
+            ExtraSubgridParams::default(),
+            "NtupleSubgrid",
+        )
+        .unwrap();
+        ntuple
+            .fill(
+                0,
+                0.5,
+                0,
+                &Ntuple {
+                    x1: 0.1,
+                    x2: 0.5,
+                    q2: 100.0,
+                    q2r: 400.0,
+                    weight: 1.0,
+                },
+            )
+            .unwrap();
+
+        // the PDFs depend on the factorization scale, the strong coupling on the renormalization
+        // scale
+        let pdf = ClosurePdf::new(2212, |_, x, q2| x * q2, |q2| q2);
+        let xi = [(1.0, 1.0, 1.0), (2.0, 1.0, 1.0), (1.0, 2.0, 2.0)];
+
+        for grid in &[&import_only, &ntuple] {
+            let result = grid.convolute(&[&pdf, &pdf], &[], &[], &[], &xi);
+
+            assert!(approx_eq!(f64, result[0], 1e4 * 400.0, ulps = 4));
+            assert!(approx_eq!(f64, result[1], 1e4 * 1600.0, ulps = 4));
+            assert!(approx_eq!(f64, result[2], 1.6e5 * 400.0, ulps = 4));
+        }
+
+        // an identity operator for the scale `100`, which is the factorization scale
+        let mut operator = Array5::zeros((2, 1, 2, 1, 1));
+        operator[[0, 0, 0, 0, 0]] = 1.0;
+        operator[[1, 0, 1, 0, 0]] = 1.0;
+        let operator =
+            EvolutionOperator::new(operator, x.clone(), vec![2], x, vec![2], vec![100.0], 1.0)
+                .unwrap();
+
+        let fk_table = import_only
+            .evolve(&operator, &|q2| q2, &|_| 1.0, &[], (1.0, 1.0))
+            .unwrap();
+
+        assert_eq!(
+            fk_table.convolute(&[&pdf, &pdf], &[], &[], &[], &[(1.0, 1.0, 1.0)]),
+            [400.0]
+        );
+    }
+
+    #[test]
+    fn grid_delete() {
+        let create_grid = || {
+            let mut grid = Grid::new(
+                vec![lumi_entry![2, 2, 1.0], lumi_entry![1, 1, 1.0]],
+                vec![
+                    Order::new(0, 2, 0, 0, 0),
+                    Order::new(1, 2, 0, 0, 0),
+                    Order::new(0, 3, 0, 0, 0),
+                ],
+                vec![0.0, 1.0, 2.0, 3.0, 4.0, 5.0],
+                SubgridParams::default(),
+            );
+
+            for ((order, bin, lumi), _) in Array3::<f64>::zeros((3, 5, 2)).indexed_iter() {
+                let mut array = SparseArray3::new(1, 1, 1);
+                array[[0, 0, 0]] = f64_from_usize(100 * order + 10 * bin + lumi + 1);
+
+                grid.set_subgrid(
+                    order,
+                    bin,
+                    lumi,
+                    ImportOnlySubgridV1::new(array, vec![100.0], vec![0.5], vec![0.5]).into(),
+                );
+            }
+
+            grid
+        };
+
+        let pdf = ClosurePdf::new(2212, |_, x, _| x, |_| 1.0);
+
+        let mut grid = create_grid();
+        grid.delete_bins(&[4]);
+
+        // deleting the right-most bin does not require a remapper
+        assert_eq!(grid.bin_info().dimensions(), 1);
+        assert_eq!(grid.bin_info().left(0), [0.0, 1.0, 2.0, 3.0]);
+        assert_eq!(grid.bin_info().right(0), [1.0, 2.0, 3.0, 4.0]);
+
+        let mut grid = create_grid();
+        grid.delete_orders(&[1, 7]);
+        grid.delete_channels(&[0]);
+        grid.delete_bins(&[0, 2, 4, 2]);
+
+        assert_eq!(
+            grid.orders(),
+            [Order::new(0, 2, 0, 0, 0), Order::new(0, 3, 0, 0, 0)]
+        );
+        assert_eq!(grid.lumi(), [lumi_entry![1, 1, 1.0]]);
+        assert_eq!(grid.bin_info().bins(), 2);
+        assert_eq!(grid.bin_info().left(0), [1.0, 3.0]);
+        assert_eq!(grid.bin_info().right(0), [2.0, 4.0]);
+        assert_eq!(grid.bin_info().normalizations(), [1.0, 1.0]);
+
+        assert_eq!(
+            grid.convolute(&[&pdf, &pdf], &[], &[], &[], &[(1.0, 1.0, 1.0)]),
+            [12.0 + 212.0, 32.0 + 232.0]
+        );
+
+        // deleting the remaining bins also removes the remapper
+        grid.delete_bins(&[0, 1]);
+
+        assert!(grid.remapper().is_none());
+        assert_eq!(grid.bin_info().bins(), 0);
+        assert_eq!(grid.bin_info().dimensions(), 1);
+    }
+
+    #[test]
+    fn grid_optimize_ntuples() {
+        let create_grid = || {
+            let mut grid = Grid::with_subgrid_type(
+                vec![
+                    lumi_entry![2, 2, 1.0],
+                    lumi_entry![1, 2, 1.0],
+                    lumi_entry![2, 1, 1.0],
+                ],
+                vec![Order::new(0, 2, 0, 0, 0)],
+                vec![0.0, 1.0, 2.0],
+                SubgridParams::default(),
+                ExtraSubgridParams::default(),
+                "NtupleSubgrid",
+            )
+            .unwrap();
+
+            for &(x1, x2, observable) in &[(0.1, 0.2, 0.5), (0.3, 0.05, 0.5), (0.01, 0.4, 1.5)] {
+                grid.fill_all(
+                    0,
+                    observable,
+                    &Ntuple {
+                        x1,
+                        x2,
+                        q2: 1e4,
+                        q2r: 1e4,
+                        weight: (),
+                    },
+                    &[1.0, 2.0, 3.0],
+                )
+                .unwrap();
+            }
+
+            grid
+        };
+
+        let pdf = ClosurePdf::new(
+            2212,
+            |id, x, _| f64::from(id) * x * (1.0 - x).powi(3),
+            |_| 0.118,
+        );
+
+        let mut grid = create_grid();
+        let reference = grid.convolute(&[&pdf, &pdf], &[], &[], &[], &[(1.0, 1.0, 1.0)]);
+
+        assert_eq!(grid.optimize(), 0);
+
+        assert!(grid
+            .subgrids
+            .iter()
+            .all(|subgrid| matches!(subgrid, SubgridEnum::ImportOnlySubgridV1(_))));
+        // the two non-diagonal channels are transposes of each other and were symmetrized
+        assert_eq!(grid.lumi().len(), 2);
+
+        let result = grid.convolute(&[&pdf, &pdf], &[], &[], &[], &[(1.0, 1.0, 1.0)]);
+
+        for (result, reference) in result.iter().zip(&reference) {
+            assert!(((result / reference) - 1.0).abs() < 1e-3);
+        }
+
+        // fewer interpolation nodes give a larger interpolation error, in particular for the
+        // second bin, which has an event with a small momentum fraction
+        let mut subgrid_params = SubgridParams::default();
+        subgrid_params.set_x_bins(20);
+
+        let mut grid = create_grid();
+        let discarded =
+            grid.optimize_using(&subgrid_params, &ExtraSubgridParams::from(&subgrid_params));
+
+        assert_eq!(discarded, 0);
+
+        let result = grid.convolute(&[&pdf, &pdf], &[], &[], &[], &[(1.0, 1.0, 1.0)]);
+
+        for (result, reference) in result.iter().zip(&reference) {
+            assert!(((result / reference) - 1.0).abs() < 1e-1);
+        }
+
+        assert!(((result[1] / reference[1]) - 1.0).abs() > 1e-2);
+
+        // n-tuples outside of the interpolation ranges are discarded and counted; here all three
+        // events in each of the three channels have a scale that is too large
+        let mut subgrid_params = SubgridParams::default();
+        subgrid_params.set_q2_max(1e3);
+
+        let mut grid = create_grid();
+        let discarded =
+            grid.optimize_using(&subgrid_params, &ExtraSubgridParams::from(&subgrid_params));
+
+        assert_eq!(discarded, 9);
+
+        // n-tuples with different scales are not interpolated
+        let mut grid = create_grid();
+        grid.fill(
+            0,
+            0.5,
+            0,
+            &Ntuple {
+                x1: 0.1,
+                x2: 0.1,
+                q2: 1e4,
+                q2r: 4e4,
+                weight: 1.0,
+            },
+        )
+        .unwrap();
+        grid.optimize();
+
+        assert!(matches!(
+            grid.subgrids[[0, 0, 0]],
+            SubgridEnum::NtupleSubgridV2(_)
+        ));
+    }
+
+    #[test]
+    fn grid_convolute_subgrid_ntuples() {
+        let mut subgrid_params = SubgridParams::default();
+        subgrid_params.set_x_bins(2);
+        subgrid_params.set_x_min(1e-2);
+        subgrid_params.set_x_max(1.0);
+        subgrid_params.set_q2_bins(1);
+        subgrid_params.set_q2_min(1e2);
+        subgrid_params.set_q2_max(1e4);
+
+        let mut grid = Grid::with_subgrid_type(
+            vec![lumi_entry![2, 21, 1.0]],
+            vec![Order::new(1, 0, 0, 0, 0)],
+            vec![0.0, 1.0],
+            subgrid_params,
+            ExtraSubgridParams::default(),
+            "NtupleSubgrid",
+        )
+        .unwrap();
+
+        for &(x1, x2, weight) in &[(0.5, 0.05, 1.0), (0.2, 0.02, 2.0), (0.03, 0.3, 4.0)] {
+            grid.fill(
+                0,
+                0.5,
+                0,
+                &Ntuple {
+                    x1,
+                    x2,
+                    q2: 1e3,
+                    q2r: 1e3,
+                    weight,
+                },
+            )
+            .unwrap();
+        }
+
+        let pdf = ClosurePdf::new(2212, |_, x, q2| x * q2, |q2| q2);
+        let array = grid.convolute_subgrid(&[&pdf, &pdf], 0, 0, 0, 1.0, 1.0, 1.0);
+
+        // the PDFs divided by the momentum fractions and the strong coupling are evaluated at the
+        // bin center of the scale, and all but the last event end up in the same bin
+        assert_eq!(array.shape(), [1, 2, 2]);
+        assert!(approx_eq!(f64, array[[0, 1, 0]], 3.0 * 1e9, ulps = 16));
+        assert!(approx_eq!(f64, array[[0, 0, 1]], 4.0 * 1e9, ulps = 16));
+        assert_eq!(array[[0, 0, 0]], 0.0);
+        assert_eq!(array[[0, 1, 1]], 0.0);
+    }
+
+    #[test]
+    fn grid_set_subgrid_params() {
+        let mut grid = Grid::new(
+            vec![lumi_entry![2, 21, 1.0]],
+            vec![Order::new(0, 0, 0, 0, 0)],
+            vec![0.0, 1.0],
+            SubgridParams::default(),
+        );
+
+        let mut subgrid_params = SubgridParams::default();
+        subgrid_params.set_x_bins(0);
+
+        assert!(matches!(
+            grid.set_subgrid_params(subgrid_params),
+            Err(GridSetSubgridParamsError::NoBins)
+        ));
+
+        let mut subgrid_params = SubgridParams::default();
+        subgrid_params.set_q2_min(1e4);
+        subgrid_params.set_q2_max(1e4);
+
+        assert!(matches!(
+            grid.set_subgrid_params(subgrid_params),
+            Err(GridSetSubgridParamsError::InvalidLimits { .. })
+        ));
+
+        let mut subgrid_params = SubgridParams::default();
+        subgrid_params.set_x_min(0.0);
+
+        assert!(matches!(
+            grid.set_subgrid_params(subgrid_params),
+            Err(GridSetSubgridParamsError::InvalidLimits { .. })
+        ));
+
+        // the parameters of the grid are unchanged
+        assert_eq!(
+            grid.subgrid_params().x_bins(),
+            SubgridParams::default().x_bins()
+        );
+
+        let mut subgrid_params = SubgridParams::default();
+        subgrid_params.set_x_bins(10);
+
+        grid.set_subgrid_params(subgrid_params).unwrap();
+
+        assert_eq!(grid.subgrid_params().x_bins(), 10);
+    }
+
+    #[test]
+    fn grid_merge_channels() {
+        let mut grid = Grid::new(
+            vec![
+                lumi_entry![2, -2, 1.0],
+                lumi_entry![1, -1, 1.0; 2, -2, 0.5],
+                lumi_entry![21, 21, 1.0],
+                lumi_entry![21, 2, 1.0],
+                lumi_entry![3, -3, 1.0],
+            ],
+            vec![Order::new(0, 2, 0, 0, 0), Order::new(1, 2, 0, 0, 0)],
+            vec![0.0, 1.0, 2.0],
+            SubgridParams::default(),
+        );
 
-            if let Err(e) = self.bin_limits.merge(&other.bin_limits) {
-                return Err(GridMergeError::DifferentBins(e));
+        for ((order, bin, lumi), _) in Array3::<f64>::zeros((2, 2, 5)).indexed_iter() {
+            // the third channel is empty everywhere
+            if lumi == 2 {
+                continue;
             }
 
-            let new_bins = other.bin_limits.bins();
+            let value = f64_from_usize(order + 2 * bin + 1);
+            let mut array = SparseArray3::new(1, 2, 2);
 
-            self.increase_shape(&(0, new_bins, 0));
+            // the fourth channel is not proportional to any other channel
+            if lumi == 3 {
+                array[[0, 0, 1]] = value;
+                array[[0, 1, 0]] = 1.0;
+            } else {
+                // the second and the fifth channel are proportional to the first
+                let factor = [1.0, 4.0, 0.0, 0.0, 0.25][lumi];
 
-            // TODO: figure out a better strategy than removing the remapper
-            match &mut self.more_members {
-                MoreMembers::V1(_) => {}
-                MoreMembers::V2(mmv2) => {
-                    mmv2.remapper = None;
-                }
-                MoreMembers::V3(mmv3) => {
-                    mmv3.remapper = None;
-                }
+                array[[0, 0, 1]] = factor * value;
+                array[[0, 1, 1]] = factor * 3.0 * value;
             }
+
+            grid.set_subgrid(
+                order,
+                bin,
+                lumi,
+                ImportOnlySubgridV1::new(array, vec![100.0], vec![0.1, 0.5], vec![0.1, 0.5]).into(),
+            );
         }
 
-        for ((i, j, k), subgrid) in other
-            .subgrids
-            .indexed_iter_mut()
-            .filter(|((_, _, _), subgrid)| !subgrid.is_empty())
-        {
-            let other_order = &other.orders[i];
-            let other_bin = other.bin_limits.limits()[j];
-            let other_entry = &other.lumi[k];
+        let pdf = ClosurePdf::new(
+            2212,
+            |id, x, _| f64::from(id.abs() + 2 * id.signum()) * x * (1.0 - x),
+            |_| 1.0,
+        );
+        let reference = grid.convolute(&[&pdf, &pdf], &[], &[], &[], &[(1.0, 1.0, 1.0)]);
 
-            let self_i = self.orders.iter().position(|x| x == other_order).unwrap();
-            let self_j = self
-                .bin_limits
-                .limits()
-                .iter()
-                .position(|x| approx_eq!(f64, *x, other_bin, ulps = 4))
-                .unwrap();
-            let self_k = self.lumi.iter().position(|y| y == other_entry).unwrap();
+        grid.merge_channels();
 
-            if self.subgrids[[self_i, self_j, self_k]].is_empty() {
-                mem::swap(&mut self.subgrids[[self_i, self_j, self_k]], subgrid);
-            } else {
-                self.subgrids[[self_i, self_j, self_k]].merge(&mut *subgrid, false);
-            }
+        assert_eq!(
+            grid.lumi(),
+            [
+                lumi_entry![1, -1, 4.0; 2, -2, 3.0; 3, -3, 0.25],
+                lumi_entry![21, 2, 1.0]
+            ]
+        );
+
+        let result = grid.convolute(&[&pdf, &pdf], &[], &[], &[], &[(1.0, 1.0, 1.0)]);
+
+        for (result, reference) in result.iter().zip(&reference) {
+            assert!(approx_eq!(f64, *result, *reference, ulps = 16));
         }
 
-        Ok(())
+        // channels of grids with three convolutions are merged as well
+        let mut grid = Grid::new(
+            vec![
+                LumiEntry::new(vec![(vec![2, -2, 21], 1.0)]),
+                LumiEntry::new(vec![(vec![21, 21, 21], 1.0)]),
+                LumiEntry::new(vec![(vec![1, -1, 21], 1.0)]),
+            ],
+            vec![Order::new(0, 0, 0, 0, 0)],
+            vec![0.0, 1.0],
+            SubgridParams::default(),
+        );
+
+        grid.fill_x(0, 0.5, 0, &[0.1, 0.2, 0.3], 90.0_f64.powi(2), 2.0);
+        grid.fill_x(0, 0.5, 1, &[0.01, 0.5, 0.05], 90.0_f64.powi(2), 3.0);
+        grid.fill_x(0, 0.5, 2, &[0.1, 0.2, 0.3], 90.0_f64.powi(2), 0.5);
+
+        let reference = grid.convolute(&[&pdf, &pdf, &pdf], &[], &[], &[], &[(1.0, 1.0, 1.0)]);
+
+        grid.merge_channels();
+
+        assert_eq!(
+            grid.lumi(),
+            [
+                LumiEntry::new(vec![(vec![2, -2, 21], 1.0), (vec![1, -1, 21], 0.25)]),
+                LumiEntry::new(vec![(vec![21, 21, 21], 1.0)]),
+            ]
+        );
+
+        let result = grid.convolute(&[&pdf, &pdf, &pdf], &[], &[], &[], &[(1.0, 1.0, 1.0)]);
+
+        assert!(approx_eq!(f64, result[0], reference[0], ulps = 16));
     }
 
-    fn increase_shape(&mut self, new_dim: &(usize, usize, usize)) {
-        let old_dim = self.subgrids.raw_dim().into_pattern();
-        let mut new_subgrids = Array3::from_shape_simple_fn(
-            (
-                old_dim.0 + new_dim.0,
-                old_dim.1 + new_dim.1,
-                old_dim.2 + new_dim.2,
-            ),
-            || self.subgrids[[0, 0, 0]].clone_empty(),
+    #[test]
+    fn grid_convolute_alpha() {
+        let mut grid = Grid::new(
+            vec![lumi_entry![2, -2, 1.0]],
+            vec![Order::new(0, 2, 0, 0, 0), Order::new(1, 1, 0, 0, 0)],
+            vec![0.0, 1.0],
+            SubgridParams::default(),
         );
 
-        for ((i, j, k), subgrid) in self.subgrids.indexed_iter_mut() {
-            mem::swap(&mut new_subgrids[[i, j, k]], subgrid);
+        for order in 0..2 {
+            let mut array = SparseArray3::new(1, 2, 2);
+            array[[0, 0, 1]] = 1.0;
+
+            grid.set_subgrid(
+                order,
+                0,
+                0,
+                ImportOnlySubgridV1::new(array, vec![100.0], vec![0.1, 0.5], vec![0.1, 0.5]).into(),
+            );
         }
 
-        mem::swap(&mut self.subgrids, &mut new_subgrids);
+        let pdf = ClosurePdf::new(2212, |_, x, _| x, |_| 0.5);
+        let result = grid.convolute(&[&pdf, &pdf], &[], &[], &[], &[(1.0, 1.0, 1.0)]);
+
+        // without an electroweak coupling only the strong coupling is multiplied
+        assert!(approx_eq!(f64, result[0], 1.5, ulps = 4));
+
+        // the electroweak coupling is evaluated at the renormalization scale
+        let pdf = AlphaPdf::new(&pdf, |q2| q2 / 1000.0);
+        let result = grid.convolute(
+            &[&pdf, &pdf],
+            &[],
+            &[],
+            &[],
+            &[(1.0, 1.0, 1.0), (2.0, 1.0, 1.0)],
+        );
+        let subgrid = grid.convolute_subgrid(&[&pdf, &pdf], 1, 0, 0, 1.0, 1.0, 1.0);
+
+        assert!(approx_eq!(f64, result[0], 0.01 + 0.05, ulps = 4));
+        assert!(approx_eq!(f64, result[1], 0.16 + 0.2, ulps = 4));
+        assert!(approx_eq!(f64, subgrid[[0, 0, 1]], 0.05, ulps = 4));
     }
 
-    /// Scale all subgrids by `factor`.
-    pub fn scale(&mut self, factor: f64) {
-        self.subgrids
-            .iter_mut()
-            .for_each(|subgrid| subgrid.scale(factor));
+    #[test]
+    fn grid_generate_scale_logs() {
+        let mut grid = Grid::new(
+            vec![lumi_entry![2, -2, 1.0; 4, -4, 1.0]],
+            vec![Order::new(1, 0, 0, 0, 0), Order::new(2, 0, 0, 0, 0)],
+            vec![0.0, 1.0],
+            SubgridParams::default(),
+        );
+
+        let x = vec![0.05, 0.1, 0.2, 0.4, 0.8];
+        let mut array = SparseArray3::new(1, x.len(), x.len());
+        array[[0, 1, 2]] = 2.0;
+
+        grid.set_subgrid(
+            0,
+            0,
+            0,
+            ImportOnlySubgridV2::new(
+                array,
+                vec![Mu2 {
+                    ren: 100.0,
+                    fac: 100.0,
+                }],
+                x.clone(),
+                x.clone(),
+            )
+            .into(),
+        );
+
+        grid.generate_scale_logs(5).unwrap();
+
+        assert_eq!(
+            grid.orders(),
+            [
+                Order::new(1, 0, 0, 0, 0),
+                Order::new(2, 0, 0, 0, 0),
+                Order::new(2, 0, 1, 0, 0),
+                Order::new(2, 0, 0, 1, 0),
+            ]
+        );
+        assert_eq!(
+            grid.lumi(),
+            [
+                lumi_entry![2, -2, 1.0; 4, -4, 1.0],
+                lumi_entry![21, -2, 1.0; 21, -4, 1.0],
+                lumi_entry![2, 21, 1.0; 4, 21, 1.0],
+            ]
+        );
+
+        // the next-to-leading order is still empty
+        assert!((0..3).all(|lumi| grid.subgrid(1, 0, lumi).is_empty()));
+
+        let ren_subgrid = grid.subgrid(2, 0, 0);
+        assert_eq!(ren_subgrid.iter().count(), 1);
+        assert!(approx_eq!(
+            f64,
+            *ren_subgrid.iter().next().unwrap().1,
+            2.0 * splitting::beta0(5) / (4.0 * PI),
+            ulps = 4
+        ));
+
+        // PDFs without gluons, for which only the quark-quark splitting function contributes
+        let xf = |x: f64| x.sqrt() * (1.0 - x).powi(3);
+        let pdf = ClosurePdf::new(
+            2212,
+            |id, x, _| if id == 21 { 0.0 } else { xf(x) },
+            |_| 0.25,
+        );
+        let xi = [(1.0, 1.0, 1.0), (2.0, 1.0, 1.0), (1.0, 2.0, 1.0)];
+        let lo = grid.convolute(&[&pdf, &pdf], &[true, false, false, false], &[], &[], &xi);
+        let ren = grid.convolute(&[&pdf, &pdf], &[false, false, true, false], &[], &[], &xi);
+        let fac = grid.convolute(&[&pdf, &pdf], &[false, false, false, true], &[], &[], &xi);
+
+        assert!(approx_eq!(
+            f64,
+            lo[0],
+            2.0 * 2.0 * 0.25 * xf(0.1) * xf(0.2) / 0.02,
+            ulps = 4
+        ));
+        assert_eq!(ren[0], 0.0);
+        assert!(approx_eq!(
+            f64,
+            ren[1],
+            lo[1] * 0.25 * splitting::beta0(5) * 4.0_f64.ln() / (4.0 * PI),
+            ulps = 16
+        ));
+        assert_eq!(ren[2], 0.0);
+
+        let matrix = Splitting::QuarkQuark.matrix(&x, 5);
+        let pqq_f =
+            |i: usize| -> f64 { (0..x.len()).map(|k| matrix[[i, k]] * xf(x[k]) / x[k]).sum() };
+        let reference = -2.0 * 2.0 * 0.25 * 0.25 * 4.0_f64.ln() / (2.0 * PI)
+            * (xf(0.1) / 0.1).mul_add(pqq_f(2), pqq_f(1) * xf(0.2) / 0.2);
+
+        assert_eq!(fac[0], 0.0);
+        assert_eq!(fac[1], 0.0);
+        assert!(approx_eq!(f64, fac[2], reference, ulps = 64));
+
+        // orders that already exist are not generated again
+        grid.generate_scale_logs(5).unwrap();
+        assert_eq!(grid.orders().len(), 4);
+        assert_eq!(grid.lumi().len(), 3);
+
+        // the logarithms beyond the next-to-leading order can not be generated
+        let mut grid = Grid::new(
+            vec![lumi_entry![2, -2, 1.0]],
+            vec![
+                Order::new(1, 0, 0, 0, 0),
+                Order::new(2, 0, 0, 0, 0),
+                Order::new(3, 0, 0, 0, 0),
+            ],
+            vec![0.0, 1.0],
+            SubgridParams::default(),
+        );
+
+        assert!(matches!(
+            grid.generate_scale_logs(5),
+            Err(GridScaleLogsError::UnsupportedOrder {
+                alphas: 3,
+                alpha: 0
+            })
+        ));
+        assert_eq!(grid.orders().len(), 3);
     }
 
-    /// Scales each subgrid by a factor which is the product of the given values `alphas`, `alpha`,
-    /// `logxir`, and `logxif`, each raised to the corresponding powers for each subgrid. In
-    /// addition, every subgrid is scaled by a factor `global` independently of its order.
-    ///
-    /// # Panics
-    ///
-    /// TODO
-    pub fn scale_by_order(
-        &mut self,
-        alphas: f64,
-        alpha: f64,
-        logxir: f64,
-        logxif: f64,
-        global: f64,
-    ) {
-        for ((i, _, _), subgrid) in self.subgrids.indexed_iter_mut() {
-            let order = &self.orders[i];
-            let factor = global
-                * alphas.powi(order.alphas.try_into().unwrap())
-                * alpha.powi(order.alpha.try_into().unwrap())
-                * logxir.powi(order.logxir.try_into().unwrap())
-                * logxif.powi(order.logxif.try_into().unwrap());
+    #[test]
+    fn grid_rotate_pid_basis() {
+        let lumi = vec![
+            lumi_entry![2, -2, 1.0; 4, -4, 1.0],
+            lumi_entry![21, 1, 1.0; 21, -3, 2.0],
+            lumi_entry![21, 21, 1.0],
+        ];
+        let mut grid = Grid::new(
+            lumi.clone(),
+            vec![Order::new(0, 2, 0, 0, 0)],
+            vec![0.0, 1.0],
+            SubgridParams::default(),
+        );
 
-            subgrid.scale(factor);
+        for channel in 0..3 {
+            let mut array = SparseArray3::new(1, 2, 2);
+            array[[0, 0, 1]] = f64_from_usize(channel + 1);
+            array[[0, 1, 0]] = 0.5;
+
+            grid.set_subgrid(
+                0,
+                0,
+                channel,
+                ImportOnlySubgridV1::new(array, vec![100.0], vec![0.1, 0.5], vec![0.1, 0.5]).into(),
+            );
         }
-    }
 
-    /// Returns the subgrid parameters.
-    #[must_use]
-    pub fn orders(&self) -> &[Order] {
-        &self.orders
+        let pdf = ClosurePdf::new(
+            2212,
+            |id, x, _| f64::from(id.abs() + 2 * id.signum()) * x * (1.0 - x),
+            |_| 1.0,
+        );
+        let reference = grid.convolute(&[&pdf, &pdf], &[], &[], &[], &[(1.0, 1.0, 1.0)]);
+
+        assert_eq!(grid.pid_basis().unwrap(), PidBasis::Pdg);
+
+        grid.rotate_pid_basis(PidBasis::Evol).unwrap();
+
+        assert_eq!(grid.pid_basis().unwrap(), PidBasis::Evol);
+        assert_eq!(grid.lumi()[2], lumi_entry![21, 21, 1.0]);
+        assert!(grid
+            .lumi()
+            .iter()
+            .flat_map(LumiEntry::entry)
+            .flat_map(|(pids, _)| pids)
+            .all(|pid| !(1..=6).contains(&pid.abs())));
+
+        let result = grid.convolute(&[&pdf, &pdf], &[], &[], &[], &[(1.0, 1.0, 1.0)]);
+        assert!(approx_eq!(f64, result[0], reference[0], ulps = 64));
+
+        grid.rotate_pid_basis(PidBasis::Pdg).unwrap();
+
+        assert_eq!(grid.pid_basis().unwrap(), PidBasis::Pdg);
+
+        for (entry, reference) in grid.lumi().iter().zip(&lumi) {
+            assert_eq!(entry.entry().len(), reference.entry().len());
+
+            for ((pids, factor), (ref_pids, ref_factor)) in
+                entry.entry().iter().zip(reference.entry())
+            {
+                assert_eq!(pids, ref_pids);
+                assert!(approx_eq!(f64, *factor, *ref_factor, ulps = 16));
+            }
+        }
+
+        let result = grid.convolute(&[&pdf, &pdf], &[], &[], &[], &[(1.0, 1.0, 1.0)]);
+        assert!(approx_eq!(f64, result[0], reference[0], ulps = 64));
+
+        let other = Grid::new(
+            lumi,
+            vec![Order::new(0, 2, 0, 0, 0)],
+            vec![0.0, 1.0],
+            SubgridParams::default(),
+        );
+        grid.rotate_pid_basis(PidBasis::Evol).unwrap();
+
+        assert!(matches!(
+            grid.merge(other),
+            Err(GridMergeError::DifferentPidBasis)
+        ));
+
+        grid.set_key_value("pid_basis", "FLAVOR");
+
+        assert_eq!(
+            grid.pid_basis().unwrap_err().to_string(),
+            "unknown basis of particle ids `FLAVOR`"
+        );
+        assert!(matches!(
+            grid.rotate_pid_basis(PidBasis::Pdg),
+            Err(UnknownPidBasis(_))
+        ));
     }
 
-    /// Returns the subgrid with the specified indices `order`, `bin`, and `lumi`.
-    #[must_use]
-    pub fn subgrid(&self, order: usize, bin: usize, lumi: usize) -> &SubgridEnum {
-        &self.subgrids[[order, bin, lumi]]
+    #[test]
+    fn grid_merge_different_convolutions() {
+        let mut grid = Grid::new(
+            vec![lumi_entry![2, 2, 1.0]],
+            vec![Order::new(0, 0, 0, 0, 0)],
+            vec![0.0, 1.0],
+            SubgridParams::default(),
+        );
+        let other = Grid::new(
+            vec![LumiEntry::new(vec![(vec![2], 1.0)])],
+            vec![Order::new(0, 0, 0, 0, 0)],
+            vec![0.0, 1.0],
+            SubgridParams::default(),
+        );
+
+        assert!(matches!(
+            grid.merge(other),
+            Err(GridMergeError::DifferentConvolutions)
+        ));
     }
 
-    /// Replaces the subgrid for the specified indices `order`, `bin`, and `lumi` with `subgrid`.
-    pub fn set_subgrid(&mut self, order: usize, bin: usize, lumi: usize, subgrid: SubgridEnum) {
-        self.subgrids[[order, bin, lumi]] = subgrid;
+    #[test]
+    fn grid_merge_incompatible_subgrids() {
+        let new_grid = || {
+            Grid::new(
+                vec![lumi_entry![2, 2, 1.0]],
+                vec![Order::new(0, 2, 0, 0, 0)],
+                vec![0.0, 1.0],
+                SubgridParams::default(),
+            )
+        };
+        let new_subgrid = || {
+            let mut subgrid = LagrangeSubgridV3::new(&SubgridParams::default(), 2);
+            subgrid.fill_x(&[0.1, 0.2], 90.0_f64.powi(2), 1.0);
+            subgrid
+        };
+
+        let mut grid = new_grid();
+        grid.fill(
+            0,
+            0.5,
+            0,
+            &Ntuple {
+                x1: 0.1,
+                x2: 0.2,
+                q2: 90.0_f64.powi(2),
+                q2r: 90.0_f64.powi(2),
+                weight: 1.0,
+            },
+        )
+        .unwrap();
+
+        let mut other = new_grid();
+        other.set_subgrid(0, 0, 0, new_subgrid().into());
+
+        assert!(matches!(
+            grid.merge(other),
+            Err(GridMergeError::IncompatibleSubgrids)
+        ));
+
+        // subgrids of the same type are merged
+        let mut grid = new_grid();
+        grid.set_subgrid(0, 0, 0, new_subgrid().into());
+        let mut other = new_grid();
+        other.set_subgrid(0, 0, 0, new_subgrid().into());
+
+        grid.merge(other).unwrap();
+
+        let mut reference: SubgridEnum = new_subgrid().into();
+        reference.scale(2.0);
+
+        assert_eq!(
+            nonzero_values(grid.subgrid(0, 0, 0)),
+            nonzero_values(&reference)
+        );
     }
 
-    /// Sets a remapper. A remapper can change the dimensions and limits of each bin in this grid.
-    /// This is useful because many Monte Carlo integrators and also `PineAPPL` do not support
-    /// multi-dimensional bins. To work around the problem the multi-dimensional bins can be
-    /// projected to one-dimensional bins, and the remapper can be used to restore the multi
-    /// dimensionality. Furthermore, it allows to normalize each bin separately, and independently
-    /// of the bin widths.
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if the number of bins in the grid and in the remapper do not agree.
-    ///
-    /// # Panics
-    ///
-    /// TODO
-    pub fn set_remapper(&mut self, remapper: BinRemapper) -> Result<(), GridSetBinRemapperError> {
-        if remapper.bins() != self.bin_limits.bins() {
-            return Err(GridSetBinRemapperError::BinMismatch {
-                grid_bins: self.bin_limits.bins(),
-                remapper_bins: remapper.bins(),
-            });
-        }
+    #[test]
+    fn grid_read_write() {
+        let mut grid = Grid::new(
+            vec![lumi_entry![2, 2, 1.0; 4, 4, 1.0], lumi_entry![21, 21, 1.0]],
+            vec![Order::new(0, 2, 0, 0, 0)],
+            vec![0.0, 0.5, 1.0],
+            SubgridParams::default(),
+        );
 
-        self.more_members.upgrade();
+        grid.fill_all(
+            0,
+            0.25,
+            &Ntuple {
+                x1: 0.1,
+                x2: 0.2,
+                q2: 90.0_f64.powi(2),
+                q2r: 90.0_f64.powi(2),
+                weight: (),
+            },
+            &[1.0, 2.0],
+        )
+        .unwrap();
 
-        match &mut self.more_members {
-            MoreMembers::V1(_) => unreachable!(),
-            MoreMembers::V2(mmv2) => mmv2.remapper = Some(remapper),
-            MoreMembers::V3(mmv3) => mmv3.remapper = Some(remapper),
-        }
+        let pdf = ClosurePdf::new(2212, |_, x: f64, _| x * (1.0 - x), |q2: f64| 1.0 / q2.ln());
+        let reference = grid.convolute(&[&pdf, &pdf], &[], &[], &[], &[(1.0, 1.0, 1.0)]);
 
-        Ok(())
+        let mut buffer = Vec::new();
+        grid.write(&mut buffer).unwrap();
+
+        assert_eq!(buffer[..8], FILE_MAGIC);
+
+        let result = Grid::read(Cursor::new(&buffer)).unwrap();
+
+        assert_eq!(result.lumi(), grid.lumi());
+        assert_eq!(
+            result.convolute(&[&pdf, &pdf], &[], &[], &[], &[(1.0, 1.0, 1.0)]),
+            reference
+        );
     }
 
-    /// Returns all information about the bins in this grid.
-    #[must_use]
-    pub const fn bin_info(&self) -> BinInfo {
-        BinInfo::new(
-            &self.bin_limits,
-            match &self.more_members {
-                MoreMembers::V1(_) => None,
-                MoreMembers::V2(mmv2) => mmv2.remapper.as_ref(),
-                MoreMembers::V3(mmv3) => mmv3.remapper.as_ref(),
+    #[test]
+    fn grid_read_version0() {
+        // this file was written before the file format was versioned, with two PDG ids per
+        // luminosity function entry and orders without fragmentation-scale logarithms
+        let version0 = &include_bytes!("../tests/data/grid-v0.pineappl.lz4")[..];
+        let result = Grid::read(Cursor::new(version0)).unwrap();
+
+        let mut grid = Grid::new(
+            vec![lumi_entry![2, 2, 1.0; 4, 4, 1.0], lumi_entry![21, 21, 1.0]],
+            vec![
+                Order::new(0, 2, 0, 0, 0),
+                Order::new(1, 2, 0, 0, 0),
+                Order::new(1, 2, 1, 0, 0),
+                Order::new(1, 2, 0, 1, 0),
+            ],
+            vec![0.0, 0.5, 1.0],
+            SubgridParams::default(),
+        );
+        grid.fill_all(
+            0,
+            0.25,
+            &Ntuple {
+                x1: 0.1,
+                x2: 0.2,
+                q2: 90.0_f64.powi(2),
+                q2r: 90.0_f64.powi(2),
+                weight: (),
             },
+            &[1.0, 2.0],
         )
-    }
+        .unwrap();
+        grid.fill_all(
+            1,
+            0.75,
+            &Ntuple {
+                x1: 0.3,
+                x2: 0.05,
+                q2: 20.0_f64.powi(2),
+                q2r: 20.0_f64.powi(2),
+                weight: (),
+            },
+            &[0.5, 4.0],
+        )
+        .unwrap();
 
-    /// Optimize the internal datastructures for space efficiency. This changes all subgrids of
-    /// type `LagrangeSubgrid` to `LagrangeSparseSubgrid`.
-    ///
-    /// # Panics
-    ///
-    /// TODO
-    pub fn optimize(&mut self) {
-        if self
-            .key_values()
-            .map_or(true, |map| map["initial_state_1"] == map["initial_state_2"])
-        {
-            self.symmetrize();
-        }
+        assert_eq!(result.lumi(), grid.lumi());
+        assert_eq!(result.orders(), grid.orders());
+        assert!(result.bin_info() == grid.bin_info());
 
-        for subgrid in self.subgrids.iter_mut() {
-            if subgrid.is_empty() {
-                *subgrid = EmptySubgridV1::default().into();
-            } else {
-                match subgrid {
-                    SubgridEnum::LagrangeSubgridV1(grid) => {
-                        let mut new_subgrid = LagrangeSparseSubgridV1::from(&*grid).into();
-                        mem::swap(subgrid, &mut new_subgrid);
-                    }
-                    SubgridEnum::LagrangeSubgridV2(grid) => {
-                        let mut new_subgrid = ImportOnlySubgridV1::from(&*grid).into();
-                        mem::swap(subgrid, &mut new_subgrid);
-                    }
-                    SubgridEnum::EmptySubgridV1(_)
-                    | SubgridEnum::LagrangeSparseSubgridV1(_)
-                    | SubgridEnum::ImportOnlySubgridV1(_) => {
-                        // nothing to optimize here
-                    }
-                    SubgridEnum::NtupleSubgridV1(_) => todo!(),
-                }
-            }
-        }
+        let pdf = ClosurePdf::new(2212, |_, x: f64, _| x * (1.0 - x), |q2: f64| 1.0 / q2.ln());
+
+        assert_eq!(
+            result.convolute(&[&pdf, &pdf], &[], &[], &[], &[(1.0, 1.0, 1.0)]),
+            grid.convolute(&[&pdf, &pdf], &[], &[], &[], &[(1.0, 1.0, 1.0)])
+        );
     }
 
-    fn symmetrize(&mut self) {
-        let mut indices: Vec<usize> = (0..self.lumi.len()).rev().collect();
-        let mut pairs: Vec<(usize, usize)> = Vec::new();
-        let mut not_symmetrized: Vec<usize> = Vec::new();
+    #[test]
+    fn grid_read_errors() {
+        let grid = Grid::new(
+            vec![lumi_entry![2, 2, 1.0; 4, 4, 1.0], lumi_entry![21, 21, 1.0]],
+            vec![Order::new(0, 2, 0, 0, 0)],
+            vec![0.0, 0.5, 1.0],
+            SubgridParams::default(),
+        );
 
-        'looop: while let Some(index) = indices.pop() {
-            let lumi_entry = &self.lumi[index];
+        let mut buffer = Vec::new();
+        grid.write(&mut buffer).unwrap();
 
-            if *lumi_entry == lumi_entry.transpose() {
-                for order in 0..self.orders.len() {
-                    for bin in 0..self.bin_limits.bins() {
-                        let subgrid = &self.subgrids[[order, bin, index]];
+        let mut unsupported = FILE_MAGIC.to_vec();
+        unsupported.extend(bincode::serialize(&(FILE_VERSION + 1)).unwrap());
 
-                        // check if in all cases the limits are compatible with merging
-                        if !subgrid.is_empty() && (subgrid.x1_grid() != subgrid.x2_grid()) {
-                            not_symmetrized.push(index);
+        assert_eq!(
+            Grid::read(Cursor::new(unsupported))
+                .err()
+                .unwrap()
+                .to_string(),
+            format!("file format version {} is not supported", FILE_VERSION + 1)
+        );
 
-                            continue 'looop;
-                        }
-                    }
-                }
+        let mut truncated = buffer.clone();
+        truncated.truncate(buffer.len() - 1);
 
-                pairs.push((index, index));
-            } else if let Some((j, &other_index)) = indices
-                .iter()
-                .enumerate()
-                .find(|(_, i)| self.lumi[**i] == lumi_entry.transpose())
-            {
-                indices.remove(j);
+        assert_eq!(
+            Grid::read(Cursor::new(truncated))
+                .err()
+                .unwrap()
+                .to_string(),
+            "the file is truncated: the position of the index of the subgrids is incomplete"
+        );
 
-                for order in 0..self.orders.len() {
-                    for bin in 0..self.bin_limits.bins() {
-                        let lhs = &self.subgrids[[order, bin, index]];
-                        let rhs = &self.subgrids[[order, bin, other_index]];
+        // remove the last byte of the last subgrid and everything that follows it
+        let reader = GridReader::new(Cursor::new(&buffer)).unwrap();
+        let mut truncated = buffer.clone();
+        truncated.truncate(usize::try_from(reader.start + reader.offsets[4]).unwrap() - 1);
+
+        assert_eq!(
+            Grid::read(Cursor::new(truncated))
+                .err()
+                .unwrap()
+                .to_string(),
+            "the file is truncated: the subgrid (0, 1, 1) is incomplete"
+        );
 
-                        // check if in all cases the limits are compatible with merging
-                        if !lhs.is_empty()
-                            && !rhs.is_empty()
-                            && ((lhs.x1_grid() != rhs.x2_grid())
-                                || (lhs.x2_grid() != rhs.x1_grid()))
-                        {
-                            not_symmetrized.push(index);
-                            not_symmetrized.push(other_index);
+        let mut truncated = buffer.clone();
+        truncated.truncate(20);
 
-                            continue 'looop;
-                        }
-                    }
-                }
+        assert_eq!(
+            Grid::read(Cursor::new(truncated))
+                .err()
+                .unwrap()
+                .to_string(),
+            "the file is truncated: the metadata is incomplete"
+        );
 
-                pairs.push((index, other_index));
-            } else {
-                not_symmetrized.push(index);
-            }
-        }
+        // flip a bit in the contents of the first subgrid, which follow its size
+        let mut corrupted = buffer.clone();
+        corrupted[usize::try_from(reader.start).unwrap() + 8] ^= 1;
 
-        let i_size = self.orders.len();
-        let j_size = self.bin_limits.bins();
-        let k_size = self.lumi.len();
+        assert_eq!(
+            Grid::read(Cursor::new(corrupted))
+                .err()
+                .unwrap()
+                .to_string(),
+            "the file is corrupted: the checksum of the subgrid (0, 0, 0) does not match"
+        );
+    }
 
-        let subgrids = mem::replace(
-            &mut self.subgrids,
-            Array3::from_shape_vec((0, 0, 0), vec![]).unwrap(),
+    #[test]
+    fn grid_reader() {
+        let mut grid = Grid::new(
+            vec![lumi_entry![2, 2, 1.0; 4, 4, 1.0], lumi_entry![21, 21, 1.0]],
+            vec![Order::new(0, 2, 0, 0, 0)],
+            vec![0.0, 0.5, 1.0],
+            SubgridParams::default(),
         );
-        let mut subgrids = Array3::from_shape_vec(
-            (i_size, j_size, k_size),
-            subgrids.into_raw_vec().into_iter().map(Some).collect(),
+        grid.set_key_value("label", "value");
+
+        grid.fill_all(
+            0,
+            0.75,
+            &Ntuple {
+                x1: 0.1,
+                x2: 0.2,
+                q2: 90.0_f64.powi(2),
+                q2r: 90.0_f64.powi(2),
+                weight: (),
+            },
+            &[1.0, 2.0],
         )
         .unwrap();
 
-        for i in 0..i_size {
-            for j in 0..j_size {
-                for &(k1, k2) in &pairs {
-                    if k1 == k2 {
-                        subgrids[[i, j, k1]].as_mut().unwrap().symmetrize();
-                    } else {
-                        let mut lhs = mem::replace(&mut subgrids[[i, j, k1]], None).unwrap();
-                        let mut rhs = mem::replace(&mut subgrids[[i, j, k2]], None).unwrap();
+        let pdf = ClosurePdf::new(2212, |_, x: f64, _| x * (1.0 - x), |q2: f64| 1.0 / q2.ln());
+        let reference = grid.convolute(&[&pdf, &pdf], &[], &[], &[], &[(1.0, 1.0, 1.0)]);
 
-                        subgrids[[i, j, k1]] = Some(if rhs.is_empty() {
-                            lhs
-                        } else if lhs.is_empty() {
-                            let mut new_lhs = rhs.clone_empty();
-                            new_lhs.merge(&mut rhs, true);
-                            new_lhs
-                        } else {
-                            lhs.merge(&mut rhs, true);
-                            lhs
-                        });
-                    }
-                }
-            }
-        }
+        let mut buffer = Vec::new();
+        grid.write(&mut buffer).unwrap();
 
-        self.subgrids = Array3::from_shape_vec(
-            (i_size, j_size, pairs.len() + not_symmetrized.len()),
-            subgrids.into_raw_vec().into_iter().flatten().collect(),
-        )
-        .unwrap();
+        let metadata = Grid::read_metadata(Cursor::new(&buffer)).unwrap();
 
-        let mut new_lumi_indices: Vec<_> = pairs
-            .iter()
-            .map(|(index, _)| index)
-            .chain(not_symmetrized.iter())
-            .copied()
-            .collect();
-        new_lumi_indices.sort_unstable();
+        assert_eq!(metadata.lumi(), grid.lumi());
+        assert_eq!(metadata.orders(), grid.orders());
+        assert_eq!(metadata.bin_info().bins(), 2);
+        assert_eq!(
+            metadata.key_values().unwrap()["label"],
+            grid.key_values().unwrap()["label"]
+        );
+        assert!(metadata.subgrids.iter().all(Subgrid::is_empty));
 
-        self.lumi = new_lumi_indices
-            .iter()
-            .map(|i| self.lumi[*i].clone())
-            .collect();
-    }
+        let mut reader = GridReader::new(Cursor::new(&buffer)).unwrap();
 
-    /// Upgrades the internal data structures to their latest versions.
-    pub fn upgrade(&mut self) {
-        self.more_members.upgrade();
-    }
+        assert!(reader.subgrid(0, 0, 0).unwrap().is_empty());
+        assert!(!reader.subgrid(0, 1, 1).unwrap().is_empty());
+        assert!(!reader.grid().subgrid(0, 1, 1).is_empty());
+        assert!(reader.grid().subgrid(0, 1, 0).is_empty());
 
-    /// Returns a map with key-value pairs, if there are any stored in this grid.
-    #[must_use]
-    pub const fn key_values(&self) -> Option<&HashMap<String, String>> {
-        match &self.more_members {
-            MoreMembers::V3(mmv3) => Some(&mmv3.key_value_db),
-            MoreMembers::V2(mmv2) => Some(&mmv2.key_value_db),
-            MoreMembers::V1(_) => None,
-        }
-    }
+        let result = reader.into_grid().unwrap();
 
-    /// Returns a map with key-value pairs, if there are any stored in this grid.
-    ///
-    /// # Panics
-    ///
-    /// TODO
-    #[must_use]
-    pub fn key_values_mut(&mut self) -> &mut HashMap<String, String> {
-        self.more_members.upgrade();
+        assert_eq!(
+            result.convolute(&[&pdf, &pdf], &[], &[], &[], &[(1.0, 1.0, 1.0)]),
+            reference
+        );
 
-        match &mut self.more_members {
-            MoreMembers::V1(_) => unreachable!(),
-            MoreMembers::V2(mmv2) => &mut mmv2.key_value_db,
-            MoreMembers::V3(mmv3) => &mut mmv3.key_value_db,
-        }
+        // files without a header are read completely
+        let version0 = &include_bytes!("../tests/data/grid-v0.pineappl.lz4")[..];
+        let metadata = Grid::read_metadata(Cursor::new(version0)).unwrap();
+
+        assert!(!metadata.subgrid(0, 0, 1).is_empty());
     }
 
-    /// Sets a specific key-value pair in this grid.
-    ///
-    /// # Panics
-    ///
-    /// TODO
-    pub fn set_key_value(&mut self, key: &str, value: &str) {
-        self.more_members.upgrade();
+    #[test]
+    fn grid_validate() {
+        let mut grid = Grid::new(
+            vec![lumi_entry![2, 2, 1.0; 4, 4, 1.0], lumi_entry![21, 21, 1.0]],
+            vec![Order::new(0, 2, 0, 0, 0)],
+            vec![0.0, 0.5, 1.0],
+            SubgridParams::default(),
+        );
 
-        let key_value_db = match &mut self.more_members {
-            MoreMembers::V1(_) => unreachable!(),
-            MoreMembers::V2(mmv2) => &mut mmv2.key_value_db,
-            MoreMembers::V3(mmv3) => &mut mmv3.key_value_db,
-        };
+        grid.fill_all(
+            0,
+            0.25,
+            &Ntuple {
+                x1: 0.1,
+                x2: 0.2,
+                q2: 90.0_f64.powi(2),
+                q2r: 90.0_f64.powi(2),
+                weight: (),
+            },
+            &[1.0, 2.0],
+        )
+        .unwrap();
 
-        key_value_db.insert(key.to_owned(), value.to_owned());
-    }
-}
+        assert_eq!(grid.validate(), []);
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::lumi_entry;
+        grid.fill_all(
+            0,
+            0.75,
+            &Ntuple {
+                x1: 0.1,
+                x2: 0.2,
+                q2: 90.0_f64.powi(2),
+                q2r: 90.0_f64.powi(2),
+                weight: (),
+            },
+            &[f64::NAN, 0.0],
+        )
+        .unwrap();
 
-    #[test]
-    fn order_cmp() {
-        let mut orders = vec![
-            Order::new(1, 2, 1, 0),
-            Order::new(1, 2, 0, 1),
-            Order::new(1, 2, 0, 0),
-            Order::new(0, 3, 1, 0),
-            Order::new(0, 3, 0, 1),
-            Order::new(0, 3, 0, 0),
-            Order::new(0, 2, 0, 0),
-        ];
+        let mut array = SparseArray3::new(1, 2, 2);
+        array[[0, 0, 1]] = 1.0;
+        grid.set_subgrid(
+            0,
+            0,
+            1,
+            ImportOnlySubgridV2::new(
+                array,
+                vec![Mu2 { ren: 1e9, fac: 1e9 }],
+                vec![1e-8, 0.5],
+                vec![1e-8, 0.5],
+            )
+            .into(),
+        );
 
-        orders.sort();
+        // the nodes of imported subgrids are not bound to the subgrid parameters
+        assert_eq!(
+            grid.validate(),
+            [GridFinding::NonFiniteValue {
+                order: 0,
+                bin: 1,
+                lumi: 0
+            }]
+        );
 
-        assert_eq!(orders[0], Order::new(0, 2, 0, 0));
-        assert_eq!(orders[1], Order::new(1, 2, 0, 0));
-        assert_eq!(orders[2], Order::new(1, 2, 0, 1));
-        assert_eq!(orders[3], Order::new(1, 2, 1, 0));
-        assert_eq!(orders[4], Order::new(0, 3, 0, 0));
-        assert_eq!(orders[5], Order::new(0, 3, 0, 1));
-        assert_eq!(orders[6], Order::new(0, 3, 1, 0));
-    }
+        let mut subgrid_params = SubgridParams::default();
+        subgrid_params.set_x_min(1e-8);
+        subgrid_params.set_q2_max(1e9);
+
+        let mut subgrid =
+            LagrangeSubgridV2::new(&subgrid_params, &ExtraSubgridParams::from(&subgrid_params));
+        subgrid.fill(&Ntuple {
+            x1: 2e-8,
+            x2: 2e-8,
+            q2: 5e8,
+            q2r: 5e8,
+            weight: 1.0,
+        });
+        grid.set_subgrid(0, 0, 1, subgrid.into());
+
+        grid.set_key_value("initial_state_2", "proton");
+        grid.key_values_mut().remove("initial_state_1");
+        grid.set_key_value("pid_basis", "LHAPDF");
+
+        let findings = grid.validate();
+
+        assert_eq!(findings.len(), 6);
+        assert_eq!(
+            findings[..3],
+            [
+                GridFinding::MissingInitialState(1),
+                GridFinding::InvalidInitialState {
+                    index: 2,
+                    value: "proton".to_owned()
+                },
+                GridFinding::UnknownPidBasis("LHAPDF".to_owned()),
+            ]
+        );
+        assert_eq!(findings[2].severity(), Severity::Error);
+        assert!(matches!(
+            findings[3],
+            GridFinding::NodeOutsideLimits {
+                order: 0,
+                bin: 0,
+                lumi: 1,
+                variable: "x",
+                value
+            } if value < 2e-7
+        ));
+        assert!(matches!(
+            findings[4],
+            GridFinding::NodeOutsideLimits {
+                order: 0,
+                bin: 0,
+                lumi: 1,
+                variable: "q2",
+                value
+            } if value > 1e8
+        ));
+        assert_eq!(
+            findings[5],
+            GridFinding::NonFiniteValue {
+                order: 0,
+                bin: 1,
+                lumi: 0
+            }
+        );
+        assert_eq!(findings[0].severity(), Severity::Warning);
+        assert_eq!(grid.validate()[1].severity(), Severity::Error);
+
+        // n-tuple subgrids are checked as well
+        let mut subgrid = NtupleSubgridV2::new();
+        subgrid.fill(&Ntuple {
+            x1: 0.1,
+            x2: 0.2,
+            q2: 100.0,
+            q2r: 200.0,
+            weight: f64::INFINITY,
+        });
+        grid.set_subgrid(0, 1, 1, subgrid.into());
+
+        assert_eq!(
+            grid.validate()[6],
+            GridFinding::NonFiniteValue {
+                order: 0,
+                bin: 1,
+                lumi: 1
+            }
+        );
 
-    #[test]
-    fn grid_with_subgrid_type() {
-        let subgrid_type = String::from("Idontexist");
-        let result = Grid::with_subgrid_type(
-            vec![],
-            vec![],
-            vec![],
-            SubgridParams::default(),
-            ExtraSubgridParams::default(),
-            &subgrid_type,
+        grid.set_remapper(BinRemapper::new(vec![1.0, 1.0], vec![(0.0, 0.5), (0.5, 1.0)]).unwrap())
+            .unwrap();
+        grid.bin_limits = BinLimits::new(vec![0.0, 0.5, 1.0, 1.5]);
+
+        assert_eq!(
+            grid.validate()[..2],
+            [
+                GridFinding::InconsistentShape {
+                    expected: (1, 3, 2),
+                    found: (1, 2, 2)
+                },
+                GridFinding::RemapperBinMismatch {
+                    grid_bins: 3,
+                    remapper_bins: 2
+                },
+            ]
         );
-
-        matches!(result, Err(UnknownSubgrid(x)) if x == subgrid_type);
     }
 
     #[test]
@@ -1179,7 +5483,7 @@ mod tests {
                 lumi_entry![2, 2, 1.0; 4, 4, 1.0],
                 lumi_entry![1, 1, 1.0; 3, 3, 1.0],
             ],
-            vec![Order::new(0, 2, 0, 0)],
+            vec![Order::new(0, 2, 0, 0, 0)],
             vec![0.0, 0.25, 0.5, 0.75, 1.0],
             SubgridParams::default(),
         );
@@ -1194,7 +5498,7 @@ mod tests {
                 lumi_entry![1, 1, 1.0; 3, 3, 1.0],
                 lumi_entry![2, 2, 1.0; 4, 4, 1.0],
             ],
-            vec![Order::new(1, 2, 0, 0), Order::new(1, 2, 0, 1)],
+            vec![Order::new(1, 2, 0, 0, 0), Order::new(1, 2, 0, 1, 0)],
             vec![0.0, 0.25, 0.5, 0.75, 1.0],
             SubgridParams::default(),
         );
@@ -1214,7 +5518,7 @@ mod tests {
                 lumi_entry![2, 2, 1.0; 4, 4, 1.0],
                 lumi_entry![1, 1, 1.0; 3, 3, 1.0],
             ],
-            vec![Order::new(0, 2, 0, 0)],
+            vec![Order::new(0, 2, 0, 0, 0)],
             vec![0.0, 0.25, 0.5, 0.75, 1.0],
             SubgridParams::default(),
         );
@@ -1229,36 +5533,42 @@ mod tests {
                 lumi_entry![1, 1, 1.0; 3, 3, 1.0],
             ],
             vec![
-                Order::new(1, 2, 0, 0),
-                Order::new(1, 2, 0, 1),
-                Order::new(0, 2, 0, 0),
+                Order::new(1, 2, 0, 0, 0),
+                Order::new(1, 2, 0, 1, 0),
+                Order::new(0, 2, 0, 0, 0),
             ],
             vec![0.0, 0.25, 0.5, 0.75, 1.0],
             SubgridParams::default(),
         );
 
-        other.fill_all(
-            0,
-            0.1,
-            &Ntuple {
-                x1: 0.1,
-                x2: 0.2,
-                q2: 90.0_f64.powi(2),
-                weight: (),
-            },
-            &[1.0, 2.0],
-        );
-        other.fill_all(
-            1,
-            0.1,
-            &Ntuple {
-                x1: 0.1,
-                x2: 0.2,
-                q2: 90.0_f64.powi(2),
-                weight: (),
-            },
-            &[1.0, 2.0],
-        );
+        other
+            .fill_all(
+                0,
+                0.1,
+                &Ntuple {
+                    x1: 0.1,
+                    x2: 0.2,
+                    q2: 90.0_f64.powi(2),
+                    q2r: 90.0_f64.powi(2),
+                    weight: (),
+                },
+                &[1.0, 2.0],
+            )
+            .unwrap();
+        other
+            .fill_all(
+                1,
+                0.1,
+                &Ntuple {
+                    x1: 0.1,
+                    x2: 0.2,
+                    q2: 90.0_f64.powi(2),
+                    q2r: 90.0_f64.powi(2),
+                    weight: (),
+                },
+                &[1.0, 2.0],
+            )
+            .unwrap();
 
         // merge with four non-empty subgrids
         assert!(grid.merge(other).is_ok());
@@ -1275,7 +5585,7 @@ mod tests {
                 lumi_entry![2, 2, 1.0; 4, 4, 1.0],
                 lumi_entry![1, 1, 1.0; 3, 3, 1.0],
             ],
-            vec![Order::new(0, 2, 0, 0)],
+            vec![Order::new(0, 2, 0, 0, 0)],
             vec![0.0, 0.25, 0.5, 0.75, 1.0],
             SubgridParams::default(),
         );
@@ -1286,23 +5596,26 @@ mod tests {
 
         let mut other = Grid::new(
             vec![lumi_entry![22, 22, 1.0], lumi_entry![2, 2, 1.0; 4, 4, 1.0]],
-            vec![Order::new(0, 2, 0, 0)],
+            vec![Order::new(0, 2, 0, 0, 0)],
             vec![0.0, 0.25, 0.5, 0.75, 1.0],
             SubgridParams::default(),
         );
 
         // fill the photon-photon entry
-        other.fill(
-            0,
-            0.1,
-            0,
-            &Ntuple {
-                x1: 0.1,
-                x2: 0.2,
-                q2: 90.0_f64.powi(2),
-                weight: 3.0,
-            },
-        );
+        other
+            .fill(
+                0,
+                0.1,
+                0,
+                &Ntuple {
+                    x1: 0.1,
+                    x2: 0.2,
+                    q2: 90.0_f64.powi(2),
+                    q2r: 90.0_f64.powi(2),
+                    weight: 3.0,
+                },
+            )
+            .unwrap();
 
         assert!(grid.merge(other).is_ok());
 
@@ -1311,6 +5624,165 @@ mod tests {
         assert_eq!(grid.orders().len(), 1);
     }
 
+    #[test]
+    fn grid_add_scaled() {
+        let ntuple = |weight| Ntuple {
+            x1: 0.1,
+            x2: 0.2,
+            q2: 90.0_f64.powi(2),
+            q2r: 90.0_f64.powi(2),
+            weight,
+        };
+        let mut grid = Grid::new(
+            vec![
+                lumi_entry![2, 2, 1.0; 4, 4, 1.0],
+                lumi_entry![1, 1, 1.0; 3, 3, 1.0],
+            ],
+            vec![Order::new(0, 2, 0, 0, 0)],
+            vec![0.0, 0.25, 0.5, 0.75, 1.0],
+            SubgridParams::default(),
+        );
+        grid.fill_all(0, 0.1, &ntuple(()), &[1.0, 2.0]).unwrap();
+        grid.fill_all(0, 0.6, &ntuple(()), &[3.0, 4.0]).unwrap();
+
+        // `other` has a different luminosity function and an additional order
+        let mut other = Grid::new(
+            vec![lumi_entry![22, 22, 1.0], lumi_entry![2, 2, 1.0; 4, 4, 1.0]],
+            vec![Order::new(1, 2, 0, 0, 0), Order::new(0, 2, 0, 0, 0)],
+            vec![0.0, 0.25, 0.5, 0.75, 1.0],
+            SubgridParams::default(),
+        );
+        other.fill_all(0, 0.1, &ntuple(()), &[5.0, 6.0]).unwrap();
+        other.fill_all(1, 0.6, &ntuple(()), &[7.0, 8.0]).unwrap();
+
+        let pdf = ClosurePdf::new(2212, |_, x: f64, _| x * (1.0 - x), |q2: f64| 1.0 / q2.ln());
+        let xi = [(1.0, 1.0, 1.0)];
+        let lhs = grid.convolute(&[&pdf, &pdf], &[], &[], &[], &xi);
+        let rhs = other.convolute(&[&pdf, &pdf], &[], &[], &[], &xi);
+
+        grid.scale(2.0);
+        grid.add_scaled(-0.5, other).unwrap();
+
+        assert_eq!(grid.lumi().len(), 3);
+        assert_eq!(grid.orders().len(), 2);
+
+        let result = grid.convolute(&[&pdf, &pdf], &[], &[], &[], &xi);
+
+        assert!(result.iter().any(|&value| value != 0.0));
+
+        for ((result, lhs), rhs) in result.iter().zip(&lhs).zip(&rhs) {
+            assert!(approx_eq!(
+                f64,
+                *result,
+                2.0_f64.mul_add(*lhs, -0.5 * rhs),
+                ulps = 16
+            ));
+        }
+
+        let other = Grid::new(
+            vec![lumi_entry![2, 2, 1.0; 4, 4, 1.0]],
+            vec![Order::new(0, 2, 0, 0, 0)],
+            vec![0.0, 0.5, 1.0],
+            SubgridParams::default(),
+        );
+
+        assert!(matches!(
+            grid.add_scaled(1.0, other),
+            Err(GridCombineError::DifferentBins)
+        ));
+
+        let mut other = Grid::new(
+            vec![lumi_entry![2, 2, 1.0; 4, 4, 1.0]],
+            vec![Order::new(0, 2, 0, 0, 0)],
+            vec![0.0, 0.25, 0.5, 0.75, 1.0],
+            SubgridParams::default(),
+        );
+        other.set_key_value("initial_state_2", "-2212");
+
+        assert!(matches!(
+            grid.add_scaled(1.0, other),
+            Err(GridCombineError::DifferentInitialStates)
+        ));
+
+        let mut other = Grid::new(
+            vec![lumi_entry![2, 2, 1.0; 4, 4, 1.0]],
+            vec![Order::new(0, 2, 0, 0, 0)],
+            vec![0.0, 0.25, 0.5, 0.75, 1.0],
+            SubgridParams::default(),
+        );
+        other.set_key_value("pid_basis", "EVOL");
+
+        assert!(matches!(
+            grid.add_scaled(1.0, other),
+            Err(GridCombineError::Merge(GridMergeError::DifferentPidBasis))
+        ));
+
+        // import-only subgrids with the same momentum fractions as the interpolating subgrids are
+        // added after converting the interpolating subgrids
+        let mut other = Grid::new(
+            vec![lumi_entry![2, 2, 1.0; 4, 4, 1.0]],
+            vec![Order::new(0, 2, 0, 0, 0)],
+            vec![0.0, 0.25, 0.5, 0.75, 1.0],
+            SubgridParams::default(),
+        );
+        other.fill_all(0, 0.1, &ntuple(()), &[1.0]).unwrap();
+        other.optimize();
+
+        assert!(matches!(
+            other.subgrid(0, 0, 0),
+            SubgridEnum::ImportOnlySubgridV1(_)
+        ));
+
+        let before = grid.convolute(&[&pdf, &pdf], &[], &[], &[], &xi);
+        let added = other.convolute(&[&pdf, &pdf], &[], &[], &[], &xi);
+
+        grid.add_scaled(3.0, other).unwrap();
+
+        let result = grid.convolute(&[&pdf, &pdf], &[], &[], &[], &xi);
+
+        for ((result, before), added) in result.iter().zip(&before).zip(&added) {
+            assert!(approx_eq!(
+                f64,
+                *result,
+                3.0_f64.mul_add(*added, *before),
+                ulps = 16
+            ));
+        }
+
+        // import-only subgrids with other momentum fractions can not be added
+        let mut other = Grid::new(
+            vec![lumi_entry![2, 2, 1.0; 4, 4, 1.0]],
+            vec![Order::new(0, 2, 0, 0, 0)],
+            vec![0.0, 0.25, 0.5, 0.75, 1.0],
+            SubgridParams::default(),
+        );
+        let mut array = SparseArray3::new(1, 2, 2);
+        array[[0, 0, 1]] = 1.0;
+        other.set_subgrid(
+            0,
+            0,
+            0,
+            ImportOnlySubgridV2::new(
+                array,
+                vec![Mu2 {
+                    ren: 100.0,
+                    fac: 200.0,
+                }],
+                vec![0.1, 0.5],
+                vec![0.1, 0.5],
+            )
+            .into(),
+        );
+
+        assert!(matches!(
+            grid.add_scaled(1.0, other),
+            Err(GridCombineError::Merge(
+                GridMergeError::IncompatibleSubgrids
+            ))
+        ));
+        assert_eq!(grid.convolute(&[&pdf, &pdf], &[], &[], &[], &xi), result);
+    }
+
     #[test]
     fn grid_merge_bins() {
         let mut grid = Grid::new(
@@ -1318,7 +5790,7 @@ mod tests {
                 lumi_entry![2, 2, 1.0; 4, 4, 1.0],
                 lumi_entry![1, 1, 1.0; 3, 3, 1.0],
             ],
-            vec![Order::new(0, 2, 0, 0)],
+            vec![Order::new(0, 2, 0, 0, 0)],
             vec![0.0, 0.25, 0.5],
             SubgridParams::default(),
         );
@@ -1333,22 +5805,25 @@ mod tests {
                 lumi_entry![1, 1, 1.0; 3, 3, 1.0],
                 lumi_entry![2, 2, 1.0; 4, 4, 1.0],
             ],
-            vec![Order::new(0, 2, 0, 0)],
+            vec![Order::new(0, 2, 0, 0, 0)],
             vec![0.5, 0.75, 1.0],
             SubgridParams::default(),
         );
 
-        other.fill_all(
-            0,
-            0.1,
-            &Ntuple {
-                x1: 0.1,
-                x2: 0.2,
-                q2: 90.0_f64.powi(2),
-                weight: (),
-            },
-            &[2.0, 3.0],
-        );
+        other
+            .fill_all(
+                0,
+                0.1,
+                &Ntuple {
+                    x1: 0.1,
+                    x2: 0.2,
+                    q2: 90.0_f64.powi(2),
+                    q2r: 90.0_f64.powi(2),
+                    weight: (),
+                },
+                &[2.0, 3.0],
+            )
+            .unwrap();
 
         assert!(grid.merge(other).is_ok());
 
@@ -1356,4 +5831,564 @@ mod tests {
         assert_eq!(grid.lumi().len(), 2);
         assert_eq!(grid.orders().len(), 1);
     }
+
+    #[test]
+    fn grid_merge_bins_with_remapper() {
+        let create_grid = |slice: usize| {
+            let mut grid = Grid::new(
+                vec![lumi_entry![2, 2, 1.0]],
+                vec![Order::new(0, 2, 0, 0, 0)],
+                vec![0.0, 1.0, 2.0],
+                SubgridParams::default(),
+            );
+
+            for bin in 0..2 {
+                let mut array = SparseArray3::new(1, 1, 1);
+                array[[0, 0, 0]] = f64_from_usize(10 * slice + bin + 1);
+
+                grid.set_subgrid(
+                    0,
+                    bin,
+                    0,
+                    ImportOnlySubgridV1::new(array, vec![100.0], vec![0.5], vec![0.5]).into(),
+                );
+            }
+
+            let slice = f64_from_usize(slice);
+
+            grid.set_remapper(
+                BinRemapper::new(
+                    vec![1.0, 0.5],
+                    vec![
+                        (slice, slice + 1.0),
+                        (0.0, 1.0),
+                        (slice, slice + 1.0),
+                        (1.0, 1.5),
+                    ],
+                )
+                .unwrap(),
+            )
+            .unwrap();
+
+            grid
+        };
+
+        let pdf = ClosurePdf::new(2212, |_, x, _| x, |_| 1.0);
+
+        let mut grid = create_grid(0);
+        grid.merge(create_grid(1)).unwrap();
+
+        assert_eq!(grid.bin_info().bins(), 4);
+        assert_eq!(grid.bin_info().dimensions(), 2);
+        assert_eq!(grid.bin_info().left(0), [0.0, 0.0, 1.0, 1.0]);
+        assert_eq!(grid.bin_info().left(1), [0.0, 1.0, 0.0, 1.0]);
+        assert_eq!(grid.bin_info().normalizations(), [1.0, 0.5, 1.0, 0.5]);
+        assert_eq!(grid.bin_info().slices(), [(0, 2), (2, 4)]);
+        assert_eq!(
+            grid.convolute(&[&pdf, &pdf], &[], &[], &[], &[(1.0, 1.0, 1.0)]),
+            [1.0, 4.0, 11.0, 24.0]
+        );
+
+        // grids without remappers have one-dimensional bins
+        let other = Grid::new(
+            vec![lumi_entry![2, 2, 1.0]],
+            vec![Order::new(0, 2, 0, 0, 0)],
+            vec![4.0, 5.0],
+            SubgridParams::default(),
+        );
+
+        assert!(matches!(
+            grid.merge(other),
+            Err(GridMergeError::DifferentBins(
+                MergeBinError::DifferentDimensions { lhs: 2, rhs: 1 }
+            ))
+        ));
+    }
+
+    #[test]
+    fn grid_fill_array() {
+        let new_grid = || {
+            Grid::new(
+                vec![lumi_entry![2, 2, 1.0; 4, 4, 1.0], lumi_entry![21, 21, 1.0]],
+                vec![Order::new(0, 2, 0, 0, 0), Order::new(1, 2, 0, 0, 0)],
+                vec![0.0, 0.25, 0.5, 0.75, 1.0],
+                SubgridParams::default(),
+            )
+        };
+
+        let events = 100;
+        let x1: Vec<_> = (0..events)
+            .map(|i| 0.009_f64.mul_add(f64_from_usize(i), 0.01))
+            .collect();
+        let x2: Vec<_> = x1.iter().rev().copied().collect();
+        let q2: Vec<_> = (0..events).map(|i| 100.0 + f64_from_usize(i * i)).collect();
+        let orders: Vec<_> = (0..events).map(|i| i % 2).collect();
+        // some of the observables are outside of the bin limits
+        let observables: Vec<_> = x1.iter().map(|x| 1.1 * x).collect();
+        let lumis: Vec<_> = (0..events).map(|i| (i / 3) % 2).collect();
+        let weights: Vec<_> = (0..events).map(|i| 1.0 + f64_from_usize(i % 7)).collect();
+
+        let mut reference = new_grid();
+
+        for i in 0..events {
+            reference
+                .fill(
+                    orders[i],
+                    observables[i],
+                    lumis[i],
+                    &Ntuple {
+                        x1: x1[i],
+                        x2: x2[i],
+                        q2: q2[i],
+                        q2r: q2[i],
+                        weight: weights[i],
+                    },
+                )
+                .unwrap();
+        }
+
+        let events: Vec<_> = izip!(&x1, &x2, &q2, &orders, &observables, &lumis, &weights)
+            .map(
+                |(&x1, &x2, &q2, &order, &observable, &lumi, &weight)| Event {
+                    x: vec![x1, x2],
+                    q2,
+                    order,
+                    observable,
+                    lumi,
+                    weight,
+                },
+            )
+            .collect();
+
+        let mut grid = new_grid();
+        grid.fill_array(&events);
+
+        let pdf = ClosurePdf::new(2212, |_, x: f64, _| x * (1.0 - x), |q2: f64| 1.0 / q2.ln());
+        let result = reference.convolute(&[&pdf, &pdf], &[], &[], &[], &[(1.0, 1.0, 1.0)]);
+
+        assert!(result.iter().all(|&value| value != 0.0));
+        assert_eq!(
+            grid.convolute(&[&pdf, &pdf], &[], &[], &[], &[(1.0, 1.0, 1.0)]),
+            result
+        );
+
+        #[cfg(feature = "rayon")]
+        {
+            let mut grid = new_grid();
+            grid.par_fill_array(&events);
+
+            assert_eq!(
+                grid.convolute(&[&pdf, &pdf], &[], &[], &[], &[(1.0, 1.0, 1.0)]),
+                result
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "the grid has 2 convolutions, but an event has 3 momentum fractions")]
+    fn grid_fill_array_too_many_momentum_fractions() {
+        let mut grid = Grid::new(
+            vec![lumi_entry![21, 21, 1.0]],
+            vec![Order::new(0, 2, 0, 0, 0)],
+            vec![0.0, 1.0],
+            SubgridParams::default(),
+        );
+
+        grid.fill_array(&[Event {
+            x: vec![0.1, 0.2, 0.3],
+            q2: 100.0,
+            order: 0,
+            observable: 0.5,
+            lumi: 0,
+            weight: 1.0,
+        }]);
+    }
+
+    #[test]
+    fn concurrent_grid() {
+        let new_grid = || {
+            let mut grid = Grid::new(
+                vec![lumi_entry![2, 2, 1.0; 4, 4, 1.0], lumi_entry![21, 21, 1.0]],
+                vec![Order::new(0, 2, 0, 0, 0), Order::new(1, 2, 0, 0, 0)],
+                vec![0.0, 0.25, 0.5, 0.75, 1.0],
+                SubgridParams::default(),
+            );
+            grid.enable_mc_statistics();
+            grid
+        };
+
+        let events = 100;
+        let x1: Vec<_> = (0..events)
+            .map(|i| 0.009_f64.mul_add(f64_from_usize(i), 0.01))
+            .collect();
+        let x2: Vec<_> = x1.iter().rev().copied().collect();
+        let q2: Vec<_> = (0..events).map(|i| 100.0 + f64_from_usize(i * i)).collect();
+        let orders: Vec<_> = (0..events).map(|i| i % 2).collect();
+        let observables: Vec<_> = x1.iter().map(|x| 1.1 * x).collect();
+        let lumis: Vec<_> = (0..events).map(|i| (i / 3) % 2).collect();
+        let weights: Vec<_> = (0..events).map(|i| 1.0 + f64_from_usize(i % 7)).collect();
+
+        let events: Vec<_> = izip!(&x1, &x2, &q2, &orders, &observables, &lumis, &weights)
+            .map(
+                |(&x1, &x2, &q2, &order, &observable, &lumi, &weight)| Event {
+                    x: vec![x1, x2],
+                    q2,
+                    order,
+                    observable,
+                    lumi,
+                    weight,
+                },
+            )
+            .collect();
+
+        let mut reference = new_grid();
+        reference.fill_array(&events);
+
+        let grid = Arc::new(ConcurrentGrid::from(new_grid()));
+        let threads = 4;
+        let chunk = events.len() / threads;
+
+        let handles: Vec<_> = (0..threads)
+            .map(|thread| {
+                let grid = Arc::clone(&grid);
+                let events = events.clone();
+
+                thread::spawn(move || {
+                    let range = thread * chunk..(thread + 1) * chunk;
+
+                    // half of the threads fill single events, the other half batches
+                    if thread % 2 == 0 {
+                        for event in &events[range] {
+                            grid.fill(
+                                event.order,
+                                event.observable,
+                                event.lumi,
+                                &Ntuple {
+                                    x1: event.x[0],
+                                    x2: event.x[1],
+                                    q2: event.q2,
+                                    q2r: event.q2,
+                                    weight: event.weight,
+                                },
+                            )
+                            .unwrap();
+                        }
+                    } else {
+                        grid.fill_array(&events[range]);
+                    }
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let grid = Arc::try_unwrap(grid).ok().unwrap().into_grid();
+
+        // the weights are integers, so their sums don't depend on the order of the events
+        assert_eq!(grid.mc_statistics(), reference.mc_statistics());
+
+        let pdf = ClosurePdf::new(2212, |_, x: f64, _| x * (1.0 - x), |q2: f64| 1.0 / q2.ln());
+        let result = grid.convolute(&[&pdf, &pdf], &[], &[], &[], &[(1.0, 1.0, 1.0)]);
+        let reference = reference.convolute(&[&pdf, &pdf], &[], &[], &[], &[(1.0, 1.0, 1.0)]);
+
+        assert!(reference.iter().all(|&value| value != 0.0));
+
+        for (result, reference) in result.iter().zip(&reference) {
+            assert!(approx_eq!(f64, *result, *reference, ulps = 16));
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn grid_par_convolute() {
+        let mut grid = Grid::new(
+            vec![lumi_entry![2, 2, 1.0; 4, 4, 1.0], lumi_entry![21, 21, 1.0]],
+            vec![
+                Order::new(0, 2, 0, 0, 0),
+                Order::new(1, 2, 0, 0, 0),
+                Order::new(1, 2, 1, 0, 0),
+            ],
+            vec![0.0, 0.25, 0.5, 0.75, 1.0],
+            SubgridParams::default(),
+        );
+
+        for (index, x1) in [0.1, 0.2, 0.3, 0.4, 0.5].iter().enumerate() {
+            grid.fill_all(
+                index % 3,
+                x1 + 0.1,
+                &Ntuple {
+                    x1: *x1,
+                    x2: 0.2,
+                    q2: 90.0_f64.powi(2),
+                    q2r: 90.0_f64.powi(2),
+                    weight: (),
+                },
+                &[2.0, 3.0],
+            )
+            .unwrap();
+        }
+
+        let pdf = ClosurePdf::new(2212, |_, x: f64, _| x * (1.0 - x), |q2: f64| 1.0 / q2.ln());
+        let xi = [(1.0, 1.0, 1.0), (2.0, 1.0, 1.0), (0.5, 0.5, 1.0)];
+
+        assert_eq!(
+            grid.par_convolute(&[&pdf, &pdf], &[], &[], &[], &xi),
+            grid.convolute(&[&pdf, &pdf], &[], &[], &[], &xi)
+        );
+        assert_eq!(
+            grid.par_convolute(&[&pdf, &pdf], &[], &[3, 1], &[true, false], &xi),
+            grid.convolute(&[&pdf, &pdf], &[], &[3, 1], &[true, false], &xi)
+        );
+
+        // the bins 1 and 2 receive contributions from two orders each
+        let order_mask = [true, true, false];
+        let results = grid.par_convolute(&[&pdf, &pdf], &order_mask, &[1, 2], &[false, true], &xi);
+
+        assert!(results.iter().all(|&result| result != 0.0));
+        assert_eq!(
+            results,
+            grid.convolute(&[&pdf, &pdf], &order_mask, &[1, 2], &[false, true], &xi)
+        );
+    }
+
+    #[test]
+    fn grid_evolve() {
+        let x = vec![0.1, 0.5];
+        let q2 = vec![100.0, 1000.0];
+        let mut grid = Grid::new(
+            vec![lumi_entry![1, 21, 1.0; 21, 1, 0.5]],
+            vec![Order::new(0, 2, 0, 0, 0), Order::new(1, 2, 0, 0, 0)],
+            vec![0.0, 0.5, 1.0],
+            SubgridParams::default(),
+        );
+
+        for (order, bin) in [(0, 0), (1, 0), (1, 1)].iter().copied() {
+            let mut array = SparseArray3::new(2, 2, 2);
+            array[[0, 0, 1]] = 1.0 + f64::from(u8::try_from(order + bin).unwrap());
+            array[[1, 0, 0]] = 2.0;
+            array[[1, 1, 1]] = 0.5;
+
+            grid.set_subgrid(
+                order,
+                bin,
+                0,
+                ImportOnlySubgridV1::new(array, q2.clone(), x.clone(), x.clone()).into(),
+            );
+        }
+
+        // the operator mixes gluons and quarks, and depends on the scale, but not on `x`
+        let mixing = [[1.0, 0.25], [0.5, 1.0]];
+        let pids = vec![21, 1];
+        let operator = EvolutionOperator::new(
+            Array5::from_shape_fn((2, 2, 2, 2, 2), |(ix, pid, ix0, pid0, iq2)| {
+                if ix == ix0 {
+                    mixing[pid][pid0] * (1.0 + f64::from(u8::try_from(iq2).unwrap()))
+                } else {
+                    0.0
+                }
+            }),
+            x.clone(),
+            pids.clone(),
+            x.clone(),
+            pids.clone(),
+            q2.clone(),
+            1.0,
+        )
+        .unwrap();
+
+        let xfx0 = |pid: i32, x: f64, _: f64| x * (1.0 - x) * if pid == 21 { 2.0 } else { 1.0 };
+        let xfx = |pid: i32, x: f64, q2: f64| {
+            let pid = pids.iter().position(|&p| p == pid).unwrap();
+            let factor = if q2 < 500.0 { 1.0 } else { 2.0 };
+
+            factor
+                * pids
+                    .iter()
+                    .enumerate()
+                    .map(|(pid0, &p)| mixing[pid][pid0] * xfx0(p, x, 1.0))
+                    .sum::<f64>()
+        };
+        let alphas = |q2: f64| 1.0 / q2.ln();
+
+        let fk_table = grid
+            .evolve(&operator, &alphas, &|_| 1.0, &[], (1.0, 1.0))
+            .unwrap();
+
+        assert_eq!(fk_table.orders(), [Order::new(0, 0, 0, 0, 0)]);
+        assert_eq!(fk_table.bin_info().bins(), 2);
+        assert_eq!(fk_table.lumi().len(), 4);
+
+        let pdf = ClosurePdf::new(2212, xfx, alphas);
+        let pdf0 = ClosurePdf::new(2212, xfx0, |_| 0.0);
+        let reference = grid.convolute(&[&pdf, &pdf], &[], &[], &[], &[(1.0, 1.0, 1.0)]);
+        let result = fk_table.convolute(&[&pdf0, &pdf0], &[], &[], &[], &[(1.0, 1.0, 1.0)]);
+
+        for (reference, result) in reference.iter().zip(result.iter()) {
+            assert!(approx_eq!(f64, *reference, *result, ulps = 64));
+        }
+
+        // both orders have two powers of the electroweak coupling, which is absorbed as well
+        let alpha = |q2: f64| q2.sqrt() / 1000.0;
+        let fk_table = grid
+            .evolve(&operator, &alphas, &alpha, &[], (1.0, 1.0))
+            .unwrap();
+
+        let pdf = AlphaPdf::new(&pdf, alpha);
+        let reference = grid.convolute(&[&pdf, &pdf], &[], &[], &[], &[(1.0, 1.0, 1.0)]);
+        let result = fk_table.convolute(&[&pdf0, &pdf0], &[], &[], &[], &[(1.0, 1.0, 1.0)]);
+
+        for (reference, result) in reference.iter().zip(result.iter()) {
+            assert!(approx_eq!(f64, *reference, *result, ulps = 64));
+        }
+
+        // the operator doesn't know the photon
+        let mut grid = Grid::new(
+            vec![lumi_entry![22, 21, 1.0]],
+            vec![Order::new(0, 2, 0, 0, 0)],
+            vec![0.0, 0.5, 1.0],
+            SubgridParams::default(),
+        );
+        let mut array = SparseArray3::new(2, 2, 2);
+        array[[0, 0, 0]] = 1.0;
+        grid.set_subgrid(
+            0,
+            0,
+            0,
+            ImportOnlySubgridV1::new(array, q2, x.clone(), x).into(),
+        );
+
+        assert!(matches!(
+            grid.evolve(&operator, &alphas, &|_| 1.0, &[], (1.0, 1.0)),
+            Err(GridEvolveError::MissingParticleId(22))
+        ));
+
+        // only grids with two convolutions can be evolved
+        let grid = Grid::new(
+            vec![LumiEntry::new(vec![(vec![21], 1.0)])],
+            vec![Order::new(0, 0, 0, 0, 0)],
+            vec![0.0, 1.0],
+            SubgridParams::default(),
+        );
+
+        assert!(matches!(
+            grid.evolve(&operator, &alphas, &|_| 1.0, &[], (1.0, 1.0)),
+            Err(GridEvolveError::UnsupportedConvolutions(1))
+        ));
+    }
+
+    #[test]
+    fn grid_mc_statistics() {
+        let mut grid = Grid::new(
+            vec![lumi_entry![2, 2, 1.0; 4, 4, 1.0], lumi_entry![21, 21, 1.0]],
+            vec![Order::new(0, 2, 0, 0, 0)],
+            vec![0.0, 0.5, 1.0],
+            SubgridParams::default(),
+        );
+        let pdf = ClosurePdf::new(2212, |_, x: f64, _| x * (1.0 - x), |q2: f64| 1.0 / q2.ln());
+
+        assert!(grid.mc_statistics().is_none());
+        assert!(grid
+            .convolute_mc_uncertainty(&[&pdf, &pdf], &[], &[], &[])
+            .is_none());
+
+        grid.enable_mc_statistics();
+
+        for &(observable, weight) in &[(0.25, 1.0), (0.25, 3.0), (0.75, 1.0)] {
+            grid.fill(
+                0,
+                observable,
+                0,
+                &Ntuple {
+                    x1: 0.1,
+                    x2: 0.2,
+                    q2: 100.0,
+                    q2r: 100.0,
+                    weight,
+                },
+            )
+            .unwrap();
+        }
+
+        // an event with its counter-event in the second bin, and a third event in the first bin
+        let events: Vec<_> = [
+            (0.3, 0.4, 100.0, 0.75, 2.0),
+            (0.3, 0.4, 100.0, 0.75, -1.5),
+            (0.4, 0.5, 1000.0, 0.25, 1.0),
+        ]
+        .iter()
+        .map(|&(x1, x2, q2, observable, weight)| Event {
+            x: vec![x1, x2],
+            q2,
+            order: 0,
+            observable,
+            lumi: 1,
+            weight,
+        })
+        .collect();
+        grid.fill_group(&events);
+
+        let mc_statistics = grid.mc_statistics().unwrap();
+
+        assert_eq!(mc_statistics.dim(), (1, 2, 2));
+        assert_eq!(mc_statistics[[0, 0, 0]].events(), 2);
+        assert_eq!(mc_statistics[[0, 0, 0]].sum_weights(), 4.0);
+        assert_eq!(mc_statistics[[0, 0, 0]].sum_weights2(), 10.0);
+        assert_eq!(mc_statistics[[0, 0, 1]].events(), 1);
+        assert_eq!(mc_statistics[[0, 0, 1]].sum_weights(), 1.0);
+        assert_eq!(mc_statistics[[0, 1, 0]].events(), 1);
+        assert_eq!(mc_statistics[[0, 1, 1]].events(), 1);
+        assert_eq!(mc_statistics[[0, 1, 1]].sum_weights(), 0.5);
+        assert_eq!(mc_statistics[[0, 1, 1]].sum_weights2(), 0.25);
+
+        // the statistics are written and read back
+        let mut buffer = Vec::new();
+        grid.write(&mut buffer).unwrap();
+        let read = Grid::read(Cursor::new(&buffer)).unwrap();
+        assert_eq!(read.mc_statistics(), grid.mc_statistics());
+
+        let results = grid.convolute(&[&pdf, &pdf], &[], &[], &[], &[(1.0, 1.0, 1.0)]);
+        let channels: Vec<_> = [[true, false], [false, true]]
+            .iter()
+            .map(|mask| grid.convolute(&[&pdf, &pdf], &[], &[], mask, &[(1.0, 1.0, 1.0)]))
+            .collect();
+        let uncertainties = grid
+            .convolute_mc_uncertainty(&[&pdf, &pdf], &[], &[], &[])
+            .unwrap();
+
+        assert!(results.iter().all(|&result| result != 0.0));
+        // in the second bin the relative uncertainties of both subgrids are one
+        assert!(approx_eq!(
+            f64,
+            uncertainties[1],
+            channels[0][1].hypot(channels[1][1]),
+            ulps = 4
+        ));
+        assert!(approx_eq!(
+            f64,
+            uncertainties[0],
+            (10.0_f64.sqrt() / 4.0 * channels[0][0]).hypot(channels[1][0]),
+            ulps = 4
+        ));
+
+        grid.scale(2.0);
+        grid.merge_bins(0..2).unwrap();
+
+        let mc_statistics = grid.mc_statistics().unwrap();
+
+        assert_eq!(mc_statistics.dim(), (1, 1, 2));
+        assert_eq!(mc_statistics[[0, 0, 0]].events(), 3);
+        assert_eq!(mc_statistics[[0, 0, 0]].sum_weights(), 10.0);
+        assert_eq!(mc_statistics[[0, 0, 1]].events(), 2);
+        assert_eq!(mc_statistics[[0, 0, 1]].sum_weights(), 3.0);
+        assert_eq!(mc_statistics[[0, 0, 1]].sum_weights2(), 5.0);
+
+        grid.delete_channels(&[0]);
+
+        let mc_statistics = grid.mc_statistics().unwrap();
+
+        assert_eq!(mc_statistics.dim(), (1, 1, 1));
+        assert_eq!(mc_statistics[[0, 0, 0]].sum_weights(), 3.0);
+    }
 }
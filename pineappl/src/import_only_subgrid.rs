@@ -1,9 +1,10 @@
 //! TODO
 
+use super::empty_subgrid::EmptySubgridV1;
 use super::grid::Ntuple;
 use super::lagrange_subgrid::{self, LagrangeSubgridV2};
 use super::sparse_array3::SparseArray3;
-use super::subgrid::{Subgrid, SubgridEnum};
+use super::subgrid::{Mu2, Subgrid, SubgridEnum};
 use either::Either;
 use ndarray::Axis;
 use serde::{Deserialize, Serialize};
@@ -48,16 +49,15 @@ impl ImportOnlySubgridV1 {
 impl Subgrid for ImportOnlySubgridV1 {
     fn convolute(
         &self,
-        _: &[f64],
-        _: &[f64],
-        _: &[f64],
-        lumi: Either<&dyn Fn(usize, usize, usize) -> f64, &dyn Fn(f64, f64, f64) -> f64>,
+        _: &[&[f64]],
+        _: &[Mu2],
+        lumi: Either<&dyn Fn(&[usize], usize) -> f64, &dyn Fn(&[f64], Mu2) -> f64>,
     ) -> f64 {
         let lumi = lumi.left().unwrap();
 
         self.array
             .indexed_iter()
-            .map(|((iq2, ix1, ix2), sigma)| sigma * lumi(ix1, ix2, iq2))
+            .map(|((iq2, ix1, ix2), sigma)| sigma * lumi(&[ix1, ix2], iq2))
             .sum()
     }
 
@@ -184,8 +184,218 @@ impl Subgrid for ImportOnlySubgridV1 {
         .into()
     }
 
-    fn iter(&self) -> Box<dyn Iterator<Item = ((usize, usize, usize), &f64)> + '_> {
-        Box::new(self.array.indexed_iter())
+    fn iter(&self) -> Box<dyn Iterator<Item = (Vec<usize>, &f64)> + '_> {
+        Box::new(
+            self.array
+                .indexed_iter()
+                .map(|((i, j, k), value)| (vec![i, j, k], value)),
+        )
+    }
+}
+
+/// Import-only sparse subgrid whose scale nodes each consist of a renormalization and a
+/// factorization scale, which do not need to be the same.
+#[derive(Deserialize, Serialize)]
+pub struct ImportOnlySubgridV2 {
+    array: SparseArray3<f64>,
+    mu2_grid: Vec<Mu2>,
+    x1_grid: Vec<f64>,
+    x2_grid: Vec<f64>,
+}
+
+impl ImportOnlySubgridV2 {
+    /// Constructor.
+    #[must_use]
+    pub fn new(
+        array: SparseArray3<f64>,
+        mu2_grid: Vec<Mu2>,
+        x1_grid: Vec<f64>,
+        x2_grid: Vec<f64>,
+    ) -> Self {
+        Self {
+            array,
+            mu2_grid,
+            x1_grid,
+            x2_grid,
+        }
+    }
+
+    /// Return the array containing the numerical values of the grid.
+    pub fn array_mut(&mut self) -> &mut SparseArray3<f64> {
+        &mut self.array
+    }
+}
+
+impl Subgrid for ImportOnlySubgridV2 {
+    fn convolute(
+        &self,
+        _: &[&[f64]],
+        _: &[Mu2],
+        lumi: Either<&dyn Fn(&[usize], usize) -> f64, &dyn Fn(&[f64], Mu2) -> f64>,
+    ) -> f64 {
+        let lumi = lumi.left().unwrap();
+
+        self.array
+            .indexed_iter()
+            .map(|((imu2, ix1, ix2), sigma)| sigma * lumi(&[ix1, ix2], imu2))
+            .sum()
+    }
+
+    fn fill(&mut self, _: &Ntuple<f64>) {
+        panic!("this grid doesn't support the fill operation");
+    }
+
+    fn q2_grid(&self) -> Cow<[f64]> {
+        self.mu2_grid.iter().map(|mu2| mu2.fac).collect()
+    }
+
+    fn mu2_grid(&self) -> Cow<[Mu2]> {
+        Cow::Borrowed(&self.mu2_grid)
+    }
+
+    fn x1_grid(&self) -> Cow<[f64]> {
+        Cow::Borrowed(&self.x1_grid)
+    }
+
+    fn x2_grid(&self) -> Cow<[f64]> {
+        Cow::Borrowed(&self.x2_grid)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.array.is_empty()
+    }
+
+    fn merge(&mut self, other: &mut SubgridEnum, transpose: bool) {
+        let mut other_grid = match mem::replace(other, EmptySubgridV1::default().into()) {
+            SubgridEnum::ImportOnlySubgridV2(other_grid) => other_grid,
+            SubgridEnum::ImportOnlySubgridV1(other_grid) => other_grid.into(),
+            other => {
+                assert!(
+                    other.is_empty(),
+                    "an `ImportOnlySubgridV2` can only be merged with import-only subgrids"
+                );
+                return;
+            }
+        };
+
+        if self.array.is_empty() && !transpose {
+            mem::swap(self, &mut other_grid);
+        } else {
+            // TODO: the general case isn't implemented
+            assert!(self.x1_grid() == other_grid.x1_grid());
+            assert!(self.x2_grid() == other_grid.x2_grid());
+
+            if self.mu2_grid == other_grid.mu2_grid {
+                if transpose {
+                    for ((i, k, j), value) in other_grid.array.indexed_iter() {
+                        self.array[[i, j, k]] += value;
+                    }
+                } else {
+                    for ((i, j, k), value) in other_grid.array.indexed_iter() {
+                        self.array[[i, j, k]] += value;
+                    }
+                }
+            } else {
+                for (other_index, mu2) in other_grid.mu2_grid.iter().enumerate() {
+                    let index = match self.mu2_grid.binary_search_by(|val| {
+                        (val.fac, val.ren).partial_cmp(&(mu2.fac, mu2.ren)).unwrap()
+                    }) {
+                        Ok(index) => index,
+                        Err(index) => {
+                            self.mu2_grid.insert(index, *mu2);
+                            self.array.increase_x_at(index);
+                            index
+                        }
+                    };
+
+                    for ((_, j, k), value) in other_grid
+                        .array
+                        .indexed_iter()
+                        .filter(|&((i, _, _), _)| i == other_index)
+                    {
+                        let (j, k) = if transpose { (k, j) } else { (j, k) };
+                        self.array[[index, j, k]] += value;
+                    }
+                }
+            }
+        }
+    }
+
+    fn scale(&mut self, factor: f64) {
+        if factor == 0.0 {
+            self.array.clear();
+        } else {
+            self.array.iter_mut().for_each(|x| *x *= factor);
+        }
+    }
+
+    fn q2_slice(&self) -> Range<usize> {
+        self.array.x_range()
+    }
+
+    fn fill_q2_slice(&self, q2_slice: usize, grid: &mut [f64]) {
+        let x1: Vec<_> = self.x1_grid.iter().map(|&x| 1.0 / x).collect();
+        let x2: Vec<_> = self.x2_grid.iter().map(|&x| 1.0 / x).collect();
+
+        for value in grid.iter_mut() {
+            *value = 0.0;
+        }
+
+        for ((_, ix1, ix2), value) in self
+            .array
+            .indexed_iter()
+            .filter(|((imu2, _, _), _)| *imu2 == q2_slice)
+        {
+            grid[ix1 * self.x2_grid.len() + ix2] = value * x1[ix1] * x2[ix2];
+        }
+    }
+
+    fn symmetrize(&mut self) {
+        let mut new_array =
+            SparseArray3::new(self.mu2_grid.len(), self.x1_grid.len(), self.x2_grid.len());
+
+        for ((i, j, k), &sigma) in self.array.indexed_iter().filter(|((_, j, k), _)| k >= j) {
+            new_array[[i, j, k]] = sigma;
+        }
+        // do not change the diagonal entries (k==j)
+        for ((i, j, k), &sigma) in self.array.indexed_iter().filter(|((_, j, k), _)| k < j) {
+            new_array[[i, k, j]] += sigma;
+        }
+
+        mem::swap(&mut self.array, &mut new_array);
+    }
+
+    fn clone_empty(&self) -> SubgridEnum {
+        Self {
+            array: SparseArray3::new(self.mu2_grid.len(), self.x1_grid.len(), self.x2_grid.len()),
+            mu2_grid: self.mu2_grid.clone(),
+            x1_grid: self.x1_grid.clone(),
+            x2_grid: self.x2_grid.clone(),
+        }
+        .into()
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = (Vec<usize>, &f64)> + '_> {
+        Box::new(
+            self.array
+                .indexed_iter()
+                .map(|((i, j, k), value)| (vec![i, j, k], value)),
+        )
+    }
+}
+
+impl From<ImportOnlySubgridV1> for ImportOnlySubgridV2 {
+    fn from(subgrid: ImportOnlySubgridV1) -> Self {
+        Self {
+            array: subgrid.array,
+            mu2_grid: subgrid
+                .q2_grid
+                .iter()
+                .map(|&q2| Mu2 { ren: q2, fac: q2 })
+                .collect(),
+            x1_grid: subgrid.x1_grid,
+            x2_grid: subgrid.x2_grid,
+        }
     }
 }
 
@@ -243,3 +453,104 @@ impl From<&LagrangeSubgridV2> for ImportOnlySubgridV1 {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn import_only_v1(q2: f64, value: f64) -> ImportOnlySubgridV1 {
+        let mut array = SparseArray3::new(1, 2, 2);
+        array[[0, 0, 1]] = value;
+
+        ImportOnlySubgridV1::new(array, vec![q2], vec![0.1, 0.5], vec![0.1, 0.5])
+    }
+
+    #[test]
+    fn merge_v2_with_v1() {
+        let mut array = SparseArray3::new(1, 2, 2);
+        array[[0, 1, 1]] = 1.0;
+
+        let mut grid = ImportOnlySubgridV2::new(
+            array,
+            vec![Mu2 {
+                ren: 200.0,
+                fac: 100.0,
+            }],
+            vec![0.1, 0.5],
+            vec![0.1, 0.5],
+        );
+
+        grid.merge(&mut import_only_v1(100.0, 2.0).into(), false);
+
+        // the fixed scale of the merged subgrid is a separate node
+        assert_eq!(
+            grid.mu2_grid().as_ref(),
+            [
+                Mu2 {
+                    ren: 100.0,
+                    fac: 100.0
+                },
+                Mu2 {
+                    ren: 200.0,
+                    fac: 100.0
+                }
+            ]
+        );
+        assert_eq!(
+            grid.iter().collect::<Vec<_>>(),
+            [(vec![0, 0, 1], &2.0), (vec![1, 1, 1], &1.0)]
+        );
+
+        // merging an empty subgrid does nothing
+        grid.merge(&mut EmptySubgridV1::default().into(), false);
+
+        assert_eq!(grid.iter().count(), 2);
+    }
+
+    #[test]
+    fn merge_into_empty_v2() {
+        let mut grid = ImportOnlySubgridV2::new(
+            SparseArray3::new(0, 0, 0),
+            Vec::new(),
+            Vec::new(),
+            Vec::new(),
+        );
+
+        grid.merge(&mut import_only_v1(50.0, 3.0).into(), false);
+
+        // the nodes are taken from the merged subgrid together with its values
+        assert_eq!(
+            grid.mu2_grid().as_ref(),
+            [Mu2 {
+                ren: 50.0,
+                fac: 50.0
+            }]
+        );
+        assert_eq!(grid.x1_grid().as_ref(), [0.1, 0.5]);
+        assert_eq!(grid.x2_grid().as_ref(), [0.1, 0.5]);
+        assert_eq!(grid.iter().collect::<Vec<_>>(), [(vec![0, 0, 1], &3.0)]);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "an `ImportOnlySubgridV2` can only be merged with import-only subgrids"
+    )]
+    fn merge_v2_with_ntuples() {
+        let mut grid = ImportOnlySubgridV2::new(
+            SparseArray3::new(0, 0, 0),
+            Vec::new(),
+            Vec::new(),
+            Vec::new(),
+        );
+        let mut other = crate::ntuple_subgrid::NtupleSubgridV1::new();
+        other.fill(&Ntuple {
+            x1: 0.1,
+            x2: 0.2,
+            q2: 100.0,
+            q2r: 100.0,
+            weight: 1.0,
+        });
+
+        grid.merge(&mut other.into(), false);
+    }
+}
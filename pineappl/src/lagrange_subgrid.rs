@@ -3,10 +3,11 @@
 use super::convert::{f64_from_usize, usize_from_f64};
 use super::grid::Ntuple;
 use super::sparse_array3::SparseArray3;
-use super::subgrid::{ExtraSubgridParams, Subgrid, SubgridEnum, SubgridParams};
+use super::subgrid::{ExtraSubgridParams, Mu2, Subgrid, SubgridEnum, SubgridParams};
 use arrayvec::ArrayVec;
 use either::Either;
-use ndarray::Array3;
+use itertools::Itertools;
+use ndarray::{Array3, ArrayD, Axis, Dimension, Slice};
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
 use std::iter;
@@ -45,6 +46,13 @@ fn fq2(tau: f64) -> f64 {
     0.0625 * tau.exp().exp()
 }
 
+fn check_single_scale(ntuple: &Ntuple<f64>) {
+    assert_eq!(
+        ntuple.q2, ntuple.q2r,
+        "interpolating subgrids do not support different renormalization and factorization scales"
+    );
+}
+
 fn fi(i: usize, n: usize, u: f64) -> f64 {
     let mut factorials = 1;
     let mut product = 1.0;
@@ -133,10 +141,9 @@ impl LagrangeSubgridV1 {
 impl Subgrid for LagrangeSubgridV1 {
     fn convolute(
         &self,
-        x1: &[f64],
-        x2: &[f64],
-        _: &[f64],
-        lumi: Either<&dyn Fn(usize, usize, usize) -> f64, &dyn Fn(f64, f64, f64) -> f64>,
+        x: &[&[f64]],
+        _: &[Mu2],
+        lumi: Either<&dyn Fn(&[usize], usize) -> f64, &dyn Fn(&[f64], Mu2) -> f64>,
     ) -> f64 {
         self.grid.as_ref().map_or(0.0, |grid| {
             let lumi = lumi.left().unwrap();
@@ -146,9 +153,9 @@ impl Subgrid for LagrangeSubgridV1 {
                     if sigma == 0.0 {
                         0.0
                     } else {
-                        let mut value = sigma * lumi(ix1, ix2, q2 + self.itaumin);
+                        let mut value = sigma * lumi(&[ix1, ix2], q2 + self.itaumin);
                         if self.reweight {
-                            value *= weightfun(x1[ix1]) * weightfun(x2[ix2]);
+                            value *= weightfun(x[0][ix1]) * weightfun(x[1][ix2]);
                         }
                         value
                     }
@@ -158,6 +165,8 @@ impl Subgrid for LagrangeSubgridV1 {
     }
 
     fn fill(&mut self, ntuple: &Ntuple<f64>) {
+        check_single_scale(ntuple);
+
         let y1 = fy(ntuple.x1);
         let y2 = fy(ntuple.x2);
         let tau = ftau(ntuple.q2);
@@ -353,13 +362,14 @@ impl Subgrid for LagrangeSubgridV1 {
         .into()
     }
 
-    fn iter(&self) -> Box<dyn Iterator<Item = ((usize, usize, usize), &f64)> + '_> {
+    fn iter(&self) -> Box<dyn Iterator<Item = (Vec<usize>, &f64)> + '_> {
         self.grid.as_ref().map_or_else(
-            || Box::new(iter::empty()) as Box<dyn Iterator<Item = ((usize, usize, usize), &f64)>>,
+            || Box::new(iter::empty()) as Box<dyn Iterator<Item = (Vec<usize>, &f64)>>,
             |grid| {
                 Box::new(
                     grid.indexed_iter()
-                        .filter(|&((_, _, _), value)| *value != 0.0),
+                        .filter(|&(_, value)| *value != 0.0)
+                        .map(|((i, j, k), value)| (vec![i, j, k], value)),
                 )
             },
         )
@@ -415,6 +425,21 @@ impl LagrangeSubgridV2 {
         }
     }
 
+    /// Returns `true` if the momentum fractions and the scale of `ntuple` are inside the ranges of
+    /// the interpolation grids. N-tuples outside of these ranges are discarded by `Subgrid::fill`.
+    pub(crate) fn contains(&self, ntuple: &Ntuple<f64>) -> bool {
+        let y1 = fy(ntuple.x1);
+        let y2 = fy(ntuple.x2);
+        let tau = ftau(ntuple.q2);
+
+        (y2 >= self.y2min)
+            && (y2 <= self.y2max)
+            && (y1 >= self.y1min)
+            && (y1 <= self.y1max)
+            && (tau >= self.taumin)
+            && (tau <= self.taumax)
+    }
+
     fn deltay1(&self) -> f64 {
         (self.y1max - self.y1min) / f64_from_usize(self.ny1 - 1)
     }
@@ -473,10 +498,9 @@ impl LagrangeSubgridV2 {
 impl Subgrid for LagrangeSubgridV2 {
     fn convolute(
         &self,
-        x1: &[f64],
-        x2: &[f64],
-        _: &[f64],
-        lumi: Either<&dyn Fn(usize, usize, usize) -> f64, &dyn Fn(f64, f64, f64) -> f64>,
+        x: &[&[f64]],
+        _: &[Mu2],
+        lumi: Either<&dyn Fn(&[usize], usize) -> f64, &dyn Fn(&[f64], Mu2) -> f64>,
     ) -> f64 {
         self.grid.as_ref().map_or(0.0, |grid| {
             let lumi = lumi.left().unwrap();
@@ -486,12 +510,12 @@ impl Subgrid for LagrangeSubgridV2 {
                     if sigma == 0.0 {
                         0.0
                     } else {
-                        let mut value = sigma * lumi(ix1, ix2, q2 + self.itaumin);
+                        let mut value = sigma * lumi(&[ix1, ix2], q2 + self.itaumin);
                         if self.reweight1 {
-                            value *= weightfun(x1[ix1]);
+                            value *= weightfun(x[0][ix1]);
                         }
                         if self.reweight2 {
-                            value *= weightfun(x2[ix2]);
+                            value *= weightfun(x[1][ix2]);
                         }
                         value
                     }
@@ -501,6 +525,8 @@ impl Subgrid for LagrangeSubgridV2 {
     }
 
     fn fill(&mut self, ntuple: &Ntuple<f64>) {
+        check_single_scale(ntuple);
+
         let y1 = fy(ntuple.x1);
         let y2 = fy(ntuple.x2);
         let tau = ftau(ntuple.q2);
@@ -511,13 +537,7 @@ impl Subgrid for LagrangeSubgridV2 {
             self.static_q2 = -1.0;
         }
 
-        if (y2 < self.y2min)
-            || (y2 > self.y2max)
-            || (y1 < self.y1min)
-            || (y1 > self.y1max)
-            || (tau < self.taumin)
-            || (tau > self.taumax)
-        {
+        if !self.contains(ntuple) {
             return;
         }
 
@@ -721,13 +741,14 @@ impl Subgrid for LagrangeSubgridV2 {
         .into()
     }
 
-    fn iter(&self) -> Box<dyn Iterator<Item = ((usize, usize, usize), &f64)> + '_> {
+    fn iter(&self) -> Box<dyn Iterator<Item = (Vec<usize>, &f64)> + '_> {
         self.grid.as_ref().map_or_else(
-            || Box::new(iter::empty()) as Box<dyn Iterator<Item = ((usize, usize, usize), &f64)>>,
+            || Box::new(iter::empty()) as Box<dyn Iterator<Item = (Vec<usize>, &f64)>>,
             |grid| {
                 Box::new(
                     grid.indexed_iter()
-                        .filter(|&((_, _, _), value)| *value != 0.0),
+                        .filter(|&(_, value)| *value != 0.0)
+                        .map(|((i, j, k), value)| (vec![i, j, k], value)),
                 )
             },
         )
@@ -792,19 +813,18 @@ impl LagrangeSparseSubgridV1 {
 impl Subgrid for LagrangeSparseSubgridV1 {
     fn convolute(
         &self,
-        x1: &[f64],
-        x2: &[f64],
-        _: &[f64],
-        lumi: Either<&dyn Fn(usize, usize, usize) -> f64, &dyn Fn(f64, f64, f64) -> f64>,
+        x: &[&[f64]],
+        _: &[Mu2],
+        lumi: Either<&dyn Fn(&[usize], usize) -> f64, &dyn Fn(&[f64], Mu2) -> f64>,
     ) -> f64 {
         let lumi = lumi.left().unwrap();
 
         self.array
             .indexed_iter()
             .map(|((iq2, ix1, ix2), sigma)| {
-                let mut value = sigma * lumi(ix1, ix2, iq2);
+                let mut value = sigma * lumi(&[ix1, ix2], iq2);
                 if self.reweight {
-                    value *= weightfun(x1[ix1]) * weightfun(x2[ix2]);
+                    value *= weightfun(x[0][ix1]) * weightfun(x[1][ix2]);
                 }
                 value
             })
@@ -812,6 +832,8 @@ impl Subgrid for LagrangeSparseSubgridV1 {
     }
 
     fn fill(&mut self, ntuple: &Ntuple<f64>) {
+        check_single_scale(ntuple);
+
         let y1 = fy(ntuple.x1);
         let y2 = fy(ntuple.x2);
         let tau = ftau(ntuple.q2);
@@ -971,8 +993,12 @@ impl Subgrid for LagrangeSparseSubgridV1 {
         .into()
     }
 
-    fn iter(&self) -> Box<dyn Iterator<Item = ((usize, usize, usize), &f64)> + '_> {
-        Box::new(self.array.indexed_iter())
+    fn iter(&self) -> Box<dyn Iterator<Item = (Vec<usize>, &f64)> + '_> {
+        Box::new(
+            self.array
+                .indexed_iter()
+                .map(|((i, j, k), value)| (vec![i, j, k], value)),
+        )
     }
 }
 
@@ -996,6 +1022,410 @@ impl From<&LagrangeSubgridV1> for LagrangeSparseSubgridV1 {
     }
 }
 
+/// Subgrid which uses Lagrange-interpolation for an arbitrary number of convolutions. Each
+/// convolution has its own `x` axis, all of which share the interpolation parameters.
+#[derive(Deserialize, Serialize)]
+pub struct LagrangeSubgridV3 {
+    grid: Option<ArrayD<f64>>,
+    ntau: usize,
+    ny: Vec<usize>,
+    yorder: Vec<usize>,
+    tauorder: usize,
+    itaumin: usize,
+    itaumax: usize,
+    reweight: Vec<bool>,
+    ymin: Vec<f64>,
+    ymax: Vec<f64>,
+    taumin: f64,
+    taumax: f64,
+    static_q2: f64,
+}
+
+impl LagrangeSubgridV3 {
+    /// Constructor. The subgrid has `convolutions` axes for the momentum fractions, which all use
+    /// the `x` parameters of `subgrid_params`.
+    #[must_use]
+    pub fn new(subgrid_params: &SubgridParams, convolutions: usize) -> Self {
+        Self {
+            grid: None,
+            ntau: subgrid_params.q2_bins(),
+            ny: vec![subgrid_params.x_bins(); convolutions],
+            yorder: vec![subgrid_params.x_order(); convolutions],
+            tauorder: subgrid_params.q2_order(),
+            itaumin: 0,
+            itaumax: 0,
+            reweight: vec![subgrid_params.reweight(); convolutions],
+            ymin: vec![fy(subgrid_params.x_max()); convolutions],
+            ymax: vec![fy(subgrid_params.x_min()); convolutions],
+            taumin: ftau(subgrid_params.q2_min()),
+            taumax: ftau(subgrid_params.q2_max()),
+            static_q2: 0.0,
+        }
+    }
+
+    fn deltay(&self, index: usize) -> f64 {
+        (self.ymax[index] - self.ymin[index]) / f64_from_usize(self.ny[index] - 1)
+    }
+
+    fn deltatau(&self) -> f64 {
+        (self.taumax - self.taumin) / f64_from_usize(self.ntau - 1)
+    }
+
+    fn gety(&self, index: usize, iy: usize) -> f64 {
+        if self.ymin[index] == self.ymax[index] {
+            debug_assert_eq!(iy, 0);
+            self.ymin[index]
+        } else {
+            f64_from_usize(iy).mul_add(self.deltay(index), self.ymin[index])
+        }
+    }
+
+    fn gettau(&self, iy: usize) -> f64 {
+        if self.taumin == self.taumax {
+            debug_assert_eq!(iy, 0);
+            self.taumin
+        } else {
+            f64_from_usize(iy).mul_add(self.deltatau(), self.taumin)
+        }
+    }
+
+    fn x_grid(&self, index: usize) -> Cow<[f64]> {
+        if index < self.ny.len() {
+            (0..self.ny[index])
+                .map(|iy| fx(self.gety(index, iy)))
+                .collect()
+        } else {
+            Cow::Borrowed(&[])
+        }
+    }
+
+    fn shape(&self, ntau: usize) -> Vec<usize> {
+        iter::once(ntau).chain(self.ny.iter().copied()).collect()
+    }
+
+    #[allow(clippy::float_cmp)]
+    fn has_same_nodes(&self, other: &Self) -> bool {
+        (self.ntau == other.ntau)
+            && (self.ny == other.ny)
+            && (self.reweight == other.reweight)
+            && (self.ymin == other.ymin)
+            && (self.ymax == other.ymax)
+            && (self.taumin == other.taumin)
+            && (self.taumax == other.taumax)
+    }
+
+    fn increase_tau(&mut self, new_itaumin: usize, new_itaumax: usize) {
+        let min_diff = self.itaumin - new_itaumin;
+        let old_grid = self.grid.take().unwrap();
+
+        let mut new_grid = ArrayD::zeros(self.shape(new_itaumax - new_itaumin));
+        new_grid
+            .slice_axis_mut(
+                Axis(0),
+                Slice::from(min_diff..min_diff + old_grid.len_of(Axis(0))),
+            )
+            .assign(&old_grid);
+
+        self.itaumin = new_itaumin;
+        self.itaumax = new_itaumax;
+        self.grid = Some(new_grid);
+    }
+}
+
+impl Subgrid for LagrangeSubgridV3 {
+    fn convolute(
+        &self,
+        x: &[&[f64]],
+        _: &[Mu2],
+        lumi: Either<&dyn Fn(&[usize], usize) -> f64, &dyn Fn(&[f64], Mu2) -> f64>,
+    ) -> f64 {
+        self.grid.as_ref().map_or(0.0, |grid| {
+            let lumi = lumi.left().unwrap();
+
+            grid.indexed_iter()
+                .map(|(index, &sigma)| {
+                    if sigma == 0.0 {
+                        0.0
+                    } else {
+                        let index = index.slice();
+                        let mut value = sigma * lumi(&index[1..], index[0] + self.itaumin);
+
+                        for ((&ix, x), &reweight) in index[1..].iter().zip(x).zip(&self.reweight) {
+                            if reweight {
+                                value *= weightfun(x[ix]);
+                            }
+                        }
+
+                        value
+                    }
+                })
+                .sum()
+        })
+    }
+
+    fn fill(&mut self, ntuple: &Ntuple<f64>) {
+        check_single_scale(ntuple);
+        self.fill_x(&[ntuple.x1, ntuple.x2], ntuple.q2, ntuple.weight);
+    }
+
+    fn fill_x(&mut self, x: &[f64], q2: f64, weight: f64) {
+        assert_eq!(x.len(), self.ny.len());
+
+        let y: Vec<_> = x.iter().map(|&x| fy(x)).collect();
+        let tau = ftau(q2);
+
+        if self.static_q2 == 0.0 {
+            self.static_q2 = q2;
+        } else if (self.static_q2 != -1.0) && (self.static_q2 != q2) {
+            self.static_q2 = -1.0;
+        }
+
+        if y.iter()
+            .enumerate()
+            .any(|(i, &y)| (y < self.ymin[i]) || (y > self.ymax[i]))
+            || (tau < self.taumin)
+            || (tau > self.taumax)
+        {
+            return;
+        }
+
+        let k: Vec<_> = y
+            .iter()
+            .enumerate()
+            .map(|(i, &y)| {
+                usize_from_f64(
+                    (y - self.ymin[i]) / self.deltay(i) - f64_from_usize(self.yorder[i] / 2),
+                )
+                .min(self.ny[i] - 1 - self.yorder[i])
+            })
+            .collect();
+        let fi_y: Vec<ArrayVec<[_; 8]>> = y
+            .iter()
+            .enumerate()
+            .map(|(i, &y)| {
+                let u_y = (y - self.gety(i, k[i])) / self.deltay(i);
+                (0..=self.yorder[i])
+                    .map(|j| fi(j, self.yorder[i], u_y))
+                    .collect()
+            })
+            .collect();
+
+        let ktau = usize_from_f64(
+            (tau - self.taumin) / self.deltatau() - f64_from_usize(self.tauorder / 2),
+        )
+        .min(self.ntau - 1 - self.tauorder);
+
+        let u_tau = (tau - self.gettau(ktau)) / self.deltatau();
+
+        let factor = 1.0
+            / x.iter()
+                .zip(&self.reweight)
+                .map(|(&x, &reweight)| if reweight { weightfun(x) } else { 1.0 })
+                .product::<f64>();
+
+        let size = self.tauorder + 1;
+
+        if self.grid.is_none() {
+            self.itaumin = ktau;
+            self.itaumax = ktau + size;
+        } else if ktau < self.itaumin || ktau + size > self.itaumax {
+            self.increase_tau(self.itaumin.min(ktau), self.itaumax.max(ktau + size));
+        }
+
+        let shape = self.shape(size);
+        let itaumin = self.itaumin;
+        let grid = self.grid.get_or_insert_with(|| ArrayD::zeros(shape));
+        let mut index = vec![0; x.len() + 1];
+
+        for itau in 0..=self.tauorder {
+            let fi_tau = fi(itau, self.tauorder, u_tau);
+
+            index[0] = ktau + itau - itaumin;
+
+            for iy in self
+                .yorder
+                .iter()
+                .map(|&order| 0..=order)
+                .multi_cartesian_product()
+            {
+                let mut fillweight = factor;
+
+                for (i, &j) in iy.iter().enumerate() {
+                    fillweight *= fi_y[i][j];
+                    index[i + 1] = k[i] + j;
+                }
+
+                grid[index.as_slice()] += fillweight * fi_tau * weight;
+            }
+        }
+    }
+
+    fn q2_grid(&self) -> Cow<[f64]> {
+        (0..self.ntau).map(|itau| fq2(self.gettau(itau))).collect()
+    }
+
+    fn x1_grid(&self) -> Cow<[f64]> {
+        self.x_grid(0)
+    }
+
+    fn x2_grid(&self) -> Cow<[f64]> {
+        self.x_grid(1)
+    }
+
+    fn x_grids(&self) -> Vec<Cow<[f64]>> {
+        (0..self.ny.len()).map(|index| self.x_grid(index)).collect()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.grid.is_none()
+    }
+
+    fn merge(&mut self, other: &mut SubgridEnum, transpose: bool) {
+        if let SubgridEnum::LagrangeSubgridV3(other_grid) = other {
+            assert!(
+                self.has_same_nodes(other_grid),
+                "the merged subgrid uses different interpolation parameters"
+            );
+
+            if let Some(mut other_grid_grid) = other_grid.grid.take() {
+                if transpose {
+                    other_grid_grid.swap_axes(1, 2);
+                }
+
+                if self.grid.is_some() {
+                    let new_itaumin = self.itaumin.min(other_grid.itaumin);
+                    let new_itaumax = self.itaumax.max(other_grid.itaumax);
+
+                    if (self.itaumin != new_itaumin) || (self.itaumax != new_itaumax) {
+                        self.increase_tau(new_itaumin, new_itaumax);
+                    }
+
+                    if (other_grid.static_q2 == -1.0) || (self.static_q2 != other_grid.static_q2) {
+                        self.static_q2 = -1.0;
+                    }
+
+                    let offset = other_grid.itaumin - self.itaumin;
+                    let mut self_grid = self.grid.as_mut().unwrap().slice_axis_mut(
+                        Axis(0),
+                        Slice::from(offset..offset + other_grid_grid.len_of(Axis(0))),
+                    );
+                    self_grid += &other_grid_grid;
+                } else {
+                    self.grid = Some(other_grid_grid);
+                    self.itaumin = other_grid.itaumin;
+                    self.itaumax = other_grid.itaumax;
+                    self.static_q2 = other_grid.static_q2;
+                }
+            }
+        } else {
+            assert!(
+                other.is_empty(),
+                "a `LagrangeSubgridV3` can only be merged with subgrids of the same type"
+            );
+        }
+    }
+
+    fn scale(&mut self, factor: f64) {
+        if factor == 0.0 {
+            self.grid = None;
+        } else if let Some(self_grid) = &mut self.grid {
+            self_grid.iter_mut().for_each(|x| *x *= factor);
+        }
+    }
+
+    fn q2_slice(&self) -> Range<usize> {
+        self.itaumin..self.itaumax
+    }
+
+    fn fill_q2_slice(&self, q2_slice: usize, grid: &mut [f64]) {
+        if let Some(self_grid) = &self.grid {
+            let factors: Vec<Vec<_>> = self
+                .x_grids()
+                .iter()
+                .zip(&self.reweight)
+                .map(|(x, &reweight)| {
+                    x.iter()
+                        .map(|&x| if reweight { weightfun(x) } else { 1.0 } / x)
+                        .collect()
+                })
+                .collect();
+
+            for ((index, &value), result) in self_grid
+                .index_axis(Axis(0), q2_slice - self.itaumin)
+                .indexed_iter()
+                .zip(grid.iter_mut())
+            {
+                *result = index
+                    .slice()
+                    .iter()
+                    .zip(&factors)
+                    .fold(value, |value, (&ix, factors)| value * factors[ix]);
+            }
+        } else {
+            for value in grid.iter_mut() {
+                *value = 0.0;
+            }
+        }
+    }
+
+    fn symmetrize(&mut self) {
+        if let Some(grid) = self.grid.as_mut() {
+            if grid.ndim() < 3 {
+                return;
+            }
+
+            let indices: Vec<_> = grid
+                .indexed_iter()
+                .filter(|(index, &value)| (index[1] > index[2]) && (value != 0.0))
+                .map(|(index, _)| index)
+                .collect();
+
+            for index in indices {
+                let mut transposed = index.clone();
+                transposed[1] = index[2];
+                transposed[2] = index[1];
+
+                let value = mem::replace(&mut grid[index], 0.0);
+                grid[transposed] += value;
+            }
+        }
+    }
+
+    fn clone_empty(&self) -> SubgridEnum {
+        Self {
+            grid: None,
+            ntau: self.ntau,
+            ny: self.ny.clone(),
+            yorder: self.yorder.clone(),
+            tauorder: self.tauorder,
+            itaumin: 0,
+            itaumax: 0,
+            reweight: self.reweight.clone(),
+            ymin: self.ymin.clone(),
+            ymax: self.ymax.clone(),
+            taumin: self.taumin,
+            taumax: self.taumax,
+            static_q2: 0.0,
+        }
+        .into()
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = (Vec<usize>, &f64)> + '_> {
+        self.grid.as_ref().map_or_else(
+            || Box::new(iter::empty()) as Box<dyn Iterator<Item = (Vec<usize>, &f64)>>,
+            |grid| {
+                Box::new(grid.indexed_iter().filter(|&(_, value)| *value != 0.0).map(
+                    move |(index, value)| {
+                        let mut index = index.slice().to_vec();
+                        index[0] += self.itaumin;
+                        (index, value)
+                    },
+                ))
+            },
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1006,24 +1436,28 @@ mod tests {
             x1: 0.1,
             x2: 0.2,
             q2: 90.0_f64.powi(2),
+            q2r: 90.0_f64.powi(2),
             weight: 1.0,
         });
         grid.fill(&Ntuple {
             x1: 0.9,
             x2: 0.1,
             q2: 90.0_f64.powi(2),
+            q2r: 90.0_f64.powi(2),
             weight: 1.0,
         });
         grid.fill(&Ntuple {
             x1: 0.009,
             x2: 0.01,
             q2: 90.0_f64.powi(2),
+            q2r: 90.0_f64.powi(2),
             weight: 1.0,
         });
         grid.fill(&Ntuple {
             x1: 0.009,
             x2: 0.5,
             q2: 90.0_f64.powi(2),
+            q2r: 90.0_f64.powi(2),
             weight: 1.0,
         });
 
@@ -1032,13 +1466,12 @@ mod tests {
 
         let x1 = grid.x1_grid();
         let x2 = grid.x2_grid();
-        let q2 = grid.q2_grid();
+        let mu2 = grid.mu2_grid();
 
         let reference = grid.convolute(
-            &x1,
-            &x2,
-            &q2,
-            Either::Left(&|ix1, ix2, _| 1.0 / (x1[ix1] * x2[ix2])),
+            &[&x1, &x2],
+            &mu2,
+            Either::Left(&|ix, _| 1.0 / (x1[ix[0]] * x2[ix[1]])),
         );
 
         let mut buffer = vec![0.0; x1.len() * x2.len()];
@@ -1062,24 +1495,28 @@ mod tests {
             x1: 0.1,
             x2: 0.2,
             q2: 90.0_f64.powi(2),
+            q2r: 90.0_f64.powi(2),
             weight: 1.0,
         });
         grid1.fill(&Ntuple {
             x1: 0.9,
             x2: 0.1,
             q2: 90.0_f64.powi(2),
+            q2r: 90.0_f64.powi(2),
             weight: 1.0,
         });
         grid1.fill(&Ntuple {
             x1: 0.009,
             x2: 0.01,
             q2: 90.0_f64.powi(2),
+            q2r: 90.0_f64.powi(2),
             weight: 1.0,
         });
         grid1.fill(&Ntuple {
             x1: 0.009,
             x2: 0.5,
             q2: 90.0_f64.powi(2),
+            q2r: 90.0_f64.powi(2),
             weight: 1.0,
         });
 
@@ -1088,13 +1525,12 @@ mod tests {
 
         let x1 = grid1.x1_grid().into_owned();
         let x2 = grid1.x2_grid().into_owned();
-        let q2 = grid1.q2_grid().into_owned();
+        let mu2 = grid1.mu2_grid().into_owned();
 
         let reference = grid1.convolute(
-            &x1,
-            &x2,
-            &q2,
-            Either::Left(&|ix1, ix2, _| 1.0 / (x1[ix1] * x2[ix2])),
+            &[&x1, &x2],
+            &mu2,
+            Either::Left(&|ix, _| 1.0 / (x1[ix[0]] * x2[ix[1]])),
         );
 
         // merge filled grid into empty one
@@ -1102,10 +1538,9 @@ mod tests {
         assert!(!grid2.is_empty());
 
         let merged = grid2.convolute(
-            &x1,
-            &x2,
-            &q2,
-            Either::Left(&|ix1, ix2, _| 1.0 / (x1[ix1] * x2[ix2])),
+            &[&x1, &x2],
+            &mu2,
+            Either::Left(&|ix, _| 1.0 / (x1[ix[0]] * x2[ix[1]])),
         );
 
         assert!(approx_eq!(f64, reference, merged, ulps = 8));
@@ -1114,34 +1549,37 @@ mod tests {
             x1: 0.1,
             x2: 0.2,
             q2: 90.0_f64.powi(2),
+            q2r: 90.0_f64.powi(2),
             weight: 1.0,
         });
         grid3.fill(&Ntuple {
             x1: 0.9,
             x2: 0.1,
             q2: 90.0_f64.powi(2),
+            q2r: 90.0_f64.powi(2),
             weight: 1.0,
         });
         grid3.fill(&Ntuple {
             x1: 0.009,
             x2: 0.01,
             q2: 90.0_f64.powi(2),
+            q2r: 90.0_f64.powi(2),
             weight: 1.0,
         });
         grid3.fill(&Ntuple {
             x1: 0.009,
             x2: 0.5,
             q2: 90.0_f64.powi(2),
+            q2r: 90.0_f64.powi(2),
             weight: 1.0,
         });
 
         grid2.merge(&mut grid3.into(), false);
 
         let merged = grid2.convolute(
-            &x1,
-            &x2,
-            &q2,
-            Either::Left(&|ix1, ix2, _| 1.0 / (x1[ix1] * x2[ix2])),
+            &[&x1, &x2],
+            &mu2,
+            Either::Left(&|ix, _| 1.0 / (x1[ix[0]] * x2[ix[1]])),
         );
 
         assert!(approx_eq!(f64, 2.0 * reference, merged, ulps = 8));
@@ -1155,6 +1593,7 @@ mod tests {
             x1: 0.5,
             x2: 0.5,
             q2: 2e+8,
+            q2r: 2e+8,
             weight: 1.0,
         });
         // q2 is too small
@@ -1162,6 +1601,7 @@ mod tests {
             x1: 0.5,
             x2: 0.5,
             q2: 5e+1,
+            q2r: 5e+1,
             weight: 1.0,
         });
         // x1 is too large
@@ -1169,6 +1609,7 @@ mod tests {
             x1: 1.1,
             x2: 0.5,
             q2: 1e+3,
+            q2r: 1e+3,
             weight: 1.0,
         });
         // x1 is too small
@@ -1176,6 +1617,7 @@ mod tests {
             x1: 0.5,
             x2: 1e-7,
             q2: 1e+3,
+            q2r: 1e+3,
             weight: 1.0,
         });
         // x1 is too large
@@ -1183,6 +1625,7 @@ mod tests {
             x1: 0.5,
             x2: 1.1,
             q2: 1e+3,
+            q2r: 1e+3,
             weight: 1.0,
         });
         // x1 is too small
@@ -1190,14 +1633,15 @@ mod tests {
             x1: 1e-7,
             x2: 0.5,
             q2: 1e+3,
+            q2r: 1e+3,
             weight: 1.0,
         });
 
         let x1 = grid.x1_grid();
         let x2 = grid.x2_grid();
-        let q2 = grid.q2_grid();
+        let mu2 = grid.mu2_grid();
 
-        let result = grid.convolute(&x1, &x2, &q2, Either::Left(&|_, _, _| 1.0));
+        let result = grid.convolute(&[&x1, &x2], &mu2, Either::Left(&|_, _| 1.0));
 
         assert_eq!(result, 0.0);
     }
@@ -1215,6 +1659,11 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn q2_slice_v3() {
+        test_q2_slice_methods(LagrangeSubgridV3::new(&SubgridParams::default(), 2));
+    }
+
     #[test]
     fn sparse_q2_slice() {
         test_q2_slice_methods(LagrangeSparseSubgridV1::new(&SubgridParams::default()));
@@ -1228,11 +1677,11 @@ mod tests {
         let sparse = LagrangeSparseSubgridV1::from(&dense);
         assert!(sparse.is_empty());
 
-        let q2 = dense.q2_grid().into_owned();
+        let mu2 = dense.mu2_grid().into_owned();
         let x1 = dense.x1_grid().into_owned();
         let x2 = dense.x2_grid().into_owned();
 
-        assert_eq!(q2, *sparse.q2_grid());
+        assert_eq!(mu2, *sparse.mu2_grid());
         assert_eq!(x1, *sparse.x1_grid());
         assert_eq!(x2, *sparse.x2_grid());
 
@@ -1241,12 +1690,14 @@ mod tests {
             x1: 0.1,
             x2: 0.2,
             q2: 90.0_f64.powi(2),
+            q2r: 90.0_f64.powi(2),
             weight: 1.0,
         });
         dense.fill(&Ntuple {
             x1: 0.9,
             x2: 0.1,
             q2: 90.0_f64.powi(2),
+            q2r: 90.0_f64.powi(2),
             weight: 1.0,
         });
 
@@ -1256,16 +1707,14 @@ mod tests {
         assert!(!sparse.is_empty());
 
         let reference = dense.convolute(
-            &x1,
-            &x2,
-            &q2,
-            Either::Left(&|ix1, ix2, _| 1.0 / (x1[ix1] * x2[ix2])),
+            &[&x1, &x2],
+            &mu2,
+            Either::Left(&|ix, _| 1.0 / (x1[ix[0]] * x2[ix[1]])),
         );
         let converted = sparse.convolute(
-            &x1,
-            &x2,
-            &q2,
-            Either::Left(&|ix1, ix2, _| 1.0 / (x1[ix1] * x2[ix2])),
+            &[&x1, &x2],
+            &mu2,
+            Either::Left(&|ix, _| 1.0 / (x1[ix[0]] * x2[ix[1]])),
         );
 
         assert!(approx_eq!(f64, reference, converted, ulps = 8));
@@ -1299,6 +1748,23 @@ mod tests {
         two.merge(&mut one.into(), false);
     }
 
+    #[test]
+    #[should_panic(
+        expected = "interpolating subgrids do not support different renormalization and factorization scales"
+    )]
+    fn fill_separate_scales_v2() {
+        let mut grid =
+            LagrangeSubgridV2::new(&SubgridParams::default(), &ExtraSubgridParams::default());
+
+        grid.fill(&Ntuple {
+            x1: 0.1,
+            x2: 0.2,
+            q2: 90.0_f64.powi(2),
+            q2r: 45.0_f64.powi(2),
+            weight: 1.0,
+        });
+    }
+
     #[test]
     #[should_panic]
     fn merge_dense_v2_with_sparse() {
@@ -1346,6 +1812,15 @@ mod tests {
         );
     }
 
+    #[test]
+    fn merge_dense_v3() {
+        test_merge_method(
+            LagrangeSubgridV3::new(&SubgridParams::default(), 2),
+            LagrangeSubgridV3::new(&SubgridParams::default(), 2),
+            LagrangeSubgridV3::new(&SubgridParams::default(), 2),
+        );
+    }
+
     #[test]
     fn merge_sparse() {
         test_merge_method(
@@ -1372,4 +1847,102 @@ mod tests {
     fn empty_sparse() {
         test_empty_subgrid(LagrangeSparseSubgridV1::new(&SubgridParams::default()));
     }
+
+    #[test]
+    fn empty_v3() {
+        test_empty_subgrid(LagrangeSubgridV3::new(&SubgridParams::default(), 2));
+    }
+
+    #[test]
+    fn v3_agrees_with_v2() {
+        let mut v2 =
+            LagrangeSubgridV2::new(&SubgridParams::default(), &ExtraSubgridParams::default());
+        let mut v3 = LagrangeSubgridV3::new(&SubgridParams::default(), 2);
+
+        for &(x1, x2, q2) in &[
+            (0.1, 0.2, 90.0_f64.powi(2)),
+            (0.9, 0.1, 90.0_f64.powi(2)),
+            (0.009, 0.01, 120.0_f64.powi(2)),
+            (0.009, 0.5, 50.0_f64.powi(2)),
+        ] {
+            let ntuple = Ntuple {
+                x1,
+                x2,
+                q2,
+                q2r: q2,
+                weight: 1.0,
+            };
+
+            v2.fill(&ntuple);
+            v3.fill(&ntuple);
+        }
+
+        assert_eq!(v2.x1_grid(), v3.x1_grid());
+        assert_eq!(v2.x2_grid(), v3.x2_grid());
+        assert_eq!(v2.q2_grid(), v3.q2_grid());
+        assert_eq!(v2.q2_slice(), v3.q2_slice());
+
+        // `LagrangeSubgridV2::iter` returns indices relative to the first non-empty q2 slice
+        let itaumin = v2.q2_slice().start;
+        assert_eq!(
+            v2.iter()
+                .map(|(mut index, value)| {
+                    index[0] += itaumin;
+                    (index, value)
+                })
+                .collect::<Vec<_>>(),
+            v3.iter().collect::<Vec<_>>()
+        );
+
+        let x1 = v2.x1_grid().into_owned();
+        let x2 = v2.x2_grid().into_owned();
+        let mu2 = v2.mu2_grid().into_owned();
+        let lumi = |ix: &[usize], iq2: usize| x1[ix[0]] * x2[ix[1]] * mu2[iq2].fac.ln();
+
+        assert!(approx_eq!(
+            f64,
+            v2.convolute(&[&x1, &x2], &mu2, Either::Left(&lumi)),
+            v3.convolute(&[&x1, &x2], &mu2, Either::Left(&lumi)),
+            ulps = 8
+        ));
+    }
+
+    #[test]
+    fn three_convolutions() {
+        let mut subgrid_params = SubgridParams::default();
+        subgrid_params.set_reweight(false);
+
+        let mut grid = LagrangeSubgridV3::new(&subgrid_params, 3);
+
+        assert!(grid.is_empty());
+        assert_eq!(grid.x_grids().len(), 3);
+        assert!(grid.x_grids().iter().all(|x| *x == grid.x1_grid()));
+
+        grid.fill_x(&[0.1, 0.2, 0.3], 90.0_f64.powi(2), 1.0);
+        grid.fill_x(&[0.01, 0.5, 0.05], 120.0_f64.powi(2), 2.0);
+
+        // events outside the interpolation range are skipped
+        grid.fill_x(&[0.1, 1e-7, 0.3], 90.0_f64.powi(2), 1.0);
+
+        assert!(!grid.is_empty());
+
+        // each index consists of the index of the scale and one index per convolution
+        assert!(grid.iter().all(|(index, _)| index.len() == 4));
+
+        let x = grid.x1_grid().into_owned();
+        let mu2 = grid.mu2_grid().into_owned();
+
+        // without reweighting the interpolation weights of each event add up to one
+        let result = grid.convolute(&[&x, &x, &x], &mu2, Either::Left(&|_, _| 1.0));
+
+        assert!(approx_eq!(f64, result, 3.0, ulps = 64));
+
+        let mut other = grid.clone_empty();
+        other.merge(&mut grid.into(), false);
+        other.scale(2.0);
+
+        let result = other.convolute(&[&x, &x, &x], &mu2, Either::Left(&|_, _| 1.0));
+
+        assert!(approx_eq!(f64, result, 6.0, ulps = 64));
+    }
 }
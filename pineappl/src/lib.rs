@@ -7,11 +7,19 @@
 mod convert;
 
 pub mod bin;
+pub mod composite;
 pub mod empty_subgrid;
+pub mod evolution;
+pub mod fastnlo;
+pub mod fk_table;
 pub mod grid;
 pub mod import_only_subgrid;
 pub mod lagrange_subgrid;
 pub mod lumi;
 pub mod ntuple_subgrid;
+pub mod pdf;
+pub mod pids;
 pub mod sparse_array3;
+mod splitting;
+pub mod statistics;
 pub mod subgrid;
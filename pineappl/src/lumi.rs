@@ -3,17 +3,18 @@
 use serde::{Deserialize, Serialize};
 
 /// This structure represens an entry of a luminosity function. Each entry consists of a tuple,
-/// which contains, in the following order, the PDG id of the first incoming parton, then the PDG
-/// id of the second parton, and finally a numerical factor that will multiply the result for this
-/// specific combination.
+/// which contains, in the following order, the PDG ids of the partons taking part in each
+/// convolution, and a numerical factor that will multiply the result for this specific
+/// combination. For hadron-hadron collisions there are two convolutions, and the first and second
+/// PDG id belong to the first and second incoming parton, respectively.
 #[derive(Clone, Debug, Deserialize, PartialEq, PartialOrd, Serialize)]
 pub struct LumiEntry {
-    entry: Vec<(i32, i32, f64)>,
+    entry: Vec<(Vec<i32>, f64)>,
 }
 
 impl LumiEntry {
-    /// Constructor for `LumiEntry`. Note that `entry` must be non-empty, otherwise this function
-    /// panics.
+    /// Constructor for `LumiEntry`. Note that `entry` must be non-empty and that each of its
+    /// elements must have the same number of PDG ids, otherwise this function panics.
     ///
     /// # Examples
     ///
@@ -22,13 +23,24 @@ impl LumiEntry {
     /// ```rust
     /// use pineappl::lumi::LumiEntry;
     ///
-    /// let entry1 = LumiEntry::new(vec![(2, 2, 1.0), (4, 4, 1.0)]);
-    /// let entry2 = LumiEntry::new(vec![(4, 4, 1.0), (2, 2, 1.0)]);
+    /// let entry1 = LumiEntry::new(vec![(vec![2, 2], 1.0), (vec![4, 4], 1.0)]);
+    /// let entry2 = LumiEntry::new(vec![(vec![4, 4], 1.0), (vec![2, 2], 1.0)]);
     ///
     /// // checks that the ordering doesn't matter
     /// assert_eq!(entry1, entry2);
     /// ```
     ///
+    /// The number of PDG ids determines the number of convolutions, which is three for a
+    /// hadron-hadron collision with an identified hadron in the final state:
+    ///
+    /// ```rust
+    /// use pineappl::lumi::LumiEntry;
+    ///
+    /// let entry = LumiEntry::new(vec![(vec![2, 21, 2], 1.0)]);
+    ///
+    /// assert_eq!(entry.convolutions(), 3);
+    /// ```
+    ///
     /// # Panics
     ///
     /// Creating an entry with content panics:
@@ -38,13 +50,24 @@ impl LumiEntry {
     ///
     /// let _ = LumiEntry::new(vec![]);
     /// ```
+    ///
+    /// Creating an entry with different numbers of PDG ids panics as well:
+    ///
+    /// ```rust,should_panic
+    /// use pineappl::lumi::LumiEntry;
+    ///
+    /// let _ = LumiEntry::new(vec![(vec![2, 2], 1.0), (vec![2], 1.0)]);
+    /// ```
     #[must_use]
-    pub fn new(mut entry: Vec<(i32, i32, f64)>) -> Self {
+    pub fn new(mut entry: Vec<(Vec<i32>, f64)>) -> Self {
         assert!(!entry.is_empty());
 
+        let convolutions = entry[0].0.len();
+        assert!(entry.iter().all(|(pids, _)| pids.len() == convolutions));
+
         // sort `entry` because the ordering doesn't matter and because it makes it easier to
         // compare `LumiEntry` objects with each other
-        entry.sort_by(|x, y| (x.0, x.1, x.2).partial_cmp(&(y.0, y.1, y.2)).unwrap());
+        entry.sort_by(|x, y| x.partial_cmp(y).unwrap());
 
         Self { entry }
     }
@@ -75,21 +98,106 @@ impl LumiEntry {
     ///
     /// let entry = lumi_entry![4, 4, 1.0; 2, 2, 1.0];
     ///
-    /// assert_eq!(entry.entry(), [(2, 2, 1.0), (4, 4, 1.0)]);
+    /// assert_eq!(entry.entry(), [(vec![2, 2], 1.0), (vec![4, 4], 1.0)]);
     /// ```
     #[must_use]
-    pub fn entry(&self) -> &[(i32, i32, f64)] {
+    pub fn entry(&self) -> &[(Vec<i32>, f64)] {
         &self.entry
     }
 
-    /// Creates a new object with the initial states transposed.
+    /// Returns the number of convolutions, which is the number of PDG ids in each element of this
+    /// entry.
+    #[must_use]
+    pub fn convolutions(&self) -> usize {
+        self.entry[0].0.len()
+    }
+
+    /// Creates a new object with the first two initial states transposed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this entry has less than two convolutions.
     #[must_use]
     pub fn transpose(&self) -> Self {
-        Self::new(self.entry.iter().map(|(a, b, c)| (*b, *a, *c)).collect())
+        Self::new(
+            self.entry
+                .iter()
+                .map(|(pids, factor)| {
+                    let mut pids = pids.clone();
+                    pids.swap(0, 1);
+                    (pids, *factor)
+                })
+                .collect(),
+        )
+    }
+
+    /// Creates a new object in which each PDG id is replaced by the linear combination of
+    /// particle ids returned by `translator`. Products of the linear combinations are expanded,
+    /// identical combinations of particle ids are summed, and combinations whose factors cancel
+    /// are removed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use pineappl::lumi_entry;
+    /// use pineappl::pids;
+    ///
+    /// let entry = lumi_entry![2, 21, 1.0; -2, 21, 1.0];
+    /// let translated = entry.translate(&pids::pdg_mc_ids_to_evol);
+    ///
+    /// // up and anti-up quarks only contribute to the plus sector
+    /// assert_eq!(translated.entry().len(), 6);
+    /// assert_eq!(translated.entry()[0], (vec![100, 21], 1.0 / 6.0));
+    /// ```
+    #[must_use]
+    pub fn translate(&self, translator: &dyn Fn(i32) -> Vec<(i32, f64)>) -> Self {
+        let mut result: Vec<(Vec<i32>, f64)> = Vec::new();
+
+        for (pids, factor) in &self.entry {
+            let mut products = vec![(Vec::with_capacity(pids.len()), *factor)];
+
+            for &pid in pids {
+                let combination = translator(pid);
+
+                products = products
+                    .into_iter()
+                    .flat_map(|(ids, factor)| {
+                        combination.iter().map(move |&(id, other_factor)| {
+                            let mut ids = ids.clone();
+                            ids.push(id);
+                            (ids, factor * other_factor)
+                        })
+                    })
+                    .collect();
+            }
+
+            for (pids, factor) in products {
+                if let Some((_, other_factor)) = result
+                    .iter_mut()
+                    .find(|(other_pids, _)| *other_pids == pids)
+                {
+                    *other_factor += factor;
+                } else {
+                    result.push((pids, factor));
+                }
+            }
+        }
+
+        // remove the combinations whose factors cancelled up to rounding errors
+        let max = result
+            .iter()
+            .map(|(_, factor)| factor.abs())
+            .fold(0.0, f64::max);
+
+        if max > 0.0 {
+            result.retain(|&(_, factor)| factor.abs() > 64.0 * f64::EPSILON * max);
+        }
+
+        Self::new(result)
     }
 }
 
-/// Helper macro to quickly generate a LumiEntry at compile time.
+/// Helper macro to quickly generate a LumiEntry with two convolutions at compile time.
 ///
 /// # Examples
 ///
@@ -106,6 +214,6 @@ impl LumiEntry {
 #[macro_export]
 macro_rules! lumi_entry {
     ($a:expr, $b:expr, $factor:expr $(; $c:expr, $d:expr, $fac:expr)*) => {
-        $crate::lumi::LumiEntry::new(vec![($a, $b, $factor), $(($c, $d, $fac)),*])
+        $crate::lumi::LumiEntry::new(vec![(vec![$a, $b], $factor), $((vec![$c, $d], $fac)),*])
     };
 }
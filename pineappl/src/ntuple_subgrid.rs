@@ -1,16 +1,110 @@
 //! Provides an implementation of the `Grid` trait with n-tuples.
 
+use super::convert::{f64_from_usize, usize_from_f64};
 use super::grid::Ntuple;
-use super::subgrid::{Subgrid, SubgridEnum};
+use super::import_only_subgrid::ImportOnlySubgridV2;
+use super::lagrange_subgrid::LagrangeSubgridV2;
+use super::sparse_array3::SparseArray3;
+use super::subgrid::{ExtraSubgridParams, Mu2, Subgrid, SubgridEnum, SubgridParams};
 use either::Either;
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
 use std::ops::Range;
 
+/// N-tuple with a single scale, which is the storage format of `NtupleSubgridV1`.
+#[derive(Clone, Deserialize, Serialize)]
+struct NtupleV1 {
+    x1: f64,
+    x2: f64,
+    q2: f64,
+    weight: f64,
+}
+
+/// Returns the index of the bin containing `value`, for `bins` bins that are equally spaced in the
+/// logarithm between `min` and `max`. Values outside of this range are assigned to the first or
+/// last bin, respectively.
+fn log_bin(value: f64, min: f64, max: f64, bins: usize) -> usize {
+    let index = (value / min).log(max / min) * f64_from_usize(bins);
+
+    usize_from_f64(index).min(bins - 1)
+}
+
+/// Returns the center of the bin with index `index`, for `bins` bins that are equally spaced in
+/// the logarithm between `min` and `max`.
+fn log_bin_center(index: usize, min: f64, max: f64, bins: usize) -> f64 {
+    min * (max / min).powf((f64_from_usize(index) + 0.5) / f64_from_usize(bins))
+}
+
+/// Sums the weights of `ntuples` into the bins given by `subgrid_params`. The momentum fractions
+/// use the bins of `x_bins`, `x_min` and `x_max`, and the renormalization and factorization
+/// scales use the bins of `q2_bins`, `q2_min` and `q2_max`, each equally spaced in the logarithm.
+fn histogram(
+    ntuples: impl Iterator<Item = Ntuple<f64>>,
+    subgrid_params: &SubgridParams,
+) -> ImportOnlySubgridV2 {
+    let x_bins = subgrid_params.x_bins();
+    let (x_min, x_max) = (subgrid_params.x_min(), subgrid_params.x_max());
+    let q2_bins = subgrid_params.q2_bins();
+    let (q2_min, q2_max) = (subgrid_params.q2_min(), subgrid_params.q2_max());
+
+    assert!(
+        x_bins > 0 && q2_bins > 0,
+        "the subgrid parameters have no bins"
+    );
+    assert!(
+        (0.0 < x_min) && (x_min < x_max) && (0.0 < q2_min) && (q2_min < q2_max),
+        "the limits of the subgrid parameters are invalid"
+    );
+
+    let entries: Vec<_> = ntuples
+        .map(|ntuple| {
+            (
+                (
+                    log_bin(ntuple.q2, q2_min, q2_max, q2_bins),
+                    log_bin(ntuple.q2r, q2_min, q2_max, q2_bins),
+                ),
+                log_bin(ntuple.x1, x_min, x_max, x_bins),
+                log_bin(ntuple.x2, x_min, x_max, x_bins),
+                ntuple.weight,
+            )
+        })
+        .collect();
+
+    // only keep the pairs of factorization and renormalization scale bins that are filled
+    let mut mu2_bins: Vec<_> = entries.iter().map(|&(mu2, _, _, _)| mu2).collect();
+    mu2_bins.sort_unstable();
+    mu2_bins.dedup();
+
+    let mut array = SparseArray3::new(mu2_bins.len(), x_bins, x_bins);
+
+    for (mu2, ix1, ix2, weight) in entries {
+        let imu2 = mu2_bins
+            .binary_search(&mu2)
+            .unwrap_or_else(|_| unreachable!());
+        array[[imu2, ix1, ix2]] += weight;
+    }
+
+    let mu2_grid = mu2_bins
+        .into_iter()
+        .map(|(fac, ren)| Mu2 {
+            ren: log_bin_center(ren, q2_min, q2_max, q2_bins),
+            fac: log_bin_center(fac, q2_min, q2_max, q2_bins),
+        })
+        .collect();
+    let x_grid: Vec<_> = (0..x_bins)
+        .map(|index| log_bin_center(index, x_min, x_max, x_bins))
+        .collect();
+
+    ImportOnlySubgridV2::new(array, mu2_grid, x_grid.clone(), x_grid)
+}
+
 /// Structure holding a grid with an n-tuple as the storage method for weights.
+///
+/// This subgrid only supports n-tuples whose renormalization and factorization scales are the
+/// same; use `NtupleSubgridV2` for the general case.
 #[derive(Default, Deserialize, Serialize)]
 pub struct NtupleSubgridV1 {
-    ntuples: Vec<Ntuple<f64>>,
+    ntuples: Vec<NtupleV1>,
 }
 
 impl NtupleSubgridV1 {
@@ -19,28 +113,97 @@ impl NtupleSubgridV1 {
     pub const fn new() -> Self {
         Self { ntuples: vec![] }
     }
+
+    /// Interpolates the n-tuples of this subgrid with a `LagrangeSubgridV2` that is constructed
+    /// from `subgrid_params` and `extra_params`. N-tuples outside the ranges of the interpolation
+    /// grids are discarded. Returns the interpolated subgrid and the number of discarded
+    /// n-tuples.
+    #[must_use]
+    pub fn interpolate(
+        &self,
+        subgrid_params: &SubgridParams,
+        extra_params: &ExtraSubgridParams,
+    ) -> (LagrangeSubgridV2, usize) {
+        let mut subgrid = LagrangeSubgridV2::new(subgrid_params, extra_params);
+        let mut discarded = 0;
+
+        for ntuple in &self.ntuples {
+            let ntuple = Ntuple {
+                x1: ntuple.x1,
+                x2: ntuple.x2,
+                q2: ntuple.q2,
+                q2r: ntuple.q2,
+                weight: ntuple.weight,
+            };
+
+            if !subgrid.contains(&ntuple) {
+                discarded += 1;
+            }
+
+            subgrid.fill(&ntuple);
+        }
+
+        (subgrid, discarded)
+    }
+
+    /// Sums the weights of the n-tuples of this subgrid into bins, which are determined by
+    /// `subgrid_params`. The momentum fractions are binned according to `x_bins`, `x_min` and
+    /// `x_max`, and the scales according to `q2_bins`, `q2_min` and `q2_max`, each equally spaced
+    /// in the logarithm. The nodes of the returned subgrid are the centers of the bins, and
+    /// n-tuples outside of the binning ranges are assigned to the first or last bin.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `subgrid_params` has no bins or if its limits are not positive and increasing,
+    /// which are the parameters rejected by `Grid::set_subgrid_params`.
+    #[must_use]
+    pub fn histogram(&self, subgrid_params: &SubgridParams) -> ImportOnlySubgridV2 {
+        histogram(
+            self.ntuples.iter().map(|ntuple| Ntuple {
+                x1: ntuple.x1,
+                x2: ntuple.x2,
+                q2: ntuple.q2,
+                q2r: ntuple.q2,
+                weight: ntuple.weight,
+            }),
+            subgrid_params,
+        )
+    }
 }
 
 impl Subgrid for NtupleSubgridV1 {
     fn convolute(
         &self,
-        _: &[f64],
-        _: &[f64],
-        _: &[f64],
-        lumi: Either<&dyn Fn(usize, usize, usize) -> f64, &dyn Fn(f64, f64, f64) -> f64>,
+        _: &[&[f64]],
+        _: &[Mu2],
+        lumi: Either<&dyn Fn(&[usize], usize) -> f64, &dyn Fn(&[f64], Mu2) -> f64>,
     ) -> f64 {
         let lumi = lumi.right().unwrap();
         let mut result = 0.0;
 
         for ntuple in &self.ntuples {
-            result += lumi(ntuple.x1, ntuple.x2, ntuple.q2) * ntuple.weight;
+            let mu2 = Mu2 {
+                ren: ntuple.q2,
+                fac: ntuple.q2,
+            };
+            result += lumi(&[ntuple.x1, ntuple.x2], mu2) * ntuple.weight;
         }
 
         result
     }
 
     fn fill(&mut self, ntuple: &Ntuple<f64>) {
-        self.ntuples.push(ntuple.clone());
+        assert_eq!(
+            ntuple.q2, ntuple.q2r,
+            "NtupleSubgridV1 does not support different renormalization and factorization scales"
+        );
+
+        self.ntuples.push(NtupleV1 {
+            x1: ntuple.x1,
+            x2: ntuple.x2,
+            q2: ntuple.q2,
+            weight: ntuple.weight,
+        });
     }
 
     fn q2_grid(&self) -> Cow<[f64]> {
@@ -87,15 +250,169 @@ impl Subgrid for NtupleSubgridV1 {
         Self::new().into()
     }
 
-    fn iter(&self) -> Box<dyn Iterator<Item = ((usize, usize, usize), &f64)>> {
+    fn iter(&self) -> Box<dyn Iterator<Item = (Vec<usize>, &f64)>> {
         unimplemented!();
     }
 }
 
+/// Structure holding a grid with an n-tuple as the storage method for weights, which keeps the
+/// renormalization and factorization scales of each n-tuple separately.
+///
+/// # Panics
+///
+/// Since n-tuples are not stored on nodes, the methods `Subgrid::q2_slice`,
+/// `Subgrid::fill_q2_slice` and `Subgrid::iter` are not supported and panic; use
+/// `NtupleSubgridV2::interpolate` or `NtupleSubgridV2::histogram` to convert this subgrid into one
+/// with nodes first. `Subgrid::merge` panics if the merged subgrid is neither empty nor an n-tuple
+/// subgrid.
+#[derive(Default, Deserialize, Serialize)]
+pub struct NtupleSubgridV2 {
+    ntuples: Vec<Ntuple<f64>>,
+}
+
+impl NtupleSubgridV2 {
+    /// Constructor.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { ntuples: vec![] }
+    }
+
+    /// Interpolates the n-tuples of this subgrid with a `LagrangeSubgridV2` that is constructed
+    /// from `subgrid_params` and `extra_params`. N-tuples outside the ranges of the interpolation
+    /// grids are discarded. Returns the interpolated subgrid and the number of discarded
+    /// n-tuples. Since interpolating subgrids do not support different renormalization and
+    /// factorization scales, `None` is returned if any n-tuple has different scales.
+    #[must_use]
+    pub fn interpolate(
+        &self,
+        subgrid_params: &SubgridParams,
+        extra_params: &ExtraSubgridParams,
+    ) -> Option<(LagrangeSubgridV2, usize)> {
+        if self.ntuples.iter().any(|ntuple| ntuple.q2 != ntuple.q2r) {
+            return None;
+        }
+
+        let mut subgrid = LagrangeSubgridV2::new(subgrid_params, extra_params);
+        let mut discarded = 0;
+
+        for ntuple in &self.ntuples {
+            if !subgrid.contains(ntuple) {
+                discarded += 1;
+            }
+
+            subgrid.fill(ntuple);
+        }
+
+        Some((subgrid, discarded))
+    }
+
+    /// Sums the weights of the n-tuples of this subgrid into bins, like
+    /// `NtupleSubgridV1::histogram`. The renormalization and factorization scales are binned
+    /// separately.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as `NtupleSubgridV1::histogram`.
+    #[must_use]
+    pub fn histogram(&self, subgrid_params: &SubgridParams) -> ImportOnlySubgridV2 {
+        histogram(self.ntuples.iter().cloned(), subgrid_params)
+    }
+}
+
+impl Subgrid for NtupleSubgridV2 {
+    fn convolute(
+        &self,
+        _: &[&[f64]],
+        _: &[Mu2],
+        lumi: Either<&dyn Fn(&[usize], usize) -> f64, &dyn Fn(&[f64], Mu2) -> f64>,
+    ) -> f64 {
+        let lumi = lumi.right().unwrap();
+        let mut result = 0.0;
+
+        for ntuple in &self.ntuples {
+            let mu2 = Mu2 {
+                ren: ntuple.q2r,
+                fac: ntuple.q2,
+            };
+            result += lumi(&[ntuple.x1, ntuple.x2], mu2) * ntuple.weight;
+        }
+
+        result
+    }
+
+    fn fill(&mut self, ntuple: &Ntuple<f64>) {
+        self.ntuples.push(ntuple.clone());
+    }
+
+    fn q2_grid(&self) -> Cow<[f64]> {
+        Cow::Borrowed(&[])
+    }
+
+    fn x1_grid(&self) -> Cow<[f64]> {
+        Cow::Borrowed(&[])
+    }
+
+    fn x2_grid(&self) -> Cow<[f64]> {
+        Cow::Borrowed(&[])
+    }
+
+    fn is_empty(&self) -> bool {
+        self.ntuples.is_empty()
+    }
+
+    fn merge(&mut self, other: &mut SubgridEnum, transpose: bool) {
+        assert!(!transpose);
+
+        match other {
+            SubgridEnum::NtupleSubgridV2(other_grid) => {
+                self.ntuples.append(&mut other_grid.ntuples);
+            }
+            SubgridEnum::NtupleSubgridV1(other_grid) => {
+                self.ntuples
+                    .extend(other_grid.ntuples.drain(..).map(|ntuple| Ntuple {
+                        x1: ntuple.x1,
+                        x2: ntuple.x2,
+                        q2: ntuple.q2,
+                        q2r: ntuple.q2,
+                        weight: ntuple.weight,
+                    }));
+            }
+            _ => assert!(
+                other.is_empty(),
+                "an `NtupleSubgridV2` can only be merged with n-tuple subgrids"
+            ),
+        }
+    }
+
+    fn scale(&mut self, factor: f64) {
+        self.ntuples.iter_mut().for_each(|t| t.weight *= factor);
+    }
+
+    fn q2_slice(&self) -> Range<usize> {
+        unimplemented!("an `NtupleSubgridV2` does not have q2 slices");
+    }
+
+    fn fill_q2_slice(&self, _: usize, _: &mut [f64]) {
+        unimplemented!("an `NtupleSubgridV2` does not have q2 slices");
+    }
+
+    fn symmetrize(&mut self) {}
+
+    fn clone_empty(&self) -> SubgridEnum {
+        Self::new().into()
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = (Vec<usize>, &f64)>> {
+        unimplemented!("an `NtupleSubgridV2` can not be iterated over, since it has no nodes");
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::empty_subgrid::EmptySubgridV1;
     use either::Either::Right;
+    use float_cmp::approx_eq;
 
     #[test]
     fn test() {
@@ -106,6 +423,7 @@ mod tests {
             x1: 0.5,
             x2: 0.5,
             q2: 10.0,
+            q2r: 10.0,
             weight: 1.0,
         });
         assert!(!subgrid.is_empty());
@@ -114,10 +432,11 @@ mod tests {
             x1: 0.25,
             x2: 0.75,
             q2: 100.0,
+            q2r: 100.0,
             weight: 3.0,
         });
         assert_eq!(
-            subgrid.convolute(&[], &[], &[], Right(&|x1, x2, q2| x1 * x2 * q2)),
+            subgrid.convolute(&[], &[], Right(&|x, mu2| x[0] * x[1] * mu2.fac)),
             2.5 + 56.25
         );
 
@@ -127,22 +446,23 @@ mod tests {
             x1: 0.25,
             x2: 0.5,
             q2: 20.0,
+            q2r: 20.0,
             weight: 2.0,
         });
         assert_eq!(
-            other_subgrid.convolute(&[], &[], &[], Right(&|x1, x2, q2| x1 * x2 * q2)),
+            other_subgrid.convolute(&[], &[], Right(&|x, mu2| x[0] * x[1] * mu2.fac)),
             5.0
         );
 
         subgrid.merge(&mut other_subgrid, false);
         assert_eq!(
-            subgrid.convolute(&[], &[], &[], Right(&|x1, x2, q2| x1 * x2 * q2)),
+            subgrid.convolute(&[], &[], Right(&|x, mu2| x[0] * x[1] * mu2.fac)),
             2.5 + 56.25 + 5.0
         );
 
         subgrid.scale(0.5);
         assert_eq!(
-            subgrid.convolute(&[], &[], &[], Right(&|x1, x2, q2| x1 * x2 * q2)),
+            subgrid.convolute(&[], &[], Right(&|x, mu2| x[0] * x[1] * mu2.fac)),
             1.25 + 28.125 + 2.5
         );
     }
@@ -162,4 +482,161 @@ mod tests {
 
         subgrid.fill_q2_slice(0, &mut []);
     }
+
+    #[test]
+    fn separate_scales() {
+        let mut subgrid: SubgridEnum = NtupleSubgridV2::new().into();
+        assert!(subgrid.is_empty());
+
+        subgrid.fill(&Ntuple {
+            x1: 0.5,
+            x2: 0.5,
+            q2: 10.0,
+            q2r: 40.0,
+            weight: 1.0,
+        });
+        assert!(!subgrid.is_empty());
+        assert_eq!(
+            subgrid.convolute(&[], &[], Right(&|x, mu2| x[0] * x[1] * mu2.ren)),
+            10.0
+        );
+        assert_eq!(
+            subgrid.convolute(&[], &[], Right(&|x, mu2| x[0] * x[1] * mu2.fac)),
+            2.5
+        );
+
+        let mut other_subgrid: SubgridEnum = NtupleSubgridV1::new().into();
+
+        other_subgrid.fill(&Ntuple {
+            x1: 0.25,
+            x2: 0.5,
+            q2: 20.0,
+            q2r: 20.0,
+            weight: 2.0,
+        });
+
+        subgrid.merge(&mut other_subgrid, false);
+        assert!(other_subgrid.is_empty());
+        assert_eq!(
+            subgrid.convolute(&[], &[], Right(&|x, mu2| x[0] * x[1] * mu2.ren)),
+            10.0 + 5.0
+        );
+
+        subgrid.scale(0.5);
+        assert_eq!(
+            subgrid.convolute(&[], &[], Right(&|x, mu2| x[0] * x[1] * mu2.ren)),
+            5.0 + 2.5
+        );
+    }
+
+    #[test]
+    fn merge_v2_with_empty() {
+        let mut subgrid: SubgridEnum = NtupleSubgridV2::new().into();
+
+        subgrid.fill(&Ntuple {
+            x1: 0.5,
+            x2: 0.5,
+            q2: 10.0,
+            q2r: 40.0,
+            weight: 1.0,
+        });
+        subgrid.merge(&mut EmptySubgridV1::default().into(), false);
+
+        assert_eq!(
+            subgrid.convolute(&[], &[], Right(&|x, mu2| x[0] * x[1] * mu2.ren)),
+            10.0
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "an `NtupleSubgridV2` does not have q2 slices")]
+    fn q2_slice_v2() {
+        let subgrid: SubgridEnum = NtupleSubgridV2::new().into();
+
+        subgrid.q2_slice();
+    }
+
+    #[test]
+    fn histogram() {
+        let mut subgrid = NtupleSubgridV2::new();
+        let mut subgrid_params = SubgridParams::default();
+        subgrid_params.set_x_bins(4);
+        subgrid_params.set_x_min(1e-4);
+        subgrid_params.set_x_max(1.0);
+        subgrid_params.set_q2_bins(2);
+        subgrid_params.set_q2_min(1e2);
+        subgrid_params.set_q2_max(1e4);
+
+        for &(x1, x2, q2, q2r, weight) in &[
+            (0.5, 0.002, 200.0, 200.0, 1.0),
+            (0.6, 0.005, 900.0, 900.0, 2.0),
+            (2e-5, 0.05, 5e4, 200.0, 4.0),
+        ] {
+            subgrid.fill(&Ntuple {
+                x1,
+                x2,
+                q2,
+                q2r,
+                weight,
+            });
+        }
+
+        let histogram = subgrid.histogram(&subgrid_params);
+
+        assert_eq!(histogram.x1_grid().len(), 4);
+        assert!(approx_eq!(
+            f64,
+            histogram.x1_grid()[0],
+            10.0_f64.powf(-3.5),
+            ulps = 4
+        ));
+        assert!(approx_eq!(
+            f64,
+            histogram.x2_grid()[3],
+            10.0_f64.powf(-0.5),
+            ulps = 4
+        ));
+        assert_eq!(histogram.mu2_grid().len(), 2);
+        assert!(approx_eq!(
+            f64,
+            histogram.mu2_grid()[0].fac,
+            10.0_f64.powf(2.5),
+            ulps = 4
+        ));
+        assert!(approx_eq!(
+            f64,
+            histogram.mu2_grid()[1].fac,
+            10.0_f64.powf(3.5),
+            ulps = 4
+        ));
+        assert!(approx_eq!(
+            f64,
+            histogram.mu2_grid()[1].ren,
+            10.0_f64.powf(2.5),
+            ulps = 4
+        ));
+
+        let values: Vec<_> = histogram
+            .iter()
+            .map(|(index, &value)| (index, value))
+            .collect();
+
+        assert_eq!(values, [(vec![0, 3, 1], 3.0), (vec![1, 0, 2], 4.0)]);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "NtupleSubgridV1 does not support different renormalization and factorization scales"
+    )]
+    fn fill_separate_scales_v1() {
+        let mut subgrid: SubgridEnum = NtupleSubgridV1::new().into();
+
+        subgrid.fill(&Ntuple {
+            x1: 0.5,
+            x2: 0.5,
+            q2: 10.0,
+            q2r: 40.0,
+            weight: 1.0,
+        });
+    }
 }
@@ -0,0 +1,360 @@
+//! Module containing the trait `PdfProvider`, which supplies PDFs and the strong coupling to
+//! convolutions.
+
+use super::pids::{self, PidBasis};
+use std::ptr;
+
+/// Trait for objects that provide PDFs of a single hadron, and the strong coupling, for a
+/// convolution with a `Grid`.
+///
+/// The grid itself decides, based on its `initial_state_1` and `initial_state_2` metadata and the
+/// value returned by `particle_id`, whether the PDFs must be charge conjugated or are not needed at
+/// all, which is the case for leptons.
+pub trait PdfProvider {
+    /// Returns the PDG MC id of the hadron whose PDFs are provided, for instance `2212` for a
+    /// proton.
+    fn particle_id(&self) -> i32;
+
+    /// Returns the PDF for the parton with PDG MC id `id`, multiplied with its momentum fraction
+    /// `x`, at the scale `q2`.
+    fn xfx(&self, id: i32, x: f64, q2: f64) -> f64;
+
+    /// Returns the strong coupling at the scale `q2`.
+    fn alphas(&self, q2: f64) -> f64;
+
+    /// Returns the electroweak coupling at the scale `q2`, which multiplies each order with the
+    /// corresponding power `Order::alpha`. The default implementation returns one, which is
+    /// correct for grids that already include the electroweak couplings, for instance by using
+    /// `Grid::scale_by_order`; use `AlphaPdf` to choose another coupling.
+    fn alpha(&self, _q2: f64) -> f64 {
+        1.0
+    }
+
+    /// Evaluates the PDF for the parton with PDG MC id `id` for all momentum fractions `x` at the
+    /// scale `q2` and writes the results, multiplied by the corresponding momentum fractions, into
+    /// `xfx`, which must be as long as `x`. The default implementation calls `xfx` for each value;
+    /// implementations can override it if evaluating many momentum fractions at once is faster.
+    fn xfx_batch(&self, id: i32, x: &[f64], q2: f64, xfx: &mut [f64]) {
+        for (result, &x) in xfx.iter_mut().zip(x) {
+            *result = self.xfx(id, x, q2);
+        }
+    }
+}
+
+/// Implementation of `PdfProvider` using closures.
+pub struct ClosurePdf<F, G> {
+    particle_id: i32,
+    xfx: F,
+    alphas: G,
+}
+
+impl<F: Fn(i32, f64, f64) -> f64, G: Fn(f64) -> f64> ClosurePdf<F, G> {
+    /// Constructor. The PDFs of the hadron with PDG MC id `particle_id` are given by `xfx`, which
+    /// must return the PDF for the given parton id, `x` and `q2` multiplied with `x`, and the
+    /// strong coupling is given by `alphas`.
+    pub const fn new(particle_id: i32, xfx: F, alphas: G) -> Self {
+        Self {
+            particle_id,
+            xfx,
+            alphas,
+        }
+    }
+}
+
+impl<F: Fn(i32, f64, f64) -> f64, G: Fn(f64) -> f64> PdfProvider for ClosurePdf<F, G> {
+    fn particle_id(&self) -> i32 {
+        self.particle_id
+    }
+
+    fn xfx(&self, id: i32, x: f64, q2: f64) -> f64 {
+        (self.xfx)(id, x, q2)
+    }
+
+    fn alphas(&self, q2: f64) -> f64 {
+        (self.alphas)(q2)
+    }
+}
+
+/// Input schemes of the electroweak coupling with a fixed value.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum EwScheme {
+    /// Electroweak coupling in the Thomson limit, `1 / alpha(0) = 137.035999084`.
+    Alpha0,
+    /// Electroweak coupling in the modified minimal-subtraction scheme at the mass of the Z
+    /// boson, `1 / alpha(mZ) = 127.951`.
+    AlphaMz,
+    /// Electroweak coupling derived from the Fermi constant `G_mu = 1.1663787e-5 GeV^-2` and the
+    /// masses of the W and Z bosons, `mW = 80.379 GeV` and `mZ = 91.1876 GeV`.
+    GMu,
+}
+
+impl EwScheme {
+    /// Returns the value of the electroweak coupling in this scheme.
+    #[must_use]
+    pub fn alpha(self) -> f64 {
+        match self {
+            Self::Alpha0 => 1.0 / 137.035_999_084,
+            Self::AlphaMz => 1.0 / 127.951,
+            Self::GMu => {
+                let gmu = 1.166_378_7e-5;
+                let mw2 = 80.379_f64.powi(2);
+                let mz2 = 91.1876_f64.powi(2);
+
+                2.0_f64.sqrt() * gmu * mw2 * (1.0 - mw2 / mz2) / std::f64::consts::PI
+            }
+        }
+    }
+}
+
+/// Wraps a `PdfProvider` and replaces its electroweak coupling with the one given by a closure,
+/// which can either run or be fixed, for instance to the value of an `EwScheme`.
+pub struct AlphaPdf<'a, P: ?Sized, F> {
+    pdf: &'a P,
+    alpha: F,
+}
+
+impl<'a, P: PdfProvider + ?Sized, F: Fn(f64) -> f64> AlphaPdf<'a, P, F> {
+    /// Constructor. The PDFs and the strong coupling are taken from `pdf`, the electroweak
+    /// coupling at the scale `q2` is given by `alpha(q2)`.
+    pub const fn new(pdf: &'a P, alpha: F) -> Self {
+        Self { pdf, alpha }
+    }
+}
+
+impl<P: PdfProvider + ?Sized, F: Fn(f64) -> f64> PdfProvider for AlphaPdf<'_, P, F> {
+    fn particle_id(&self) -> i32 {
+        self.pdf.particle_id()
+    }
+
+    fn xfx(&self, id: i32, x: f64, q2: f64) -> f64 {
+        self.pdf.xfx(id, x, q2)
+    }
+
+    fn alphas(&self, q2: f64) -> f64 {
+        self.pdf.alphas(q2)
+    }
+
+    fn alpha(&self, q2: f64) -> f64 {
+        (self.alpha)(q2)
+    }
+
+    fn xfx_batch(&self, id: i32, x: &[f64], q2: f64, xfx: &mut [f64]) {
+        self.pdf.xfx_batch(id, x, q2, xfx);
+    }
+}
+
+/// Returns the PDG MC id of the charge-conjugated particle of `pid`.
+#[must_use]
+pub const fn charge_conjugate_pid(pid: i32) -> i32 {
+    match pid {
+        // gluons and photons are their own anti-particles
+        21 | 22 => pid,
+        _ => -pid,
+    }
+}
+
+#[derive(Eq, PartialEq)]
+enum Mode {
+    Pdf,
+    ChargeConjugatedPdf,
+    NoPdf,
+}
+
+/// Wraps a `PdfProvider` such that it provides the PDFs for a specific initial state of a grid.
+/// If the grid's luminosity function uses the evolution basis, the PDFs of the evolution basis are
+/// built from linear combinations of the PDFs with PDG MC ids.
+pub(crate) struct InitialStatePdf<'a, P: ?Sized> {
+    pdf: &'a P,
+    mode: Mode,
+    pid_basis: PidBasis,
+}
+
+impl<'a, P: PdfProvider + ?Sized> InitialStatePdf<'a, P> {
+    /// Returns a wrapper around `pdf` which provides the PDFs for the hadron with PDG MC id
+    /// `initial_state`, for parton ids given in the basis `pid_basis`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `initial_state` neither is the particle of `pdf`, its anti-particle nor a lepton.
+    pub(crate) fn new(initial_state: i32, pid_basis: PidBasis, pdf: &'a P) -> Self {
+        let particle_id = pdf.particle_id();
+
+        let mode = if initial_state == particle_id {
+            Mode::Pdf
+        } else if initial_state == -particle_id {
+            Mode::ChargeConjugatedPdf
+        } else {
+            match initial_state {
+                11 | 13 | -11 | -13 => Mode::NoPdf,
+                _ => panic!(
+                    "PDFs for particle {} can not be used for the initial state {}",
+                    particle_id, initial_state
+                ),
+            }
+        };
+
+        Self {
+            pdf,
+            mode,
+            pid_basis,
+        }
+    }
+
+    /// Returns `true` if `self` and `other` provide the same PDFs, which is the case if they wrap
+    /// the same `PdfProvider` for the same initial state and basis of particle ids.
+    pub(crate) fn same_pdfs(&self, other: &Self) -> bool {
+        let (pdf, other_pdf): (*const P, *const P) = (self.pdf, other.pdf);
+
+        ptr::eq(pdf.cast::<()>(), other_pdf.cast::<()>())
+            && (self.mode == other.mode)
+            && (self.pid_basis == other.pid_basis)
+    }
+
+    fn xfx_pdg(&self, id: i32, x: f64, q2: f64) -> f64 {
+        match self.mode {
+            Mode::Pdf => self.pdf.xfx(id, x, q2),
+            Mode::ChargeConjugatedPdf => self.pdf.xfx(charge_conjugate_pid(id), x, q2),
+            Mode::NoPdf => x,
+        }
+    }
+
+    fn xfx_batch_pdg(&self, id: i32, x: &[f64], q2: f64, xfx: &mut [f64]) {
+        match self.mode {
+            Mode::Pdf => self.pdf.xfx_batch(id, x, q2, xfx),
+            Mode::ChargeConjugatedPdf => self.pdf.xfx_batch(charge_conjugate_pid(id), x, q2, xfx),
+            Mode::NoPdf => xfx.copy_from_slice(x),
+        }
+    }
+}
+
+impl<P: PdfProvider + ?Sized> PdfProvider for InitialStatePdf<'_, P> {
+    fn particle_id(&self) -> i32 {
+        match self.mode {
+            Mode::Pdf | Mode::NoPdf => self.pdf.particle_id(),
+            Mode::ChargeConjugatedPdf => -self.pdf.particle_id(),
+        }
+    }
+
+    fn xfx(&self, id: i32, x: f64, q2: f64) -> f64 {
+        match self.pid_basis {
+            PidBasis::Pdg => self.xfx_pdg(id, x, q2),
+            PidBasis::Evol => pids::evol_to_pdg_mc_ids(id)
+                .into_iter()
+                .map(|(pid, factor)| factor * self.xfx_pdg(pid, x, q2))
+                .sum(),
+        }
+    }
+
+    fn alphas(&self, q2: f64) -> f64 {
+        self.pdf.alphas(q2)
+    }
+
+    fn alpha(&self, q2: f64) -> f64 {
+        self.pdf.alpha(q2)
+    }
+
+    fn xfx_batch(&self, id: i32, x: &[f64], q2: f64, xfx: &mut [f64]) {
+        match self.pid_basis {
+            PidBasis::Pdg => self.xfx_batch_pdg(id, x, q2, xfx),
+            PidBasis::Evol => {
+                let mut buffer = vec![0.0; x.len()];
+
+                for value in xfx.iter_mut() {
+                    *value = 0.0;
+                }
+
+                for (pid, factor) in pids::evol_to_pdg_mc_ids(id) {
+                    self.xfx_batch_pdg(pid, x, q2, &mut buffer);
+
+                    for (value, &term) in xfx.iter_mut().zip(&buffer) {
+                        *value += factor * term;
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn initial_state_pdf() {
+        let pdf = ClosurePdf::new(2212, |id, x, _| f64::from(id) * x, |q2| q2);
+
+        let proton = InitialStatePdf::new(2212, PidBasis::Pdg, &pdf);
+        let anti_proton = InitialStatePdf::new(-2212, PidBasis::Pdg, &pdf);
+        let electron = InitialStatePdf::new(11, PidBasis::Pdg, &pdf);
+
+        assert_eq!(proton.particle_id(), 2212);
+        assert_eq!(proton.xfx(2, 0.5, 10.0), 1.0);
+        assert_eq!(proton.xfx(21, 0.5, 10.0), 10.5);
+        assert_eq!(proton.alphas(10.0), 10.0);
+
+        assert_eq!(anti_proton.particle_id(), -2212);
+        assert_eq!(anti_proton.xfx(2, 0.5, 10.0), -1.0);
+        assert_eq!(anti_proton.xfx(21, 0.5, 10.0), 10.5);
+        assert_eq!(anti_proton.xfx(22, 0.5, 10.0), 11.0);
+
+        assert_eq!(electron.xfx(11, 0.5, 10.0), 0.5);
+
+        let mut xfx = [0.0; 2];
+        anti_proton.xfx_batch(-1, &[0.25, 0.5], 10.0, &mut xfx);
+        assert_eq!(xfx, [0.25, 0.5]);
+        electron.xfx_batch(11, &[0.25, 0.5], 10.0, &mut xfx);
+        assert_eq!(xfx, [0.25, 0.5]);
+    }
+
+    #[test]
+    fn initial_state_pdf_evol() {
+        let pdf = ClosurePdf::new(2212, |id, x, _| f64::from(id) * x, |q2| q2);
+
+        let proton = InitialStatePdf::new(2212, PidBasis::Evol, &pdf);
+        let anti_proton = InitialStatePdf::new(-2212, PidBasis::Evol, &pdf);
+
+        // the singlet vanishes, because quarks and anti-quarks cancel each other
+        assert_eq!(proton.xfx(100, 0.5, 10.0), 0.0);
+        // V3 = (u - ubar) - (d - dbar)
+        assert_eq!(proton.xfx(203, 0.5, 10.0), 1.0);
+        assert_eq!(anti_proton.xfx(203, 0.5, 10.0), -1.0);
+        assert_eq!(proton.xfx(21, 0.5, 10.0), 10.5);
+
+        let mut xfx = [0.0; 2];
+        proton.xfx_batch(208, &[0.25, 0.5], 10.0, &mut xfx);
+        assert_eq!(xfx, [-1.5, -3.0]);
+    }
+
+    #[test]
+    fn alpha_pdf() {
+        let pdf = ClosurePdf::new(2212, |id, x, _| f64::from(id) * x, |q2| q2);
+
+        assert_eq!(pdf.alpha(10.0), 1.0);
+
+        let alpha_pdf = AlphaPdf::new(&pdf, |q2| 2.0 * q2);
+
+        assert_eq!(alpha_pdf.particle_id(), 2212);
+        assert_eq!(alpha_pdf.xfx(2, 0.5, 10.0), 1.0);
+        assert_eq!(alpha_pdf.alphas(10.0), 10.0);
+        assert_eq!(alpha_pdf.alpha(10.0), 20.0);
+
+        let proton = InitialStatePdf::new(2212, PidBasis::Pdg, &alpha_pdf);
+
+        assert_eq!(proton.alpha(10.0), 20.0);
+    }
+
+    #[test]
+    fn ew_scheme() {
+        assert!((1.0 / EwScheme::Alpha0.alpha() - 137.036).abs() < 1e-3);
+        assert!((1.0 / EwScheme::AlphaMz.alpha() - 127.951).abs() < 1e-3);
+        assert!((1.0 / EwScheme::GMu.alpha() - 132.2).abs() < 0.1);
+    }
+
+    #[test]
+    #[should_panic(expected = "PDFs for particle 2212 can not be used for the initial state 211")]
+    fn initial_state_pdf_pion() {
+        let pdf = ClosurePdf::new(2212, |_, x, _| x, |_| 0.118);
+
+        let _ = InitialStatePdf::new(211, PidBasis::Pdg, &pdf);
+    }
+}
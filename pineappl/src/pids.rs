@@ -0,0 +1,245 @@
+//! Module containing the translation of particle ids between different bases.
+
+use std::convert::TryFrom;
+
+/// Basis in which the particle ids of a luminosity function are given.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PidBasis {
+    /// Particle ids of the Particle Data Group's Monte Carlo numbering scheme.
+    Pdg,
+    /// Particle ids of the PDF evolution basis.
+    ///
+    /// Quark ids are replaced by the singlet `100`, the valence distribution `200`, and the
+    /// non-singlet combinations `103`, `108`, `115`, `124` and `135` (T3, T8, T15, T24 and T35)
+    /// and `203`, `208`, `215`, `224` and `235` (V3, V8, V15, V24 and V35). All other particle
+    /// ids, for instance the gluon `21` and the photon `22`, are the same as in the `Pdg` basis.
+    Evol,
+}
+
+/// Returns the coefficients of the evolution basis element `id` for the flavours `1` to `6`.
+///
+/// The coefficients multiply the sums of quarks and anti-quarks if the second element of the
+/// returned tuple is `1.0`, and their differences if it is `-1.0`. The id `id` must be one of
+/// `100`, `103`, `108`, `115`, `124`, `135` or the corresponding ids of the valence sector, which
+/// are larger by `100`.
+fn evol_coefficients(id: i32) -> Option<([f64; 6], f64)> {
+    let (sector, index) = match id {
+        100..=135 => (1.0, id - 100),
+        200..=235 => (-1.0, id - 200),
+        _ => return None,
+    };
+
+    let mut coefficients = [0.0; 6];
+
+    match index {
+        // the singlet and the valence distribution: sum over all flavours
+        0 => coefficients = [1.0; 6],
+        // the non-singlet combination of the lightest two flavours: up minus down
+        3 => coefficients[..2].copy_from_slice(&[-1.0, 1.0]),
+        // T_{n^2 - 1} is the sum over the lightest `n - 1` flavours minus `n - 1` times flavour `n`
+        8 | 15 | 24 | 35 => {
+            let n: u8 = match index {
+                8 => 3,
+                15 => 4,
+                24 => 5,
+                _ => 6,
+            };
+            let n_minus_one = usize::from(n - 1);
+
+            for coefficient in coefficients.iter_mut().take(n_minus_one) {
+                *coefficient = 1.0;
+            }
+
+            coefficients[n_minus_one] = -f64::from(n - 1);
+        }
+        _ => return None,
+    }
+
+    Some((coefficients, sector))
+}
+
+/// Ids of the evolution basis elements that mix quark distributions.
+const EVOL_IDS: [i32; 12] = [100, 103, 108, 115, 124, 135, 200, 203, 208, 215, 224, 235];
+
+/// Translates the evolution basis id `id` into a linear combination of PDG MC ids.
+///
+/// Ids that do not belong to a quark combination of the evolution basis, for instance gluons, are
+/// returned unchanged with factor `1.0`.
+///
+/// # Examples
+///
+/// ```rust
+/// use pineappl::pids::evol_to_pdg_mc_ids;
+///
+/// assert_eq!(evol_to_pdg_mc_ids(103), [(1, -1.0), (-1, -1.0), (2, 1.0), (-2, 1.0)]);
+/// assert_eq!(evol_to_pdg_mc_ids(21), [(21, 1.0)]);
+/// ```
+#[must_use]
+pub fn evol_to_pdg_mc_ids(id: i32) -> Vec<(i32, f64)> {
+    evol_coefficients(id).map_or_else(
+        || vec![(id, 1.0)],
+        |(coefficients, sector)| {
+            (1..=6)
+                .zip(coefficients.iter())
+                .filter(|&(_, &coefficient)| coefficient != 0.0)
+                .flat_map(|(pid, &coefficient)| {
+                    vec![(pid, coefficient), (-pid, sector * coefficient)]
+                })
+                .collect()
+        },
+    )
+}
+
+/// Translates the PDG MC id `id` into a linear combination of evolution basis ids.
+///
+/// This is the inverse transformation of [`evol_to_pdg_mc_ids`]. Ids that are not quarks, for
+/// instance gluons, are returned unchanged with factor `1.0`.
+///
+/// # Examples
+///
+/// ```rust
+/// use pineappl::pids::{evol_to_pdg_mc_ids, pdg_mc_ids_to_evol};
+///
+/// // the strange quark is a combination of ten basis elements
+/// let strange = pdg_mc_ids_to_evol(3);
+/// assert_eq!(strange.len(), 10);
+/// assert_eq!(strange[0], (100, 1.0 / 12.0));
+///
+/// // transforming back gives the strange quark
+/// let coefficient: f64 = strange
+///     .into_iter()
+///     .flat_map(|(evol, factor)| {
+///         evol_to_pdg_mc_ids(evol)
+///             .into_iter()
+///             .map(move |(pid, other_factor)| (pid, factor * other_factor))
+///     })
+///     .filter_map(|(pid, factor)| (pid == 3).then(|| factor))
+///     .sum();
+/// assert!((coefficient - 1.0).abs() < 1e-15);
+///
+/// assert_eq!(pdg_mc_ids_to_evol(21), [(21, 1.0)]);
+/// ```
+#[must_use]
+pub fn pdg_mc_ids_to_evol(id: i32) -> Vec<(i32, f64)> {
+    if !(1..=6).contains(&id.abs()) {
+        return vec![(id, 1.0)];
+    }
+
+    // the index of the flavour, and the sign of the valence sector
+    let flavour = usize::try_from(id.abs() - 1).unwrap_or_else(|_| unreachable!());
+    let sign = f64::from(id.signum());
+
+    EVOL_IDS
+        .iter()
+        .filter_map(|&evol| {
+            let (coefficients, sector) = evol_coefficients(evol).unwrap_or_else(|| unreachable!());
+            let norm: f64 = coefficients.iter().map(|c| c * c).sum();
+            let coefficient = coefficients[flavour];
+
+            // the basis is orthogonal, so the inverse is given by the transposed matrix divided by
+            // the squared norms; the additional factor two comes from q = (q^+ + q^-) / 2
+            (coefficient != 0.0).then(|| {
+                let factor = if sector > 0.0 { 1.0 } else { sign };
+                (evol, factor * coefficient / (2.0 * norm))
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use float_cmp::approx_eq;
+
+    #[test]
+    fn evol_to_pdg_mc_ids_quarks() {
+        assert_eq!(
+            evol_to_pdg_mc_ids(100),
+            [
+                (1, 1.0),
+                (-1, 1.0),
+                (2, 1.0),
+                (-2, 1.0),
+                (3, 1.0),
+                (-3, 1.0),
+                (4, 1.0),
+                (-4, 1.0),
+                (5, 1.0),
+                (-5, 1.0),
+                (6, 1.0),
+                (-6, 1.0)
+            ]
+        );
+        assert_eq!(
+            evol_to_pdg_mc_ids(208),
+            [
+                (1, 1.0),
+                (-1, -1.0),
+                (2, 1.0),
+                (-2, -1.0),
+                (3, -2.0),
+                (-3, 2.0)
+            ]
+        );
+        assert_eq!(
+            evol_to_pdg_mc_ids(135),
+            [
+                (1, 1.0),
+                (-1, 1.0),
+                (2, 1.0),
+                (-2, 1.0),
+                (3, 1.0),
+                (-3, 1.0),
+                (4, 1.0),
+                (-4, 1.0),
+                (5, 1.0),
+                (-5, 1.0),
+                (6, -5.0),
+                (-6, -5.0)
+            ]
+        );
+        assert_eq!(evol_to_pdg_mc_ids(22), [(22, 1.0)]);
+        assert_eq!(evol_to_pdg_mc_ids(101), [(101, 1.0)]);
+    }
+
+    #[test]
+    fn pdg_mc_ids_to_evol_inverse() {
+        for pid in (-6..=6).filter(|&pid| pid != 0) {
+            let mut result = vec![0.0; 13];
+
+            for (evol, factor) in pdg_mc_ids_to_evol(pid) {
+                for (other_pid, other_factor) in evol_to_pdg_mc_ids(evol) {
+                    result[usize::try_from(other_pid + 6).unwrap()] += factor * other_factor;
+                }
+            }
+
+            for (index, value) in result.into_iter().enumerate() {
+                let expected = if index == usize::try_from(pid + 6).unwrap() {
+                    1.0
+                } else {
+                    0.0
+                };
+
+                assert!(approx_eq!(f64, value, expected, epsilon = 1e-15));
+            }
+        }
+
+        assert_eq!(
+            pdg_mc_ids_to_evol(-2),
+            [
+                (100, 1.0 / 12.0),
+                (103, 1.0 / 4.0),
+                (108, 1.0 / 12.0),
+                (115, 1.0 / 24.0),
+                (124, 1.0 / 40.0),
+                (135, 1.0 / 60.0),
+                (200, -1.0 / 12.0),
+                (203, -1.0 / 4.0),
+                (208, -1.0 / 12.0),
+                (215, -1.0 / 24.0),
+                (224, -1.0 / 40.0),
+                (235, -1.0 / 60.0)
+            ]
+        );
+    }
+}
@@ -379,7 +379,11 @@ impl<T: Clone + Default + PartialEq> SparseArray3<T> {
         if x <= self.start {
             self.start += 1
         } else if x < self.start + nx {
-            todo!();
+            let index = (x - self.start) * dim1;
+            let offset = self.indices[index].1;
+
+            self.indices
+                .splice(index..index, iter::repeat((0, offset)).take(dim1));
         } else if x <= self.dimensions.0 {
             // nothing to do here
         } else {
@@ -906,6 +910,28 @@ mod tests {
         assert_eq!(array[[2, 5, 5]], 7.0);
         assert_eq!(array[[2, 6, 3]], 8.0);
         assert_eq!(array[[2, 6, 0]], 9.0);
+
+        // insert between the two filled entries of the x-axis
+        array.increase_x_at(2);
+
+        assert_eq!(array.dimensions(), (7, 50, 50));
+        assert_eq!(array.x_range(), 1..4);
+        assert_eq!(array[[1, 2, 3]], 1.0);
+        assert_eq!(array[[1, 2, 4]], 2.0);
+        assert_eq!(array[[1, 2, 5]], 3.0);
+        assert_eq!(array[[1, 3, 0]], 4.0);
+        assert!(array.indexed_iter().all(|((x, _, _), _)| x != 2));
+        assert_eq!(array[[3, 5, 0]], 5.0);
+        assert_eq!(array[[3, 5, 5]], 7.0);
+        assert_eq!(array[[3, 6, 3]], 8.0);
+        assert_eq!(array[[3, 6, 0]], 9.0);
+
+        array[[2, 4, 4]] = 10.0;
+
+        assert_eq!(array[[1, 3, 0]], 4.0);
+        assert_eq!(array[[2, 4, 4]], 10.0);
+        assert_eq!(array[[3, 5, 0]], 5.0);
+        assert_eq!(array.len(), 9);
     }
 
     #[test]
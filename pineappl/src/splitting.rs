@@ -0,0 +1,278 @@
+//! Module containing the leading-order Altarelli-Parisi splitting functions and their
+//! convolutions with PDFs that are only known on a set of momentum fractions.
+
+use super::convert::f64_from_usize;
+use itertools::Itertools;
+use ndarray::Array2;
+use std::f64::consts::PI;
+
+const CA: f64 = 3.0;
+const CF: f64 = 4.0 / 3.0;
+const TR: f64 = 0.5;
+
+/// Number of Gauss-Legendre points used to integrate each interval between two momentum
+/// fractions.
+const GAUSS_POINTS: usize = 8;
+
+/// Returns the first coefficient of the QCD beta function for `nf` active flavours, normalized
+/// such that the strong coupling `as` runs as `d as / d ln(mu2) = -beta0 as^2 / (4 pi)`.
+pub fn beta0(nf: u8) -> f64 {
+    11.0 * CA / 3.0 - 4.0 * TR * f64::from(nf) / 3.0
+}
+
+/// Leading-order splitting functions `P_ab(z)`, which describe the emission of a parton `a` from a
+/// parton `b`. They are normalized such that the PDFs evolve as `d f_a / d ln(mu2) = as / (2 pi)
+/// sum_b P_ab x f_b`, where `x` denotes the Mellin convolution.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Splitting {
+    /// Quark from a quark of the same flavour.
+    QuarkQuark,
+    /// Quark from a gluon.
+    QuarkGluon,
+    /// Gluon from a quark.
+    GluonQuark,
+    /// Gluon from a gluon.
+    GluonGluon,
+}
+
+impl Splitting {
+    /// Part of the splitting function that is a regular function of `z`.
+    fn regular(self, z: f64) -> f64 {
+        match self {
+            Self::QuarkQuark => 0.0,
+            Self::QuarkGluon => TR * z.mul_add(z, (1.0 - z).powi(2)),
+            Self::GluonQuark => CF * (1.0 - z).mul_add(1.0 - z, 1.0) / z,
+            Self::GluonGluon => 2.0 * CA * (1.0 - z) * (z.recip() + z),
+        }
+    }
+
+    /// Coefficient of the plus distribution `1 / (1 - z)_+`.
+    fn plus(self, z: f64) -> f64 {
+        match self {
+            Self::QuarkQuark => CF * z.mul_add(z, 1.0),
+            Self::QuarkGluon | Self::GluonQuark => 0.0,
+            Self::GluonGluon => 2.0 * CA * z,
+        }
+    }
+
+    /// Coefficient of the delta distribution `delta(1 - z)`.
+    fn delta(self, nf: u8) -> f64 {
+        match self {
+            Self::QuarkQuark => 1.5 * CF,
+            Self::QuarkGluon | Self::GluonQuark => 0.0,
+            Self::GluonGluon => beta0(nf) / 2.0,
+        }
+    }
+
+    /// Returns the particle ids of the partons that emit the parton with particle id `pid`, for
+    /// `nf` active quark flavours. The returned vector is empty if this splitting function does
+    /// not describe the emission of `pid`.
+    pub fn sources(self, pid: i32, nf: u8) -> Vec<i32> {
+        let quark = (pid != 0) && (pid.abs() <= i32::from(nf));
+
+        match self {
+            Self::QuarkQuark if quark => vec![pid],
+            Self::QuarkGluon if quark => vec![21],
+            Self::GluonQuark if pid == 21 => (-i32::from(nf)..=i32::from(nf))
+                .filter(|&id| id != 0)
+                .collect(),
+            Self::GluonGluon if pid == 21 => vec![21],
+            _ => vec![],
+        }
+    }
+
+    /// Returns the matrix `m` that approximates the convolution of this splitting function with a
+    /// PDF `f` on the momentum fractions `x`, such that `(P x f)(x[i]) = sum_k m[[i, k]] f(x[k])`.
+    /// Between the momentum fractions the function `x f(x)` is interpolated linearly in `ln(x)`,
+    /// and above the largest momentum fraction it is interpolated to zero at `x = 1`. The
+    /// momentum fractions in `x` must be unique, but do not have to be sorted.
+    pub fn matrix(self, x: &[f64], nf: u8) -> Array2<f64> {
+        let (points, weights) = gauss_legendre(GAUSS_POINTS);
+        let sorted: Vec<_> = (0..x.len())
+            .sorted_by(|&lhs, &rhs| x[lhs].partial_cmp(&x[rhs]).unwrap())
+            .collect();
+        let plus_at_one = self.plus(1.0);
+        let mut matrix = Array2::zeros((x.len(), x.len()));
+
+        for (position, &i) in sorted.iter().enumerate() {
+            let xi = x[i];
+
+            if xi >= 1.0 {
+                continue;
+            }
+
+            // nodes of the interpolation, the last one without an index is `x = 1`
+            let mut nodes: Vec<_> = sorted[position..]
+                .iter()
+                .map(|&k| (Some(k), x[k].ln()))
+                .collect();
+
+            if x[sorted[sorted.len() - 1]] < 1.0 {
+                nodes.push((None, 0.0));
+            }
+
+            for ((left, left_ln), (right, right_ln)) in nodes.iter().copied().tuple_windows() {
+                let width = right_ln - left_ln;
+
+                for (&point, &weight) in points.iter().zip(&weights) {
+                    // `u = -ln(z)`, where `z` is the argument of the splitting function
+                    let u = point.mul_add(width, left_ln) - xi.ln();
+                    let z = (-u).exp();
+                    let one_minus_z = -(-u).exp_m1();
+                    let jacobian = weight * width * z;
+
+                    for (node, hat) in [(left, 1.0 - point), (right, point)] {
+                        if let Some(k) = node {
+                            matrix[[i, k]] += jacobian
+                                * hat
+                                * (self.regular(z) + self.plus(z) / one_minus_z)
+                                * x[k]
+                                / xi;
+                        }
+                    }
+
+                    // subtraction term of the plus distribution
+                    matrix[[i, i]] -= jacobian * plus_at_one / one_minus_z;
+                }
+            }
+
+            matrix[[i, i]] += plus_at_one.mul_add((-xi).ln_1p(), self.delta(nf));
+        }
+
+        matrix
+    }
+}
+
+/// Returns the points and weights of the Gauss-Legendre quadrature with `n` points for the
+/// interval from zero to one.
+fn gauss_legendre(n: usize) -> (Vec<f64>, Vec<f64>) {
+    let mut points = Vec::with_capacity(n);
+    let mut weights = Vec::with_capacity(n);
+    let nf64 = f64_from_usize(n);
+
+    for i in 1..=n {
+        let mut z = (PI * (f64_from_usize(i) - 0.25) / (nf64 + 0.5)).cos();
+        let mut derivative;
+
+        loop {
+            // evaluate the Legendre polynomial of degree `n` with its recurrence relation
+            let mut p1 = 1.0;
+            let mut p2 = 0.0;
+
+            for j in 1..=n {
+                let p3 = p2;
+                let j = f64_from_usize(j);
+                p2 = p1;
+                p1 = (2.0_f64.mul_add(j, -1.0) * z).mul_add(p2, -(j - 1.0) * p3) / j;
+            }
+
+            derivative = nf64 * z.mul_add(p1, -p2) / z.mul_add(z, -1.0);
+            let delta = p1 / derivative;
+            z -= delta;
+
+            if delta.abs() < 1e-14 {
+                break;
+            }
+        }
+
+        points.push((1.0 - z) / 2.0);
+        weights.push(1.0 / ((1.0 - z * z) * derivative * derivative));
+    }
+
+    (points, weights)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use float_cmp::approx_eq;
+
+    // momentum fraction times a PDF, which vanishes at `x = 1`
+    fn xf(x: f64) -> f64 {
+        x.sqrt() * (1.0 - x).powi(3)
+    }
+
+    // direct evaluation of the convolution of `splitting` with `xf(x) / x` at `x`
+    fn convolution(splitting: Splitting, x: f64, nf: u8) -> f64 {
+        let (points, weights) = gauss_legendre(GAUSS_POINTS);
+        let intervals = 1000;
+        let width = (1.0 - x) / f64_from_usize(intervals);
+        let mut result = 0.0;
+
+        for interval in 0..intervals {
+            for (&point, &weight) in points.iter().zip(&weights) {
+                let z = (f64_from_usize(interval) + point).mul_add(width, x);
+                let plus = splitting
+                    .plus(z)
+                    .mul_add(xf(x / z), -splitting.plus(1.0) * xf(x));
+
+                result +=
+                    weight * width * splitting.regular(z).mul_add(xf(x / z), plus / (1.0 - z));
+            }
+        }
+
+        result += xf(x)
+            * splitting
+                .plus(1.0)
+                .mul_add((-x).ln_1p(), splitting.delta(nf));
+
+        result / x
+    }
+
+    #[test]
+    fn gauss_legendre_weights() {
+        let (points, weights) = gauss_legendre(GAUSS_POINTS);
+
+        assert!(approx_eq!(f64, weights.iter().sum(), 1.0, epsilon = 1e-14));
+        // polynomials of degree `2n - 1` are integrated exactly
+        assert!(approx_eq!(
+            f64,
+            points
+                .iter()
+                .zip(&weights)
+                .map(|(x, w)| w * x.powi(15))
+                .sum(),
+            1.0 / 16.0,
+            epsilon = 1e-14
+        ));
+    }
+
+    #[test]
+    fn sources() {
+        assert_eq!(Splitting::QuarkQuark.sources(-2, 5), [-2]);
+        assert_eq!(Splitting::QuarkQuark.sources(6, 5), []);
+        assert_eq!(Splitting::QuarkGluon.sources(3, 5), [21]);
+        assert_eq!(Splitting::QuarkGluon.sources(21, 5), []);
+        assert_eq!(Splitting::GluonQuark.sources(21, 3), [-3, -2, -1, 1, 2, 3]);
+        assert_eq!(Splitting::GluonGluon.sources(21, 5), [21]);
+        assert_eq!(Splitting::GluonGluon.sources(22, 5), []);
+    }
+
+    #[test]
+    fn matrix() {
+        let nf = 5;
+        let x: Vec<_> = (0..100)
+            .rev()
+            .map(|i| (-0.1 * f64_from_usize(i)).exp())
+            .collect();
+
+        for &splitting in &[
+            Splitting::QuarkQuark,
+            Splitting::QuarkGluon,
+            Splitting::GluonQuark,
+            Splitting::GluonGluon,
+        ] {
+            let matrix = splitting.matrix(&x, nf);
+
+            for &i in &[10, 50, 90] {
+                let result: f64 = (0..x.len()).map(|k| matrix[[i, k]] * xf(x[k]) / x[k]).sum();
+                let reference = convolution(splitting, x[i], nf);
+
+                assert!(((result - reference) / reference).abs() < 1e-2);
+            }
+
+            // the node at `x = 1` does not receive contributions
+            assert!(matrix.row(99).iter().all(|&value| value == 0.0));
+        }
+    }
+}
@@ -0,0 +1,107 @@
+//! Module containing the `McStatistics` struct, which tracks the Monte Carlo statistics of the
+//! events filled into a subgrid.
+
+use serde::{Deserialize, Serialize};
+
+/// Monte Carlo statistics of the events filled into a single subgrid.
+///
+/// The statistics consist of the sum of the weights, the sum of the squared weights, and the
+/// number of events. Events that are correlated, for instance an event of the real-emission
+/// matrix element and its counter-events, are counted as a single event whose weight is the sum
+/// of their weights, see `Grid::fill_group`.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct McStatistics {
+    sum_weights: f64,
+    sum_weights2: f64,
+    events: u64,
+}
+
+impl McStatistics {
+    /// Adds an event with the given `weight`.
+    pub fn fill(&mut self, weight: f64) {
+        self.sum_weights += weight;
+        self.sum_weights2 += weight * weight;
+        self.events += 1;
+    }
+
+    /// Adds the events of `other`, which must be statistically independent of the events of
+    /// `self`.
+    pub fn merge(&mut self, other: &Self) {
+        self.sum_weights += other.sum_weights;
+        self.sum_weights2 += other.sum_weights2;
+        self.events += other.events;
+    }
+
+    /// Multiplies the weights of all events with `factor`.
+    pub fn scale(&mut self, factor: f64) {
+        self.sum_weights *= factor;
+        self.sum_weights2 *= factor * factor;
+    }
+
+    /// Returns the sum of the weights of all events.
+    #[must_use]
+    pub const fn sum_weights(&self) -> f64 {
+        self.sum_weights
+    }
+
+    /// Returns the sum of the squared weights of all events.
+    #[must_use]
+    pub const fn sum_weights2(&self) -> f64 {
+        self.sum_weights2
+    }
+
+    /// Returns the number of events.
+    #[must_use]
+    pub const fn events(&self) -> u64 {
+        self.events
+    }
+
+    /// Returns the estimate of the relative Monte Carlo uncertainty of the sum of the weights,
+    /// which is the square root of the sum of the squared weights divided by the absolute value
+    /// of the sum of the weights. If the sum of the weights is zero, zero is returned.
+    #[must_use]
+    pub fn relative_uncertainty(&self) -> f64 {
+        if self.sum_weights == 0.0 {
+            0.0
+        } else {
+            self.sum_weights2.sqrt() / self.sum_weights.abs()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fill_merge_and_scale() {
+        let mut statistics = McStatistics::default();
+
+        assert_eq!(statistics.events(), 0);
+        assert_eq!(statistics.relative_uncertainty(), 0.0);
+
+        statistics.fill(1.0);
+        statistics.fill(3.0);
+
+        assert_eq!(statistics.sum_weights(), 4.0);
+        assert_eq!(statistics.sum_weights2(), 10.0);
+        assert_eq!(statistics.events(), 2);
+        assert_eq!(statistics.relative_uncertainty(), 10.0_f64.sqrt() / 4.0);
+
+        let mut other = McStatistics::default();
+        other.fill(-2.0);
+        statistics.merge(&other);
+
+        assert_eq!(statistics.sum_weights(), 2.0);
+        assert_eq!(statistics.sum_weights2(), 14.0);
+        assert_eq!(statistics.events(), 3);
+
+        // scaling doesn't change the relative uncertainty
+        statistics.scale(-0.5);
+
+        assert_eq!(statistics.sum_weights(), -1.0);
+        assert_eq!(statistics.sum_weights2(), 3.5);
+        assert_eq!(statistics.events(), 3);
+        assert_eq!(statistics.relative_uncertainty(), 3.5_f64.sqrt());
+    }
+}
@@ -2,9 +2,11 @@
 
 use super::empty_subgrid::EmptySubgridV1;
 use super::grid::Ntuple;
-use super::import_only_subgrid::ImportOnlySubgridV1;
-use super::lagrange_subgrid::{LagrangeSparseSubgridV1, LagrangeSubgridV1, LagrangeSubgridV2};
-use super::ntuple_subgrid::NtupleSubgridV1;
+use super::import_only_subgrid::{ImportOnlySubgridV1, ImportOnlySubgridV2};
+use super::lagrange_subgrid::{
+    LagrangeSparseSubgridV1, LagrangeSubgridV1, LagrangeSubgridV2, LagrangeSubgridV3,
+};
+use super::ntuple_subgrid::{NtupleSubgridV1, NtupleSubgridV2};
 use either::Either;
 use enum_dispatch::enum_dispatch;
 use serde::{Deserialize, Serialize};
@@ -28,6 +30,21 @@ pub enum SubgridEnum {
     ImportOnlySubgridV1,
     /// Empty subgrid.
     EmptySubgridV1,
+    /// Lagrange-interpolation subgrid with an arbitrary number of convolutions.
+    LagrangeSubgridV3,
+    /// Import-only sparse subgrid with separate renormalization and factorization scales.
+    ImportOnlySubgridV2,
+    /// N-tuple subgrid with separate renormalization and factorization scales.
+    NtupleSubgridV2,
+}
+
+/// Structure denoting renormalization and factorization scale values.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Mu2 {
+    /// The (squared) renormalization scale value.
+    pub ren: f64,
+    /// The (squared) factorization scale value.
+    pub fac: f64,
 }
 
 /// Trait each subgrid must implement.
@@ -45,26 +62,69 @@ pub trait Subgrid {
     /// return an empty `Vec`.
     fn x2_grid(&self) -> Cow<[f64]>;
 
+    /// Return a `Vec` of pairs of renormalization and factorization scales. If the subgrid does
+    /// not use a grid, this method should return an empty `Vec`. The default implementation uses
+    /// the values of `q2_grid` for both scales, which is correct for all subgrids that do not
+    /// distinguish them.
+    fn mu2_grid(&self) -> Cow<[Mu2]> {
+        self.q2_grid()
+            .iter()
+            .map(|&q2| Mu2 { ren: q2, fac: q2 })
+            .collect()
+    }
+
+    /// Return the `x` grids of all convolutions, in the same order as the PDG ids of the
+    /// luminosity function. If the subgrid does not use grids, the vectors are empty. The default
+    /// implementation returns the grids of `x1_grid` and `x2_grid`, which is correct for subgrids
+    /// with two convolutions.
+    fn x_grids(&self) -> Vec<Cow<[f64]>> {
+        vec![self.x1_grid(), self.x2_grid()]
+    }
+
     /// Convolute the subgrid with a luminosity function, which either takes indices as arguments,
-    /// in which case the `x1`, `x2` and `q2` values can be read from the given slices, or takes
-    /// the usual values `x1`, `x2`, and `q2`. If the method `x1_grid` and `x2_grid` return a
-    /// non-empty vector, this method must use the indexed luminosity function.
+    /// in which case the `x` and `mu2` values can be read from the given slices, or takes the
+    /// usual values `x` and `mu2`. The first argument of the luminosity function contains the
+    /// indices or values of the momentum fractions of each convolution, the second argument the
+    /// index or value of the renormalization and factorization scales. If the method `x_grids`
+    /// returns non-empty vectors, this method must use the indexed luminosity function.
     fn convolute(
         &self,
-        x1: &[f64],
-        x2: &[f64],
-        q2: &[f64],
-        lumi: Either<&dyn Fn(usize, usize, usize) -> f64, &dyn Fn(f64, f64, f64) -> f64>,
+        x: &[&[f64]],
+        mu2: &[Mu2],
+        lumi: Either<&dyn Fn(&[usize], usize) -> f64, &dyn Fn(&[f64], Mu2) -> f64>,
     ) -> f64;
 
-    /// Fills the subgrid with `weight` for the parton momentum fractions `x1` and `x2`, and the
-    /// scale `q2`.
+    /// Fills the subgrid with `weight` for the parton momentum fractions `x1` and `x2`, the
+    /// factorization scale `q2` and the renormalization scale `q2r`. Subgrids that do not support
+    /// different scales panic if `q2` and `q2r` are not the same.
     fn fill(&mut self, ntuple: &Ntuple<f64>);
 
+    /// Fills the subgrid with `weight` for the parton momentum fractions `x`, one for each
+    /// convolution, and the scale `q2`. The default implementation calls `fill` and therefore
+    /// panics if `x` does not contain exactly two momentum fractions.
+    fn fill_x(&mut self, x: &[f64], q2: f64, weight: f64) {
+        if let [x1, x2] = *x {
+            self.fill(&Ntuple {
+                x1,
+                x2,
+                q2,
+                q2r: q2,
+                weight,
+            });
+        } else {
+            panic!("this subgrid only supports two convolutions");
+        }
+    }
+
     /// Returns true if `fill` was never called for this grid.
     fn is_empty(&self) -> bool;
 
     /// Merges `other` into this subgrid.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `other` is not empty and its type or its nodes are not compatible with this
+    /// subgrid.
     fn merge(&mut self, other: &mut SubgridEnum, transpose: bool);
 
     /// Scale the subgrid by `factor`.
@@ -85,12 +145,14 @@ pub trait Subgrid {
     /// Returns an empty copy of the current subgrid.
     fn clone_empty(&self) -> SubgridEnum;
 
-    /// Return an iterator over all non-zero elements of the subgrid.
-    fn iter(&self) -> Box<dyn Iterator<Item = ((usize, usize, usize), &f64)> + '_>;
+    /// Return an iterator over all non-zero elements of the subgrid together with their indices.
+    /// The first index is the index of the scale, the remaining ones are the indices of the
+    /// momentum fractions of each convolution.
+    fn iter(&self) -> Box<dyn Iterator<Item = (Vec<usize>, &f64)> + '_>;
 }
 
 /// Subgrid creation parameters for subgrids that perform interpolation.
-#[derive(Deserialize, Serialize)]
+#[derive(Clone, Deserialize, Serialize)]
 pub struct SubgridParams {
     q2_bins: usize,
     q2_max: f64,
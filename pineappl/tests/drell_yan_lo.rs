@@ -3,6 +3,7 @@ use lhapdf::Pdf;
 use pineappl::bin::BinRemapper;
 use pineappl::grid::{Grid, Ntuple, Order};
 use pineappl::lumi_entry;
+use pineappl::pdf::ClosurePdf;
 use pineappl::subgrid::{ExtraSubgridParams, SubgridParams};
 use rand::Rng;
 use rand_pcg::Pcg64;
@@ -81,6 +82,7 @@ fn fill_drell_yan_lo_grid(
         alpha: 2,
         logxir: 0,
         logxif: 0,
+        logxia: 0,
     }];
 
     // we bin in rapidity from 0 to 2.4 in steps of 0.1
@@ -149,7 +151,19 @@ fn fill_drell_yan_lo_grid(
         let weight = jacobian * int_photo(s, u, t);
         let q2 = if dynamic { mll * mll } else { 90.0 * 90.0 };
 
-        grid.fill(0, yll.abs(), 0, &Ntuple { x1, x2, q2, weight });
+        grid.fill(
+            0,
+            yll.abs(),
+            0,
+            &Ntuple {
+                x1,
+                x2,
+                q2,
+                q2r: q2,
+                weight,
+            },
+        )
+        .unwrap();
     }
 
     Ok(grid)
@@ -176,8 +190,7 @@ fn dy_aa_lagrange_subgrid_static() -> anyhow::Result<()> {
     assert!(lhapdf::available_pdf_sets().iter().any(|x| x == &pdf_set));
 
     let pdf = Pdf::with_setname_and_member(&pdf_set, 0);
-    let xfx = |id, x, q2| pdf.xfx_q2(id, x, q2);
-    let alphas = |_| 0.0;
+    let pdf = ClosurePdf::new(2212, |id, x, q2| pdf.xfx_q2(id, x, q2), |_| 0.0);
 
     // check `read` and `write`
     let mut file = Cursor::new(Vec::new());
@@ -187,10 +200,10 @@ fn dy_aa_lagrange_subgrid_static() -> anyhow::Result<()> {
     let mut grid = Grid::read(&mut file)?;
 
     // some useless scalings
-    grid.scale_by_order(10.0, 0.5, 10.0, 10.0, 1.0);
-    grid.scale_by_order(10.0, 1.0, 10.0, 10.0, 4.0);
+    grid.scale_by_order(10.0, 0.5, 10.0, 10.0, 1.0, 1.0);
+    grid.scale_by_order(10.0, 1.0, 10.0, 10.0, 1.0, 4.0);
 
-    let bins = grid.convolute(&xfx, &xfx, &alphas, &[], &[], &[], &[(1.0, 1.0)]);
+    let bins = grid.convolute(&[&pdf, &pdf], &[], &[], &[], &[(1.0, 1.0, 1.0)]);
     let reference = vec![
         5.29438499470369e-1,
         5.407794857747981e-1,
@@ -236,7 +249,7 @@ fn dy_aa_lagrange_subgrid_static() -> anyhow::Result<()> {
             .collect::<Vec<(f64, f64)>>(),
     )?)?;
 
-    let bins = grid.convolute(&xfx, &xfx, &alphas, &[], &[], &[], &[(1.0, 1.0)]);
+    let bins = grid.convolute(&[&pdf, &pdf], &[], &[], &[], &[(1.0, 1.0, 1.0)]);
 
     // results are slightly different because of the static scale detection - the interpolation
     // error in the Q^2 dimension is removed
@@ -295,8 +308,7 @@ fn dy_aa_ntuple_subgrid_dynamic() -> anyhow::Result<()> {
     assert!(lhapdf::available_pdf_sets().iter().any(|x| x == &pdf_set));
 
     let pdf = Pdf::with_setname_and_member(&pdf_set, 0);
-    let xfx = |id, x, q2| pdf.xfx_q2(id, x, q2);
-    let alphas = |_| 0.0;
+    let pdf = ClosurePdf::new(2212, |id, x, q2| pdf.xfx_q2(id, x, q2), |_| 0.0);
 
     // check `read` and `write`
     let mut file = Cursor::new(Vec::new());
@@ -306,10 +318,10 @@ fn dy_aa_ntuple_subgrid_dynamic() -> anyhow::Result<()> {
     let mut grid = Grid::read(&mut file)?;
 
     // some useless scalings
-    grid.scale_by_order(10.0, 0.5, 10.0, 10.0, 1.0);
-    grid.scale_by_order(10.0, 1.0, 10.0, 10.0, 4.0);
+    grid.scale_by_order(10.0, 0.5, 10.0, 10.0, 1.0, 1.0);
+    grid.scale_by_order(10.0, 1.0, 10.0, 10.0, 1.0, 4.0);
 
-    let bins = grid.convolute(&xfx, &xfx, &alphas, &[], &[], &[], &[(1.0, 1.0)]);
+    let bins = grid.convolute(&[&pdf, &pdf], &[], &[], &[], &[(1.0, 1.0, 1.0)]);
 
     let reference = vec![
         5.092821448721474e-1,
@@ -344,13 +356,11 @@ fn dy_aa_ntuple_subgrid_dynamic() -> anyhow::Result<()> {
 
     // check with `bin_indices` non-empty
     let other = grid.convolute(
-        &xfx,
-        &xfx,
-        &alphas,
+        &[&pdf, &pdf],
         &[],
         &(0..24).collect::<Vec<_>>(),
         &[],
-        &[(1.0, 1.0)],
+        &[(1.0, 1.0, 1.0)],
     );
     assert_eq!(bins, other);
 
@@ -378,8 +388,7 @@ fn dy_aa_lagrange_subgrid_dynamic() -> anyhow::Result<()> {
     assert!(lhapdf::available_pdf_sets().iter().any(|x| x == &pdf_set));
 
     let pdf = Pdf::with_setname_and_member(&pdf_set, 0);
-    let xfx = |id, x, q2| pdf.xfx_q2(id, x, q2);
-    let alphas = |_| 0.0;
+    let pdf = ClosurePdf::new(2212, |id, x, q2| pdf.xfx_q2(id, x, q2), |_| 0.0);
 
     // check `read` and `write`
     let mut file = Cursor::new(Vec::new());
@@ -389,10 +398,10 @@ fn dy_aa_lagrange_subgrid_dynamic() -> anyhow::Result<()> {
     let mut grid = Grid::read(&mut file)?;
 
     // some useless scalings
-    grid.scale_by_order(10.0, 0.5, 10.0, 10.0, 1.0);
-    grid.scale_by_order(10.0, 1.0, 10.0, 10.0, 4.0);
+    grid.scale_by_order(10.0, 0.5, 10.0, 10.0, 1.0, 1.0);
+    grid.scale_by_order(10.0, 1.0, 10.0, 10.0, 1.0, 4.0);
 
-    let bins = grid.convolute(&xfx, &xfx, &alphas, &[], &[], &[], &[(1.0, 1.0)]);
+    let bins = grid.convolute(&[&pdf, &pdf], &[], &[], &[], &[(1.0, 1.0, 1.0)]);
     let reference = vec![
         5.093090431949207e-1,
         5.191668797562395e-1,
@@ -428,7 +437,7 @@ fn dy_aa_lagrange_subgrid_dynamic() -> anyhow::Result<()> {
     grid.optimize();
 
     // check that the results are still the same
-    let bins = grid.convolute(&xfx, &xfx, &alphas, &[], &[], &[], &[(1.0, 1.0)]);
+    let bins = grid.convolute(&[&pdf, &pdf], &[], &[], &[], &[(1.0, 1.0, 1.0)]);
 
     for (result, reference) in bins.iter().zip(reference.iter()) {
         assert!(approx_eq!(f64, *result, *reference, ulps = 16));
@@ -458,8 +467,7 @@ fn dy_aa_lagrange_subgrid_v2_dynamic() -> anyhow::Result<()> {
     assert!(lhapdf::available_pdf_sets().iter().any(|x| x == &pdf_set));
 
     let pdf = Pdf::with_setname_and_member(&pdf_set, 0);
-    let xfx = |id, x, q2| pdf.xfx_q2(id, x, q2);
-    let alphas = |_| 0.0;
+    let pdf = ClosurePdf::new(2212, |id, x, q2| pdf.xfx_q2(id, x, q2), |_| 0.0);
 
     // check `read` and `write`
     let mut file = Cursor::new(Vec::new());
@@ -469,10 +477,10 @@ fn dy_aa_lagrange_subgrid_v2_dynamic() -> anyhow::Result<()> {
     let mut grid = Grid::read(&mut file)?;
 
     // some useless scalings
-    grid.scale_by_order(10.0, 0.5, 10.0, 10.0, 1.0);
-    grid.scale_by_order(10.0, 1.0, 10.0, 10.0, 4.0);
+    grid.scale_by_order(10.0, 0.5, 10.0, 10.0, 1.0, 1.0);
+    grid.scale_by_order(10.0, 1.0, 10.0, 10.0, 1.0, 4.0);
 
-    let bins = grid.convolute(&xfx, &xfx, &alphas, &[], &[], &[], &[(1.0, 1.0)]);
+    let bins = grid.convolute(&[&pdf, &pdf], &[], &[], &[], &[(1.0, 1.0, 1.0)]);
     let reference = vec![
         5.093090431949207e-1,
         5.191668797562395e-1,
@@ -508,7 +516,7 @@ fn dy_aa_lagrange_subgrid_v2_dynamic() -> anyhow::Result<()> {
     //grid.optimize();
 
     // check that the results are still the same
-    let bins = grid.convolute(&xfx, &xfx, &alphas, &[], &[], &[], &[(1.0, 1.0)]);
+    let bins = grid.convolute(&[&pdf, &pdf], &[], &[], &[], &[(1.0, 1.0, 1.0)]);
 
     for (result, reference) in bins.iter().zip(reference.iter()) {
         assert!(approx_eq!(f64, *result, *reference, ulps = 16));
@@ -538,8 +546,7 @@ fn dy_aa_lagrange_sparse_subgrid_dynamic() -> anyhow::Result<()> {
     assert!(lhapdf::available_pdf_sets().iter().any(|x| x == &pdf_set));
 
     let pdf = Pdf::with_setname_and_member(&pdf_set, 0);
-    let xfx = |id, x, q2| pdf.xfx_q2(id, x, q2);
-    let alphas = |_| 0.0;
+    let pdf = ClosurePdf::new(2212, |id, x, q2| pdf.xfx_q2(id, x, q2), |_| 0.0);
 
     // check `read` and `write`
     let mut file = Cursor::new(Vec::new());
@@ -549,10 +556,10 @@ fn dy_aa_lagrange_sparse_subgrid_dynamic() -> anyhow::Result<()> {
     let mut grid = Grid::read(&mut file)?;
 
     // some useless scalings
-    grid.scale_by_order(10.0, 0.5, 10.0, 10.0, 1.0);
-    grid.scale_by_order(10.0, 1.0, 10.0, 10.0, 4.0);
+    grid.scale_by_order(10.0, 0.5, 10.0, 10.0, 1.0, 1.0);
+    grid.scale_by_order(10.0, 1.0, 10.0, 10.0, 1.0, 4.0);
 
-    let bins = grid.convolute(&xfx, &xfx, &alphas, &[], &[], &[], &[(1.0, 1.0)]);
+    let bins = grid.convolute(&[&pdf, &pdf], &[], &[], &[], &[(1.0, 1.0, 1.0)]);
     let reference = vec![
         5.093090431949207e-1,
         5.191668797562395e-1,
@@ -588,7 +595,7 @@ fn dy_aa_lagrange_sparse_subgrid_dynamic() -> anyhow::Result<()> {
     grid.optimize();
 
     // check that the results are still the same
-    let bins = grid.convolute(&xfx, &xfx, &alphas, &[], &[], &[], &[(1.0, 1.0)]);
+    let bins = grid.convolute(&[&pdf, &pdf], &[], &[], &[], &[(1.0, 1.0, 1.0)]);
 
     for (result, reference) in bins.iter().zip(reference.iter()) {
         assert!(approx_eq!(f64, *result, *reference, ulps = 16));
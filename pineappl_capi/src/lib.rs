@@ -3,21 +3,21 @@
 
 //! C-language interface for `PineAPPL`.
 
-use itertools::izip;
 use pineappl::bin::BinRemapper;
 use pineappl::empty_subgrid::EmptySubgridV1;
-use pineappl::grid::{Grid, Ntuple, Order};
-use pineappl::import_only_subgrid::ImportOnlySubgridV1;
+use pineappl::grid::{ConcurrentGrid, Event, Grid, Ntuple, Order};
+use pineappl::import_only_subgrid::{ImportOnlySubgridV1, ImportOnlySubgridV2};
 use pineappl::lumi::LumiEntry;
+use pineappl::pdf::{AlphaPdf, ClosurePdf, PdfProvider};
 use pineappl::sparse_array3::SparseArray3;
-use pineappl::subgrid::{ExtraSubgridParams, Subgrid, SubgridParams};
+use pineappl::subgrid::{ExtraSubgridParams, Mu2, Subgrid, SubgridEnum, SubgridParams};
 use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::ffi::{CStr, CString};
 use std::fs::File;
 use std::io::{BufReader, BufWriter};
 use std::os::raw::{c_char, c_void};
-use std::slice;
+use std::{iter, slice};
 
 unsafe fn grid_params(key_vals: *const KeyVal) -> (String, SubgridParams, ExtraSubgridParams) {
     let mut subgrid_type = "LagrangeSubgrid".to_string();
@@ -109,12 +109,140 @@ unsafe fn grid_params(key_vals: *const KeyVal) -> (String, SubgridParams, ExtraS
     (subgrid_type, subgrid_params, extra)
 }
 
+#[allow(clippy::too_many_arguments)]
+unsafe fn events(
+    x1: *const f64,
+    x2: *const f64,
+    q2: *const f64,
+    orders: *const usize,
+    observables: *const f64,
+    lumis: *const usize,
+    weights: *const f64,
+    size: usize,
+) -> Vec<Event> {
+    let x1 = slice::from_raw_parts(x1, size);
+    let x2 = slice::from_raw_parts(x2, size);
+    let q2 = slice::from_raw_parts(q2, size);
+    let orders = slice::from_raw_parts(orders, size);
+    let observables = slice::from_raw_parts(observables, size);
+    let lumis = slice::from_raw_parts(lumis, size);
+    let weights = slice::from_raw_parts(weights, size);
+
+    (0..size)
+        .map(|index| Event {
+            x: vec![x1[index], x2[index]],
+            q2: q2[index],
+            order: orders[index],
+            observable: observables[index],
+            lumi: lumis[index],
+            weight: weights[index],
+        })
+        .collect()
+}
+
 /// Type for defining a luminosity function.
 #[derive(Default)]
 pub struct Lumi(Vec<LumiEntry>);
 
 /// Type for reading and accessing subgrids.
-pub struct SubGrid(ImportOnlySubgridV1);
+pub struct SubGrid(SubgridEnum);
+
+/// Delete a concurrent grid previously created with `pineappl_concurrent_grid_new`.
+#[no_mangle]
+#[allow(unused_variables)]
+pub extern "C" fn pineappl_concurrent_grid_delete(grid: Option<Box<ConcurrentGrid>>) {}
+
+/// Fill `grid` for the given momentum fractions `x1` and `x2`, at the scale `q2` for the given
+/// value of the `order`, `observable`, and `lumi` with `weight`.
+///
+/// This function can be called from many threads at the same time.
+///
+/// # Safety
+///
+/// If `grid` does not point to a valid `ConcurrentGrid` object, for example when `grid` is the
+/// null pointer, this function is not safe to call.
+#[no_mangle]
+pub unsafe extern "C" fn pineappl_concurrent_grid_fill(
+    grid: *const ConcurrentGrid,
+    x1: f64,
+    x2: f64,
+    q2: f64,
+    order: usize,
+    observable: f64,
+    lumi: usize,
+    weight: f64,
+) {
+    (*grid)
+        .fill(
+            order,
+            observable,
+            lumi,
+            &Ntuple {
+                x1,
+                x2,
+                q2,
+                q2r: q2,
+                weight,
+            },
+        )
+        .unwrap();
+}
+
+/// Fill `grid` with as many points as indicated by `size`. This function can be called from many
+/// threads at the same time.
+///
+/// # Safety
+///
+/// If `grid` does not point to a valid `ConcurrentGrid` object, for example when `grid` is the
+/// null pointer, this function is not safe to call. Additionally, all remaining pointer
+/// parameters must be arrays as long as specified by `size`.
+#[no_mangle]
+pub unsafe extern "C" fn pineappl_concurrent_grid_fill_array(
+    grid: *const ConcurrentGrid,
+    x1: *const f64,
+    x2: *const f64,
+    q2: *const f64,
+    orders: *const usize,
+    observables: *const f64,
+    lumis: *const usize,
+    weights: *const f64,
+    size: usize,
+) {
+    let events = events(x1, x2, q2, orders, observables, lumis, weights, size);
+
+    (*grid).fill_array(&events);
+}
+
+/// Returns the grid filled by `grid` and deletes `grid`. No other thread may use `grid` while or
+/// after calling this function.
+///
+/// # Safety
+///
+/// The parameter `grid` must be a valid `ConcurrentGrid` object created by
+/// `pineappl_concurrent_grid_new`.
+#[no_mangle]
+#[must_use]
+pub unsafe extern "C" fn pineappl_concurrent_grid_into_grid(
+    grid: *mut ConcurrentGrid,
+) -> Box<Grid> {
+    Box::new(Box::from_raw(grid).into_grid())
+}
+
+/// Turns `grid` into a concurrent grid, which can be filled from many threads at the same time,
+/// and deletes `grid`.
+///
+/// All threads share the memory of a single grid, and each of its subgrids is locked separately.
+/// Use `pineappl_concurrent_grid_into_grid` to get the filled grid back.
+///
+/// # Safety
+///
+/// The parameter `grid` must be a valid `Grid` object created by either `pineappl_grid_new` or
+/// `pineappl_grid_read`.
+#[no_mangle]
+#[must_use]
+pub unsafe extern "C" fn pineappl_concurrent_grid_new(grid: *mut Grid) -> Box<ConcurrentGrid> {
+    Box::new(ConcurrentGrid::from(*Box::from_raw(grid)))
+}
 
 /// Returns the number of bins in `grid`.
 ///
@@ -205,6 +333,11 @@ pub unsafe extern "C" fn pineappl_grid_bin_limits_right(
 /// which corresponds to `1.0`. After convolution of the grid with the PDFs the differential cross
 /// section for each bin is written into `results`.
 ///
+/// The PDFs `xfx1` and `xfx2` are assumed to be the PDFs of the initial states of `grid` and are
+/// used as they are. The functions `pineappl_grid_convolute_with_one` and
+/// `pineappl_grid_convolute_with_two` should be preferred, because they charge conjugate the PDFs
+/// if necessary and do not evaluate PDFs for leptonic initial states.
+///
 /// # Safety
 ///
 /// If `grid` does not point to a valid `Grid` object, for example when `grid` is the null pointer,
@@ -224,11 +357,71 @@ pub unsafe extern "C" fn pineappl_grid_convolute(
     xi_ren: f64,
     xi_fac: f64,
     results: *mut f64,
+) {
+    let initial_state = |index: usize| {
+        (*grid)
+            .key_values()
+            .and_then(|map| map.get(&format!("initial_state_{}", index)))
+            .map_or(2212, |value| value.parse().unwrap())
+    };
+
+    pineappl_grid_convolute_with_two(
+        grid,
+        initial_state(1),
+        xfx1,
+        initial_state(2),
+        xfx2,
+        alphas,
+        state,
+        order_mask,
+        lumi_mask,
+        xi_ren,
+        xi_fac,
+        results,
+    );
+}
+
+/// Convolutes the specified grid with the PDF `xfx` of the hadron with the PDG id `pdg_id`, which
+/// is used for all initial states, and strong coupling `alphas`. Depending on the initial states
+/// of `grid`, the PDF is charge conjugated or not evaluated at all, which is the case for leptons.
+/// All other parameters have the same meaning as for `pineappl_grid_convolute`.
+///
+/// # Safety
+///
+/// The same requirements as for `pineappl_grid_convolute` apply.
+#[no_mangle]
+pub unsafe extern "C" fn pineappl_grid_convolute_with_one(
+    grid: *const Grid,
+    pdg_id: i32,
+    xfx: extern "C" fn(pdg_id: i32, x: f64, q2: f64, state: *mut c_void) -> f64,
+    alphas: extern "C" fn(q2: f64, state: *mut c_void) -> f64,
+    state: *mut c_void,
+    order_mask: *const bool,
+    lumi_mask: *const bool,
+    xi_ren: f64,
+    xi_fac: f64,
+    results: *mut f64,
 ) {
     let grid = &*grid;
-    let pdf1 = |id, x, q2| xfx1(id, x, q2, state);
-    let pdf2 = |id, x, q2| xfx2(id, x, q2, state);
-    let als = |q2| alphas(q2, state);
+    let pdf = ClosurePdf::new(
+        pdg_id,
+        |id, x, q2| xfx(id, x, q2, state),
+        |q2| alphas(q2, state),
+    );
+    let pdfs: Vec<&dyn PdfProvider> = vec![&pdf; grid.convolutions()];
+
+    convolute(grid, &pdfs, order_mask, lumi_mask, xi_ren, xi_fac, results);
+}
+
+unsafe fn convolute(
+    grid: &Grid,
+    pdfs: &[&dyn PdfProvider],
+    order_mask: *const bool,
+    lumi_mask: *const bool,
+    xi_ren: f64,
+    xi_fac: f64,
+    results: *mut f64,
+) {
     let order_mask = if order_mask.is_null() {
         vec![]
     } else {
@@ -242,21 +435,165 @@ pub unsafe extern "C" fn pineappl_grid_convolute(
     let results = slice::from_raw_parts_mut(results, grid.bin_info().bins());
 
     results.copy_from_slice(&grid.convolute(
-        &pdf1,
-        &pdf2,
-        &als,
+        pdfs,
         &order_mask,
         &[],
         &lumi_mask,
-        &[(xi_ren, xi_fac)],
+        &[(xi_ren, xi_fac, 1.0)],
     ));
 }
 
+/// Convolutes the specified grid with the PDF `xfx1` of the hadron with the PDG id `pdg_id1` and
+/// the PDF `xfx2` of the hadron with the PDG id `pdg_id2`, and strong coupling `alphas`. Depending
+/// on the initial states of `grid`, the PDFs are charge conjugated or not evaluated at all, which
+/// is the case for leptons. All other parameters have the same meaning as for
+/// `pineappl_grid_convolute`.
+///
+/// # Safety
+///
+/// The same requirements as for `pineappl_grid_convolute` apply.
+#[no_mangle]
+pub unsafe extern "C" fn pineappl_grid_convolute_with_two(
+    grid: *const Grid,
+    pdg_id1: i32,
+    xfx1: extern "C" fn(pdg_id: i32, x: f64, q2: f64, state: *mut c_void) -> f64,
+    pdg_id2: i32,
+    xfx2: extern "C" fn(pdg_id: i32, x: f64, q2: f64, state: *mut c_void) -> f64,
+    alphas: extern "C" fn(q2: f64, state: *mut c_void) -> f64,
+    state: *mut c_void,
+    order_mask: *const bool,
+    lumi_mask: *const bool,
+    xi_ren: f64,
+    xi_fac: f64,
+    results: *mut f64,
+) {
+    let grid = &*grid;
+    let pdf1 = ClosurePdf::new(
+        pdg_id1,
+        |id, x, q2| xfx1(id, x, q2, state),
+        |q2| alphas(q2, state),
+    );
+    let pdf2 = ClosurePdf::new(
+        pdg_id2,
+        |id, x, q2| xfx2(id, x, q2, state),
+        |q2| alphas(q2, state),
+    );
+
+    convolute(
+        grid,
+        &[&pdf1, &pdf2],
+        order_mask,
+        lumi_mask,
+        xi_ren,
+        xi_fac,
+        results,
+    );
+}
+
+/// Convolutes the specified grid like `pineappl_grid_convolute_with_two`, but additionally
+/// multiplies each order with the electroweak coupling `alpha`.
+///
+/// The electroweak coupling is evaluated at the renormalization scale and raised to the power of
+/// the order's exponent of the electroweak coupling. This allows choosing a running electroweak
+/// coupling or a different electroweak input scheme for grids that do not include the electroweak
+/// couplings.
+///
+/// # Safety
+///
+/// The same requirements as for `pineappl_grid_convolute` apply.
+#[no_mangle]
+pub unsafe extern "C" fn pineappl_grid_convolute_with_alpha(
+    grid: *const Grid,
+    pdg_id1: i32,
+    xfx1: extern "C" fn(pdg_id: i32, x: f64, q2: f64, state: *mut c_void) -> f64,
+    pdg_id2: i32,
+    xfx2: extern "C" fn(pdg_id: i32, x: f64, q2: f64, state: *mut c_void) -> f64,
+    alphas: extern "C" fn(q2: f64, state: *mut c_void) -> f64,
+    alpha: extern "C" fn(q2: f64, state: *mut c_void) -> f64,
+    state: *mut c_void,
+    order_mask: *const bool,
+    lumi_mask: *const bool,
+    xi_ren: f64,
+    xi_fac: f64,
+    results: *mut f64,
+) {
+    let grid = &*grid;
+    let pdf1 = ClosurePdf::new(
+        pdg_id1,
+        |id, x, q2| xfx1(id, x, q2, state),
+        |q2| alphas(q2, state),
+    );
+    let pdf1 = AlphaPdf::new(&pdf1, |q2| alpha(q2, state));
+    let pdf2 = ClosurePdf::new(
+        pdg_id2,
+        |id, x, q2| xfx2(id, x, q2, state),
+        |q2| alphas(q2, state),
+    );
+
+    convolute(
+        grid,
+        &[&pdf1, &pdf2],
+        order_mask,
+        lumi_mask,
+        xi_ren,
+        xi_fac,
+        results,
+    );
+}
+
 /// Delete a grid previously created with `pineappl_grid_new`.
 #[no_mangle]
 #[allow(unused_variables)]
 pub extern "C" fn pineappl_grid_delete(grid: Option<Box<Grid>>) {}
 
+/// Deletes the bins of `grid` whose indices are given in the array `bin_indices`, which has
+/// `count` elements. Indices that do not correspond to a bin are ignored.
+///
+/// # Safety
+///
+/// If `grid` does not point to a valid `Grid` object, for example when `grid` is the null pointer,
+/// this function is not safe to call. The array `bin_indices` must be at least `count` long.
+#[no_mangle]
+pub unsafe extern "C" fn pineappl_grid_delete_bins(
+    grid: *mut Grid,
+    count: usize,
+    bin_indices: *const usize,
+) {
+    (*grid).delete_bins(slice::from_raw_parts(bin_indices, count));
+}
+
+/// Deletes the channels of `grid` whose indices are given in the array `channel_indices`, which
+/// has `count` elements. Indices that do not correspond to a channel are ignored.
+///
+/// # Safety
+///
+/// If `grid` does not point to a valid `Grid` object, for example when `grid` is the null pointer,
+/// this function is not safe to call. The array `channel_indices` must be at least `count` long.
+#[no_mangle]
+pub unsafe extern "C" fn pineappl_grid_delete_channels(
+    grid: *mut Grid,
+    count: usize,
+    channel_indices: *const usize,
+) {
+    (*grid).delete_channels(slice::from_raw_parts(channel_indices, count));
+}
+
+/// Deletes the orders of `grid` whose indices are given in the array `order_indices`, which has
+/// `count` elements. Indices that do not correspond to an order are ignored.
+///
+/// # Safety
+///
+/// If `grid` does not point to a valid `Grid` object, for example when `grid` is the null pointer,
+/// this function is not safe to call. The array `order_indices` must be at least `count` long.
+#[no_mangle]
+pub unsafe extern "C" fn pineappl_grid_delete_orders(
+    grid: *mut Grid,
+    count: usize,
+    order_indices: *const usize,
+) {
+    (*grid).delete_orders(slice::from_raw_parts(order_indices, count));
+}
+
 /// Fill `grid` for the given momentum fractions `x1` and `x2`, at the scale `q2` for the given
 /// value of the `order`, `observable`, and `lumi` with `weight`.
 ///
@@ -275,7 +612,57 @@ pub unsafe extern "C" fn pineappl_grid_fill(
     lumi: usize,
     weight: f64,
 ) {
-    (*grid).fill(order, observable, lumi, &Ntuple { x1, x2, q2, weight });
+    (*grid)
+        .fill(
+            order,
+            observable,
+            lumi,
+            &Ntuple {
+                x1,
+                x2,
+                q2,
+                q2r: q2,
+                weight,
+            },
+        )
+        .unwrap();
+}
+
+/// Fill `grid` for the given momentum fractions `x1` and `x2`, at the factorization scale `q2`
+/// and the renormalization scale `q2r` for the given value of the `order`, `observable`, and
+/// `lumi` with `weight`. If both scales are different, the grid must have been created with the
+/// subgrid type `NtupleSubgrid`.
+///
+/// # Safety
+///
+/// If `grid` does not point to a valid `Grid` object, for example when `grid` is the null pointer,
+/// this function is not safe to call.
+#[no_mangle]
+pub unsafe extern "C" fn pineappl_grid_fill2(
+    grid: *mut Grid,
+    x1: f64,
+    x2: f64,
+    q2: f64,
+    q2r: f64,
+    order: usize,
+    observable: f64,
+    lumi: usize,
+    weight: f64,
+) {
+    (*grid)
+        .fill(
+            order,
+            observable,
+            lumi,
+            &Ntuple {
+                x1,
+                x2,
+                q2,
+                q2r,
+                weight,
+            },
+        )
+        .unwrap();
 }
 
 /// Fill `grid` for the given momentum fractions `x1` and `x2`, at the scale `q2` for the given
@@ -305,10 +692,12 @@ pub unsafe extern "C" fn pineappl_grid_fill_all(
             x1,
             x2,
             q2,
+            q2r: q2,
             weight: (),
         },
         slice::from_raw_parts(weights, grid.lumi().len()),
-    );
+    )
+    .unwrap();
 }
 
 /// Fill `grid` with as many points as indicated by `size`.
@@ -330,19 +719,9 @@ pub unsafe extern "C" fn pineappl_grid_fill_array(
     weights: *const f64,
     size: usize,
 ) {
-    let x1 = slice::from_raw_parts(x1, size);
-    let x2 = slice::from_raw_parts(x2, size);
-    let q2 = slice::from_raw_parts(q2, size);
-    let orders = slice::from_raw_parts(orders, size);
-    let observables = slice::from_raw_parts(observables, size);
-    let lumis = slice::from_raw_parts(lumis, size);
-    let weights = slice::from_raw_parts(weights, size);
+    let events = events(x1, x2, q2, orders, observables, lumis, weights, size);
 
-    for (&x1, &x2, &q2, &order, &observable, &lumi, &weight) in
-        izip!(x1, x2, q2, orders, observables, lumis, weights)
-    {
-        (*grid).fill(order, observable, lumi, &Ntuple { x1, x2, q2, weight });
-    }
+    (*grid).fill_array(&events);
 }
 
 /// Return the luminosity function of `grid`.
@@ -356,7 +735,8 @@ pub unsafe extern "C" fn pineappl_grid_lumi(grid: *const Grid) -> Box<Lumi> {
     Box::new(Lumi((*grid).lumi().to_vec()))
 }
 
-/// Write the order parameters of `grid` into `order_params`.
+/// Write the order parameters of `grid` into `order_params`. The exponent of the logarithm of
+/// the fragmentation scale is not written; use `pineappl_grid_order_params2` to retrieve it.
 ///
 /// # Safety
 ///
@@ -376,6 +756,31 @@ pub unsafe extern "C" fn pineappl_grid_order_params(grid: *const Grid, order_par
     }
 }
 
+/// Write the order parameters of `grid` into `order_params`, including logarithms of the
+/// fragmentation scale.
+///
+/// Each order is given by the four integers of `pineappl_grid_order_params` followed by the
+/// exponent of the logarithm of the fragmentation scale.
+///
+/// # Safety
+///
+/// If `grid` does not point to a valid `Grid` object, for example when `grid` is the null pointer,
+/// this function is not safe to call. The pointer `order_params` must point to an array as large
+/// as five times the number of orders in `grid`.
+#[no_mangle]
+pub unsafe extern "C" fn pineappl_grid_order_params2(grid: *const Grid, order_params: *mut u32) {
+    let orders = (*grid).orders();
+    let order_params = slice::from_raw_parts_mut(order_params, 5 * orders.len());
+
+    for (i, order) in orders.iter().enumerate() {
+        order_params[5 * i] = order.alphas;
+        order_params[5 * i + 1] = order.alpha;
+        order_params[5 * i + 2] = order.logxir;
+        order_params[5 * i + 3] = order.logxif;
+        order_params[5 * i + 4] = order.logxia;
+    }
+}
+
 /// Return the number of orders in `grid`.
 ///
 /// # Safety
@@ -403,6 +808,8 @@ pub unsafe extern "C" fn pineappl_grid_order_count(grid: *const Grid) -> usize {
 /// - More (optional) information can be given in a key-value storage `key_vals`, which might be
 /// a null pointer, to signal there are no further parameters that need to be set.
 ///
+/// Orders with logarithms of the fragmentation scale can be created with `pineappl_grid_new2`.
+///
 /// # Safety
 ///
 /// The parameter `lumi` must point a valid luminosity function created by `pineappl_lumi_new`.
@@ -418,14 +825,51 @@ pub unsafe extern "C" fn pineappl_grid_new(
     bin_limits: *const f64,
     key_vals: *const KeyVal,
 ) -> Box<Grid> {
-    let order_params = slice::from_raw_parts(order_params, 4 * orders);
-    let orders: Vec<_> = order_params
+    let order_params: Vec<_> = slice::from_raw_parts(order_params, 4 * orders)
         .chunks(4)
+        .flat_map(|s| s.iter().copied().chain(iter::once(0)))
+        .collect();
+
+    pineappl_grid_new2(
+        lumi,
+        orders,
+        order_params.as_ptr(),
+        bins,
+        bin_limits,
+        key_vals,
+    )
+}
+
+/// Creates a new and empty grid, like `pineappl_grid_new`, with orders including logarithms of
+/// the fragmentation scale.
+///
+/// The array `order_params` must contain 5 integers for each order: the four exponents of
+/// `pineappl_grid_new` followed by the exponent of the logarithm of the fragmentation scale.
+///
+/// # Safety
+///
+/// The parameter `lumi` must point a valid luminosity function created by `pineappl_lumi_new`.
+/// `order_params` must be an array with a length of `5 * orders`, and `bin_limits` an array with
+/// length `bins + 1`. `key_vals` must be a valid `KeyVal` object created by `pineappl_keyval_new`.
+#[no_mangle]
+#[must_use]
+pub unsafe extern "C" fn pineappl_grid_new2(
+    lumi: *const Lumi,
+    orders: usize,
+    order_params: *const u32,
+    bins: usize,
+    bin_limits: *const f64,
+    key_vals: *const KeyVal,
+) -> Box<Grid> {
+    let order_params = slice::from_raw_parts(order_params, 5 * orders);
+    let orders: Vec<_> = order_params
+        .chunks(5)
         .map(|s| Order {
             alphas: s[0],
             alpha: s[1],
             logxir: s[2],
             logxif: s[3],
+            logxia: s[4],
         })
         .collect();
 
@@ -507,6 +951,28 @@ pub unsafe extern "C" fn pineappl_grid_optimize(grid: *mut Grid) {
     (&mut *grid).optimize();
 }
 
+/// Optimizes the grid representation for space efficiency, like `pineappl_grid_optimize`.
+///
+/// Subgrids of type `NtupleSubgrid` are interpolated with the parameters given in `key_vals`,
+/// which are the same as the ones accepted by `pineappl_grid_new`. If `key_vals` is the null
+/// pointer, the default parameters are used. Returns the number of n-tuples that were discarded,
+/// because they are outside of the ranges of the interpolation grids.
+///
+/// # Safety
+///
+/// If `grid` does not point to a valid `Grid` object, for example when `grid` is the null pointer,
+/// this function is not safe to call. The parameter `key_vals` must be a valid `KeyVal` object
+/// created by `pineappl_keyval_new` or the null pointer.
+#[no_mangle]
+pub unsafe extern "C" fn pineappl_grid_optimize_using(
+    grid: *mut Grid,
+    key_vals: *const KeyVal,
+) -> usize {
+    let (_, subgrid_params, extra) = grid_params(key_vals);
+
+    (&mut *grid).optimize_using(&subgrid_params, &extra)
+}
+
 /// Scales each subgrid by a factor which is the product of the given values `alphas`, `alpha`,
 /// `logxir`, and `logxif`, each raised to the corresponding powers for each subgrid. In addition,
 /// every subgrid is scaled by a factor `global` independently of its order.
@@ -524,7 +990,7 @@ pub unsafe extern "C" fn pineappl_grid_scale_by_order(
     logxif: f64,
     global: f64,
 ) {
-    (&mut *grid).scale_by_order(alphas, alpha, logxir, logxif, global);
+    (&mut *grid).scale_by_order(alphas, alpha, logxir, logxif, 1.0, global);
 }
 
 /// Sets an internal key-value pair for the grid.
@@ -617,7 +1083,7 @@ pub unsafe extern "C" fn pineappl_lumi_add(
         slice::from_raw_parts(pdg_id_pairs, 2 * combinations)
             .chunks(2)
             .zip(factors)
-            .map(|x| (x.0[0], x.0[1], x.1))
+            .map(|x| (x.0.to_vec(), x.1))
             .collect(),
     ));
 }
@@ -656,8 +1122,9 @@ pub extern "C" fn pineappl_lumi_delete(lumi: Option<Box<Lumi>>) {}
 ///
 /// The parameter `lumi` must point to a valid `Lumi` object created by `pineappl_lumi_new` or
 /// `pineappl_grid_lumi`. The parameter `factors` must point to an array as long as the size
-/// returned by `pineappl_lumi_combinations` and `pdg_ids` must point to an array that is twice as
-/// long.
+/// returned by `pineappl_lumi_combinations` and `pdg_ids` must point to an array that is longer
+/// by a factor given by the number of convolutions of the luminosity function, which is two for
+/// luminosities created with `pineappl_lumi_add`.
 #[no_mangle]
 pub unsafe extern "C" fn pineappl_lumi_entry(
     lumi: *mut Lumi,
@@ -666,17 +1133,17 @@ pub unsafe extern "C" fn pineappl_lumi_entry(
     factors: *mut f64,
 ) {
     let entry = (*lumi).0[entry].entry();
-    let pdg_ids = slice::from_raw_parts_mut(pdg_ids, 2 * entry.len());
+    let pdg_ids = slice::from_raw_parts_mut(pdg_ids, entry[0].0.len() * entry.len());
     let factors = slice::from_raw_parts_mut(factors, entry.len());
 
     entry
         .iter()
-        .flat_map(|(id1, id2, _)| vec![id1, id2])
+        .flat_map(|(pids, _)| pids)
         .zip(pdg_ids.iter_mut())
         .for_each(|(from, to)| *to = *from);
     entry
         .iter()
-        .map(|(_, _, factor)| factor)
+        .map(|(_, factor)| factor)
         .zip(factors.iter_mut())
         .for_each(|(from, to)| *to = *from);
 }
@@ -732,8 +1199,8 @@ pub unsafe extern "C" fn pineappl_grid_nonzero_q2_slices(
     tuple[1] = slice.end;
 }
 
-/// Deletes a subgrid created with `pineappl_subgrid_new`. If `subgrid` is the null pointer,
-/// nothing is done.
+/// Deletes a subgrid created with `pineappl_subgrid_new` or `pineappl_subgrid_new2`. If `subgrid`
+/// is the null pointer, nothing is done.
 #[no_mangle]
 #[allow(unused_variables)]
 pub extern "C" fn pineappl_subgrid_delete(subgrid: Option<Box<SubGrid>>) {}
@@ -758,10 +1225,7 @@ pub unsafe extern "C" fn pineappl_grid_replace_and_delete(
         order,
         bin,
         lumi,
-        subgrid.map_or_else(
-            || EmptySubgridV1::default().into(),
-            |subgrid| subgrid.0.into(),
-        ),
+        subgrid.map_or_else(|| EmptySubgridV1::default().into(), |subgrid| subgrid.0),
     );
 }
 
@@ -784,12 +1248,53 @@ pub unsafe extern "C" fn pineappl_subgrid_new(
     let x1 = slice::from_raw_parts(x1_grid, x1_grid_len);
     let x2 = slice::from_raw_parts(x2_grid, x2_grid_len);
 
-    Box::new(SubGrid(ImportOnlySubgridV1::new(
-        SparseArray3::new(q2.len(), x1.len(), x2.len()),
-        q2.to_vec(),
-        x1.to_vec(),
-        x2.to_vec(),
-    )))
+    Box::new(SubGrid(
+        ImportOnlySubgridV1::new(
+            SparseArray3::new(q2.len(), x1.len(), x2.len()),
+            q2.to_vec(),
+            x1.to_vec(),
+            x2.to_vec(),
+        )
+        .into(),
+    ))
+}
+
+/// Creates a new subgrid whose scale nodes consist of a renormalization and a factorization
+/// scale, given by `mu2_ren_grid` and `mu2_fac_grid`, respectively. The subgrid can be filled with
+/// [`pineappl_subgrid_import_q2_slice`], where the index of the slice selects a pair of scales.
+///
+/// # Safety
+///
+/// The arrays `mu2_ren_grid` and `mu2_fac_grid` must be non-`NULL` and at least as long as
+/// specified by `mu2_grid_len`. The arrays `x1_grid`, and `x2_grid` must be non-`NULL` and at
+/// least as long as specified by `x1_grid_len` and `x2_grid_len`, respectively.
+#[no_mangle]
+pub unsafe extern "C" fn pineappl_subgrid_new2(
+    mu2_grid_len: usize,
+    mu2_ren_grid: *const f64,
+    mu2_fac_grid: *const f64,
+    x1_grid_len: usize,
+    x1_grid: *const f64,
+    x2_grid_len: usize,
+    x2_grid: *const f64,
+) -> Box<SubGrid> {
+    let ren = slice::from_raw_parts(mu2_ren_grid, mu2_grid_len);
+    let fac = slice::from_raw_parts(mu2_fac_grid, mu2_grid_len);
+    let x1 = slice::from_raw_parts(x1_grid, x1_grid_len);
+    let x2 = slice::from_raw_parts(x2_grid, x2_grid_len);
+
+    Box::new(SubGrid(
+        ImportOnlySubgridV2::new(
+            SparseArray3::new(mu2_grid_len, x1.len(), x2.len()),
+            ren.iter()
+                .zip(fac)
+                .map(|(&ren, &fac)| Mu2 { ren, fac })
+                .collect(),
+            x1.to_vec(),
+            x2.to_vec(),
+        )
+        .into(),
+    ))
 }
 
 /// Imports `slice` for the given index into `subgrid`.
@@ -798,14 +1303,18 @@ pub unsafe extern "C" fn pineappl_subgrid_new(
 ///
 /// The parameter `subgrid` and the array `slice` must be non-`NULL` and `slice` must be at least
 /// as long as the product `x1_grid_len * x2_grid_len` that were used to create the subgrid with.
-/// The index `q2_slice` must be smaller than `q2_grid_len`.
+/// The index `q2_slice` must be smaller than `q2_grid_len` or `mu2_grid_len`, respectively.
 #[no_mangle]
 pub unsafe extern "C" fn pineappl_subgrid_import_q2_slice(
     subgrid: *mut SubGrid,
     q2_slice: usize,
     slice: *const f64,
 ) {
-    let array = (*subgrid).0.array_mut();
+    let array = match &mut (*subgrid).0 {
+        SubgridEnum::ImportOnlySubgridV1(subgrid) => subgrid.array_mut(),
+        SubgridEnum::ImportOnlySubgridV2(subgrid) => subgrid.array_mut(),
+        _ => unreachable!(),
+    };
     let (_, nx1, nx2) = array.dimensions();
     let slice = slice::from_raw_parts(slice, nx1 * nx2);
 
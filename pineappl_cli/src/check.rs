@@ -0,0 +1,24 @@
+use super::helpers;
+use anyhow::{ensure, Result};
+use pineappl::grid::Severity;
+
+pub fn subcommand(input: &str) -> Result<()> {
+    let grid = helpers::read_grid(input)?;
+    let findings = grid.validate();
+
+    for finding in &findings {
+        match finding.severity() {
+            Severity::Warning => eprintln!("warning: {}", finding),
+            Severity::Error => eprintln!("error: {}", finding),
+        }
+    }
+
+    let errors = findings
+        .iter()
+        .filter(|finding| finding.severity() == Severity::Error)
+        .count();
+
+    ensure!(errors == 0, "the grid '{}' has {} error(s)", input, errors);
+
+    Ok(())
+}
@@ -0,0 +1,22 @@
+use super::helpers;
+use anyhow::{ensure, Context, Result};
+
+pub fn subcommand(output: &str, input: &[&str], coefficients: &[f64]) -> Result<()> {
+    ensure!(
+        input.len() == coefficients.len(),
+        "the number of coefficients ({}) is different from the number of grids ({})",
+        coefficients.len(),
+        input.len()
+    );
+
+    let mut grid0 = helpers::read_grid(input[0])?;
+    grid0.scale(coefficients[0]);
+
+    for (i, &coefficient) in input.iter().zip(coefficients).skip(1) {
+        grid0
+            .add_scaled(coefficient, helpers::read_grid(i)?)
+            .context(format!("unable to add '{}'", i))?;
+    }
+
+    helpers::write_grid(output, &grid0)
+}
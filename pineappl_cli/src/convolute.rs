@@ -18,7 +18,8 @@ pub fn subcommand(
         .parse()
         .map_or_else(|_| Pdf::with_setname_and_member(pdfset, 0), Pdf::with_lhaid);
 
-    let results = helpers::convolute(&grid, &pdf, orders, show_bins, &[], scales);
+    let results = helpers::par_convolute(&grid, &pdf, orders, show_bins, &[], scales);
+    let mc_uncertainties = helpers::convolute_mc_uncertainty(&grid, &pdf, orders, show_bins);
 
     let other_results: Vec<f64> = other_pdfsets
         .iter()
@@ -26,7 +27,7 @@ pub fn subcommand(
             let pdf = pdfset
                 .parse()
                 .map_or_else(|_| Pdf::with_setname_and_member(pdfset, 0), Pdf::with_lhaid);
-            helpers::convolute(&grid, &pdf, &[], show_bins, &[], 1)
+            helpers::par_convolute(&grid, &pdf, &[], show_bins, &[], 1)
         })
         .collect();
 
@@ -59,6 +60,10 @@ pub fn subcommand(
         title.add_cell(cell!(c->"pos unc"));
     }
 
+    if mc_uncertainties.is_some() {
+        title.add_cell(cell!(c->"MC unc"));
+    }
+
     for other in other_pdfsets.iter() {
         let mut cell = cell!(c->other);
         cell.set_hspan(2);
@@ -101,6 +106,16 @@ pub fn subcommand(
             row.add_cell(cell!(r->&format!("{:.2}%", (max_value / values[0] - 1.0) * 100.0)));
         }
 
+        if let Some(mc_uncertainties) = &mc_uncertainties {
+            let uncertainty = mc_uncertainties[index];
+
+            if absolute {
+                row.add_cell(cell!(r->&format!("{:.7e}", if integrated { uncertainty * normalizations[bin] } else { uncertainty })));
+            } else {
+                row.add_cell(cell!(r->&format!("{:.2}%", uncertainty / values[0].abs() * 100.0)));
+            }
+        }
+
         let bins = if show_bins.is_empty() {
             bin_info.bins()
         } else {
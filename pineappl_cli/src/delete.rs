@@ -0,0 +1,18 @@
+use super::helpers;
+use anyhow::Result;
+
+pub fn subcommand(
+    input: &str,
+    output: &str,
+    bins: &[usize],
+    orders: &[usize],
+    channels: &[usize],
+) -> Result<()> {
+    let mut grid = helpers::read_grid(input)?;
+
+    grid.delete_bins(bins);
+    grid.delete_orders(orders);
+    grid.delete_channels(channels);
+
+    helpers::write_grid(output, &grid)
+}
@@ -33,8 +33,8 @@ pub fn subcommand(input1: &str, input2: &str, pdfset: &str, ignore_orders: bool)
 
             table.set_titles(title);
 
-            let results1 = helpers::convolute(&grid1, &pdf, &[], &[], &[], 1);
-            let results2 = helpers::convolute(&grid2, &pdf, &[], &[], &[], 1);
+            let results1 = helpers::par_convolute(&grid1, &pdf, &[], &[], &[], 1);
+            let results2 = helpers::par_convolute(&grid2, &pdf, &[], &[], &[], 1);
 
             for (bin, (result1, result2)) in results1.iter().zip(results2.iter()).enumerate() {
                 let row = table.add_empty_row();
@@ -54,12 +54,12 @@ pub fn subcommand(input1: &str, input2: &str, pdfset: &str, ignore_orders: bool)
             let orders1: HashSet<_> = grid1
                 .orders()
                 .iter()
-                .filter(|order| (order.logxir == 0) && (order.logxif == 0))
+                .filter(|order| (order.logxir == 0) && (order.logxif == 0) && (order.logxia == 0))
                 .collect();
             let orders2: HashSet<_> = grid2
                 .orders()
                 .iter()
-                .filter(|order| (order.logxir == 0) && (order.logxif == 0))
+                .filter(|order| (order.logxir == 0) && (order.logxif == 0) && (order.logxia == 0))
                 .collect();
 
             let mut diff1: Vec<_> = orders1.difference(&orders2).collect();
@@ -126,13 +126,27 @@ pub fn subcommand(input1: &str, input2: &str, pdfset: &str, ignore_orders: bool)
             let order_results1: Vec<Vec<f64>> = orders
                 .iter()
                 .map(|order| {
-                    helpers::convolute(&grid1, &pdf, &[(order.alphas, order.alpha)], &[], &[], 1)
+                    helpers::par_convolute(
+                        &grid1,
+                        &pdf,
+                        &[(order.alphas, order.alpha)],
+                        &[],
+                        &[],
+                        1,
+                    )
                 })
                 .collect();
             let order_results2: Vec<Vec<f64>> = orders
                 .iter()
                 .map(|order| {
-                    helpers::convolute(&grid1, &pdf, &[(order.alphas, order.alpha)], &[], &[], 1)
+                    helpers::par_convolute(
+                        &grid1,
+                        &pdf,
+                        &[(order.alphas, order.alpha)],
+                        &[],
+                        &[],
+                        1,
+                    )
                 })
                 .collect();
 
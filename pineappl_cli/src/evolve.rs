@@ -0,0 +1,45 @@
+use super::helpers;
+use anyhow::{Context, Result};
+use lhapdf::Pdf;
+use pineappl::evolution::EvolutionOperator;
+use pineappl::fk_table::FkTable;
+use std::convert::TryFrom;
+use std::fs::File;
+use std::io::BufReader;
+
+pub fn subcommand(
+    input: &str,
+    operator: &str,
+    output: &str,
+    pdfset: &str,
+    orders: &[(u32, u32)],
+    xir: f64,
+    xif: f64,
+) -> Result<()> {
+    let grid = helpers::read_grid(input)?;
+    let operator = EvolutionOperator::read(BufReader::new(
+        File::open(operator).context(format!("unable to open '{}'", operator))?,
+    ))
+    .context(format!("unable to read '{}'", operator))?;
+    let pdf = pdfset
+        .parse()
+        .map_or_else(|_| Pdf::with_setname_and_member(pdfset, 0), Pdf::with_lhaid);
+
+    let order_mask: Vec<_> = grid
+        .orders()
+        .iter()
+        .map(|order| {
+            orders.is_empty()
+                || orders
+                    .iter()
+                    .any(|other| (order.alphas == other.0) && (order.alpha == other.1))
+        })
+        .collect();
+    let alphas = |q2| pdf.alphas_q2(q2);
+
+    // like for the convolutions, the electroweak coupling is assumed to be part of the grid
+    let fk_table =
+        FkTable::try_from(grid.evolve(&operator, &alphas, &|_| 1.0, &order_mask, (xir, xif))?)?;
+
+    helpers::write_grid(output, fk_table.grid())
+}
@@ -0,0 +1,21 @@
+use super::helpers;
+use anyhow::{Context, Result};
+use pineappl::fastnlo;
+use std::fs::OpenOptions;
+use std::io::BufWriter;
+
+pub fn subcommand(input: &str, output: &str) -> Result<()> {
+    let grid = helpers::read_grid(input)?;
+
+    fastnlo::export(
+        &grid,
+        BufWriter::new(
+            OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(output)
+                .context(format!("unable to write '{}'", output))?,
+        ),
+    )
+    .context(format!("unable to export '{}'", input))
+}
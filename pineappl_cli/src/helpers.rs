@@ -2,6 +2,7 @@ use anyhow::{Context, Result};
 use lhapdf::Pdf;
 use ndarray::Array3;
 use pineappl::grid::Grid;
+use pineappl::pdf::{ClosurePdf, PdfProvider};
 use prettytable::format::{FormatBuilder, LinePosition, LineSeparator};
 use prettytable::Table;
 use std::fs::{File, OpenOptions};
@@ -14,6 +15,13 @@ pub fn read_grid(input: &str) -> Result<Grid> {
     .context(format!("unable to read '{}'", input))
 }
 
+pub fn read_grid_metadata(input: &str) -> Result<Grid> {
+    Grid::read_metadata(BufReader::new(
+        File::open(input).context(format!("unable to open '{}'", input))?,
+    ))
+    .context(format!("unable to read '{}'", input))
+}
+
 pub fn write_grid(output: &str, grid: &Grid) -> Result<()> {
     grid.write(BufWriter::new(
         OpenOptions::new()
@@ -35,16 +43,17 @@ pub fn create_table() -> Table {
     table
 }
 
-pub const SCALES_VECTOR: [(f64, f64); 9] = [
-    (1.0, 1.0),
-    (2.0, 2.0),
-    (0.5, 0.5),
-    (2.0, 1.0),
-    (1.0, 2.0),
-    (0.5, 1.0),
-    (1.0, 0.5),
-    (2.0, 0.5),
-    (0.5, 2.0),
+// the fragmentation scale (third element) is always varied together with the factorization scale
+pub const SCALES_VECTOR: [(f64, f64, f64); 9] = [
+    (1.0, 1.0, 1.0),
+    (2.0, 2.0, 2.0),
+    (0.5, 0.5, 0.5),
+    (2.0, 1.0, 1.0),
+    (1.0, 2.0, 2.0),
+    (0.5, 1.0, 1.0),
+    (1.0, 0.5, 0.5),
+    (2.0, 0.5, 0.5),
+    (0.5, 2.0, 2.0),
 ];
 
 pub fn labels(grid: &Grid) -> Vec<String> {
@@ -77,8 +86,45 @@ pub fn convolute(
     lumis: &[bool],
     scales: usize,
 ) -> Vec<f64> {
-    let orders: Vec<_> = grid
-        .orders()
+    let orders = order_mask(grid, orders);
+    let pdf = pdf_provider(lhapdf);
+    let pdfs: Vec<&dyn PdfProvider> = vec![&pdf; grid.convolutions()];
+
+    grid.convolute(&pdfs, &orders, bins, lumis, &SCALES_VECTOR[0..scales])
+}
+
+/// Like `convolute`, but distributes the bins over the threads of the global thread pool. Do not
+/// call this function from loops that are already parallel, for instance over PDF members.
+pub fn par_convolute(
+    grid: &Grid,
+    lhapdf: &Pdf,
+    orders: &[(u32, u32)],
+    bins: &[usize],
+    lumis: &[bool],
+    scales: usize,
+) -> Vec<f64> {
+    let orders = order_mask(grid, orders);
+    let pdf = pdf_provider(lhapdf);
+    let pdfs: Vec<&(dyn PdfProvider + Sync)> = vec![&pdf; grid.convolutions()];
+
+    grid.par_convolute(&pdfs, &orders, bins, lumis, &SCALES_VECTOR[0..scales])
+}
+
+pub fn convolute_mc_uncertainty(
+    grid: &Grid,
+    lhapdf: &Pdf,
+    orders: &[(u32, u32)],
+    bins: &[usize],
+) -> Option<Vec<f64>> {
+    let orders = order_mask(grid, orders);
+    let pdf = pdf_provider(lhapdf);
+    let pdfs: Vec<&dyn PdfProvider> = vec![&pdf; grid.convolutions()];
+
+    grid.convolute_mc_uncertainty(&pdfs, &orders, bins, &[])
+}
+
+fn order_mask(grid: &Grid, orders: &[(u32, u32)]) -> Vec<bool> {
+    grid.orders()
         .iter()
         .map(|order| {
             orders.is_empty()
@@ -86,65 +132,7 @@ pub fn convolute(
                     .iter()
                     .any(|other| (order.alphas == other.0) && (order.alpha == other.1))
         })
-        .collect();
-
-    let initial_state_1 = grid.key_values().map_or(2212, |map| {
-        map.get("initial_state_1").unwrap().parse::<i32>().unwrap()
-    });
-    let initial_state_2 = grid.key_values().map_or(2212, |map| {
-        map.get("initial_state_2").unwrap().parse::<i32>().unwrap()
-    });
-
-    // if the field 'Particle' is missing we assume it's a proton PDF
-    let pdf_pdg_id = lhapdf
-        .set()
-        .entry("Particle")
-        .unwrap_or_else(|| "2212".to_string())
-        .parse::<i32>()
-        .unwrap();
-
-    let pdf = |id, x, q2| lhapdf.xfx_q2(id, x, q2);
-    let anti_pdf = |id, x, q2| {
-        let id = match id {
-            -6..=6 | 11 | 13 | -11 | -13 => -id,
-            21 | 22 => id,
-            _ => unimplemented!(),
-        };
-        lhapdf.xfx_q2(id, x, q2)
-    };
-    let no_pdf = |_, x, _| x;
-
-    let xfx1: Box<dyn Fn(i32, f64, f64) -> f64> = if initial_state_1 == pdf_pdg_id {
-        Box::new(&pdf)
-    } else if initial_state_1 == -pdf_pdg_id {
-        Box::new(&anti_pdf)
-    } else {
-        match initial_state_1 {
-            11 | 13 | -11 | -13 => Box::new(&no_pdf),
-            _ => unimplemented!(),
-        }
-    };
-    let xfx2: Box<dyn Fn(i32, f64, f64) -> f64> = if initial_state_2 == pdf_pdg_id {
-        Box::new(&pdf)
-    } else if initial_state_2 == -pdf_pdg_id {
-        Box::new(&anti_pdf)
-    } else {
-        match initial_state_2 {
-            11 | 13 | -11 | -13 => Box::new(&no_pdf),
-            _ => unimplemented!(),
-        }
-    };
-    let alphas = |q2| lhapdf.alphas_q2(q2);
-
-    grid.convolute(
-        &xfx1,
-        &xfx2,
-        &alphas,
-        &orders,
-        bins,
-        lumis,
-        &SCALES_VECTOR[0..scales],
-    )
+        .collect()
 }
 
 pub fn convolute_subgrid(
@@ -154,13 +142,12 @@ pub fn convolute_subgrid(
     bin: usize,
     lumi: usize,
 ) -> Array3<f64> {
-    let initial_state_1 = grid.key_values().map_or(2212, |map| {
-        map.get("initial_state_1").unwrap().parse::<i32>().unwrap()
-    });
-    let initial_state_2 = grid.key_values().map_or(2212, |map| {
-        map.get("initial_state_2").unwrap().parse::<i32>().unwrap()
-    });
+    let pdf = pdf_provider(lhapdf);
+
+    grid.convolute_subgrid(&[&pdf, &pdf], order, bin, lumi, 1.0, 1.0, 1.0)
+}
 
+fn pdf_provider(lhapdf: &Pdf) -> impl PdfProvider + Sync + '_ {
     // if the field 'Particle' is missing we assume it's a proton PDF
     let pdf_pdg_id = lhapdf
         .set()
@@ -169,38 +156,9 @@ pub fn convolute_subgrid(
         .parse::<i32>()
         .unwrap();
 
-    let pdf = |id, x, q2| lhapdf.xfx_q2(id, x, q2);
-    let anti_pdf = |id, x, q2| {
-        let id = match id {
-            -6..=6 | 11 | 13 | -11 | -13 => -id,
-            21 | 22 => id,
-            _ => unimplemented!(),
-        };
-        lhapdf.xfx_q2(id, x, q2)
-    };
-    let no_pdf = |_, x, _| x;
-
-    let xfx1: Box<dyn Fn(i32, f64, f64) -> f64> = if initial_state_1 == pdf_pdg_id {
-        Box::new(&pdf)
-    } else if initial_state_1 == -pdf_pdg_id {
-        Box::new(&anti_pdf)
-    } else {
-        match initial_state_1 {
-            11 | 13 | -11 | -13 => Box::new(&no_pdf),
-            _ => unimplemented!(),
-        }
-    };
-    let xfx2: Box<dyn Fn(i32, f64, f64) -> f64> = if initial_state_2 == pdf_pdg_id {
-        Box::new(&pdf)
-    } else if initial_state_2 == -pdf_pdg_id {
-        Box::new(&anti_pdf)
-    } else {
-        match initial_state_2 {
-            11 | 13 | -11 | -13 => Box::new(&no_pdf),
-            _ => unimplemented!(),
-        }
-    };
-    let alphas = |q2| lhapdf.alphas_q2(q2);
-
-    grid.convolute_subgrid(&xfx1, &xfx2, &alphas, order, bin, lumi, 1.0, 1.0)
+    ClosurePdf::new(
+        pdf_pdg_id,
+        move |id, x, q2| lhapdf.xfx_q2(id, x, q2),
+        move |q2| lhapdf.alphas_q2(q2),
+    )
 }
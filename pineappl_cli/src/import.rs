@@ -0,0 +1,26 @@
+use super::helpers;
+use anyhow::{Context, Result};
+use pineappl::fastnlo::{self, FlexibleScale};
+use std::fs::File;
+use std::io::BufReader;
+
+fn flexible_scale(scale: &str) -> FlexibleScale {
+    match scale {
+        "scale1" => FlexibleScale::Scale1,
+        "scale2" => FlexibleScale::Scale2,
+        "quadratic-sum" => FlexibleScale::QuadraticSum,
+        "quadratic-mean" => FlexibleScale::QuadraticMean,
+        _ => unreachable!(),
+    }
+}
+
+pub fn subcommand(input: &str, output: &str, mur: &str, muf: &str) -> Result<()> {
+    let mut grid = fastnlo::import(
+        BufReader::new(File::open(input).context(format!("unable to open '{}'", input))?),
+        flexible_scale(mur),
+        flexible_scale(muf),
+    )
+    .context(format!("unable to import '{}'", input))?;
+    grid.optimize();
+    helpers::write_grid(output, &grid)
+}
@@ -3,12 +3,12 @@ use anyhow::Result;
 use itertools::Itertools;
 
 pub fn subcommand_qcd_ew(input: &str, mode: &str) -> Result<()> {
-    let grid = helpers::read_grid(input)?;
+    let grid = helpers::read_grid_metadata(input)?;
 
     let mut sorted_grid_orders: Vec<_> = grid
         .orders()
         .iter()
-        .filter(|order| (order.logxir == 0) && (order.logxif == 0))
+        .filter(|order| (order.logxir == 0) && (order.logxif == 0) && (order.logxia == 0))
         .collect();
     sorted_grid_orders.sort();
 
@@ -39,7 +39,7 @@ pub fn subcommand_qcd_ew(input: &str, mode: &str) -> Result<()> {
 }
 
 pub fn subcommand_get(input: &str, key: &str) -> Result<()> {
-    let mut grid = helpers::read_grid(input)?;
+    let mut grid = helpers::read_grid_metadata(input)?;
 
     grid.upgrade();
 
@@ -55,7 +55,7 @@ pub fn subcommand_get(input: &str, key: &str) -> Result<()> {
 }
 
 pub fn subcommand_keys(input: &str) -> Result<()> {
-    let mut grid = helpers::read_grid(input)?;
+    let mut grid = helpers::read_grid_metadata(input)?;
 
     grid.upgrade();
 
@@ -74,7 +74,7 @@ pub fn subcommand_keys(input: &str) -> Result<()> {
 }
 
 pub fn subcommand_show(input: &str) -> Result<()> {
-    let mut grid = helpers::read_grid(input)?;
+    let mut grid = helpers::read_grid_metadata(input)?;
 
     grid.upgrade();
 
@@ -3,7 +3,7 @@ use anyhow::Result;
 use prettytable::{cell, row, Table};
 
 pub fn subcommand(input: &str) -> Result<Table> {
-    let grid = helpers::read_grid(input)?;
+    let grid = helpers::read_grid_metadata(input)?;
 
     let mut table = helpers::create_table();
     let mut titles = row![c => "id"];
@@ -23,10 +23,14 @@ pub fn subcommand(input: &str) -> Result<Table> {
 
         row.add_cell(cell!(&format!("{}", index)));
 
-        for (id1, id2, factor) in entry.entry().iter() {
+        for (pids, factor) in entry.entry().iter() {
             row.add_cell(cell!(&format!(
-                "{} \u{d7} ({:2.}, {:2.})",
-                factor, id1, id2
+                "{} \u{d7} ({})",
+                factor,
+                pids.iter()
+                    .map(|pid| format!("{:2.}", pid))
+                    .collect::<Vec<_>>()
+                    .join(", ")
             )));
         }
     }
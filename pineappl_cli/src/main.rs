@@ -1,9 +1,15 @@
 #![warn(clippy::all, clippy::cargo, clippy::nursery, clippy::pedantic)]
 
 mod channels;
+mod check;
+mod combine;
 mod convolute;
+mod delete;
 mod diff;
+mod evolve;
+mod export;
 mod helpers;
+mod import;
 mod info;
 mod luminosity;
 mod merge;
@@ -13,12 +19,15 @@ mod pdf_uncertainty;
 mod plot;
 mod pull;
 mod remap;
+mod rotate;
+mod scale_logs;
 mod set;
 mod subgrids;
 mod sum;
 
 use anyhow::{ensure, Context, Result};
 use clap::{clap_app, crate_authors, crate_description, crate_version, ArgSettings};
+use pineappl::pids::PidBasis;
 use std::result;
 use std::str::FromStr;
 
@@ -141,6 +150,17 @@ fn main() -> Result<()> {
             (@arg integrated: -i --integrated requires("absolute")
                 "Show integrated numbers (without bin widths) instead of differential ones")
         )
+        (@subcommand check =>
+            (about: "Checks a grid for inconsistencies, NaNs and infinities")
+            (@arg input: +required "Path to the input grid")
+        )
+        (@subcommand combine =>
+            (about: "Forms a linear combination of PineAPPL grids with the same bins")
+            (@arg output: +required "Path of the combined PineAPPL file")
+            (@arg input: ... +required "Path(s) of the files that should be combined")
+            (@arg coefficients: -c --coefficients +required +use_delimiter +allow_hyphen_values
+                min_values(1) "Coefficients of the grids, one for each input")
+        )
         (@subcommand convolute =>
             (about: "Convolutes a PineAPPL grid with a PDF set")
             (@arg input: +required "Path of the input grid")
@@ -154,6 +174,15 @@ fn main() -> Result<()> {
             (@arg integrated: -i --integrated
                 "Show integrated numbers (without bin widths) instead of differential ones")
         )
+        (@subcommand delete =>
+            (about: "Deletes bins, orders or channels from a grid")
+            (@arg input: +required "Path to the input grid")
+            (@arg output: +required "Path of the modified PineAPPL file")
+            (@arg bins: --bins +takes_value "Indices of the bins that should be deleted")
+            (@arg orders: --orders +takes_value "Indices of the orders that should be deleted")
+            (@arg channels: --channels +takes_value
+                "Indices of the channels that should be deleted")
+        )
         (@subcommand diff =>
             (about: "Compares the contents of two grids with each other")
             (@arg input1: +required "Path to the first grid")
@@ -163,6 +192,33 @@ fn main() -> Result<()> {
             (@arg ignore_orders: alias("ignore_orders") long("ignore-orders")
                 "Sums over all orders")
         )
+        (@subcommand evolve =>
+            (about: "Evolves a grid with an evolution operator into an FK table")
+            (@arg input: +required "Path to the input grid")
+            (@arg operator: +required "Path to the evolution operator")
+            (@arg output: +required "Path of the evolved PineAPPL file")
+            (@arg pdfset: +required validator(validate_pdfset)
+                "LHAPDF id or name of the PDF set used for the strong coupling")
+            (@arg orders: -o --orders +use_delimiter min_values(1) "Select orders manually")
+            (@arg xir: --xir default_value("1.0") "Renormalization scale factor")
+            (@arg xif: --xif default_value("1.0") "Factorization scale factor")
+        )
+        (@subcommand export =>
+            (about: "Converts a PineAPPL grid into a fastNLO table in ASCII format")
+            (@arg input: +required "Path to the input grid")
+            (@arg output: +required "Path of the exported fastNLO table")
+        )
+        (@subcommand import =>
+            (about: "Converts a fastNLO table in ASCII format into a PineAPPL grid")
+            (@arg input: +required "Path to the fastNLO table")
+            (@arg output: +required "Path of the converted PineAPPL file")
+            (@arg mur: --mur default_value("scale1")
+                possible_values(&["scale1", "scale2", "quadratic-sum", "quadratic-mean"])
+                "Renormalization scale of flexible-scale tables as a function of both scales")
+            (@arg muf: --muf default_value("scale1")
+                possible_values(&["scale1", "scale2", "quadratic-sum", "quadratic-mean"])
+                "Factorization scale of flexible-scale tables as a function of both scales")
+        )
         (@subcommand info =>
             (about: "Shows information about the grid")
             (@arg input: +required "Path to the input grid")
@@ -191,6 +247,8 @@ fn main() -> Result<()> {
             (about: "Optimizes the internal data structure to minimize memory usage")
             (@arg input: +required "Path to the input grid")
             (@arg output: +required "Path to the optimized PineAPPL file")
+            (@arg merge_channels: long("merge-channels")
+                "Removes empty channels and merges channels with proportional subgrids")
         )
         (@subcommand orders =>
             (about: "Shows the predictions for all bin for each order separately")
@@ -222,6 +280,12 @@ fn main() -> Result<()> {
             (@arg subgrid_pull: conflicts_with("scales") long("subgrid-pull") number_of_values(3)
                 +use_delimiter value_names(&["order", "bin", "lumi"])
                 "Show the pull for a specific grid three-dimensionally")
+            (@arg x_bins: requires("subgrid_pull") long("x-bins")
+                validator(validate_pos_non_zero::<usize>)
+                "Number of bins of the momentum fractions of n-tuple subgrids")
+            (@arg q2_bins: requires("subgrid_pull") long("q2-bins")
+                validator(validate_pos_non_zero::<usize>)
+                "Number of bins of the scales of n-tuple subgrids")
         )
         (@subcommand pull =>
             (about: "Calculates the pull between two different PDF sets")
@@ -245,6 +309,20 @@ fn main() -> Result<()> {
             (@arg ignore_obs_norm: alias("ignore_obs_norm") long("ignore-obs-norm") +use_delimiter
                 "Ignore the given observables for differential normalization")
         )
+        (@subcommand rotate =>
+            (about: "Rotates the luminosity function into a different basis of particle ids")
+            (@arg input: +required "Path to the input grid")
+            (@arg output: +required "Path of the modified PineAPPL file")
+            (@arg basis: +required possible_values(&["PDG", "EVOL"])
+                "Basis of the particle ids, either PDG MC ids or the PDF evolution basis")
+        )
+        (@subcommand "scale-logs" =>
+            (about: "Generates the missing orders with scale logarithms from lower orders")
+            (@arg input: +required "Path to the input grid")
+            (@arg output: +required "Path of the modified PineAPPL file")
+            (@arg nf: --nf default_value("5") validator(validate_pos_non_zero::<u8>)
+                "Number of active quark flavours")
+        )
         (@subcommand set =>
             (about: "Modifies the internal key-value storage")
             (@arg input: +required "Path to the input grid")
@@ -294,6 +372,20 @@ fn main() -> Result<()> {
 
         channels::subcommand(input, pdfset, limit, &orders?, absolute, &lumis, integrated)?
             .printstd();
+    } else if let Some(matches) = matches.subcommand_matches("check") {
+        let input = matches.value_of("input").unwrap();
+
+        check::subcommand(input)?;
+    } else if let Some(matches) = matches.subcommand_matches("combine") {
+        let output = matches.value_of("output").unwrap();
+        let input: Vec<_> = matches.values_of("input").unwrap().collect();
+        let coefficients: Result<Vec<_>> = matches
+            .values_of("coefficients")
+            .unwrap()
+            .map(|s| str::parse(s).context(format!("unable to parse '{}'", s)))
+            .collect();
+
+        combine::subcommand(output, &input, &coefficients?)?;
     } else if let Some(matches) = matches.subcommand_matches("convolute") {
         let input = matches.value_of("input").unwrap();
         let pdfset: Vec<_> = matches.values_of("pdfset").unwrap().collect();
@@ -318,6 +410,14 @@ fn main() -> Result<()> {
             integrated,
         )?
         .printstd();
+    } else if let Some(matches) = matches.subcommand_matches("delete") {
+        let input = matches.value_of("input").unwrap();
+        let output = matches.value_of("output").unwrap();
+        let bins = parse_integer_list(matches.value_of("bins").unwrap_or(""))?;
+        let orders = parse_integer_list(matches.value_of("orders").unwrap_or(""))?;
+        let channels = parse_integer_list(matches.value_of("channels").unwrap_or(""))?;
+
+        delete::subcommand(input, output, &bins, &orders, &channels)?;
     } else if let Some(matches) = matches.subcommand_matches("diff") {
         let input1 = matches.value_of("input1").unwrap();
         let input2 = matches.value_of("input2").unwrap();
@@ -325,6 +425,32 @@ fn main() -> Result<()> {
         let ignore_orders = matches.is_present("ignore_orders");
 
         diff::subcommand(input1, input2, pdfset, ignore_orders)?.printstd();
+    } else if let Some(matches) = matches.subcommand_matches("evolve") {
+        let input = matches.value_of("input").unwrap();
+        let operator = matches.value_of("operator").unwrap();
+        let output = matches.value_of("output").unwrap();
+        let pdfset = matches.value_of("pdfset").unwrap();
+        let orders: Result<Vec<_>> = matches
+            .values_of("orders")
+            .map_or(vec![], |values| values.map(parse_order).collect())
+            .into_iter()
+            .collect();
+        let xir = matches.value_of("xir").unwrap().parse()?;
+        let xif = matches.value_of("xif").unwrap().parse()?;
+
+        evolve::subcommand(input, operator, output, pdfset, &orders?, xir, xif)?;
+    } else if let Some(matches) = matches.subcommand_matches("export") {
+        let input = matches.value_of("input").unwrap();
+        let output = matches.value_of("output").unwrap();
+
+        export::subcommand(input, output)?;
+    } else if let Some(matches) = matches.subcommand_matches("import") {
+        let input = matches.value_of("input").unwrap();
+        let output = matches.value_of("output").unwrap();
+        let mur = matches.value_of("mur").unwrap();
+        let muf = matches.value_of("muf").unwrap();
+
+        import::subcommand(input, output, mur, muf)?;
     } else if let Some(matches) = matches.subcommand_matches("info") {
         let input = matches.value_of("input").unwrap();
 
@@ -370,8 +496,9 @@ fn main() -> Result<()> {
     } else if let Some(matches) = matches.subcommand_matches("optimize") {
         let input = matches.value_of("input").unwrap();
         let output = matches.value_of("output").unwrap();
+        let merge_channels = matches.is_present("merge_channels");
 
-        optimize::subcommand(input, output)?;
+        optimize::subcommand(input, output, merge_channels)?;
     } else if let Some(matches) = matches.subcommand_matches("orders") {
         let input = matches.value_of("input").unwrap();
         let pdfset = matches.value_of("pdfset").unwrap();
@@ -408,8 +535,12 @@ fn main() -> Result<()> {
             let lumi = diff[2].parse()?;
             let pdfset1 = pdfset[0];
             let pdfset2 = pdfset[1];
+            let x_bins = matches.value_of("x_bins").map(str::parse).transpose()?;
+            let q2_bins = matches.value_of("q2_bins").map(str::parse).transpose()?;
 
-            plot::subcommand_subgrid_pull(input, pdfset1, pdfset2, order, bin, lumi)?;
+            plot::subcommand_subgrid_pull(
+                input, pdfset1, pdfset2, order, bin, lumi, x_bins, q2_bins,
+            )?;
         } else {
             let scales = matches.value_of("scales").unwrap().parse()?;
 
@@ -442,6 +573,22 @@ fn main() -> Result<()> {
             .collect();
 
         remap::subcommand(input, output, remapping, norm, &ignore_obs_norm?)?;
+    } else if let Some(matches) = matches.subcommand_matches("rotate") {
+        let input = matches.value_of("input").unwrap();
+        let output = matches.value_of("output").unwrap();
+        let pid_basis = match matches.value_of("basis").unwrap() {
+            "PDG" => PidBasis::Pdg,
+            "EVOL" => PidBasis::Evol,
+            _ => unreachable!(),
+        };
+
+        rotate::subcommand(input, output, pid_basis)?;
+    } else if let Some(matches) = matches.subcommand_matches("scale-logs") {
+        let input = matches.value_of("input").unwrap();
+        let output = matches.value_of("output").unwrap();
+        let nf = matches.value_of("nf").unwrap().parse()?;
+
+        scale_logs::subcommand(input, output, nf)?;
     } else if let Some(matches) = matches.subcommand_matches("set") {
         let input = matches.value_of("input").unwrap();
         let output = matches.value_of("output").unwrap();
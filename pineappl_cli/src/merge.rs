@@ -22,6 +22,7 @@ pub fn subcommand(
             scale_by_order[1],
             scale_by_order[2],
             scale_by_order[3],
+            1.0,
             scale_by_order[4],
         );
     }
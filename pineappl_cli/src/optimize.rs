@@ -1,8 +1,20 @@
 use super::helpers;
 use anyhow::Result;
 
-pub fn subcommand(input: &str, output: &str) -> Result<()> {
+pub fn subcommand(input: &str, output: &str, merge_channels: bool) -> Result<()> {
     let mut grid = helpers::read_grid(input)?;
-    grid.optimize();
+    let discarded = grid.optimize();
+
+    if discarded != 0 {
+        eprintln!(
+            "warning: discarded {} n-tuple(s) outside of the interpolation ranges",
+            discarded
+        );
+    }
+
+    if merge_channels {
+        grid.merge_channels();
+    }
+
     helpers::write_grid(output, &grid)
 }
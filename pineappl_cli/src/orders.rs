@@ -18,14 +18,16 @@ pub fn subcommand(
     let mut orders: Vec<_> = grid
         .orders()
         .iter()
-        .filter(|order| (order.logxir == 0) && (order.logxif == 0))
+        .filter(|order| (order.logxir == 0) && (order.logxif == 0) && (order.logxia == 0))
         .collect();
     orders.sort();
     let orders = orders;
 
     let results: Vec<Vec<f64>> = orders
         .iter()
-        .map(|order| helpers::convolute(&grid, &pdf, &[(order.alphas, order.alpha)], &[], &[], 1))
+        .map(|order| {
+            helpers::par_convolute(&grid, &pdf, &[(order.alphas, order.alpha)], &[], &[], 1)
+        })
         .collect();
 
     let lo_power = {
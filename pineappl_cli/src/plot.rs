@@ -3,7 +3,7 @@ use anyhow::Result;
 use itertools::Itertools;
 use lhapdf::{Pdf, PdfSet};
 use pineappl::bin::BinInfo;
-use pineappl::subgrid::Subgrid;
+use pineappl::subgrid::{Subgrid, SubgridEnum};
 use rayon::prelude::*;
 use std::path::Path;
 
@@ -403,6 +403,7 @@ if __name__ == '__main__':
     );
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn subcommand_subgrid_pull(
     input: &str,
     pdfset1: &str,
@@ -410,9 +411,20 @@ pub fn subcommand_subgrid_pull(
     order: usize,
     bin: usize,
     lumi: usize,
+    x_bins: Option<usize>,
+    q2_bins: Option<usize>,
 ) -> Result<()> {
     let cl = 68.268949213708581;
-    let grid = helpers::read_grid(input)?;
+    let mut grid = helpers::read_grid(input)?;
+
+    let mut subgrid_params = grid.subgrid_params().clone();
+    if let Some(x_bins) = x_bins {
+        subgrid_params.set_x_bins(x_bins);
+    }
+    if let Some(q2_bins) = q2_bins {
+        subgrid_params.set_q2_bins(q2_bins);
+    }
+    grid.set_subgrid_params(subgrid_params)?;
 
     let set1 = PdfSet::new(&pdfset1.parse().map_or_else(
         |_| pdfset1.to_string(),
@@ -440,14 +452,14 @@ pub fn subcommand_subgrid_pull(
     let full_res1 = {
         let central: Vec<f64> = pdfset1
             .iter()
-            .flat_map(|pdf| helpers::convolute(&grid, pdf, &[], &[], &[], 1))
+            .flat_map(|pdf| helpers::par_convolute(&grid, pdf, &[], &[], &[], 1))
             .collect();
         set1.uncertainty(&central, cl, false).central
     };
     let full_res2 = {
         let central: Vec<f64> = pdfset2
             .iter()
-            .flat_map(|pdf| helpers::convolute(&grid, pdf, &[], &[], &[], 1))
+            .flat_map(|pdf| helpers::par_convolute(&grid, pdf, &[], &[], &[], 1))
             .collect();
         set1.uncertainty(&central, cl, false).central
     };
@@ -472,7 +484,19 @@ pub fn subcommand_subgrid_pull(
     };
     let pull = (res2 - res1) / denominator;
 
-    let subgrid = grid.subgrid(order, bin, lumi);
+    // n-tuple subgrids are histogrammed by `convolute_subgrid`, use the same bins
+    let histogram: SubgridEnum;
+    let subgrid = match grid.subgrid(order, bin, lumi) {
+        SubgridEnum::NtupleSubgridV1(subgrid) => {
+            histogram = subgrid.histogram(grid.subgrid_params()).into();
+            &histogram
+        }
+        SubgridEnum::NtupleSubgridV2(subgrid) => {
+            histogram = subgrid.histogram(grid.subgrid_params()).into();
+            &histogram
+        }
+        subgrid => subgrid,
+    };
     //let q2 = subgrid.q2_grid();
     let x1 = subgrid.x1_grid();
     let x2 = subgrid.x2_grid();
@@ -568,7 +592,7 @@ pub fn subcommand(input: &str, pdfsets: &[&str], scales: usize) -> Result<()> {
         Pdf::with_lhaid,
     );
 
-    let results = helpers::convolute(&grid, &pdf, &[], &[], &[], scales);
+    let results = helpers::par_convolute(&grid, &pdf, &[], &[], &[], scales);
 
     let qcd_results = {
         let mut orders = grid.orders().to_vec();
@@ -585,7 +609,7 @@ pub fn subcommand(input: &str, pdfsets: &[&str], scales: usize) -> Result<()> {
             })
             .collect();
 
-        helpers::convolute(&grid, &pdf, &qcd_orders, &[], &[], scales)
+        helpers::par_convolute(&grid, &pdf, &qcd_orders, &[], &[], scales)
     };
 
     let bin_info = grid.bin_info();
@@ -0,0 +1,11 @@
+use super::helpers;
+use anyhow::Result;
+use pineappl::pids::PidBasis;
+
+pub fn subcommand(input: &str, output: &str, pid_basis: PidBasis) -> Result<()> {
+    let mut grid = helpers::read_grid(input)?;
+
+    grid.rotate_pid_basis(pid_basis)?;
+
+    helpers::write_grid(output, &grid)
+}
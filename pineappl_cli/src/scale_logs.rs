@@ -0,0 +1,10 @@
+use super::helpers;
+use anyhow::Result;
+
+pub fn subcommand(input: &str, output: &str, nf: u8) -> Result<()> {
+    let mut grid = helpers::read_grid(input)?;
+
+    grid.generate_scale_logs(nf)?;
+
+    helpers::write_grid(output, &grid)
+}
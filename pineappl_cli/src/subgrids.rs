@@ -25,6 +25,9 @@ pub fn subcommand(input: &str) -> Result<Table> {
                         SubgridEnum::LagrangeSubgridV2(_) => "LagrangeSubgridV2",
                         SubgridEnum::ImportOnlySubgridV1(_) => "ImportOnlySubgridV1",
                         SubgridEnum::EmptySubgridV1(_) => "EmptySubgridV1",
+                        SubgridEnum::LagrangeSubgridV3(_) => "LagrangeSubgridV3",
+                        SubgridEnum::ImportOnlySubgridV2(_) => "ImportOnlySubgridV2",
+                        SubgridEnum::NtupleSubgridV2(_) => "NtupleSubgridV2",
                     }
                 ));
             }
@@ -222,6 +222,42 @@ class grid:
         )
         return result
 
+    def convolute_with_one(self, pdg_id, xfx, alphas, order_mask, lumi_mask, xi_ren, xi_fac):
+        """Convolutes the grid with the PDFs of a single hadron, which are
+        charge conjugated or not used at all depending on the initial states of
+        the grid.
+
+        Args:
+            pdg_id (int): pdg id of the hadron whose PDFs are given by `xfx`.
+            xfx (function): xfxQ2 function of (id, x, q2, p).
+            alphas (function): alphasQ2 function of (q2, p).
+            order_mask (array): must be provided as long as there are perturbative orders.
+            lumi_mask (array): specify the luminosity mask.
+            xi_ren (float): renomarlization scale.
+            xi_fac (floag): factorization scale.
+
+        Return:
+            A numpy array with the convolution output for each bin.
+        """
+        cxfx = xfx_callback_prototype(xfx)
+        calphas = as_callback_prototype(alphas)
+
+        if order_mask is not None:
+            type1 = ctypes.c_bool * len(order_mask)
+            order_mask = type1(*order_mask)
+        if lumi_mask is not None:
+            type2 = ctypes.c_bool * len(lumi_mask)
+            lumi_mask = type2(*lumi_mask)
+
+        result = np.ones(self.bin_count(), dtype=np.float64)
+        pineappl_lib.pineappl_grid_convolute_with_one(
+            self._grid, ctypes.c_int32(pdg_id), cxfx, calphas, None,
+            order_mask, lumi_mask,
+            ctypes.c_double(xi_ren), ctypes.c_double(xi_fac),
+            ctypes.c_void_p(result.ctypes.data)
+        )
+        return result
+
     def write(self, filename):
         """Write grid to disk.
 